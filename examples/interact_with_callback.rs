@@ -59,9 +59,9 @@ fn main() {
 
     let mut interact = session.interact(&mut stdin, stdout);
 
-    let is_alive = interact.spawn(opts).expect("Failed to start interact");
+    let status = interact.spawn(opts).expect("Failed to start interact");
 
-    if !is_alive {
+    if !status.is_alive() {
         println!("The process was exited");
         #[cfg(unix)]
         println!("Status={:?}", interact.get_status());
@@ -126,10 +126,10 @@ fn main() {
 
     let mut interact = session.interact(&mut stdin, stdout);
 
-    let is_alive =
+    let status =
         futures_lite::future::block_on(interact.spawn(opts)).expect("Failed to start interact");
 
-    if !is_alive {
+    if !status.is_alive() {
         println!("The process was exited");
         #[cfg(unix)]
         println!("Status={:?}", interact.get_status());
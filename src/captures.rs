@@ -1,20 +1,60 @@
-use std::ops::Index;
+use std::{
+    borrow::Cow,
+    ops::{Index, Range},
+    str::{FromStr, Utf8Error},
+    time::Duration,
+};
 
 use crate::needle::Match;
 
 /// Captures is a represention of matched pattern.
 ///
 /// It might represent an empty match.
-#[derive(Debug, Clone, PartialEq, Eq)]
+///
+/// Two captures are equal if they have the same bytes and matches,
+/// timing information is not compared.
+#[derive(Debug, Clone)]
 pub struct Captures {
     buf: Vec<u8>,
     matches: Vec<Match>,
+    elapsed: Duration,
+    scanned: usize,
 }
 
 impl Captures {
     /// New returns an instance of Found.
     pub(crate) fn new(buf: Vec<u8>, matches: Vec<Match>) -> Self {
-        Self { buf, matches }
+        Self {
+            buf,
+            matches,
+            elapsed: Duration::ZERO,
+            scanned: 0,
+        }
+    }
+
+    pub(crate) fn with_elapsed(mut self, elapsed: Duration) -> Self {
+        self.elapsed = elapsed;
+        self
+    }
+
+    pub(crate) fn with_scanned(mut self, scanned: usize) -> Self {
+        self.scanned = scanned;
+        self
+    }
+
+    /// elapsed returns how long an expect call took till the match was found.
+    ///
+    /// It's zero for captures which weren't produced by `Session::expect`.
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    /// scanned returns a number of bytes a needle was checked against when the match was found.
+    ///
+    /// It includes bytes after the match, which stay in the buffer for a following expect call.
+    /// It's zero for captures which weren't produced by `Session::expect`.
+    pub fn scanned(&self) -> usize {
+        self.scanned
     }
 
     /// is_empty verifies if any matches were actually found.
@@ -29,16 +69,77 @@ impl Captures {
             .map(|m| &self.buf[m.start()..m.end()])
     }
 
+    /// groups returns first N groups of a match by their index.
+    ///
+    /// A group is [None] if it didn't participate in the match or there's no such group.
+    /// Only the first match of a regex is taken into account.
+    ///
+    /// Matches which weren't produced by a regex are taken in order, like in [Captures::get].
+    pub fn groups<const N: usize>(&self) -> [Option<&[u8]>; N] {
+        std::array::from_fn(|i| self.group(i))
+    }
+
+    fn group(&self, index: usize) -> Option<&[u8]> {
+        if self.matches.iter().all(|m| m.group().is_none()) {
+            return self.get(index);
+        }
+
+        // each match of a regex starts with a 0 group
+        let next = self
+            .matches
+            .iter()
+            .skip(1)
+            .position(|m| m.group() == Some(0))
+            .map_or(self.matches.len(), |i| i + 1);
+
+        self.matches[..next]
+            .iter()
+            .find(|m| m.group() == Some(index))
+            .map(|m| &self.buf[m.start()..m.end()])
+    }
+
+    /// parse parses a match by index via [FromStr].
+    ///
+    /// It returns [None] if there's no such match or it can't be parsed.
+    pub fn parse<T: FromStr>(&self, index: usize) -> Option<T> {
+        self.group_str(index)?.ok()?.parse().ok()
+    }
+
     /// Matches returns a list of matches.
     pub fn matches(&self) -> MatchIter<'_> {
         MatchIter::new(self)
     }
 
+    /// matched_index returns an index of a needle in [crate::Any] which was matched.
+    ///
+    /// It returns [None] if the match was not produced by [crate::Any].
+    pub fn matched_index(&self) -> Option<usize> {
+        self.matches.first().and_then(|m| m.alternative())
+    }
+
     /// before returns a bytes before match.
     pub fn before(&self) -> &[u8] {
         &self.buf[..self.left_most_index()]
     }
 
+    /// range returns offsets of the whole match within [Self::as_bytes].
+    ///
+    /// It returns [None] in case there was no match.
+    pub fn range(&self) -> Option<Range<usize>> {
+        if self.matches.is_empty() {
+            return None;
+        }
+
+        Some(self.left_most_index()..Self::right_most_index(&self.matches))
+    }
+
+    /// group_range returns offsets of a match by index within [Self::as_bytes].
+    ///
+    /// The indexes are the same as used by [Self::get].
+    pub fn group_range(&self, index: usize) -> Option<Range<usize>> {
+        self.matches.get(index).map(|m| m.start()..m.end())
+    }
+
     /// as_bytes returns all bytes involved in a match, e.g. before the match and
     /// in a match itself.
     ///
@@ -49,6 +150,52 @@ impl Captures {
         &self.buf
     }
 
+    /// as_str returns [Self::as_bytes] as a string.
+    pub fn as_str(&self) -> Result<&str, Utf8Error> {
+        std::str::from_utf8(self.as_bytes())
+    }
+
+    /// as_str_lossy returns [Self::as_bytes] as a string,
+    /// replacing invalid UTF-8 sequences.
+    pub fn as_str_lossy(&self) -> Cow<'_, str> {
+        String::from_utf8_lossy(self.as_bytes())
+    }
+
+    /// group_str returns a match by index as a string.
+    pub fn group_str(&self, index: usize) -> Option<Result<&str, Utf8Error>> {
+        self.get(index).map(std::str::from_utf8)
+    }
+
+    /// group_str_lossy returns a match by index as a string,
+    /// replacing invalid UTF-8 sequences.
+    pub fn group_str_lossy(&self, index: usize) -> Option<Cow<'_, str>> {
+        self.get(index).map(String::from_utf8_lossy)
+    }
+
+    /// before_str returns [Self::before] as a string.
+    pub fn before_str(&self) -> Result<&str, Utf8Error> {
+        std::str::from_utf8(self.before())
+    }
+
+    /// before_str_lossy returns [Self::before] as a string,
+    /// replacing invalid UTF-8 sequences.
+    pub fn before_str_lossy(&self) -> Cow<'_, str> {
+        String::from_utf8_lossy(self.before())
+    }
+
+    /// before_str_valid returns the longest prefix of [Self::before] which is a valid UTF-8 string.
+    ///
+    /// Unlike [Self::before_str_lossy] it never produces replacement characters,
+    /// so a character split at the end of the buffer is just dropped.
+    pub fn before_str_valid(&self) -> &str {
+        valid_prefix(self.before())
+    }
+
+    /// group_str_valid returns the longest prefix of a match by index which is a valid UTF-8 string.
+    pub fn group_str_valid(&self, index: usize) -> Option<&str> {
+        self.get(index).map(valid_prefix)
+    }
+
     fn left_most_index(&self) -> usize {
         self.matches
             .iter()
@@ -62,6 +209,64 @@ impl Captures {
     }
 }
 
+fn valid_prefix(buf: &[u8]) -> &str {
+    match std::str::from_utf8(buf) {
+        Ok(s) => s,
+        Err(err) => std::str::from_utf8(&buf[..err.valid_up_to()]).unwrap_or_default(),
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Captures {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+
+        let matches = self
+            .matches
+            .iter()
+            .map(|m| SerializedMatch {
+                start: m.start(),
+                end: m.end(),
+                text: String::from_utf8_lossy(&self.buf[m.start()..m.end()]),
+            })
+            .collect::<Vec<_>>();
+
+        let mut s = serializer.serialize_struct("Captures", 3)?;
+        s.serialize_field("before", &self.before_str_lossy())?;
+        s.serialize_field("bytes", &self.as_str_lossy())?;
+        s.serialize_field("matches", &matches)?;
+        s.end()
+    }
+}
+
+#[cfg(feature = "serde")]
+struct SerializedMatch<'a> {
+    start: usize,
+    end: usize,
+    text: Cow<'a, str>,
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for SerializedMatch<'_> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+
+        let mut s = serializer.serialize_struct("Match", 3)?;
+        s.serialize_field("start", &self.start)?;
+        s.serialize_field("end", &self.end)?;
+        s.serialize_field("text", &self.text)?;
+        s.end()
+    }
+}
+
+impl PartialEq for Captures {
+    fn eq(&self, other: &Self) -> bool {
+        self.buf == other.buf && self.matches == other.matches
+    }
+}
+
+impl Eq for Captures {}
+
 impl Index<usize> for Captures {
     type Output = [u8];
 
@@ -88,9 +293,13 @@ pub struct MatchIter<'a> {
 
 impl<'a> MatchIter<'a> {
     fn new(captures: &'a Captures) -> Self {
+        Self::from_parts(&captures.buf, &captures.matches)
+    }
+
+    fn from_parts(buf: &'a [u8], matches: &'a [Match]) -> Self {
         Self {
-            buf: &captures.buf,
-            matches: captures.matches.iter(),
+            buf,
+            matches: matches.iter(),
         }
     }
 }
@@ -109,9 +318,161 @@ impl<'a> Iterator for MatchIter<'a> {
 
 impl ExactSizeIterator for MatchIter<'_> {}
 
+/// A buffer of a session which [CapturesRef] borrows bytes from.
+pub(crate) trait Buffer {
+    /// Returns bytes which were read but not yet consumed.
+    fn bytes(&self) -> &[u8];
+
+    /// Removes first `n` bytes.
+    fn consume(&mut self, n: usize);
+}
+
+/// CapturesRef is a represention of matched pattern which borrows bytes from a session buffer.
+///
+/// Unlike [Captures] it doesn't copy bytes involved in a match,
+/// which matters for large matches, e.g. a content of a big file.
+/// The bytes are consumed from the session buffer once it's dropped.
+///
+/// It's created by `Session::expect_ref`.
+pub struct CapturesRef<'a> {
+    buf: &'a mut (dyn Buffer + 'a),
+    end: usize,
+    matches: Vec<Match>,
+    elapsed: Duration,
+    scanned: usize,
+}
+
+impl<'a> CapturesRef<'a> {
+    pub(crate) fn new(buf: &'a mut (dyn Buffer + 'a), end: usize, matches: Vec<Match>) -> Self {
+        Self {
+            buf,
+            end,
+            matches,
+            elapsed: Duration::ZERO,
+            scanned: 0,
+        }
+    }
+
+    pub(crate) fn with_elapsed(mut self, elapsed: Duration) -> Self {
+        self.elapsed = elapsed;
+        self
+    }
+
+    pub(crate) fn with_scanned(mut self, scanned: usize) -> Self {
+        self.scanned = scanned;
+        self
+    }
+
+    /// elapsed returns how long an expect call took till the match was found.
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    /// scanned returns a number of bytes a needle was checked against when the match was found.
+    ///
+    /// See [Captures::scanned].
+    pub fn scanned(&self) -> usize {
+        self.scanned
+    }
+
+    /// is_empty verifies if any matches were actually found.
+    pub fn is_empty(&self) -> bool {
+        self.matches.is_empty()
+    }
+
+    /// get returns a match by index.
+    pub fn get(&self, index: usize) -> Option<&[u8]> {
+        self.matches
+            .get(index)
+            .map(|m| &self.as_bytes()[m.start()..m.end()])
+    }
+
+    /// Matches returns a list of matches.
+    pub fn matches(&self) -> MatchIter<'_> {
+        MatchIter::from_parts(self.as_bytes(), &self.matches)
+    }
+
+    /// matched_index returns an index of a needle in [crate::Any] which was matched.
+    ///
+    /// It returns [None] if the match was not produced by [crate::Any].
+    pub fn matched_index(&self) -> Option<usize> {
+        self.matches.first().and_then(|m| m.alternative())
+    }
+
+    /// before returns a bytes before match.
+    pub fn before(&self) -> &[u8] {
+        let start = self
+            .matches
+            .iter()
+            .map(|m| m.start())
+            .min()
+            .unwrap_or_default();
+        &self.as_bytes()[..start]
+    }
+
+    /// as_bytes returns all bytes involved in a match, e.g. before the match and
+    /// in a match itself.
+    ///
+    /// See [Captures::as_bytes].
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.buf.bytes()[..self.end]
+    }
+
+    /// to_captures copies bytes into an owned [Captures].
+    pub fn to_captures(&self) -> Captures {
+        Captures::new(self.as_bytes().to_vec(), self.matches.clone())
+            .with_elapsed(self.elapsed)
+            .with_scanned(self.scanned)
+    }
+}
+
+impl Drop for CapturesRef<'_> {
+    fn drop(&mut self) {
+        self.buf.consume(self.end);
+    }
+}
+
+impl std::fmt::Debug for CapturesRef<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CapturesRef")
+            .field("buf", &self.as_bytes())
+            .field("matches", &self.matches)
+            .field("elapsed", &self.elapsed)
+            .field("scanned", &self.scanned)
+            .finish()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{Needle, Regex};
+
+    impl Buffer for Vec<u8> {
+        fn bytes(&self) -> &[u8] {
+            self
+        }
+
+        fn consume(&mut self, n: usize) {
+            let _ = self.drain(..n);
+        }
+    }
+
+    #[test]
+    fn test_captures_ref() {
+        let mut buf = b"You can use iterator".to_vec();
+        {
+            let m = CapturesRef::new(&mut buf, 11, vec![Match::new(4, 7), Match::new(8, 11)]);
+            assert_eq!(m.get(0), Some(b"can".as_ref()));
+            assert_eq!(m.get(2), None);
+            assert_eq!(m.before(), b"You ");
+            assert_eq!(m.as_bytes(), b"You can use");
+            assert_eq!(m.matches().collect::<Vec<_>>(), [b"can", b"use"]);
+            assert_eq!(m.to_captures().before(), b"You ");
+        }
+
+        assert_eq!(buf, b" iterator");
+    }
 
     #[test]
     fn test_captures_get() {
@@ -217,6 +578,92 @@ mod tests {
         assert_eq!(m.before(), b"".as_ref());
     }
 
+    #[test]
+    fn test_ranges() {
+        let m = Captures::new(
+            b"You can use iterator".to_vec(),
+            vec![Match::new(4, 11), Match::new(4, 7), Match::new(8, 11)],
+        );
+        assert_eq!(m.range(), Some(4..11));
+        assert_eq!(m.group_range(0), Some(4..11));
+        assert_eq!(m.group_range(2), Some(8..11));
+        assert_eq!(m.group_range(3), None);
+
+        let m = Captures::new(b"You can use iterator".to_vec(), vec![]);
+        assert_eq!(m.range(), None);
+        assert_eq!(m.group_range(0), None);
+    }
+
+    #[test]
+    fn test_groups_by_index() {
+        let buf = b"port 8080, port x".to_vec();
+        let matches = Regex(r"port (?:(\d+)|(x))").check(&buf, false).unwrap();
+        let m = Captures::new(buf, matches);
+
+        assert_eq!(
+            m.groups(),
+            [
+                Some(b"port 8080".as_ref()),
+                Some(b"8080".as_ref()),
+                None,
+                None
+            ]
+        );
+    }
+
+    #[test]
+    fn test_groups() {
+        let m = Captures::new(
+            b"port 8080".to_vec(),
+            vec![Match::new(0, 9), Match::new(5, 9)],
+        );
+        assert_eq!(
+            m.groups(),
+            [Some(b"port 8080".as_ref()), Some(b"8080".as_ref())]
+        );
+        assert_eq!(m.groups(), [Some(b"port 8080".as_ref())]);
+        assert_eq!(m.groups::<3>()[2], None);
+        assert_eq!(m.parse::<u16>(1), Some(8080));
+        assert_eq!(m.parse::<u16>(0), None);
+        assert_eq!(m.parse::<u16>(2), None);
+    }
+
+    #[test]
+    fn test_strings() {
+        let m = Captures::new(b"You can use iterator".to_vec(), vec![Match::new(4, 7)]);
+        assert_eq!(m.as_str(), Ok("You can use iterator"));
+        assert_eq!(m.group_str(0), Some(Ok("can")));
+        assert_eq!(m.group_str(1), None);
+        assert_eq!(m.before_str(), Ok("You "));
+
+        let m = Captures::new(b"\xFF can".to_vec(), vec![Match::new(2, 5)]);
+        assert!(m.as_str().is_err());
+        assert!(m.before_str().is_err());
+        assert_eq!(m.group_str(0), Some(Ok("can")));
+        assert_eq!(m.as_str_lossy(), "\u{FFFD} can");
+        assert_eq!(m.before_str_lossy(), "\u{FFFD} ");
+        assert_eq!(m.group_str_lossy(0).as_deref(), Some("can"));
+        assert_eq!(m.before_str_valid(), "");
+
+        let buf = "Жук".as_bytes();
+        let m = Captures::new(buf[..5].to_vec(), vec![Match::new(5, 5)]);
+        assert_eq!(m.before_str_valid(), "Жу");
+
+        let m = Captures::new(buf[..5].to_vec(), vec![Match::new(0, 5)]);
+        assert_eq!(m.group_str_valid(0), Some("Жу"));
+        assert_eq!(m.group_str_valid(1), None);
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_serialize() {
+        let m = Captures::new(b"You can use iterator".to_vec(), vec![Match::new(4, 7)]);
+        assert_eq!(
+            serde_json::to_string(&m).unwrap(),
+            r#"{"before":"You ","bytes":"You can use iterator","matches":[{"start":4,"end":7,"text":"can"}]}"#
+        );
+    }
+
     #[test]
     fn test_captures_into_iter() {
         assert_eq!(
@@ -271,6 +718,18 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_eq_ignores_timing() {
+        let m = Captures::new(b"Hello".to_vec(), vec![Match::new(0, 5)]);
+        let timed = m
+            .clone()
+            .with_elapsed(Duration::from_secs(1))
+            .with_scanned(10);
+        assert_eq!(timed.elapsed(), Duration::from_secs(1));
+        assert_eq!(timed.scanned(), 10);
+        assert_eq!(m, timed);
+    }
+
     #[test]
     #[should_panic]
     fn test_captures_into_iter_panics_on_invalid_match() {
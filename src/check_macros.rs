@@ -1,4 +1,4 @@
-//! This module contains a `check!` macros.
+//! This module contains a `check!` and `expect!` macros.
 
 /// Check macros provides a convient way to check if things are available in a stream of a process.
 ///
@@ -9,6 +9,31 @@
 ///
 /// You can specify a default branch which will be called if nothing was matched.
 ///
+/// You can also specify an `eof` branch which is called if EOF was reached
+/// and a `timeout` branch which is called on [crate::Error::ExpectTimeout].
+/// Otherwise these errors are returned as a result.
+///
+/// Groups of a match can be bound directly by a slice pattern, like `[_, port] = Regex("port (\\d+)")`,
+/// each of them is `Option<&[u8]>` bound by a group index.
+/// A group is [None] if it didn't participate in the match, see [crate::Captures::groups].
+///
+/// Several sessions can be checked at once by `in [a, b]` syntax.
+/// In such case a branch gets a tuple of an index of the matched session and its captures.
+/// Sessions are checked in order and EOF is reported only when all of them have reached it.
+///
+/// ```no_run
+/// # let mut server = expectrl::spawn("cat").unwrap();
+/// # let mut client = expectrl::spawn("cat").unwrap();
+/// #
+/// expectrl::check!{
+///     in [&mut server, &mut client],
+///     (index, _) = "ready" => {
+///         println!("session #{} is ready", index);
+///     },
+/// }
+/// .unwrap();
+/// ```
+///
 /// The macros levareges [crate::Session::check] function, so its just made for convience.
 ///
 /// # Example
@@ -24,6 +49,10 @@
 ///         _ = "Hello World" => {
 ///             // handle Hello World
 ///         },
+///         eof => {
+///             // handle end of output
+///             break;
+///         },
 ///         default => {
 ///             // handle no matches
 ///         },
@@ -35,7 +64,11 @@
 #[macro_export]
 macro_rules! check {
     (@check ($($tokens:tt)*) ($session:expr)) => {
-        $crate::check!(@case $session, ($($tokens)*), (), ())
+        $crate::check!(@case ($session), ($($tokens)*), (), (), ())
+    };
+    (@check (in [$($session:expr),+ $(,)?], $($tokens:tt)*) ()) => {
+        // multiple sessions
+        $crate::check!(@case [$($session),+], ($($tokens)*), (), (), ())
     };
     (@check ($session:expr, $($tokens:tt)*) ()) => {
         $crate::check!(@check ($($tokens)*) ($session))
@@ -50,40 +83,62 @@ macro_rules! check {
         // there's no reason to run 0 checks so we issue a error.
         compile_error!("There's no reason in running check with no arguments. Please supply a check branches")
     };
-    (@case $session:expr, ($var:tt = $exp:expr => $body:tt, $($tail:tt)*), ($($head:tt)*), ($($default:tt)*)) => {
+    (@case $session:tt, (eof => $body:tt, $($tail:tt)*), ($($head:tt)*), ($($default:tt)*), ($($errors:tt)*)) => {
+        // An EOF branch
+        $crate::check!(@case $session, ($($tail)*), ($($head)*), ($($default)*), ($($errors)* eof => $body, ))
+    };
+    (@case $session:tt, (eof => $body:tt $($tail:tt)*), ($($head:tt)*), ($($default:tt)*), ($($errors:tt)*)) => {
+        // An EOF branch
+        // allow missed comma `,`
+        $crate::check!(@case $session, ($($tail)*), ($($head)*), ($($default)*), ($($errors)* eof => $body, ))
+    };
+    (@case $session:tt, (timeout => $body:tt, $($tail:tt)*), ($($head:tt)*), ($($default:tt)*), ($($errors:tt)*)) => {
+        // A timeout branch
+        $crate::check!(@case $session, ($($tail)*), ($($head)*), ($($default)*), ($($errors)* timeout => $body, ))
+    };
+    (@case $session:tt, (timeout => $body:tt $($tail:tt)*), ($($head:tt)*), ($($default:tt)*), ($($errors:tt)*)) => {
+        // A timeout branch
+        // allow missed comma `,`
+        $crate::check!(@case $session, ($($tail)*), ($($head)*), ($($default)*), ($($errors)* timeout => $body, ))
+    };
+    (@case $session:tt, ($var:tt = $exp:expr => $body:tt, $($tail:tt)*), ($($head:tt)*), ($($default:tt)*), ($($errors:tt)*)) => {
         // regular case
         //
         // note: we keep order correct by putting head at the beggining
-        $crate::check!(@case $session, ($($tail)*), ($($head)* $var = $exp => $body, ), ($($default)*))
+        $crate::check!(@case $session, ($($tail)*), ($($head)* $var = $exp => $body, ), ($($default)*), ($($errors)*))
     };
-    (@case $session:expr, ($var:tt = $exp:expr => $body:tt $($tail:tt)*), ($($head:tt)*), ($($default:tt)*)) => {
+    (@case $session:tt, ($var:tt = $exp:expr => $body:tt $($tail:tt)*), ($($head:tt)*), ($($default:tt)*), ($($errors:tt)*)) => {
         // allow missing comma
         //
         // note: we keep order correct by putting head at the beggining
-        $crate::check!(@case $session, ($($tail)*), ($($head)* $var = $exp => $body, ), ($($default)*))
+        $crate::check!(@case $session, ($($tail)*), ($($head)* $var = $exp => $body, ), ($($default)*), ($($errors)*))
     };
-    (@case $session:expr, (default => $($tail:tt)*), ($($head:tt)*), ($($default:tt)+)) => {
+    (@case $session:tt, (default => $($tail:tt)*), ($($head:tt)*), ($($default:tt)+), ($($errors:tt)*)) => {
         // A repeated default branch
         compile_error!("Only 1 default case is allowed")
     };
-    (@case $session:expr, (default => $body:tt, $($tail:tt)*), ($($head:tt)*), ()) => {
+    (@case $session:tt, (default => $body:tt, $($tail:tt)*), ($($head:tt)*), (), ($($errors:tt)*)) => {
         // A default branch
-        $crate::check!(@case $session, ($($tail)*), ($($head)*), ( { $body; #[allow(unreachable_code)] Ok(()) } ))
+        $crate::check!(@case $session, ($($tail)*), ($($head)*), ( { $body; #[allow(unreachable_code)] Ok(()) } ), ($($errors)*))
     };
-    (@case $session:expr, (default => $body:tt $($tail:tt)*), ($($head:tt)*), ()) => {
+    (@case $session:tt, (default => $body:tt $($tail:tt)*), ($($head:tt)*), (), ($($errors:tt)*)) => {
         // A default branch
         // allow missed comma `,`
-        $crate::check!(@case $session, ($($tail)*), ($($head)*), ( { $body; Ok(()) } ))
+        $crate::check!(@case $session, ($($tail)*), ($($head)*), ( { $body; Ok(()) } ), ($($errors)*))
     };
-    (@case $session:expr, (), ($($head:tt)*), ()) => {
+    (@case $session:tt, (), ($($head:tt)*), (), ($($errors:tt)*)) => {
         // there's no default branch
         // so we make up our own.
-        $crate::check!(@case $session, (), ($($head)*), ( { Ok(()) } ))
+        $crate::check!(@case $session, (), ($($head)*), ( { Ok(()) } ), ($($errors)*))
     };
-    (@case $session:expr, (), ($($tail:tt)*), ($($default:tt)*)) => {
+    (@case $session:tt, (), ($($tail:tt)*), ($($default:tt)*), ($($errors:tt)*)) => {
         // last point of @case
         // call code generation via @branch
-        $crate::check!(@branch $session, ($($tail)*), ($($default)*))
+        // and pass its result to EOF and timeout branches via @errors
+        {
+            let result: Result::<(), $crate::Error> = $crate::check!(@branch $session, ($($tail)*), ($($default)*));
+            $crate::check!(@errors result, ($($errors)*))
+        }
     };
     // We need to use a variable for pattern mathing,
     // user may chose to drop var name using a placeholder '_',
@@ -100,25 +155,94 @@ macro_rules! check {
     //
     // The question is which solution is more effichient.
     // I took the following approach because there's no chance we influence user's land via the variable name we pick.
-    (@branch $session:expr, ($var:tt = $exp:expr => $body:tt, $($tail:tt)*), ($($default:tt)*)) => {
-        match $crate::session::Session::check($session, $exp) {
-            result if result.as_ref().map(|found| !found.is_empty()).unwrap_or(false) => {
-                let $var = result.unwrap();
+    (@branch $session:tt, ($var:tt = $exp:expr => $body:tt, $($tail:tt)*), ($($default:tt)*)) => {
+        match $crate::check!(@find $session, $exp) {
+            Ok(Some(found)) => {
+                $crate::check!(@bind $var, found);
                 $body;
                 #[allow(unreachable_code)]
                 Ok(())
             }
-            Ok(_) => {
+            Ok(None) => {
                 $crate::check!(@branch $session, ($($tail)*), ($($default)*))
             }
             Err(err) => Err(err),
         }
     };
-    (@branch $session:expr, (), ($default:tt)) => {
+    (@branch $session:tt, (), ($default:tt)) => {
         // A standart default branch
         $default
     };
-    (@branch $session:expr, ($($tail:tt)*), ($($default:tt)*)) => {
+    (@find ($session:expr), $exp:expr) => {
+        match $crate::session::Session::check($session, $exp) {
+            Ok(found) if !found.is_empty() => Ok(Some(found)),
+            Ok(_) => Ok(None),
+            Err(err) => Err(err),
+        }
+    };
+    (@find [$($session:expr),+], $exp:expr) => {
+        // checks sessions one by one,
+        // EOF is reported only when it's reached by all of them.
+        {
+            let mut index = 0;
+            let mut eof = 0;
+            let mut result = Ok(None);
+            $(
+                if matches!(result, Ok(None)) {
+                    match $crate::session::Session::check($session, $exp) {
+                        Ok(found) if !found.is_empty() => result = Ok(Some((index, found))),
+                        Ok(_) => {}
+                        Err($crate::Error::Eof) => eof += 1,
+                        Err(err) => result = Err(err),
+                    }
+                }
+
+                index += 1;
+            )+
+
+            match result {
+                Ok(None) if eof == index => Err($crate::Error::Eof),
+                result => result,
+            }
+        }
+    };
+    (@bind [$($group:tt),* $(,)?], $found:ident) => {
+        // bind regex groups
+        let [$($group),*] = $found.groups();
+    };
+    (@bind $var:tt, $found:ident) => {
+        let $var = $found;
+    };
+    (@timeout ($session:expr)) => {
+        $crate::session::Session::get_expect_timeout($session)
+    };
+    (@timeout [$session:expr $(, $tail:expr)*]) => {
+        $crate::session::Session::get_expect_timeout($session)
+    };
+    (@errors $result:ident, (eof => $body:tt, $($tail:tt)*)) => {
+        match $result {
+            Err($crate::Error::Eof) => {
+                $body;
+                #[allow(unreachable_code)]
+                Ok(())
+            }
+            result => $crate::check!(@errors result, ($($tail)*)),
+        }
+    };
+    (@errors $result:ident, (timeout => $body:tt, $($tail:tt)*)) => {
+        match $result {
+            Err($crate::Error::ExpectTimeout) => {
+                $body;
+                #[allow(unreachable_code)]
+                Ok(())
+            }
+            result => $crate::check!(@errors result, ($($tail)*)),
+        }
+    };
+    (@errors $result:ident, ()) => {
+        $result
+    };
+    (@branch $session:tt, ($($tail:tt)*), ($($default:tt)*)) => {
         compile_error!(
             concat!(
                 "No supported syntax tail=(",
@@ -151,7 +275,11 @@ macro_rules! check {
 #[macro_export]
 macro_rules! check {
     (@check ($($tokens:tt)*) ($session:expr)) => {
-        $crate::check!(@case $session, ($($tokens)*), (), ())
+        $crate::check!(@case ($session), ($($tokens)*), (), (), ())
+    };
+    (@check (in [$($session:expr),+ $(,)?], $($tokens:tt)*) ()) => {
+        // multiple sessions
+        $crate::check!(@case [$($session),+], ($($tokens)*), (), (), ())
     };
     (@check ($session:expr, $($tokens:tt)*) ()) => {
         $crate::check!(@check ($($tokens)*) ($session))
@@ -166,40 +294,62 @@ macro_rules! check {
         // there's no reason to run 0 checks so we issue a error.
         compile_error!("There's no reason in running check with no arguments. Please supply a check branches")
     };
-    (@case $session:expr, ($var:tt = $exp:expr => $body:tt, $($tail:tt)*), ($($head:tt)*), ($($default:tt)*)) => {
+    (@case $session:tt, (eof => $body:tt, $($tail:tt)*), ($($head:tt)*), ($($default:tt)*), ($($errors:tt)*)) => {
+        // An EOF branch
+        $crate::check!(@case $session, ($($tail)*), ($($head)*), ($($default)*), ($($errors)* eof => $body, ))
+    };
+    (@case $session:tt, (eof => $body:tt $($tail:tt)*), ($($head:tt)*), ($($default:tt)*), ($($errors:tt)*)) => {
+        // An EOF branch
+        // allow missed comma `,`
+        $crate::check!(@case $session, ($($tail)*), ($($head)*), ($($default)*), ($($errors)* eof => $body, ))
+    };
+    (@case $session:tt, (timeout => $body:tt, $($tail:tt)*), ($($head:tt)*), ($($default:tt)*), ($($errors:tt)*)) => {
+        // A timeout branch
+        $crate::check!(@case $session, ($($tail)*), ($($head)*), ($($default)*), ($($errors)* timeout => $body, ))
+    };
+    (@case $session:tt, (timeout => $body:tt $($tail:tt)*), ($($head:tt)*), ($($default:tt)*), ($($errors:tt)*)) => {
+        // A timeout branch
+        // allow missed comma `,`
+        $crate::check!(@case $session, ($($tail)*), ($($head)*), ($($default)*), ($($errors)* timeout => $body, ))
+    };
+    (@case $session:tt, ($var:tt = $exp:expr => $body:tt, $($tail:tt)*), ($($head:tt)*), ($($default:tt)*), ($($errors:tt)*)) => {
         // regular case
         //
         // note: we keep order correct by putting head at the beggining
-        $crate::check!(@case $session, ($($tail)*), ($($head)* $var = $exp => $body, ), ($($default)*))
+        $crate::check!(@case $session, ($($tail)*), ($($head)* $var = $exp => $body, ), ($($default)*), ($($errors)*))
     };
-    (@case $session:expr, ($var:tt = $exp:expr => $body:tt $($tail:tt)*), ($($head:tt)*), ($($default:tt)*)) => {
+    (@case $session:tt, ($var:tt = $exp:expr => $body:tt $($tail:tt)*), ($($head:tt)*), ($($default:tt)*), ($($errors:tt)*)) => {
         // allow missing comma
         //
         // note: we keep order correct by putting head at the beggining
-        $crate::check!(@case $session, ($($tail)*), ($($head)* $var = $exp => $body, ), ($($default)*))
+        $crate::check!(@case $session, ($($tail)*), ($($head)* $var = $exp => $body, ), ($($default)*), ($($errors)*))
     };
-    (@case $session:expr, (default => $($tail:tt)*), ($($head:tt)*), ($($default:tt)+)) => {
+    (@case $session:tt, (default => $($tail:tt)*), ($($head:tt)*), ($($default:tt)+), ($($errors:tt)*)) => {
         // A repeated default branch
         compile_error!("Only 1 default case is allowed")
     };
-    (@case $session:expr, (default => $body:tt, $($tail:tt)*), ($($head:tt)*), ()) => {
+    (@case $session:tt, (default => $body:tt, $($tail:tt)*), ($($head:tt)*), (), ($($errors:tt)*)) => {
         // A default branch
-        $crate::check!(@case $session, ($($tail)*), ($($head)*), ( { $body; #[allow(unreachable_code)] Ok(()) } ))
+        $crate::check!(@case $session, ($($tail)*), ($($head)*), ( { $body; #[allow(unreachable_code)] Ok(()) } ), ($($errors)*))
     };
-    (@case $session:expr, (default => $body:tt $($tail:tt)*), ($($head:tt)*), ()) => {
+    (@case $session:tt, (default => $body:tt $($tail:tt)*), ($($head:tt)*), (), ($($errors:tt)*)) => {
         // A default branch
         // allow missed comma `,`
-        $crate::check!(@case $session, ($($tail)*), ($($head)*), ( { $body; Ok(()) } ))
+        $crate::check!(@case $session, ($($tail)*), ($($head)*), ( { $body; Ok(()) } ), ($($errors)*))
     };
-    (@case $session:expr, (), ($($head:tt)*), ()) => {
+    (@case $session:tt, (), ($($head:tt)*), (), ($($errors:tt)*)) => {
         // there's no default branch
         // so we make up our own.
-        $crate::check!(@case $session, (), ($($head)*), ( { Ok(()) } ))
+        $crate::check!(@case $session, (), ($($head)*), ( { Ok(()) } ), ($($errors)*))
     };
-    (@case $session:expr, (), ($($tail:tt)*), ($($default:tt)*)) => {
+    (@case $session:tt, (), ($($tail:tt)*), ($($default:tt)*), ($($errors:tt)*)) => {
         // last point of @case
         // call code generation via @branch
-        $crate::check!(@branch $session, ($($tail)*), ($($default)*))
+        // and pass its result to EOF and timeout branches via @errors
+        {
+            let result: Result::<(), $crate::Error> = async { $crate::check!(@branch $session, ($($tail)*), ($($default)*)) }.await;
+            $crate::check!(@errors result, ($($errors)*))
+        }
     };
     // We need to use a variable for pattern mathing,
     // user may chose to drop var name using a placeholder '_',
@@ -216,26 +366,94 @@ macro_rules! check {
     //
     // The question is which solution is more effichient.
     // I took the following approach because there's no chance we influence user's land via the variable name we pick.
-    (@branch $session:expr, ($var:tt = $exp:expr => $body:tt, $($tail:tt)*), ($($default:tt)*)) => {
-        match $crate::session::Session::check(&mut $session, $exp).await {
-            Ok(found) => {
-                if !found.is_empty() {
-                    let $var = found;
-                    $body;
-                    #[allow(unreachable_code)]
-                    return Ok(())
-                }
-
+    (@branch $session:tt, ($var:tt = $exp:expr => $body:tt, $($tail:tt)*), ($($default:tt)*)) => {
+        match $crate::check!(@find $session, $exp) {
+            Ok(Some(found)) => {
+                $crate::check!(@bind $var, found);
+                $body;
+                #[allow(unreachable_code)]
+                return Ok(())
+            }
+            Ok(None) => {
                 $crate::check!(@branch $session, ($($tail)*), ($($default)*))
             }
             Err(err) => Err(err),
         }
     };
-    (@branch $session:expr, (), ($default:tt)) => {
+    (@branch $session:tt, (), ($default:tt)) => {
         // A standart default branch
         $default
     };
-    (@branch $session:expr, ($($tail:tt)*), ($($default:tt)*)) => {
+    (@find ($session:expr), $exp:expr) => {
+        match $crate::session::Session::check(&mut $session, $exp).await {
+            Ok(found) if !found.is_empty() => Ok(Some(found)),
+            Ok(_) => Ok(None),
+            Err(err) => Err(err),
+        }
+    };
+    (@find [$($session:expr),+], $exp:expr) => {
+        // checks sessions one by one,
+        // EOF is reported only when it's reached by all of them.
+        {
+            let mut index = 0;
+            let mut eof = 0;
+            let mut result = Ok(None);
+            $(
+                if matches!(result, Ok(None)) {
+                    match $crate::session::Session::check(&mut $session, $exp).await {
+                        Ok(found) if !found.is_empty() => result = Ok(Some((index, found))),
+                        Ok(_) => {}
+                        Err($crate::Error::Eof) => eof += 1,
+                        Err(err) => result = Err(err),
+                    }
+                }
+
+                index += 1;
+            )+
+
+            match result {
+                Ok(None) if eof == index => Err($crate::Error::Eof),
+                result => result,
+            }
+        }
+    };
+    (@bind [$($group:tt),* $(,)?], $found:ident) => {
+        // bind regex groups
+        let [$($group),*] = $found.groups();
+    };
+    (@bind $var:tt, $found:ident) => {
+        let $var = $found;
+    };
+    (@timeout ($session:expr)) => {
+        $crate::session::Session::get_expect_timeout(&$session)
+    };
+    (@timeout [$session:expr $(, $tail:expr)*]) => {
+        $crate::session::Session::get_expect_timeout(&$session)
+    };
+    (@errors $result:ident, (eof => $body:tt, $($tail:tt)*)) => {
+        match $result {
+            Err($crate::Error::Eof) => {
+                $body;
+                #[allow(unreachable_code)]
+                Ok(())
+            }
+            result => $crate::check!(@errors result, ($($tail)*)),
+        }
+    };
+    (@errors $result:ident, (timeout => $body:tt, $($tail:tt)*)) => {
+        match $result {
+            Err($crate::Error::ExpectTimeout) => {
+                $body;
+                #[allow(unreachable_code)]
+                Ok(())
+            }
+            result => $crate::check!(@errors result, ($($tail)*)),
+        }
+    };
+    (@errors $result:ident, ()) => {
+        $result
+    };
+    (@branch $session:tt, ($($tail:tt)*), ($($default:tt)*)) => {
         compile_error!(
             concat!(
                 "No supported syntax tail=(",
@@ -255,6 +473,326 @@ macro_rules! check {
     };
 }
 
+/// Expect macros waits until one of the patterns is matched and falls into a corresponding branch.
+///
+/// It has the same syntax as [crate::check!] but it blocks until any of the patterns is available
+/// or a timeout is reached, the one set via [crate::Session::set_expect_timeout].
+/// Matched bytes are consumed.
+///
+/// A `default` branch is not supported;
+/// you can specify `eof` and `timeout` branches instead.
+/// Otherwise these errors are returned as a result.
+///
+/// Branches are run outside of the waiting loop,
+/// so `break` and `continue` can be used within them.
+///
+/// Several sessions can be awaited at once by `in [a, b]` syntax, like in [crate::check!].
+/// The timeout of the first session is used.
+///
+/// Between rounds of checks it blocks until the first session gets an output,
+/// but not longer than a short tick, so other sessions are checked in time as well.
+///
+/// # Example
+/// ```no_run
+/// # let mut session = expectrl::spawn("cat").unwrap();
+/// #
+/// expectrl::expect!{
+///     &mut session,
+///     _ = "Hello World" => {
+///         // handle Hello World
+///     },
+///     eof => {
+///         // handle end of output
+///     },
+///     timeout => {
+///         // handle no matches in time
+///     },
+/// }
+/// .unwrap();
+/// ```
+#[cfg(not(feature = "async"))]
+#[macro_export]
+macro_rules! expect {
+    (@expect ($($tokens:tt)*) ($session:expr)) => {
+        $crate::expect!(@case ($session), ($($tokens)*), (), ())
+    };
+    (@expect (in [$($session:expr),+ $(,)?], $($tokens:tt)*) ()) => {
+        // multiple sessions
+        $crate::expect!(@case [$($session),+], ($($tokens)*), (), ())
+    };
+    (@expect ($session:expr, $($tokens:tt)*) ()) => {
+        $crate::expect!(@expect ($($tokens)*) ($session))
+    };
+    (@expect ($session:expr, $($tokens:tt)*) ($session2:expr)) => {
+        compile_error!("Wrong number of session arguments")
+    };
+    (@expect ($($tokens:tt)*) ()) => {
+        compile_error!("Please provide a session as a first argument")
+    };
+    (@case $session:tt, (eof => $body:tt, $($tail:tt)*), ($($head:tt)*), ($($errors:tt)*)) => {
+        // An EOF branch
+        $crate::expect!(@case $session, ($($tail)*), ($($head)*), ($($errors)* eof => $body, ))
+    };
+    (@case $session:tt, (eof => $body:tt $($tail:tt)*), ($($head:tt)*), ($($errors:tt)*)) => {
+        // An EOF branch
+        // allow missed comma `,`
+        $crate::expect!(@case $session, ($($tail)*), ($($head)*), ($($errors)* eof => $body, ))
+    };
+    (@case $session:tt, (timeout => $body:tt, $($tail:tt)*), ($($head:tt)*), ($($errors:tt)*)) => {
+        // A timeout branch
+        $crate::expect!(@case $session, ($($tail)*), ($($head)*), ($($errors)* timeout => $body, ))
+    };
+    (@case $session:tt, (timeout => $body:tt $($tail:tt)*), ($($head:tt)*), ($($errors:tt)*)) => {
+        // A timeout branch
+        // allow missed comma `,`
+        $crate::expect!(@case $session, ($($tail)*), ($($head)*), ($($errors)* timeout => $body, ))
+    };
+    (@case $session:tt, (default => $($tail:tt)*), ($($head:tt)*), ($($errors:tt)*)) => {
+        compile_error!("A default branch is not supported, please use `timeout` branch instead")
+    };
+    (@case $session:tt, ($var:tt = $exp:expr => $body:tt, $($tail:tt)*), ($($head:tt)*), ($($errors:tt)*)) => {
+        // regular case
+        //
+        // note: we keep order correct by putting head at the beggining
+        $crate::expect!(@case $session, ($($tail)*), ($($head)* $var = $exp => $body, ), ($($errors)*))
+    };
+    (@case $session:tt, ($var:tt = $exp:expr => $body:tt $($tail:tt)*), ($($head:tt)*), ($($errors:tt)*)) => {
+        // allow missing comma
+        //
+        // note: we keep order correct by putting head at the beggining
+        $crate::expect!(@case $session, ($($tail)*), ($($head)* $var = $exp => $body, ), ($($errors)*))
+    };
+    (@case $session:tt, (), (), ($($errors:tt)*)) => {
+        compile_error!("There's no reason in running expect with no patterns. Please supply a branch with a pattern")
+    };
+    (@case $session:tt, (), ($($head:tt)*), ($($errors:tt)*)) => {
+        // last point of @case
+        //
+        // we wait for a match in a loop via @branch,
+        // then run a branch via @dispatch outside of the loop
+        // so user's `break` and `continue` are not affected.
+        {
+            let timeout = $crate::check!(@timeout $session);
+            let start = ::std::time::Instant::now();
+            #[allow(unused_assignments)]
+            let result: Result::<(usize, _), $crate::Error> = loop {
+                let mut index = 0;
+                let mut eof = false;
+                $crate::expect!(@branch $session, index, eof, ($($head)*));
+
+                if eof {
+                    break Err($crate::Error::Eof);
+                }
+
+                if let Some(timeout) = timeout {
+                    if start.elapsed() > timeout {
+                        break Err($crate::Error::ExpectTimeout);
+                    }
+                }
+
+                let limit = timeout.map(|timeout| timeout.saturating_sub(start.elapsed()));
+                if let Err(err) = $crate::expect!(@wait $session, limit) {
+                    break Err($crate::Error::from(err));
+                }
+            };
+
+            let result: Result::<(), $crate::Error> = match result {
+                Ok((index, found)) => {
+                    $crate::expect!(@dispatch found, index, ($($head)*));
+                    #[allow(unreachable_code)]
+                    Ok(())
+                }
+                Err(err) => Err(err),
+            };
+
+            $crate::check!(@errors result, ($($errors)*))
+        }
+    };
+    (@branch $session:tt, $index:ident, $eof:ident, ($var:tt = $exp:expr => $body:tt, $($tail:tt)*)) => {
+        match $crate::check!(@find $session, $exp) {
+            Ok(Some(found)) => break Ok(($index, found)),
+            Ok(None) => {}
+            Err($crate::Error::Eof) => $eof = true,
+            Err(err) => break Err(err),
+        }
+
+        $index += 1;
+        $crate::expect!(@branch $session, $index, $eof, ($($tail)*));
+    };
+    (@branch $session:tt, $index:ident, $eof:ident, ()) => {};
+    (@wait ($session:expr), $limit:expr) => {
+        $crate::session::Session::__wait_output($session, $limit)
+    };
+    (@wait [$session:expr $(, $tail:expr)*], $limit:expr) => {
+        // only the first session is awaited,
+        // the others are checked again once it's ready or a short tick passes
+        $crate::session::Session::__wait_output($session, $limit)
+    };
+    (@dispatch $found:ident, $index:ident, ($var:tt = $exp:expr => $body:tt, $($tail:tt)*)) => {
+        if $index == 0 {
+            $crate::check!(@bind $var, $found);
+            $body;
+        } else {
+            let $index = $index - 1;
+            $crate::expect!(@dispatch $found, $index, ($($tail)*));
+        }
+    };
+    (@dispatch $found:ident, $index:ident, ()) => {
+        unreachable!()
+    };
+    // Entry point
+    ($($tokens:tt)*) => {
+        {
+            let result: Result::<(), $crate::Error> = $crate::expect!(@expect ($($tokens)*) ());
+            result
+        }
+    };
+}
+
+/// See sync version.
+///
+/// Async version of macros use the same approach as sync.
+// async version completely the same as sync version expect 2 words '.await' and 'async'
+// meaning its a COPY && PASTE
+#[cfg(feature = "async")]
+#[macro_export]
+macro_rules! expect {
+    (@expect ($($tokens:tt)*) ($session:expr)) => {
+        $crate::expect!(@case ($session), ($($tokens)*), (), ())
+    };
+    (@expect (in [$($session:expr),+ $(,)?], $($tokens:tt)*) ()) => {
+        // multiple sessions
+        $crate::expect!(@case [$($session),+], ($($tokens)*), (), ())
+    };
+    (@expect ($session:expr, $($tokens:tt)*) ()) => {
+        $crate::expect!(@expect ($($tokens)*) ($session))
+    };
+    (@expect ($session:expr, $($tokens:tt)*) ($session2:expr)) => {
+        compile_error!("Wrong number of session arguments")
+    };
+    (@expect ($($tokens:tt)*) ()) => {
+        compile_error!("Please provide a session as a first argument")
+    };
+    (@case $session:tt, (eof => $body:tt, $($tail:tt)*), ($($head:tt)*), ($($errors:tt)*)) => {
+        // An EOF branch
+        $crate::expect!(@case $session, ($($tail)*), ($($head)*), ($($errors)* eof => $body, ))
+    };
+    (@case $session:tt, (eof => $body:tt $($tail:tt)*), ($($head:tt)*), ($($errors:tt)*)) => {
+        // An EOF branch
+        // allow missed comma `,`
+        $crate::expect!(@case $session, ($($tail)*), ($($head)*), ($($errors)* eof => $body, ))
+    };
+    (@case $session:tt, (timeout => $body:tt, $($tail:tt)*), ($($head:tt)*), ($($errors:tt)*)) => {
+        // A timeout branch
+        $crate::expect!(@case $session, ($($tail)*), ($($head)*), ($($errors)* timeout => $body, ))
+    };
+    (@case $session:tt, (timeout => $body:tt $($tail:tt)*), ($($head:tt)*), ($($errors:tt)*)) => {
+        // A timeout branch
+        // allow missed comma `,`
+        $crate::expect!(@case $session, ($($tail)*), ($($head)*), ($($errors)* timeout => $body, ))
+    };
+    (@case $session:tt, (default => $($tail:tt)*), ($($head:tt)*), ($($errors:tt)*)) => {
+        compile_error!("A default branch is not supported, please use `timeout` branch instead")
+    };
+    (@case $session:tt, ($var:tt = $exp:expr => $body:tt, $($tail:tt)*), ($($head:tt)*), ($($errors:tt)*)) => {
+        // regular case
+        //
+        // note: we keep order correct by putting head at the beggining
+        $crate::expect!(@case $session, ($($tail)*), ($($head)* $var = $exp => $body, ), ($($errors)*))
+    };
+    (@case $session:tt, ($var:tt = $exp:expr => $body:tt $($tail:tt)*), ($($head:tt)*), ($($errors:tt)*)) => {
+        // allow missing comma
+        //
+        // note: we keep order correct by putting head at the beggining
+        $crate::expect!(@case $session, ($($tail)*), ($($head)* $var = $exp => $body, ), ($($errors)*))
+    };
+    (@case $session:tt, (), (), ($($errors:tt)*)) => {
+        compile_error!("There's no reason in running expect with no patterns. Please supply a branch with a pattern")
+    };
+    (@case $session:tt, (), ($($head:tt)*), ($($errors:tt)*)) => {
+        // last point of @case
+        //
+        // we wait for a match in a loop via @branch,
+        // then run a branch via @dispatch outside of the loop
+        // so user's `break` and `continue` are not affected.
+        {
+            let timeout = $crate::check!(@timeout $session);
+            let start = ::std::time::Instant::now();
+            #[allow(unused_assignments)]
+            let result: Result::<(usize, _), $crate::Error> = loop {
+                let mut index = 0;
+                let mut eof = false;
+                $crate::expect!(@branch $session, index, eof, ($($head)*));
+
+                if eof {
+                    break Err($crate::Error::Eof);
+                }
+
+                if let Some(timeout) = timeout {
+                    if start.elapsed() > timeout {
+                        break Err($crate::Error::ExpectTimeout);
+                    }
+                }
+
+                let limit = timeout.map(|timeout| timeout.saturating_sub(start.elapsed()));
+                if let Err(err) = $crate::expect!(@wait $session, limit) {
+                    break Err($crate::Error::from(err));
+                }
+            };
+
+            let result: Result::<(), $crate::Error> = match result {
+                Ok((index, found)) => {
+                    $crate::expect!(@dispatch found, index, ($($head)*));
+                    #[allow(unreachable_code)]
+                    Ok(())
+                }
+                Err(err) => Err(err),
+            };
+
+            $crate::check!(@errors result, ($($errors)*))
+        }
+    };
+    (@branch $session:tt, $index:ident, $eof:ident, ($var:tt = $exp:expr => $body:tt, $($tail:tt)*)) => {
+        match $crate::check!(@find $session, $exp) {
+            Ok(Some(found)) => break Ok(($index, found)),
+            Ok(None) => {}
+            Err($crate::Error::Eof) => $eof = true,
+            Err(err) => break Err(err),
+        }
+
+        $index += 1;
+        $crate::expect!(@branch $session, $index, $eof, ($($tail)*));
+    };
+    (@branch $session:tt, $index:ident, $eof:ident, ()) => {};
+    (@wait ($session:expr), $limit:expr) => {
+        $crate::session::Session::__wait_output(&mut $session, $limit).await
+    };
+    (@wait [$session:expr $(, $tail:expr)*], $limit:expr) => {
+        // only the first session is awaited,
+        // the others are checked again once it's ready or a short tick passes
+        $crate::session::Session::__wait_output(&mut $session, $limit).await
+    };
+    (@dispatch $found:ident, $index:ident, ($var:tt = $exp:expr => $body:tt, $($tail:tt)*)) => {
+        if $index == 0 {
+            $crate::check!(@bind $var, $found);
+            $body;
+        } else {
+            let $index = $index - 1;
+            $crate::expect!(@dispatch $found, $index, ($($tail)*));
+        }
+    };
+    (@dispatch $found:ident, $index:ident, ()) => {
+        unreachable!()
+    };
+    // Entry point
+    ($($tokens:tt)*) => {
+        async {
+            let result: Result::<(), $crate::Error> = $crate::expect!(@expect ($($tokens)*) ());
+            result
+        }
+    };
+}
+
 #[cfg(test)]
 mod tests {
     #[allow(unused_variables)]
@@ -293,6 +831,34 @@ mod tests {
             as11d = "zxc" => {}
             default => {}
         };
+        crate::check! {
+            &mut session,
+            as11d = "zxc" => {},
+            eof => {},
+            timeout => {},
+            default => {},
+        };
+        crate::check! {
+            &mut session,
+            eof => {}
+            as11d = "zxc" => {}
+            timeout => {}
+        };
+
+        crate::check! {
+            &mut session,
+            [_, port] = crate::Regex("port (\\d+)") => {
+                let _: Option<&[u8]> = port;
+            },
+            [all] = "zxc" => {},
+        };
+
+        let mut session2 = crate::spawn("").unwrap();
+        crate::check! {
+            in [&mut session, &mut session2],
+            (index, m) = "zxc" => {},
+            default => {},
+        };
 
         #[cfg(not(feature = "async"))]
         {
@@ -349,4 +915,68 @@ mod tests {
             .unwrap();
         };
     }
+
+    #[allow(unused_variables)]
+    #[allow(unused_must_use)]
+    #[test]
+    #[ignore = "Testing in compile time"]
+    fn test_expect() {
+        let mut session = crate::spawn("").unwrap();
+
+        #[cfg(not(feature = "async"))]
+        {
+            crate::expect! {
+                &mut session,
+                as11d = "zxc" => {},
+            }
+            .unwrap();
+            crate::expect! {
+                &mut session,
+                as11d = "zxc" => {}
+                asbb = "zxc123" => {}
+                eof => {}
+                timeout => {}
+            }
+            .unwrap();
+
+            let mut session2 = crate::spawn("").unwrap();
+            crate::expect! {
+                in [&mut session, &mut session2,],
+                (index, m) = "zxc" => {},
+                eof => {}
+            }
+            .unwrap();
+
+            loop {
+                crate::expect! {
+                    &mut session,
+                    as11d = "zxc" => {
+                        continue;
+                    },
+                    eof => {
+                        break;
+                    },
+                }
+                .unwrap();
+            }
+        }
+        #[cfg(feature = "async")]
+        async {
+            crate::expect! {
+                session,
+                as11d = "zxc" => {},
+            }
+            .await
+            .unwrap();
+            crate::expect! {
+                session,
+                as11d = "zxc" => {}
+                asbb = "zxc123" => {}
+                eof => {}
+                timeout => {}
+            }
+            .await
+            .unwrap();
+        };
+    }
 }
@@ -0,0 +1,265 @@
+//! This module contains a [`Dialogue`] which runs a list of expect and send steps against a session.
+//!
+//! # Example
+//!
+#![cfg_attr(all(unix, not(feature = "async")), doc = "```no_run")]
+#![cfg_attr(not(all(unix, not(feature = "async"))), doc = "```ignore")]
+//! use expectrl::{dialogue::Dialogue, spawn, Regex};
+//!
+//! let dialogue = Dialogue::new()
+//!     .expect("login:")
+//!     .send_line("root")
+//!     .expect("Password:")
+//!     .send_secret("qwerty")
+//!     .expect(Regex(r"\$ $"));
+//!
+//! let mut session = spawn("telnet 192.168.0.1").unwrap();
+//! let report = dialogue.run(&mut session).unwrap();
+//!
+//! for step in report.steps() {
+//!     println!("{} took {:?}", step.description, step.elapsed);
+//! }
+//! ```
+
+use std::{
+    fmt,
+    time::{Duration, Instant},
+};
+
+use crate::{secret::Password, Captures, Error, Needle, Session};
+
+#[cfg(not(feature = "async"))]
+use crate::process::NonBlocking;
+#[cfg(not(feature = "async"))]
+use std::io::{Read, Write};
+
+#[cfg(feature = "async")]
+use futures_lite::{AsyncRead, AsyncWrite};
+
+/// Dialogue is a list of steps which are run one by one.
+///
+/// A failure of a step stops the dialogue,
+/// the error is labeled by the step which failed, see [`Error::root`].
+#[derive(Default)]
+pub struct Dialogue {
+    steps: Vec<Step>,
+}
+
+enum Step {
+    Expect(Box<dyn Needle>, String),
+    Send(Vec<u8>, String),
+    SendSecret(String, String),
+}
+
+impl Dialogue {
+    /// Creates an empty dialogue.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a step which waits until a needle is matched.
+    pub fn expect<N>(mut self, needle: N) -> Self
+    where
+        N: Needle + fmt::Debug + 'static,
+    {
+        let description = format!("expect {:?}", needle);
+        self.steps.push(Step::Expect(Box::new(needle), description));
+        self
+    }
+
+    /// Adds a step which sends bytes.
+    pub fn send<B: AsRef<[u8]>>(mut self, buf: B) -> Self {
+        let buf = buf.as_ref().to_vec();
+        let description = format!("send {:?}", String::from_utf8_lossy(&buf));
+        self.steps.push(Step::Send(buf, description));
+        self
+    }
+
+    /// Adds a step which sends a line.
+    pub fn send_line<B: AsRef<[u8]>>(self, buf: B) -> Self {
+        #[cfg(windows)]
+        const LINE_ENDING: &[u8] = b"\r\n";
+        #[cfg(not(windows))]
+        const LINE_ENDING: &[u8] = b"\n";
+
+        let mut line = buf.as_ref().to_vec();
+        line.extend(LINE_ENDING);
+        self.send(line)
+    }
+
+    /// Adds a step which sends a secret followed by a line ending.
+    ///
+    /// The secret is sent by [`Session::send_secret`],
+    /// and it's never included in a step description.
+    pub fn send_secret<PW: Password + ?Sized>(mut self, secret: &PW) -> Self {
+        let description = String::from("send <secret>");
+        self.steps
+            .push(Step::SendSecret(secret.expose().to_owned(), description));
+        self
+    }
+
+    /// Returns a number of steps.
+    pub fn len(&self) -> usize {
+        self.steps.len()
+    }
+
+    /// Verifies whether there's no steps.
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+
+    /// Runs the dialogue against a session.
+    ///
+    /// Each expect step uses the session's timeout.
+    #[cfg(not(feature = "async"))]
+    pub fn run<P, S>(&self, session: &mut Session<P, S>) -> Result<Report, Error>
+    where
+        S: Read + Write + NonBlocking,
+    {
+        let mut report = Report::default();
+        for (i, step) in self.steps.iter().enumerate() {
+            let start = Instant::now();
+            let result = match step {
+                Step::Expect(needle, _) => session.expect(needle).map(Some),
+                Step::Send(buf, _) => session.send(buf).map(|_| None).map_err(Error::from),
+                Step::SendSecret(secret, _) => session
+                    .send_secret(secret)
+                    .map(|_| None)
+                    .map_err(Error::from),
+            };
+
+            report.push(self.check_step(i, step, start, result)?);
+        }
+
+        Ok(report)
+    }
+
+    /// Runs the dialogue against a session.
+    ///
+    /// Each expect step uses the session's timeout.
+    #[cfg(feature = "async")]
+    pub async fn run<P, S>(&self, session: &mut Session<P, S>) -> Result<Report, Error>
+    where
+        S: AsyncRead + AsyncWrite + Unpin,
+    {
+        let mut report = Report::default();
+        for (i, step) in self.steps.iter().enumerate() {
+            let start = Instant::now();
+            let result = match step {
+                Step::Expect(needle, _) => session.expect(needle).await.map(Some),
+                Step::Send(buf, _) => session.send(buf).await.map(|_| None).map_err(Error::from),
+                Step::SendSecret(secret, _) => session
+                    .send_secret(secret)
+                    .await
+                    .map(|_| None)
+                    .map_err(Error::from),
+            };
+
+            report.push(self.check_step(i, step, start, result)?);
+        }
+
+        Ok(report)
+    }
+
+    fn check_step(
+        &self,
+        index: usize,
+        step: &Step,
+        start: Instant,
+        result: Result<Option<Captures>, Error>,
+    ) -> Result<StepReport, Error> {
+        let description = match step {
+            Step::Expect(_, description)
+            | Step::Send(_, description)
+            | Step::SendSecret(_, description) => description,
+        };
+
+        match result {
+            Ok(captures) => Ok(StepReport {
+                index,
+                description: description.clone(),
+                elapsed: start.elapsed(),
+                captures,
+            }),
+            Err(err) => Err(err.context(format!(
+                "step {} of {} ({}) failed",
+                index + 1,
+                self.steps.len(),
+                description
+            ))),
+        }
+    }
+}
+
+impl fmt::Debug for Dialogue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let steps = self.steps.iter().map(|step| match step {
+            Step::Expect(_, description)
+            | Step::Send(_, description)
+            | Step::SendSecret(_, description) => description,
+        });
+
+        f.debug_list().entries(steps).finish()
+    }
+}
+
+/// Report contains results of all steps of a [`Dialogue`].
+#[derive(Debug, Default)]
+pub struct Report {
+    steps: Vec<StepReport>,
+}
+
+impl Report {
+    /// Returns reports of steps.
+    pub fn steps(&self) -> &[StepReport] {
+        &self.steps
+    }
+
+    /// Returns captures of a given step if it's an expect step.
+    pub fn captures(&self, step: usize) -> Option<&Captures> {
+        self.steps.get(step)?.captures.as_ref()
+    }
+
+    /// Returns a total time the dialogue took.
+    pub fn elapsed(&self) -> Duration {
+        self.steps.iter().map(|step| step.elapsed).sum()
+    }
+
+    fn push(&mut self, step: StepReport) {
+        self.steps.push(step);
+    }
+}
+
+/// A result of a single step of a [`Dialogue`].
+#[derive(Debug)]
+pub struct StepReport {
+    /// An index of the step.
+    pub index: usize,
+    /// A description of the step, e.g. `expect "login:"`.
+    pub description: String,
+    /// A time the step took.
+    pub elapsed: Duration,
+    /// Captures of an expect step.
+    pub captures: Option<Captures>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Regex;
+
+    #[test]
+    fn test_descriptions() {
+        let dialogue = Dialogue::new()
+            .expect("login:")
+            .send("root")
+            .expect(Regex("Pass.*:"))
+            .send_secret("qwerty");
+
+        assert_eq!(dialogue.len(), 4);
+        assert_eq!(
+            format!("{:?}", dialogue),
+            r#"["expect \"login:\"", "send \"root\"", "expect Regex(\"Pass.*:\")", "send <secret>"]"#
+        );
+    }
+}
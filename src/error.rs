@@ -24,9 +24,52 @@ pub enum Error {
         /// An underlying error message.
         err: String,
     },
+    /// An error which happened at a labeled step.
+    ///
+    /// See `Session::expect_ctx`.
+    Context {
+        /// A human readable name of the step.
+        label: String,
+        /// An underlying error.
+        err: Box<Error>,
+    },
 }
 
 impl Error {
+    /// Attaches a human readable label to the error.
+    pub fn context(self, label: impl Into<String>) -> Error {
+        Self::Context {
+            label: label.into(),
+            err: Box::new(self),
+        }
+    }
+
+    /// Returns the error without any labels attached by [Error::context].
+    pub fn root(&self) -> &Error {
+        match self {
+            Error::Context { err, .. } => err.root(),
+            err => err,
+        }
+    }
+
+    /// Adds a name of a session to a message of the error.
+    ///
+    /// A kind of the error is kept, so errors without a message, e.g. [Error::Eof], are left as they are.
+    pub(crate) fn with_name(self, name: &str) -> Error {
+        match self {
+            Error::IO(err) => Error::IO(io::Error::new(err.kind(), format!("{}: {}", name, err))),
+            Error::Other { message, err } => Error::Other {
+                message: format!("{}: {}", name, message),
+                err,
+            },
+            Error::Context { label, err } => Error::Context {
+                label: format!("{}: {}", name, label),
+                err,
+            },
+            err => err,
+        }
+    }
+
     #[cfg(unix)]
     pub(crate) fn unknown(message: impl Into<String>, err: impl Into<String>) -> Error {
         Self::Other {
@@ -45,11 +88,42 @@ impl Display for Error {
             Error::ExpectTimeout => write!(f, "Reached a timeout for expect type of command"),
             Error::Eof => write!(f, "EOF was reached; the read may successed later"),
             Error::Other { message, err } => write!(f, "Unexpected error; {}; {}", message, err),
+            Error::Context { label, err } => write!(f, "{}: {}", label, err),
         }
     }
 }
 
-impl error::Error for Error {}
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Error::Context { err, .. } => Some(err.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Error {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+
+        // A label is kept in a message while a kind is taken from the underlying error.
+        let kind = match self.root() {
+            Error::IO(_) => "io",
+            Error::CommandParsing => "command_parsing",
+            Error::RegexParsing => "regex_parsing",
+            Error::ExpectTimeout => "expect_timeout",
+            Error::Eof => "eof",
+            Error::Other { .. } => "other",
+            Error::Context { .. } => "context",
+        };
+
+        let mut s = serializer.serialize_struct("Error", 2)?;
+        s.serialize_field("kind", kind)?;
+        s.serialize_field("message", &self.to_string())?;
+        s.end()
+    }
+}
 
 impl From<io::Error> for Error {
     fn from(err: io::Error) -> Self {
@@ -66,3 +140,42 @@ impl From<Error> for io::Error {
 pub(crate) fn to_io_error<E: Display>(message: &'static str) -> impl FnOnce(E) -> io::Error {
     move |e: E| io::Error::new(io::ErrorKind::Other, format!("{}; {}", message, e))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_with_name() {
+        assert!(matches!(Error::Eof.with_name("router-1"), Error::Eof));
+
+        let err = Error::IO(io::ErrorKind::BrokenPipe.into()).with_name("router-1");
+        assert!(matches!(&err, Error::IO(err) if err.kind() == io::ErrorKind::BrokenPipe));
+        assert!(err.to_string().starts_with("IO error router-1: "));
+
+        let err = Error::ExpectTimeout.context("login").with_name("router-1");
+        assert!(matches!(err.root(), Error::ExpectTimeout));
+        assert_eq!(
+            err.to_string(),
+            "router-1: login: Reached a timeout for expect type of command"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_serialize() {
+        assert_eq!(
+            serde_json::to_string(&Error::ExpectTimeout).unwrap(),
+            r#"{"kind":"expect_timeout","message":"Reached a timeout for expect type of command"}"#
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_serialize_context() {
+        assert_eq!(
+            serde_json::to_string(&Error::ExpectTimeout.context("login")).unwrap(),
+            r#"{"kind":"expect_timeout","message":"login: Reached a timeout for expect type of command"}"#
+        );
+    }
+}
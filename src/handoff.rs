@@ -0,0 +1,439 @@
+//! This module contains routines to hand a live [`Session`] over to another process.
+//!
+//! A master side of a pseudo terminal is passed via `SCM_RIGHTS` over a unix socket,
+//! together with a [`SessionDescriptor`] which contains a pid of a spawned process,
+//! an expect configuration and a not yet consumed output.
+//!
+//! # Example
+//!
+#![cfg_attr(not(feature = "async"), doc = "```no_run")]
+#![cfg_attr(feature = "async", doc = "```ignore")]
+//! use std::os::unix::net::UnixStream;
+//! use expectrl::{handoff::{recv_session, send_session}, spawn};
+//!
+//! let (worker1, worker2) = UnixStream::pair().unwrap();
+//!
+//! let session = spawn("cat").unwrap();
+//! send_session(&worker1, session).unwrap();
+//!
+//! // it's supposed to be done in another process.
+//! let mut session = recv_session(&worker2).unwrap();
+//! session.send_line("Hello World").unwrap();
+//! session.expect("Hello World").unwrap();
+//! ```
+
+use std::{
+    fs::File,
+    io::{self, IoSlice, IoSliceMut, Read, Write},
+    mem::ManuallyDrop,
+    os::unix::{
+        io::{AsRawFd, FromRawFd, OwnedFd, RawFd},
+        net::UnixStream,
+    },
+    time::Duration,
+};
+
+use nix::{
+    errno::Errno,
+    sys::{
+        signal::{kill, Signal},
+        socket::{recvmsg, sendmsg, ControlMessage, ControlMessageOwned, MsgFlags},
+    },
+    unistd::Pid,
+};
+
+use crate::{
+    process::Healthcheck,
+    pty::Master,
+    session::{OsProcess, Session},
+    Error,
+};
+
+/// A descriptor of a [`Session`] which is sent alongside a master side of a pseudo terminal.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SessionDescriptor {
+    /// A pid of a spawned process.
+    pub pid: i32,
+    /// An expect timeout of a session.
+    pub expect_timeout: Option<Duration>,
+    /// An expect algorithm of a session.
+    pub expect_lazy: bool,
+    /// An output which was read but not yet consumed.
+    pub buffer: Vec<u8>,
+}
+
+impl SessionDescriptor {
+    /// Encodes the descriptor.
+    pub fn encode(&self) -> Vec<u8> {
+        let timeout = match self.expect_timeout {
+            Some(timeout) => timeout.as_millis().to_string(),
+            None => String::from("none"),
+        };
+
+        let buffer = self
+            .buffer
+            .iter()
+            .map(|b| format!("{b:02x}"))
+            .collect::<String>();
+
+        format!(
+            "pid {}\ntimeout {}\nlazy {}\nbuffer {}\n",
+            self.pid, timeout, self.expect_lazy, buffer
+        )
+        .into_bytes()
+    }
+
+    /// Decodes a descriptor which was created by [`SessionDescriptor::encode`].
+    pub fn decode(buf: &[u8]) -> Result<Self, Error> {
+        let text = std::str::from_utf8(buf).map_err(|_| invalid_descriptor("not a utf-8"))?;
+
+        let mut descriptor = Self::default();
+        for line in text.lines() {
+            let (key, value) = line.split_once(' ').unwrap_or((line, ""));
+            match key {
+                "pid" => {
+                    descriptor.pid = value.parse().map_err(|_| invalid_descriptor(line))?;
+                }
+                "timeout" => {
+                    descriptor.expect_timeout = match value {
+                        "none" => None,
+                        value => value
+                            .parse()
+                            .map(Duration::from_millis)
+                            .map(Some)
+                            .map_err(|_| invalid_descriptor(line))?,
+                    };
+                }
+                "lazy" => {
+                    descriptor.expect_lazy = value.parse().map_err(|_| invalid_descriptor(line))?;
+                }
+                "buffer" => {
+                    descriptor.buffer = value
+                        .as_bytes()
+                        .chunks(2)
+                        .map(|b| {
+                            std::str::from_utf8(b)
+                                .ok()
+                                .filter(|b| b.len() == 2)
+                                .and_then(|b| u8::from_str_radix(b, 16).ok())
+                        })
+                        .collect::<Option<_>>()
+                        .ok_or_else(|| invalid_descriptor(line))?;
+                }
+                _ => return Err(invalid_descriptor(line)),
+            }
+        }
+
+        Ok(descriptor)
+    }
+}
+
+/// A process which was spawned by another process.
+///
+/// Notice that it can't be waited, because it's not a child of the current process.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ForeignProcess {
+    pid: Pid,
+}
+
+impl ForeignProcess {
+    /// Creates a handle for a process with a given pid.
+    pub fn new(pid: i32) -> Self {
+        Self {
+            pid: Pid::from_raw(pid),
+        }
+    }
+
+    /// Returns a pid of the process.
+    pub fn pid(&self) -> i32 {
+        self.pid.as_raw()
+    }
+
+    /// Sends a signal to the process.
+    pub fn kill(&self, signal: Signal) -> Result<(), Error> {
+        kill(self.pid, signal).map_err(|err| Error::IO(err.into()))
+    }
+}
+
+impl Healthcheck for ForeignProcess {
+    fn is_alive(&mut self) -> io::Result<bool> {
+        match kill(self.pid, None) {
+            Ok(()) => Ok(true),
+            Err(Errno::ESRCH) => Ok(false),
+            Err(err) => Err(err.into()),
+        }
+    }
+}
+
+/// Sends a session over a unix socket.
+///
+/// The spawned process is not terminated when the session is sent,
+/// it's expected to be controlled by a receiver.
+#[cfg(not(feature = "async"))]
+pub fn send_session<S>(socket: &UnixStream, session: Session<OsProcess, S>) -> Result<(), Error>
+where
+    S: AsRawFd + Read,
+{
+    let expect_timeout = session.get_expect_timeout();
+    let expect_lazy = session.is_expect_lazy();
+    let (process, stream, buffer) = session.into_parts();
+
+    send(socket, process, stream, expect_timeout, expect_lazy, buffer)
+}
+
+/// Sends a session over a unix socket.
+///
+/// The spawned process is not terminated when the session is sent,
+/// it's expected to be controlled by a receiver.
+#[cfg(feature = "async")]
+pub fn send_session<S>(socket: &UnixStream, session: Session<OsProcess, S>) -> Result<(), Error>
+where
+    S: AsRawFd,
+{
+    let expect_timeout = session.get_expect_timeout();
+    let expect_lazy = session.is_expect_lazy();
+    let (process, stream, buffer) = session.into_parts();
+
+    send(socket, process, stream, expect_timeout, expect_lazy, buffer)
+}
+
+/// Receives a session which was sent by [`send_session`].
+#[cfg(not(feature = "async"))]
+pub fn recv_session(socket: &UnixStream) -> Result<Session<ForeignProcess, Master>, Error> {
+    let (descriptor, master) = recv(socket)?;
+
+    let process = ForeignProcess::new(descriptor.pid);
+    let mut session = Session::new(process, master)?;
+    session.set_expect_timeout(descriptor.expect_timeout);
+    session.set_expect_lazy(descriptor.expect_lazy);
+    session.keep_in_buffer(&descriptor.buffer);
+
+    Ok(session)
+}
+
+/// Receives a session which was sent by [`send_session`].
+#[cfg(feature = "async")]
+pub fn recv_session(
+    socket: &UnixStream,
+) -> Result<Session<ForeignProcess, async_io::Async<Master>>, Error> {
+    let (descriptor, master) = recv(socket)?;
+
+    let process = ForeignProcess::new(descriptor.pid);
+    let mut session = Session::new(process, async_io::Async::new(master)?)?;
+    session.set_expect_timeout(descriptor.expect_timeout);
+    session.set_expect_lazy(descriptor.expect_lazy);
+    session.keep_in_buffer(&descriptor.buffer);
+
+    Ok(session)
+}
+
+fn send<S: AsRawFd>(
+    socket: &UnixStream,
+    process: OsProcess,
+    stream: S,
+    expect_timeout: Option<Duration>,
+    expect_lazy: bool,
+    buffer: Vec<u8>,
+) -> Result<(), Error> {
+    let descriptor = SessionDescriptor {
+        pid: process.pid().as_raw(),
+        expect_timeout,
+        expect_lazy,
+        buffer,
+    };
+
+    send_descriptor(socket, &descriptor, stream.as_raw_fd())?;
+
+    // Dropping the process would terminate it,
+    // so we intentionally leak it as it's owned by a receiver now.
+    let _ = ManuallyDrop::new(process);
+
+    Ok(())
+}
+
+fn send_descriptor(
+    socket: &UnixStream,
+    descriptor: &SessionDescriptor,
+    fd: RawFd,
+) -> Result<(), Error> {
+    let payload = descriptor.encode();
+    let length = (payload.len() as u32).to_le_bytes();
+    let iov = [IoSlice::new(&length), IoSlice::new(&payload)];
+    let fds = [fd];
+    let cmsg = [ControlMessage::ScmRights(&fds)];
+
+    let mut sent = sendmsg::<()>(socket.as_raw_fd(), &iov, &cmsg, MsgFlags::empty(), None)
+        .map_err(|err| Error::IO(err.into()))?;
+
+    // The rest of a message is sent as a regular data.
+    let message = [&length[..], &payload[..]].concat();
+    let mut writer = socket;
+    while sent < message.len() {
+        sent += writer.write(&message[sent..])?;
+    }
+
+    Ok(())
+}
+
+fn recv(socket: &UnixStream) -> Result<(SessionDescriptor, Master), Error> {
+    // Only a length prefix is received alongside the descriptors,
+    // so no bytes of a following message are consumed.
+    let mut length = [0; 4];
+    let mut cmsg_buffer = nix::cmsg_space!([RawFd; 1]);
+
+    let (received, fds, truncated) = {
+        let mut iov = [IoSliceMut::new(&mut length)];
+        let msg = recvmsg::<()>(
+            socket.as_raw_fd(),
+            &mut iov,
+            Some(&mut cmsg_buffer),
+            MsgFlags::MSG_CMSG_CLOEXEC,
+        )
+        .map_err(|err| Error::IO(err.into()))?;
+
+        let fds = msg
+            .cmsgs()
+            .filter_map(|cmsg| match cmsg {
+                ControlMessageOwned::ScmRights(fds) => Some(fds),
+                _ => None,
+            })
+            .flatten()
+            // SAFETY: the descriptors were just received so we own them.
+            .map(|fd| unsafe { OwnedFd::from_raw_fd(fd) })
+            .collect::<Vec<_>>();
+
+        (msg.bytes, fds, msg.flags.contains(MsgFlags::MSG_CTRUNC))
+    };
+
+    if truncated {
+        return Err(invalid_descriptor("control message was truncated"));
+    }
+
+    if received == 0 {
+        return Err(Error::Eof);
+    }
+
+    let fd = fds
+        .into_iter()
+        .next()
+        .ok_or_else(|| invalid_descriptor("no file descriptor was received"))?;
+
+    let mut reader = socket;
+    reader.read_exact(&mut length[received..])?;
+
+    let length = u32::from_le_bytes(length) as usize;
+    let mut message = vec![0; length];
+    reader.read_exact(&mut message)?;
+
+    let descriptor = SessionDescriptor::decode(&message)?;
+    let master = Master::from_file(File::from(fd))?;
+
+    Ok((descriptor, master))
+}
+
+fn invalid_descriptor(reason: &str) -> Error {
+    Error::IO(io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("Invalid session descriptor: {reason}"),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_descriptor() {
+        let descriptor = SessionDescriptor {
+            pid: 123,
+            expect_timeout: Some(Duration::from_millis(1500)),
+            expect_lazy: true,
+            buffer: b"Hello\r\n".to_vec(),
+        };
+
+        let decoded = SessionDescriptor::decode(&descriptor.encode()).unwrap();
+        assert_eq!(decoded, descriptor);
+
+        let descriptor = SessionDescriptor {
+            expect_timeout: None,
+            ..Default::default()
+        };
+
+        let decoded = SessionDescriptor::decode(&descriptor.encode()).unwrap();
+        assert_eq!(decoded, descriptor);
+    }
+
+    #[test]
+    fn test_descriptor_invalid() {
+        assert!(SessionDescriptor::decode(b"pid abc\n").is_err());
+        assert!(SessionDescriptor::decode(b"buffer 0\n").is_err());
+        assert!(SessionDescriptor::decode(b"unknown 1\n").is_err());
+    }
+
+    #[cfg(not(feature = "async"))]
+    #[test]
+    fn test_send_recv() {
+        use std::process::Command;
+
+        let (sender, receiver) = UnixStream::pair().unwrap();
+
+        let master = Master::open().unwrap();
+        let slave = master.open_slave().unwrap();
+        let mut child = slave.spawn(Command::new("cat")).unwrap();
+        drop(slave);
+
+        let descriptor = SessionDescriptor {
+            pid: child.id() as i32,
+            expect_timeout: Some(Duration::from_secs(5)),
+            expect_lazy: false,
+            buffer: b"Hello ".to_vec(),
+        };
+        send_descriptor(&sender, &descriptor, master.as_raw_fd()).unwrap();
+        drop(master);
+
+        let mut session = recv_session(&receiver).unwrap();
+        assert_eq!(session.get_process().pid(), child.id() as i32);
+        assert!(session.is_alive().unwrap());
+
+        session.send_line("World").unwrap();
+        let found = session.expect("World").unwrap();
+        assert_eq!(found.before(), b"Hello ");
+
+        session.get_process().kill(Signal::SIGKILL).unwrap();
+        let _ = child.wait().unwrap();
+    }
+
+    #[test]
+    fn test_recv_keeps_next_message() {
+        let (sender, receiver) = UnixStream::pair().unwrap();
+
+        let first = SessionDescriptor {
+            pid: 1,
+            buffer: b"first".to_vec(),
+            ..Default::default()
+        };
+        let second = SessionDescriptor {
+            pid: 2,
+            buffer: b"second".to_vec(),
+            ..Default::default()
+        };
+
+        let master = Master::open().unwrap();
+        send_descriptor(&sender, &first, master.as_raw_fd()).unwrap();
+        send_descriptor(&sender, &second, master.as_raw_fd()).unwrap();
+
+        let (descriptor, _) = recv(&receiver).unwrap();
+        assert_eq!(descriptor, first);
+        let (descriptor, _) = recv(&receiver).unwrap();
+        assert_eq!(descriptor, second);
+
+        drop(sender);
+        assert!(matches!(recv(&receiver), Err(Error::Eof)));
+    }
+
+    #[test]
+    fn test_foreign_process() {
+        let mut process = ForeignProcess::new(std::process::id() as i32);
+        assert!(process.is_alive().unwrap());
+    }
+}
@@ -0,0 +1,483 @@
+//! This module contains a record/replay harness for TUI applications.
+//!
+//! A [`Recorder`] collects user input and renders the output on a [`Screen`],
+//! taking snapshots of it at checkpoints.
+//! The result is a [`Recording`] which can be saved and later replayed against a new build via [`replay`],
+//! which reports the first checkpoint at which the screen differs.
+//!
+//! # Example
+//!
+#![cfg_attr(all(unix, not(feature = "async")), doc = "```no_run")]
+#![cfg_attr(not(all(unix, not(feature = "async"))), doc = "```ignore")]
+//! use std::time::Duration;
+//! use expectrl::{spawn, harness::{replay, Recording}};
+//!
+//! let recording: Recording = std::fs::read_to_string("tests/recordings/menu.rec")
+//!     .unwrap()
+//!     .parse()
+//!     .unwrap();
+//!
+//! let mut session = spawn("my-tui").unwrap();
+//! if let Some(divergence) = replay(&recording, &mut session, Duration::from_secs(2)).unwrap() {
+//!     panic!("{}", divergence);
+//! }
+//! ```
+
+use std::{fmt, str::FromStr, time::Duration};
+
+use crate::{screen::Screen, Error, Session};
+
+#[cfg(not(feature = "async"))]
+use crate::process::NonBlocking;
+#[cfg(not(feature = "async"))]
+use std::io::{ErrorKind, Read, Write};
+
+#[cfg(feature = "async")]
+use futures_lite::{AsyncRead, AsyncReadExt, AsyncWrite};
+
+/// A single step of a [`Recording`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Step {
+    /// Bytes which were sent to a process.
+    Input(Vec<u8>),
+    /// A snapshot of a screen.
+    Checkpoint {
+        /// A name of the checkpoint.
+        name: String,
+        /// A text of the screen, see [`Screen::text`].
+        screen: String,
+    },
+}
+
+/// Recording is a list of inputs and screen snapshots.
+///
+/// It can be stored as a text via [`ToString`] and restored via [`FromStr`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Recording {
+    rows: usize,
+    cols: usize,
+    steps: Vec<Step>,
+}
+
+impl Recording {
+    /// Creates a new recording for a screen of a given size.
+    pub fn new(rows: usize, cols: usize, steps: Vec<Step>) -> Self {
+        Self { rows, cols, steps }
+    }
+
+    /// Returns a screen size as (rows, columns).
+    pub fn size(&self) -> (usize, usize) {
+        (self.rows, self.cols)
+    }
+
+    /// Returns a list of steps.
+    pub fn steps(&self) -> &[Step] {
+        &self.steps
+    }
+}
+
+impl fmt::Display for Recording {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "size {} {}", self.rows, self.cols)?;
+        for step in &self.steps {
+            match step {
+                Step::Input(bytes) => {
+                    write!(f, "input ")?;
+                    for b in bytes {
+                        write!(f, "{:02x}", b)?;
+                    }
+                    writeln!(f)?;
+                }
+                Step::Checkpoint { name, screen } => {
+                    writeln!(f, "checkpoint {}", name)?;
+                    for line in screen.split('\n') {
+                        match line.is_empty() {
+                            true => writeln!(f, "|")?,
+                            false => writeln!(f, "| {}", line)?,
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl FromStr for Recording {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut lines = s.lines();
+
+        let size = lines
+            .next()
+            .and_then(|line| line.strip_prefix("size "))
+            .ok_or_else(|| parse_error("a recording must start with a size"))?;
+        let mut size = size.split_whitespace().map(|n| n.parse::<usize>());
+        let (rows, cols) = match (size.next(), size.next()) {
+            (Some(Ok(rows)), Some(Ok(cols))) => (rows, cols),
+            _ => return Err(parse_error("a size must be 2 numbers")),
+        };
+
+        let mut steps = Vec::new();
+        let mut screen: Option<(String, Vec<&str>)> = None;
+        for line in lines {
+            if let Some(text) = line.strip_prefix('|') {
+                let text = text.strip_prefix(' ').unwrap_or(text);
+                match &mut screen {
+                    Some((_, screen)) => screen.push(text),
+                    None => return Err(parse_error("a screen line must follow a checkpoint")),
+                }
+
+                continue;
+            }
+
+            if let Some((name, lines)) = screen.take() {
+                steps.push(Step::Checkpoint {
+                    name,
+                    screen: lines.join("\n"),
+                });
+            }
+
+            if let Some(hex) = line.strip_prefix("input ") {
+                let bytes = parse_hex(hex).ok_or_else(|| parse_error("invalid input bytes"))?;
+                steps.push(Step::Input(bytes));
+            } else if let Some(name) = line.strip_prefix("checkpoint ") {
+                screen = Some((name.to_owned(), Vec::new()));
+            } else if !line.is_empty() {
+                return Err(parse_error("unexpected line"));
+            }
+        }
+
+        if let Some((name, lines)) = screen.take() {
+            steps.push(Step::Checkpoint {
+                name,
+                screen: lines.join("\n"),
+            });
+        }
+
+        Ok(Self { rows, cols, steps })
+    }
+}
+
+/// Recorder builds a [`Recording`].
+///
+/// It's expected to be fed with input and output of a session,
+/// for example from callbacks of [`InteractOptions`].
+///
+/// [`InteractOptions`]: crate::interact::InteractOptions
+#[derive(Debug, Clone)]
+pub struct Recorder {
+    screen: Screen,
+    steps: Vec<Step>,
+}
+
+impl Recorder {
+    /// Creates a recorder for a screen of a given size.
+    pub fn new(rows: usize, cols: usize) -> Self {
+        Self {
+            screen: Screen::new(rows, cols),
+            steps: Vec::new(),
+        }
+    }
+
+    /// Records bytes sent to a process.
+    pub fn input(&mut self, buf: &[u8]) {
+        if buf.is_empty() {
+            return;
+        }
+
+        match self.steps.last_mut() {
+            Some(Step::Input(input)) => input.extend(buf),
+            _ => self.steps.push(Step::Input(buf.to_vec())),
+        }
+    }
+
+    /// Records bytes received from a process.
+    pub fn output(&mut self, buf: &[u8]) {
+        self.screen.process(buf);
+    }
+
+    /// Takes a snapshot of the current screen.
+    pub fn checkpoint<S: Into<String>>(&mut self, name: S) {
+        self.steps.push(Step::Checkpoint {
+            name: name.into(),
+            screen: self.screen.text(),
+        });
+    }
+
+    /// Returns a current screen.
+    pub fn screen(&self) -> &Screen {
+        &self.screen
+    }
+
+    /// Returns a resulting recording.
+    pub fn finish(self) -> Recording {
+        Recording::new(self.screen.rows(), self.screen.cols(), self.steps)
+    }
+}
+
+/// Divergence describes a checkpoint at which a replayed screen didn't match a recorded one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Divergence {
+    /// An index of a step in [`Recording::steps`].
+    pub step: usize,
+    /// A name of the checkpoint.
+    pub checkpoint: String,
+    /// A recorded screen.
+    pub expected: String,
+    /// A screen observed during the replay.
+    pub actual: String,
+}
+
+impl Divergence {
+    /// Returns a first line which differs as (line index, expected, actual).
+    pub fn first_difference(&self) -> Option<(usize, &str, &str)> {
+        let mut expected = self.expected.split('\n');
+        let mut actual = self.actual.split('\n');
+        let mut i = 0;
+        loop {
+            match (expected.next(), actual.next()) {
+                (None, None) => return None,
+                (e, a) if e != a => return Some((i, e.unwrap_or(""), a.unwrap_or(""))),
+                _ => i += 1,
+            }
+        }
+    }
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "screen diverged at checkpoint {:?} (step {})",
+            self.checkpoint, self.step
+        )?;
+
+        if let Some((line, expected, actual)) = self.first_difference() {
+            write!(
+                f,
+                "; line {}: expected {:?}, got {:?}",
+                line, expected, actual
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Replays inputs of a recording against a session,
+/// comparing a screen at each checkpoint.
+///
+/// At each checkpoint the output is read until the screen matches the recorded one,
+/// or until a timeout is reached; in the latter case a [`Divergence`] is returned.
+#[cfg(not(feature = "async"))]
+pub fn replay<P, S>(
+    recording: &Recording,
+    session: &mut Session<P, S>,
+    timeout: Duration,
+) -> Result<Option<Divergence>, Error>
+where
+    S: Read + Write + NonBlocking,
+{
+    let mut screen = Screen::new(recording.rows, recording.cols);
+    let mut buf = [0; 512];
+
+    for (i, step) in recording.steps.iter().enumerate() {
+        let (name, expected) = match step {
+            Step::Input(input) => {
+                session.send(input)?;
+                continue;
+            }
+            Step::Checkpoint { name, screen } => (name, screen),
+        };
+
+        let start = std::time::Instant::now();
+        loop {
+            let mut eof = false;
+            match session.try_read(&mut buf) {
+                Ok(0) => eof = true,
+                Ok(n) => screen.process(&buf[..n]),
+                Err(err) if err.kind() == ErrorKind::WouldBlock => {}
+                Err(err) => return Err(err.into()),
+            }
+
+            let actual = screen.text();
+            if actual == *expected {
+                break;
+            }
+
+            if eof || start.elapsed() > timeout {
+                return Ok(Some(Divergence {
+                    step: i,
+                    checkpoint: name.clone(),
+                    expected: expected.clone(),
+                    actual,
+                }));
+            }
+        }
+    }
+
+    Ok(None)
+}
+
+/// Replays inputs of a recording against a session,
+/// comparing a screen at each checkpoint.
+///
+/// At each checkpoint the output is read until the screen matches the recorded one,
+/// or until a timeout is reached; in the latter case a [`Divergence`] is returned.
+#[cfg(feature = "async")]
+pub async fn replay<P, S>(
+    recording: &Recording,
+    session: &mut Session<P, S>,
+    timeout: Duration,
+) -> Result<Option<Divergence>, Error>
+where
+    P: Unpin,
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let mut screen = Screen::new(recording.rows, recording.cols);
+    let mut buf = [0; 512];
+
+    for (i, step) in recording.steps.iter().enumerate() {
+        let (name, expected) = match step {
+            Step::Input(input) => {
+                session.send(input).await?;
+                continue;
+            }
+            Step::Checkpoint { name, screen } => (name, screen),
+        };
+
+        let start = std::time::Instant::now();
+        loop {
+            let actual = screen.text();
+            if actual == *expected {
+                break;
+            }
+
+            let left = timeout.checked_sub(start.elapsed());
+            let read = match left {
+                Some(left) => {
+                    futures_lite::future::or(async { Some(session.read(&mut buf).await) }, async {
+                        futures_timer::Delay::new(left).await;
+                        None
+                    })
+                    .await
+                }
+                None => None,
+            };
+
+            match read {
+                Some(Ok(n)) if n > 0 => screen.process(&buf[..n]),
+                Some(Err(err)) => return Err(err.into()),
+                Some(Ok(_)) | None => {
+                    return Ok(Some(Divergence {
+                        step: i,
+                        checkpoint: name.clone(),
+                        expected: expected.clone(),
+                        actual,
+                    }))
+                }
+            }
+        }
+    }
+
+    Ok(None)
+}
+
+fn parse_hex(s: &str) -> Option<Vec<u8>> {
+    s.trim()
+        .as_bytes()
+        .chunks(2)
+        .map(|pair| match pair.len() {
+            2 => u8::from_str_radix(std::str::from_utf8(pair).ok()?, 16).ok(),
+            _ => None,
+        })
+        .collect()
+}
+
+fn parse_error(message: &str) -> Error {
+    Error::Other {
+        message: "Failed to parse a recording".to_owned(),
+        err: message.to_owned(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recorder() {
+        let mut recorder = Recorder::new(2, 10);
+        recorder.output(b"$ ");
+        recorder.input(b"l");
+        recorder.input(b"s\r");
+        recorder.output(b"ls\r\nfile\r\n$ ");
+        recorder.checkpoint("after ls");
+
+        let recording = recorder.finish();
+        assert_eq!(
+            recording.steps(),
+            &[
+                Step::Input(b"ls\r".to_vec()),
+                Step::Checkpoint {
+                    name: "after ls".to_owned(),
+                    screen: "file\n$".to_owned(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_recording_to_string_and_back() {
+        let recording = Recording::new(
+            3,
+            10,
+            vec![
+                Step::Input(b"ls\r".to_vec()),
+                Step::Checkpoint {
+                    name: "first".to_owned(),
+                    screen: "\n$ ls\n".to_owned(),
+                },
+                Step::Input(vec![0x1b, b'[', b'A']),
+                Step::Checkpoint {
+                    name: "second".to_owned(),
+                    screen: "a".to_owned(),
+                },
+            ],
+        );
+
+        let text = recording.to_string();
+        assert_eq!(
+            text,
+            "size 3 10\ninput 6c730d\ncheckpoint first\n|\n| $ ls\n|\ninput 1b5b41\ncheckpoint second\n| a\n"
+        );
+        assert_eq!(text.parse::<Recording>().unwrap(), recording);
+    }
+
+    #[test]
+    fn test_recording_parse_error() {
+        assert!("".parse::<Recording>().is_err());
+        assert!("size 1".parse::<Recording>().is_err());
+        assert!("size 1 1\ninput 1".parse::<Recording>().is_err());
+        assert!("size 1 1\n| text".parse::<Recording>().is_err());
+    }
+
+    #[test]
+    fn test_divergence() {
+        let divergence = Divergence {
+            step: 1,
+            checkpoint: "menu".to_owned(),
+            expected: "a\nb".to_owned(),
+            actual: "a\nc".to_owned(),
+        };
+
+        assert_eq!(divergence.first_difference(), Some((1, "b", "c")));
+        assert_eq!(
+            divergence.to_string(),
+            "screen diverged at checkpoint \"menu\" (step 1); line 1: expected \"b\", got \"c\""
+        );
+    }
+}
@@ -11,12 +11,26 @@ use crate::{Captures, Error, Needle};
 #[derive(Debug, Clone)]
 pub struct Lookup {
     buf: Vec<u8>,
+    limit: Option<usize>,
 }
 
 impl Lookup {
     /// Create a lookup object.
     pub fn new() -> Self {
-        Self { buf: Vec::new() }
+        Self {
+            buf: Vec::new(),
+            limit: None,
+        }
+    }
+
+    /// Create a lookup object which keeps only the last `limit` bytes which were not matched.
+    ///
+    /// So a match must fit into the `limit` bytes.
+    pub fn with_limit(limit: usize) -> Self {
+        Self {
+            buf: Vec::new(),
+            limit: Some(limit),
+        }
     }
 
     /// Checks whethere the buffer will be matched and returns [`Captures`] in such case.
@@ -25,7 +39,14 @@ impl Lookup {
         N: Needle,
     {
         self.buf.extend(buf);
-        check(&mut self.buf, pattern, eof)
+        let found = check(&mut self.buf, pattern, eof)?;
+
+        if let Some(limit) = self.limit {
+            let excess = self.buf.len().saturating_sub(limit);
+            let _ = self.buf.drain(..excess);
+        }
+
+        Ok(found)
     }
 
     /// Cleans internal buffer.
@@ -0,0 +1,145 @@
+use std::{
+    io::{self, Write},
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+#[cfg(not(feature = "async"))]
+use std::io::Read;
+
+#[cfg(feature = "async")]
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+#[cfg(feature = "async")]
+use futures_lite::{AsyncRead, AsyncWrite};
+
+/// Duplex is a shared handle to a stream which is used both as an input and an output
+/// of an [`InteractSession`].
+///
+/// It's created by [`Session::interact_with`].
+///
+/// In `async` mode the stream must implement `AsyncRead` and `AsyncWrite`,
+/// otherwise it must implement [`Read`] and [`Write`].
+///
+/// [`InteractSession`]: crate::interact::InteractSession
+/// [`Session::interact_with`]: crate::session::Session::interact_with
+/// [`Read`]: std::io::Read
+#[derive(Debug, Default)]
+pub struct Duplex<T> {
+    stream: Arc<Mutex<T>>,
+}
+
+impl<T> Duplex<T> {
+    /// Creates a new handle to a stream.
+    pub fn new(stream: T) -> Self {
+        Self {
+            stream: Arc::new(Mutex::new(stream)),
+        }
+    }
+
+    /// Returns a guard which gives access to an underlying stream.
+    pub fn lock(&self) -> MutexGuard<'_, T> {
+        self.stream.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+impl<T> Clone for Duplex<T> {
+    fn clone(&self) -> Self {
+        Self {
+            stream: self.stream.clone(),
+        }
+    }
+}
+
+#[cfg(not(feature = "async"))]
+impl<T: Read> Read for Duplex<T> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.lock().read(buf)
+    }
+}
+
+#[cfg(not(feature = "async"))]
+impl<T: Write> Write for Duplex<T> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.lock().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.lock().flush()
+    }
+}
+
+#[cfg(feature = "async")]
+impl<T: AsyncRead + Unpin> AsyncRead for Duplex<T> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        AsyncRead::poll_read(Pin::new(&mut *self.lock()), cx, buf)
+    }
+}
+
+// An output of an interact session is written synchronously in `async` mode,
+// so we block on a write the same way it's done for other writers.
+//
+// The stream is locked only for a single poll,
+// so a pending write doesn't keep others from using the stream.
+#[cfg(feature = "async")]
+impl<T: AsyncWrite + Unpin> Write for Duplex<T> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        futures_lite::future::block_on(futures_lite::future::poll_fn(|cx| {
+            AsyncWrite::poll_write(Pin::new(&mut *self.lock()), cx, buf)
+        }))
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        futures_lite::future::block_on(futures_lite::future::poll_fn(|cx| {
+            AsyncWrite::poll_flush(Pin::new(&mut *self.lock()), cx)
+        }))
+    }
+}
+
+#[cfg(unix)]
+impl<T: std::os::unix::io::AsRawFd> std::os::unix::io::AsRawFd for Duplex<T> {
+    fn as_raw_fd(&self) -> std::os::unix::io::RawFd {
+        self.lock().as_raw_fd()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(not(feature = "async"))]
+    #[test]
+    fn test_duplex_shares_stream() {
+        let mut input = Duplex::new(io::Cursor::new(Vec::new()));
+        let mut output = input.clone();
+
+        output.write_all(b"Hello World").unwrap();
+        input.lock().set_position(0);
+
+        let mut buf = String::new();
+        let _ = input.read_to_string(&mut buf).unwrap();
+        assert_eq!(buf, "Hello World");
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn test_duplex_shares_stream() {
+        use futures_lite::AsyncReadExt;
+
+        let mut input = Duplex::new(futures_lite::io::Cursor::new(Vec::new()));
+        let mut output = input.clone();
+
+        output.write_all(b"Hello World").unwrap();
+        input.lock().set_position(0);
+
+        let mut buf = String::new();
+        let _ = futures_lite::future::block_on(input.read_to_string(&mut buf)).unwrap();
+        assert_eq!(buf, "Hello World");
+    }
+}
@@ -0,0 +1,101 @@
+use std::{
+    sync::{Arc, Mutex},
+    task::Waker,
+};
+
+#[cfg(feature = "async")]
+use std::task::{Context, Poll};
+
+/// A handle which sends input to a process while an [`InteractSession`] is running.
+///
+/// It can be cloned and moved into callbacks or to another thread,
+/// so keystrokes can be simulated at scripted moments of an otherwise manual session.
+///
+/// The injected input is written to the process by the interact loop between IO events.
+/// An async interact loop is woken up as soon as the input is injected.
+/// It's written as is, so neither an input filter nor an escape character are applied to it.
+///
+/// [`InteractSession`]: crate::interact::InteractSession
+#[derive(Debug, Clone, Default)]
+pub struct InteractHandle {
+    state: Arc<Mutex<State>>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    input: Vec<u8>,
+    waker: Option<Waker>,
+}
+
+impl InteractHandle {
+    /// Queues bytes to be sent to the process.
+    pub fn inject_input<B>(&self, bytes: B)
+    where
+        B: AsRef<[u8]>,
+    {
+        let mut state = self.lock();
+        state.input.extend_from_slice(bytes.as_ref());
+        if let Some(waker) = state.waker.take() {
+            waker.wake();
+        }
+    }
+
+    /// Verifies whether there's an injected input which wasn't yet sent.
+    pub fn has_pending_input(&self) -> bool {
+        !self.lock().input.is_empty()
+    }
+
+    pub(crate) fn take(&self) -> Vec<u8> {
+        std::mem::take(&mut self.lock().input)
+    }
+
+    /// Gets ready once there's an injected input.
+    #[cfg(feature = "async")]
+    pub(crate) fn poll_input(&self, cx: &mut Context<'_>) -> Poll<()> {
+        let mut state = self.lock();
+        if state.input.is_empty() {
+            state.waker = Some(cx.waker().clone());
+            return Poll::Pending;
+        }
+
+        Poll::Ready(())
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+#[cfg(all(test, feature = "async"))]
+mod tests {
+    use std::{
+        sync::atomic::{AtomicBool, Ordering},
+        task::Wake,
+    };
+
+    use super::*;
+
+    #[derive(Default)]
+    struct Flag(AtomicBool);
+
+    impl Wake for Flag {
+        fn wake(self: Arc<Self>) {
+            self.0.store(true, Ordering::SeqCst);
+        }
+    }
+
+    #[test]
+    fn test_inject_input_wakes() {
+        let flag = Arc::new(Flag::default());
+        let waker = Waker::from(flag.clone());
+        let mut cx = Context::from_waker(&waker);
+
+        let handle = InteractHandle::default();
+        assert!(handle.poll_input(&mut cx).is_pending());
+
+        handle.clone().inject_input("ls\n");
+        assert!(flag.0.load(Ordering::SeqCst));
+        assert!(handle.poll_input(&mut cx).is_ready());
+        assert_eq!(handle.take(), b"ls\n");
+    }
+}
@@ -48,9 +48,17 @@
 
 pub mod actions;
 mod context;
+mod duplex;
+mod handle;
 mod opts;
+mod pause;
 mod session;
+mod status;
 
 pub use context::Context;
+pub use duplex::Duplex;
+pub use handle::InteractHandle;
 pub use opts::{InteractOptions, NoAction, NoFilter};
+pub use pause::PauseHandle;
 pub use session::InteractSession;
+pub use status::{InteractStatus, StopReason};
@@ -65,6 +65,12 @@ impl<C, IF, OF, IA, OA, WA> InteractOptions<C, IF, OF, IA, OA, WA> {
     /// The output_filter will be passed all the output from the child process.
     ///
     /// The filter isn't applied to user's `read` calls through the [`Context`] in callbacks.
+    ///
+    /// It can rewrite a chunk or suppress it by returning an empty one,
+    /// e.g. to hide one time passwords or to strip noisy escape sequences.
+    /// A recording set by [`InteractSession::set_recording`] still gets the unfiltered output.
+    ///
+    /// [`InteractSession::set_recording`]: crate::interact::InteractSession::set_recording
     pub fn output_filter<F>(self, filter: F) -> InteractOptions<C, IF, F, IA, OA, WA>
     where
         F: FnMut(&[u8]) -> Result<Cow<'_, [u8]>>,
@@ -149,6 +155,10 @@ impl<S, I, O, C, IF, OF, IA, OA>
     InteractOptions<C, IF, OF, IA, OA, NoAction<Session<OsProcess, S>, I, O, C>>
 {
     /// Puts a handler which will be called on each interaction when no input is detected.
+    ///
+    /// Use [`InteractSession::set_idle_timeout`] to call it only after a period of inactivity.
+    ///
+    /// [`InteractSession::set_idle_timeout`]: crate::interact::InteractSession::set_idle_timeout
     pub fn on_idle<F>(self, action: F) -> InteractOptions<C, IF, OF, IA, OA, F>
     where
         F: FnMut(Context<'_, Session<OsProcess, S>, I, O, C>) -> Result<bool>,
@@ -0,0 +1,37 @@
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+/// A handle which requests an [`InteractSession`] to hand control back to a caller.
+///
+/// It can be cloned and moved into callbacks or to another thread.
+///
+/// The request is checked by the interact loop between IO events,
+/// once it's noticed [`InteractSession::spawn`] returns `Ok(true)`
+/// and the session can be used for scripted expects.
+/// Calling [`InteractSession::spawn`] again resumes the interaction
+/// with the same input, output and buffered data.
+///
+/// [`InteractSession`]: crate::interact::InteractSession
+/// [`InteractSession::spawn`]: crate::interact::InteractSession::spawn
+#[derive(Debug, Clone, Default)]
+pub struct PauseHandle {
+    requested: Arc<AtomicBool>,
+}
+
+impl PauseHandle {
+    /// Requests the interact session to pause.
+    pub fn pause(&self) {
+        self.requested.store(true, Ordering::SeqCst);
+    }
+
+    /// Verifies whether a pause was requested but not yet handled.
+    pub fn is_requested(&self) -> bool {
+        self.requested.load(Ordering::SeqCst)
+    }
+
+    pub(crate) fn take(&self) -> bool {
+        self.requested.swap(false, Ordering::SeqCst)
+    }
+}
@@ -2,15 +2,21 @@
 
 use std::{
     borrow::{BorrowMut, Cow},
+    collections::VecDeque,
+    fmt,
     io::{ErrorKind, Write},
+    time::{Duration, Instant},
 };
 
-use crate::{session::OsProcess, Error, Session};
+use crate::{process::ExitStatus, session::OsProcess, Captures, Error, Needle, Session};
 
 #[cfg(not(feature = "async"))]
 use std::io::Read;
 
-use super::{Context, InteractOptions};
+use super::{
+    actions::lookup::Lookup, Context, InteractHandle, InteractOptions, InteractStatus, PauseHandle,
+    StopReason,
+};
 #[cfg(all(not(feature = "async"), not(feature = "polling")))]
 use crate::process::NonBlocking;
 
@@ -21,6 +27,23 @@ pub struct InteractSession<Session, Input, Output> {
     input: Input,
     output: Output,
     escape_character: u8,
+    pause: PauseHandle,
+    handle: InteractHandle,
+    is_paused: bool,
+    recording: Option<Recording>,
+    paste: Paste,
+    mouse: Option<MouseModes>,
+    bytes_sent: u64,
+    bytes_received: u64,
+    inactivity: Inactivity,
+    break_on: Option<BreakOn>,
+    captures: Option<Captures>,
+    #[cfg(all(
+        feature = "crossterm",
+        not(feature = "async"),
+        not(feature = "polling")
+    ))]
+    resize: Option<crate::stream::crossterm::Resize>,
     #[cfg(unix)]
     status: Option<crate::WaitStatus>,
 }
@@ -36,6 +59,23 @@ impl<S, I, O> InteractSession<S, I, O> {
             output,
             session,
             escape_character: Self::ESCAPE,
+            pause: PauseHandle::default(),
+            handle: InteractHandle::default(),
+            is_paused: false,
+            recording: None,
+            paste: Paste::default(),
+            mouse: None,
+            bytes_sent: 0,
+            bytes_received: 0,
+            inactivity: Inactivity::default(),
+            break_on: None,
+            captures: None,
+            #[cfg(all(
+                feature = "crossterm",
+                not(feature = "async"),
+                not(feature = "polling")
+            ))]
+            resize: None,
             #[cfg(unix)]
             status: None,
         }
@@ -43,14 +83,176 @@ impl<S, I, O> InteractSession<S, I, O> {
 
     /// Sets an escape character after seen which the interact interactions will be stopped
     /// and controll will be returned to a caller process.
+    ///
+    /// The character is ignored inside of a bracketed paste, see [`Self::set_bracketed_paste`].
     pub fn set_escape_character(mut self, c: u8) -> Self {
         self.escape_character = c;
         self
     }
 
+    /// Sets a writer which gets all the output of a process as it was read.
+    ///
+    /// The output is recorded before an [`InteractOptions::output_filter`] is applied,
+    /// so a filter can hide parts of the output from a terminal while the recording stays complete.
+    pub fn set_recording<W>(mut self, recording: W) -> Self
+    where
+        W: Write + Send + 'static,
+    {
+        self.recording = Some(Recording(Box::new(recording)));
+        self
+    }
+
+    /// Enables a bracketed paste mode of a terminal while the interaction is running.
+    ///
+    /// The terminal wraps a pasted text in `\x1b[200~` and `\x1b[201~` markers,
+    /// which are forwarded to the process as they are.
+    /// So applications which support it, e.g. shells and editors, don't run pasted lines one by one.
+    pub fn set_bracketed_paste(mut self, on: bool) -> Self {
+        self.paste.bracketed = on;
+        self
+    }
+
+    /// Enables a mouse reporting of a terminal to be forwarded to the process.
+    ///
+    /// A process turns xterm mouse modes on and off by its output, e.g. `\x1b[?1000h`,
+    /// which is passed to the terminal as it is, so the terminal reports clicks, drags and the wheel
+    /// and the reports are forwarded to the process as they are.
+    /// The modes which are on are tracked, so they're turned off when the interaction stops
+    /// and turned back on when it's resumed.
+    /// So the terminal doesn't report a mouse once control is returned to a caller process.
+    pub fn set_mouse_reporting(mut self, on: bool) -> Self {
+        self.mouse = on.then(MouseModes::default);
+        self
+    }
+
+    /// Sets a pasted input to be sent to the process in chunks with a delay after each of them,
+    /// so a tty with a small input buffer doesn't drop characters.
+    ///
+    /// An input is considered pasted if it's wrapped in bracketed paste markers,
+    /// or if it's bigger than a chunk size as a typed input comes by a few bytes at a time.
+    pub fn set_paste_throttle(mut self, chunk_size: usize, delay: Duration) -> Self {
+        self.paste.throttle = Some(Throttle {
+            chunk_size: std::cmp::max(chunk_size, 1),
+            delay,
+        });
+        self
+    }
+
+    /// Sets a time after which an idle action is called when neither side produced any data.
+    ///
+    /// The action is called once per each period of inactivity.
+    /// By default it's called on each iteration of the interact loop when there's no input.
+    ///
+    /// See [`InteractOptions::on_idle`].
+    pub fn set_idle_timeout(mut self, timeout: Duration) -> Self {
+        self.inactivity.idle_timeout = Some(timeout);
+        self
+    }
+
+    /// Sets a message which is sent to the process when neither side produced any data for a given interval.
+    ///
+    /// It keeps alive connections which are dropped when inactive, e.g. SSH sessions.
+    pub fn set_keepalive<B>(mut self, interval: Duration, message: B) -> Self
+    where
+        B: Into<Vec<u8>>,
+    {
+        self.inactivity.keepalive = Some((interval, message.into()));
+        self
+    }
+
+    /// Sets a needle after seen which in the process output the interaction will be stopped
+    /// and controll will be returned to a caller process.
+    ///
+    /// The output which contains the match is still written to the output.
+    /// The found match can be accessed by [`Self::get_captures`].
+    ///
+    /// It's useful to hand control to a user for a step which can't be scripted,
+    /// e.g. a 2FA prompt, and to resume automation once the step is done.
+    ///
+    /// Only the last 4096 bytes of the output are looked up, so a match must fit into them.
+    pub fn break_on<N>(mut self, needle: N) -> Self
+    where
+        N: Needle + Send + 'static,
+    {
+        self.break_on = Some(BreakOn {
+            needle: Box::new(needle),
+            lookup: Lookup::with_limit(BREAK_ON_LIMIT),
+        });
+        self
+    }
+
+    /// Sets terminal size changes which are passed to the process.
+    #[cfg(all(
+        feature = "crossterm",
+        not(feature = "async"),
+        not(feature = "polling")
+    ))]
+    pub(crate) fn set_resize(mut self, resize: crate::stream::crossterm::Resize) -> Self {
+        self.resize = Some(resize);
+        self
+    }
+
+    /// Returns a match of a needle set by [`Self::break_on`] if the last run was stopped by it.
+    pub fn get_captures(&self) -> Option<&Captures> {
+        self.captures.as_ref()
+    }
+
+    /// Returns a handle which can be used to pause the interaction.
+    ///
+    /// See [`PauseHandle`].
+    pub fn pause_handle(&self) -> PauseHandle {
+        self.pause.clone()
+    }
+
+    /// Returns a handle which can be used to send input to the process while the interaction is running.
+    ///
+    /// See [`InteractHandle`].
+    pub fn handle(&self) -> InteractHandle {
+        self.handle.clone()
+    }
+
+    /// Verifies whether the last run was stopped by a [`PauseHandle`].
+    pub fn is_paused(&self) -> bool {
+        self.is_paused
+    }
+
+    /// Returns a reference to a session.
+    pub fn get_session(&self) -> &S {
+        &self.session
+    }
+
+    /// Returns a mut reference to a session.
+    ///
+    /// It can be used to run scripted expects while the interaction is paused.
+    pub fn get_session_mut(&mut self) -> &mut S {
+        &mut self.session
+    }
+
+    /// Resets a state left from a previous run.
+    fn start(&mut self) {
+        self.is_paused = false;
+        self.captures = None;
+        self.bytes_sent = 0;
+        self.bytes_received = 0;
+        self.inactivity.touch();
+        if let Some(break_on) = &mut self.break_on {
+            break_on.lookup.clear();
+        }
+    }
+
+    /// Returns how long IO can be awaited so neither a timeout nor a chunk of a pasted input is missed.
+    #[cfg(any(feature = "async", feature = "polling"))]
+    fn wait_timeout(&self) -> Duration {
+        let timeout = self.inactivity.wait_timeout();
+        match self.paste.time_left() {
+            Some(left) => std::cmp::min(timeout, left),
+            None => timeout,
+        }
+    }
+
     /// Returns a status of spawned session if it was exited.
     ///
-    /// If [`Self::spawn`] returns false but this method returns None it means that a child process was shutdown by various reasons.
+    /// If [`Self::spawn`] returns [`StopReason::Exited`] but this method returns None it means that a child process was shutdown by various reasons.
     /// Which sometimes happens and it's not considered to be a valid [`WaitStatus`], so None is returned.
     ///
     /// [`Self::spawn`]: crate::interact::InteractSession::spawn
@@ -61,6 +263,153 @@ impl<S, I, O> InteractSession<S, I, O> {
     }
 }
 
+impl<S, I, O> InteractSession<&mut Session<OsProcess, S>, I, O> {
+    fn summary(&mut self, reason: StopReason) -> InteractStatus {
+        #[cfg(unix)]
+        let exit_status = {
+            if self.status.is_none() {
+                self.status = get_status(self.session).ok().flatten();
+            }
+
+            match self.status {
+                Some(crate::WaitStatus::Exited(_, code)) => Some(ExitStatus::from_code(code)),
+                Some(crate::WaitStatus::Signaled(_, signal, _)) => {
+                    Some(ExitStatus::from_signal(signal))
+                }
+                _ => None,
+            }
+        };
+
+        #[cfg(windows)]
+        let exit_status = crate::process::Termination::try_wait(self.session.get_process_mut())
+            .ok()
+            .flatten();
+
+        InteractStatus::new(reason, exit_status, self.bytes_sent, self.bytes_received)
+    }
+}
+
+impl<S, I, O> InteractSession<S, I, O>
+where
+    O: Write,
+{
+    /// Turns on or off terminal modes which were requested for the interaction.
+    fn set_terminal_modes(&mut self, on: bool) -> Result<(), Error> {
+        let mut modes = Vec::new();
+        if self.paste.bracketed {
+            let mode: &[u8] = if on { b"\x1b[?2004h" } else { b"\x1b[?2004l" };
+            modes.extend_from_slice(mode);
+        }
+
+        if let Some(mouse) = &self.mouse {
+            mouse.write_modes(&mut modes, on);
+        }
+
+        if !modes.is_empty() {
+            spin_write(&mut self.output, &modes)?;
+            spin_flush(&mut self.output)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(not(feature = "async"))]
+impl<S, I, O> InteractSession<S, I, O>
+where
+    S: Write,
+{
+    /// Sends an input to the process, a pasted input is queued to be sent in chunks.
+    ///
+    /// It returns true if an escape character was found outside of a paste.
+    /// The rest of the input is dropped then, and a queued input is sent before returning.
+    fn send_input(&mut self, buf: &[u8]) -> std::io::Result<bool> {
+        let (is_paste, escape) = self.paste.scan(buf, self.escape_character);
+        let buf = &buf[..escape.unwrap_or(buf.len())];
+        if !self.paste.enqueue(buf, is_paste) {
+            self.session.write_all(buf)?;
+            self.bytes_sent += buf.len() as u64;
+        }
+
+        if escape.is_some() {
+            self.send_all_pasted()?;
+        }
+
+        Ok(escape.is_some())
+    }
+
+    /// Sends a next chunk of a queued input if it's time to.
+    fn send_pasted(&mut self) -> std::io::Result<()> {
+        if let Some(chunk) = self.paste.next_chunk() {
+            self.session.write_all(&chunk)?;
+            self.session.flush()?;
+            self.bytes_sent += chunk.len() as u64;
+        }
+
+        Ok(())
+    }
+
+    /// Sends a whole queued input keeping the delays between chunks.
+    fn send_all_pasted(&mut self) -> std::io::Result<()> {
+        while let Some(delay) = self.paste.time_left() {
+            std::thread::sleep(delay);
+            self.send_pasted()?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "async")]
+impl<S, I, O> InteractSession<S, I, O>
+where
+    S: futures_lite::AsyncWrite + Unpin,
+{
+    /// Sends an input to the process, a pasted input is queued to be sent in chunks.
+    ///
+    /// It returns true if an escape character was found outside of a paste.
+    /// The rest of the input is dropped then, and a queued input is sent before returning.
+    async fn send_input(&mut self, buf: &[u8]) -> std::io::Result<bool> {
+        use futures_lite::AsyncWriteExt;
+
+        let (is_paste, escape) = self.paste.scan(buf, self.escape_character);
+        let buf = &buf[..escape.unwrap_or(buf.len())];
+        if !self.paste.enqueue(buf, is_paste) {
+            self.session.write_all(buf).await?;
+            self.bytes_sent += buf.len() as u64;
+        }
+
+        if escape.is_some() {
+            self.send_all_pasted().await?;
+        }
+
+        Ok(escape.is_some())
+    }
+
+    /// Sends a next chunk of a queued input if it's time to.
+    async fn send_pasted(&mut self) -> std::io::Result<()> {
+        use futures_lite::AsyncWriteExt;
+
+        if let Some(chunk) = self.paste.next_chunk() {
+            self.session.write_all(&chunk).await?;
+            self.session.flush().await?;
+            self.bytes_sent += chunk.len() as u64;
+        }
+
+        Ok(())
+    }
+
+    /// Sends a whole queued input keeping the delays between chunks.
+    async fn send_all_pasted(&mut self) -> std::io::Result<()> {
+        while let Some(delay) = self.paste.time_left() {
+            futures_timer::Delay::new(delay).await;
+            self.send_pasted().await?;
+        }
+
+        Ok(())
+    }
+}
+
 #[cfg(not(any(feature = "async", feature = "polling")))]
 impl<S, I, O> InteractSession<&mut Session<OsProcess, S>, I, O>
 where
@@ -70,10 +419,15 @@ where
 {
     /// Runs the session.
     ///
+    /// It returns a summary of the run, see [`InteractStatus`].
+    ///
     /// See [`Session::interact`].
     ///
     /// [`Session::interact`]: crate::session::Session::interact
-    pub fn spawn<C, IF, OF, IA, OA, WA, OPS>(&mut self, mut ops: OPS) -> Result<bool, Error>
+    pub fn spawn<C, IF, OF, IA, OA, WA, OPS>(
+        &mut self,
+        mut ops: OPS,
+    ) -> Result<InteractStatus, Error>
     where
         OPS: BorrowMut<InteractOptions<C, IF, OF, IA, OA, WA>>,
         IF: FnMut(&[u8]) -> Result<Cow<'_, [u8]>, Error>,
@@ -94,18 +448,25 @@ where
             }
 
             self.status = None;
-            let is_alive = interact_buzy_loop(self, ops.borrow_mut())?;
+            self.set_terminal_modes(true)?;
+            let reason = interact_buzy_loop(self, ops.borrow_mut());
+            self.set_terminal_modes(false)?;
+            let reason = reason?;
 
             if !is_echo {
                 let _ = self.session.get_process_mut().set_echo(false, None);
             }
 
-            Ok(is_alive)
+            Ok(self.summary(reason))
         }
 
         #[cfg(windows)]
         {
-            interact_buzy_loop(self, ops.borrow_mut())
+            self.set_terminal_modes(true)?;
+            let reason = interact_buzy_loop(self, ops.borrow_mut());
+            self.set_terminal_modes(false)?;
+            let reason = reason?;
+            Ok(self.summary(reason))
         }
     }
 }
@@ -119,10 +480,15 @@ where
 {
     /// Runs the session.
     ///
+    /// It returns a summary of the run, see [`InteractStatus`].
+    ///
     /// See [`Session::interact`].
     ///
     /// [`Session::interact`]: crate::session::Session::interact
-    pub fn spawn<C, IF, OF, IA, OA, WA, OPS>(&mut self, mut ops: OPS) -> Result<bool, Error>
+    pub fn spawn<C, IF, OF, IA, OA, WA, OPS>(
+        &mut self,
+        mut ops: OPS,
+    ) -> Result<InteractStatus, Error>
     where
         OPS: BorrowMut<InteractOptions<C, IF, OF, IA, OA, WA>>,
         IF: FnMut(&[u8]) -> Result<Cow<'_, [u8]>, Error>,
@@ -141,13 +507,16 @@ where
         }
 
         self.status = None;
-        let is_alive = interact_polling(self, ops.borrow_mut())?;
+        self.set_terminal_modes(true)?;
+        let reason = interact_polling(self, ops.borrow_mut());
+        self.set_terminal_modes(false)?;
+        let reason = reason?;
 
         if !is_echo {
             let _ = self.session.get_process_mut().set_echo(false, None);
         }
 
-        Ok(is_alive)
+        Ok(self.summary(reason))
     }
 }
 
@@ -160,10 +529,15 @@ where
 {
     /// Runs the session.
     ///
+    /// It returns a summary of the run, see [`InteractStatus`].
+    ///
     /// See [`Session::interact`].
     ///
     /// [`Session::interact`]: crate::session::Session::interact
-    pub async fn spawn<C, IF, OF, IA, OA, WA, OPS>(&mut self, mut opts: OPS) -> Result<bool, Error>
+    pub async fn spawn<C, IF, OF, IA, OA, WA, OPS>(
+        &mut self,
+        mut opts: OPS,
+    ) -> Result<InteractStatus, Error>
     where
         OPS: BorrowMut<InteractOptions<C, IF, OF, IA, OA, WA>>,
         IF: FnMut(&[u8]) -> Result<Cow<'_, [u8]>, Error>,
@@ -182,18 +556,25 @@ where
                 let _ = self.session.set_echo(true, None);
             }
 
-            let is_alive = interact_async(self, opts.borrow_mut()).await?;
+            self.set_terminal_modes(true)?;
+            let reason = interact_async(self, opts.borrow_mut()).await;
+            self.set_terminal_modes(false)?;
+            let reason = reason?;
 
             if !is_echo {
                 let _ = self.session.set_echo(false, None);
             }
 
-            Ok(is_alive)
+            Ok(self.summary(reason))
         }
 
         #[cfg(windows)]
         {
-            interact_async(self, opts.borrow_mut()).await
+            self.set_terminal_modes(true)?;
+            let reason = interact_async(self, opts.borrow_mut()).await;
+            self.set_terminal_modes(false)?;
+            let reason = reason?;
+            Ok(self.summary(reason))
         }
     }
 }
@@ -206,10 +587,15 @@ where
 {
     /// Runs the session.
     ///
+    /// It returns a summary of the run, see [`InteractStatus`].
+    ///
     /// See [`Session::interact`].
     ///
     /// [`Session::interact`]: crate::session::Session::interact
-    pub fn spawn<C, IF, OF, IA, OA, WA, OPS>(&mut self, mut ops: OPS) -> Result<bool, Error>
+    pub fn spawn<C, IF, OF, IA, OA, WA, OPS>(
+        &mut self,
+        mut ops: OPS,
+    ) -> Result<InteractStatus, Error>
     where
         OPS: BorrowMut<InteractOptions<C, IF, OF, IA, OA, WA>>,
         IF: FnMut(&[u8]) -> Result<Cow<'_, [u8]>, Error>,
@@ -218,7 +604,11 @@ where
         OA: FnMut(Context<'_, Session, I, O, C>) -> Result<bool, Error>,
         WA: FnMut(Context<'_, Session, I, O, C>) -> Result<bool, Error>,
     {
-        interact_polling_on_thread(self, ops.borrow_mut())
+        self.set_terminal_modes(true)?;
+        let reason = interact_polling_on_thread(self, ops.borrow_mut());
+        self.set_terminal_modes(false)?;
+        let reason = reason?;
+        Ok(self.summary(reason))
     }
 }
 
@@ -226,7 +616,7 @@ where
 fn interact_buzy_loop<S, O, I, C, IF, OF, IA, OA, WA>(
     interact: &mut InteractSession<&mut Session<OsProcess, S>, I, O>,
     opts: &mut InteractOptions<C, IF, OF, IA, OA, WA>,
-) -> Result<bool, Error>
+) -> Result<StopReason, Error>
 where
     S: NonBlocking + Write + Read,
     I: Read,
@@ -238,27 +628,53 @@ where
     WA: FnMut(Context<'_, Session<OsProcess, S>, I, O, C>) -> Result<bool, Error>,
 {
     let mut buf = [0; 512];
+
+    interact.start();
     loop {
+        if interact.pause.take() {
+            interact.send_all_pasted()?;
+            interact.is_paused = true;
+            return Ok(StopReason::Paused);
+        }
+
+        let input = interact.handle.take();
+        if !input.is_empty() {
+            interact.inactivity.touch();
+            interact.session.write_all(&input)?;
+            interact.bytes_sent += input.len() as u64;
+        }
+
+        interact.send_pasted()?;
+
         #[cfg(unix)]
         {
             let status = get_status(interact.session)?;
             if !matches!(status, Some(crate::WaitStatus::StillAlive)) {
                 interact.status = status;
-                return Ok(false);
+                return Ok(StopReason::Exited);
             }
         }
 
         #[cfg(windows)]
         {
             if !interact.session.is_alive()? {
-                return Ok(false);
+                return Ok(StopReason::Exited);
             }
         }
 
+        #[cfg(feature = "crossterm")]
+        if let Some((cols, rows)) = interact.resize.as_ref().and_then(|r| r.take()) {
+            set_window_size(interact.session, cols, rows)?;
+        }
+
         match interact.session.try_read(&mut buf) {
             Ok(n) => {
                 let eof = n == 0;
+                interact.inactivity.touch();
                 let buf = &buf[..n];
+                record(interact.recording.as_mut(), buf)?;
+                track_mouse(interact.mouse.as_mut(), buf);
+                let matched = check_break(interact.break_on.as_mut(), buf)?;
                 let buf = call_filter(opts.output_filter.as_mut(), buf)?;
 
                 let exit = call_action(
@@ -271,12 +687,22 @@ where
                     eof,
                 )?;
 
-                if eof || exit {
-                    return Ok(true);
+                if exit {
+                    return Ok(StopReason::Callback);
+                }
+
+                if eof {
+                    return Ok(StopReason::Eof);
                 }
 
                 spin_write(&mut interact.output, &buf)?;
+                interact.bytes_received += buf.len() as u64;
                 spin_flush(&mut interact.output)?;
+
+                if let Some(captures) = matched {
+                    interact.captures = Some(captures);
+                    return Ok(StopReason::BreakOn);
+                }
             }
             Err(err) if err.kind() == ErrorKind::WouldBlock => {}
             Err(err) => return Err(err.into()),
@@ -289,6 +715,7 @@ where
         match interact.input.read(&mut buf) {
             Ok(n) => {
                 let eof = n == 0;
+                interact.inactivity.touch();
                 let buf = &buf[..n];
                 let buf = call_filter(opts.input_filter.as_mut(), buf)?;
 
@@ -302,37 +729,40 @@ where
                     eof,
                 )?;
 
-                if eof | exit {
-                    return Ok(true);
+                if exit {
+                    return Ok(StopReason::Callback);
                 }
 
-                let escape_char_position = buf.iter().position(|c| *c == interact.escape_character);
-                match escape_char_position {
-                    Some(pos) => {
-                        interact.session.write_all(&buf[..pos])?;
-                        return Ok(true);
-                    }
-                    None => {
-                        interact.session.write_all(&buf[..])?;
-                    }
+                if eof {
+                    return Ok(StopReason::InputEof);
+                }
+
+                if interact.send_input(&buf)? {
+                    return Ok(StopReason::Escape);
                 }
             }
             Err(err) if err.kind() == ErrorKind::WouldBlock => {}
             Err(err) => return Err(err.into()),
         }
 
-        let exit = call_action(
-            opts.idle_action.as_mut(),
-            interact.session,
-            &mut interact.input,
-            &mut interact.output,
-            &mut opts.state,
-            &[],
-            false,
-        )?;
+        if let Some(message) = interact.inactivity.keepalive() {
+            interact.session.write_all(message)?;
+            interact.bytes_sent += message.len() as u64;
+        }
+
+        let exit = interact.inactivity.is_idle()
+            && call_action(
+                opts.idle_action.as_mut(),
+                interact.session,
+                &mut interact.input,
+                &mut interact.output,
+                &mut opts.state,
+                &[],
+                false,
+            )?;
 
         if exit {
-            return Ok(true);
+            return Ok(StopReason::Callback);
         }
     }
 }
@@ -341,7 +771,7 @@ where
 fn interact_polling<S, O, I, C, IF, OF, IA, OA, WA>(
     interact: &mut InteractSession<&mut Session<OsProcess, S>, I, O>,
     opts: &mut InteractOptions<C, IF, OF, IA, OA, WA>,
-) -> Result<bool, Error>
+) -> Result<StopReason, Error>
 where
     S: Write + Read + std::os::unix::io::AsRawFd,
     I: Read + std::os::unix::io::AsRawFd,
@@ -366,16 +796,33 @@ where
 
     // The event loop.
     let mut events = Vec::new();
+
+    interact.start();
     loop {
+        if interact.pause.take() {
+            interact.send_all_pasted()?;
+            interact.is_paused = true;
+            return Ok(StopReason::Paused);
+        }
+
+        let input = interact.handle.take();
+        if !input.is_empty() {
+            interact.inactivity.touch();
+            interact.session.write_all(&input)?;
+            interact.bytes_sent += input.len() as u64;
+        }
+
+        interact.send_pasted()?;
+
         let status = get_status(interact.session)?;
         if !matches!(status, Some(crate::WaitStatus::StillAlive)) {
             interact.status = status;
-            return Ok(false);
+            return Ok(StopReason::Exited);
         }
 
         // Wait for at least one I/O event.
         events.clear();
-        let _ = poller.wait(&mut events, Some(std::time::Duration::from_secs(5)))?;
+        let _ = poller.wait(&mut events, Some(interact.wait_timeout()))?;
 
         for ev in &events {
             if ev.key == 0 {
@@ -386,6 +833,7 @@ where
                 match interact.input.read(&mut buf) {
                     Ok(n) => {
                         let eof = n == 0;
+                        interact.inactivity.touch();
                         let buf = &buf[..n];
                         let buf = call_filter(opts.input_filter.as_mut(), buf)?;
 
@@ -399,18 +847,16 @@ where
                             eof,
                         )?;
 
-                        if eof || exit {
-                            return Ok(true);
+                        if exit {
+                            return Ok(StopReason::Callback);
+                        }
+
+                        if eof {
+                            return Ok(StopReason::InputEof);
                         }
 
-                        let escape_char_pos =
-                            buf.iter().position(|c| *c == interact.escape_character);
-                        match escape_char_pos {
-                            Some(pos) => {
-                                interact.session.write_all(&buf[..pos]).map_err(Error::IO)?;
-                                return Ok(true);
-                            }
-                            None => interact.session.write_all(&buf[..])?,
+                        if interact.send_input(&buf)? {
+                            return Ok(StopReason::Escape);
                         }
                     }
                     Err(err) if err.kind() == ErrorKind::WouldBlock => {}
@@ -425,7 +871,11 @@ where
                 match interact.session.read(&mut buf) {
                     Ok(n) => {
                         let eof = n == 0;
+                        interact.inactivity.touch();
                         let buf = &buf[..n];
+                        record(interact.recording.as_mut(), buf)?;
+                        track_mouse(interact.mouse.as_mut(), buf);
+                        let matched = check_break(interact.break_on.as_mut(), buf)?;
                         let buf = call_filter(opts.output_filter.as_mut(), buf)?;
 
                         let exit = call_action(
@@ -438,12 +888,22 @@ where
                             eof,
                         )?;
 
-                        if eof || exit {
-                            return Ok(true);
+                        if exit {
+                            return Ok(StopReason::Callback);
+                        }
+
+                        if eof {
+                            return Ok(StopReason::Eof);
                         }
 
                         spin_write(&mut interact.output, &buf)?;
+                        interact.bytes_received += buf.len() as u64;
                         spin_flush(&mut interact.output)?;
+
+                        if let Some(captures) = matched {
+                            interact.captures = Some(captures);
+                            return Ok(StopReason::BreakOn);
+                        }
                     }
                     Err(err) if err.kind() == ErrorKind::WouldBlock => {}
                     Err(err) => return Err(err.into()),
@@ -457,18 +917,24 @@ where
             }
         }
 
-        let exit = call_action(
-            opts.idle_action.as_mut(),
-            interact.session,
-            &mut interact.input,
-            &mut interact.output,
-            &mut opts.state,
-            &[],
-            false,
-        )?;
+        if let Some(message) = interact.inactivity.keepalive() {
+            interact.session.write_all(message)?;
+            interact.bytes_sent += message.len() as u64;
+        }
+
+        let exit = interact.inactivity.is_idle()
+            && call_action(
+                opts.idle_action.as_mut(),
+                interact.session,
+                &mut interact.input,
+                &mut interact.output,
+                &mut opts.state,
+                &[],
+                false,
+            )?;
 
         if exit {
-            return Ok(true);
+            return Ok(StopReason::Callback);
         }
     }
 }
@@ -477,7 +943,7 @@ where
 fn interact_polling_on_thread<O, I, C, IF, OF, IA, OA, WA>(
     interact: &mut InteractSession<&mut Session, I, O>,
     opts: &mut InteractOptions<C, IF, OF, IA, OA, WA>,
-) -> Result<bool, Error>
+) -> Result<StopReason, Error>
 where
     I: Read + Clone + Send + 'static,
     O: Write,
@@ -500,22 +966,40 @@ where
         .map_err(to_io_error(""))?;
     let mut poller = Wait2::new(interact.input.clone(), stream);
 
+    interact.start();
     loop {
+        if interact.pause.take() {
+            interact.send_all_pasted()?;
+            interact.is_paused = true;
+            return Ok(StopReason::Paused);
+        }
+
+        let input = interact.handle.take();
+        if !input.is_empty() {
+            interact.inactivity.touch();
+            interact.session.write_all(&input)?;
+            interact.bytes_sent += input.len() as u64;
+        }
+
+        interact.send_pasted()?;
+
         // In case where proceses exits we are trying to
         // fill buffer to run callbacks if there was something in.
         //
         // We ignore errors because there might be errors like EOCHILD etc.
         if interact.session.is_alive()? {
-            return Ok(false);
+            return Ok(StopReason::Exited);
         }
 
         // Wait for at least one I/O event.
+        poller.set_timeout(interact.wait_timeout());
         let event = poller.recv().map_err(to_io_error(""))?;
         match event {
             Recv::R1(b) => match b {
                 Ok(b) => {
                     let buf = b.map_or([0], |b| [b]);
                     let eof = b.is_none();
+                    interact.inactivity.touch();
                     let n = if eof { 0 } else { 1 };
                     let buf = &buf[..n];
 
@@ -531,18 +1015,16 @@ where
                         eof,
                     )?;
 
-                    if eof || exit {
-                        return Ok(true);
+                    if exit {
+                        return Ok(StopReason::Callback);
                     }
 
-                    // todo: replace all of these by 1 by 1 write
-                    let escape_char_pos = buf.iter().position(|c| *c == interact.escape_character);
-                    match escape_char_pos {
-                        Some(pos) => {
-                            interact.session.write_all(&buf[..pos])?;
-                            return Ok(true);
-                        }
-                        None => interact.session.write_all(&buf[..])?,
+                    if eof {
+                        return Ok(StopReason::InputEof);
+                    }
+
+                    if interact.send_input(&buf)? {
+                        return Ok(StopReason::Escape);
                     }
                 }
                 Err(err) if err.kind() == std::io::ErrorKind::WouldBlock => {}
@@ -552,9 +1034,13 @@ where
                 Ok(b) => {
                     let buf = b.map_or([0], |b| [b]);
                     let eof = b.is_none();
+                    interact.inactivity.touch();
                     let n = if eof { 0 } else { 1 };
                     let buf = &buf[..n];
 
+                    record(interact.recording.as_mut(), buf)?;
+                    track_mouse(interact.mouse.as_mut(), buf);
+                    let matched = check_break(interact.break_on.as_mut(), buf)?;
                     let buf = call_filter(opts.output_filter.as_mut(), buf)?;
 
                     let exit = call_action(
@@ -567,29 +1053,45 @@ where
                         eof,
                     )?;
 
-                    if eof || exit {
-                        return Ok(true);
+                    if exit {
+                        return Ok(StopReason::Callback);
+                    }
+
+                    if eof {
+                        return Ok(StopReason::Eof);
                     }
 
                     interact.output.write_all(&buf)?;
+                    interact.bytes_received += buf.len() as u64;
                     interact.output.flush()?;
+
+                    if let Some(captures) = matched {
+                        interact.captures = Some(captures);
+                        return Ok(StopReason::BreakOn);
+                    }
                 }
                 Err(err) if err.kind() == std::io::ErrorKind::WouldBlock => {}
                 Err(err) => return Err(err.into()),
             },
             Recv::Timeout => {
-                let exit = call_action(
-                    opts.idle_action.as_mut(),
-                    interact.session,
-                    &mut interact.input,
-                    &mut interact.output,
-                    &mut opts.state,
-                    &[],
-                    false,
-                )?;
+                if let Some(message) = interact.inactivity.keepalive() {
+                    interact.session.write_all(message)?;
+                    interact.bytes_sent += message.len() as u64;
+                }
+
+                let exit = interact.inactivity.is_idle()
+                    && call_action(
+                        opts.idle_action.as_mut(),
+                        interact.session,
+                        &mut interact.input,
+                        &mut interact.output,
+                        &mut opts.state,
+                        &[],
+                        false,
+                    )?;
 
                 if exit {
-                    return Ok(true);
+                    return Ok(StopReason::Callback);
                 }
             }
         }
@@ -600,7 +1102,7 @@ where
 async fn interact_async<S, O, I, C, IF, OF, IA, OA, WA>(
     interact: &mut InteractSession<&mut Session<OsProcess, S>, I, O>,
     opts: &mut InteractOptions<C, IF, OF, IA, OA, WA>,
-) -> Result<bool, Error>
+) -> Result<StopReason, Error>
 where
     S: futures_lite::AsyncRead + futures_lite::AsyncWrite + Unpin,
     I: futures_lite::AsyncRead + Unpin,
@@ -617,20 +1119,37 @@ where
 
     let mut stdin_buf = [0; 512];
     let mut proc_buf = [0; 512];
+
+    interact.start();
     loop {
+        if interact.pause.take() {
+            interact.send_all_pasted().await?;
+            interact.is_paused = true;
+            return Ok(StopReason::Paused);
+        }
+
+        let input = interact.handle.take();
+        if !input.is_empty() {
+            interact.inactivity.touch();
+            interact.session.write_all(&input).await?;
+            interact.bytes_sent += input.len() as u64;
+        }
+
+        interact.send_pasted().await?;
+
         #[cfg(unix)]
         {
             let status = get_status(interact.session)?;
             if !matches!(status, Some(crate::WaitStatus::StillAlive)) {
                 interact.status = status;
-                return Ok(false);
+                return Ok(StopReason::Exited);
             }
         }
 
         #[cfg(windows)]
         {
             if !interact.session.is_alive()? {
-                return Ok(false);
+                return Ok(StopReason::Exited);
             }
         }
 
@@ -638,9 +1157,11 @@ where
         enum ReadFrom {
             Stdin,
             OsProcessess,
+            Handle,
             Timeout,
         }
 
+        let wait_timeout = interact.wait_timeout();
         let read_process = async {
             (
                 ReadFrom::OsProcessess,
@@ -652,21 +1173,32 @@ where
             (
                 ReadFrom::Timeout,
                 async {
-                    futures_timer::Delay::new(std::time::Duration::from_secs(5)).await;
+                    futures_timer::Delay::new(wait_timeout).await;
                     io::Result::Ok(0)
                 }
                 .await,
             )
         };
 
+        // an injected input is written at the beginning of the loop
+        let injected = async {
+            futures_lite::future::poll_fn(|cx| interact.handle.poll_input(cx)).await;
+            (ReadFrom::Handle, io::Result::Ok(0))
+        };
+
         let read_fut = futures_lite::future::or(read_process, read_stdin);
+        let read_fut = futures_lite::future::or(read_fut, injected);
         let (read_from, result) = futures_lite::future::or(read_fut, timeout).await;
 
         match read_from {
             ReadFrom::OsProcessess => {
                 let n = result?;
                 let eof = n == 0;
+                interact.inactivity.touch();
                 let buf = &proc_buf[..n];
+                record(interact.recording.as_mut(), buf)?;
+                track_mouse(interact.mouse.as_mut(), buf);
+                let matched = check_break(interact.break_on.as_mut(), buf)?;
                 let buf = call_filter(opts.output_filter.as_mut(), buf)?;
 
                 let exit = call_action(
@@ -679,12 +1211,22 @@ where
                     eof,
                 )?;
 
-                if eof || exit {
-                    return Ok(true);
+                if exit {
+                    return Ok(StopReason::Callback);
+                }
+
+                if eof {
+                    return Ok(StopReason::Eof);
                 }
 
                 spin_write(&mut interact.output, &buf)?;
+                interact.bytes_received += buf.len() as u64;
                 spin_flush(&mut interact.output)?;
+
+                if let Some(captures) = matched {
+                    interact.captures = Some(captures);
+                    return Ok(StopReason::BreakOn);
+                }
             }
             ReadFrom::Stdin => {
                 // We dont't print user input back to the screen.
@@ -694,8 +1236,9 @@ where
                 match result {
                     Ok(n) => {
                         let eof = n == 0;
+                        interact.inactivity.touch();
                         let buf = &stdin_buf[..n];
-                        let buf = call_filter(opts.output_filter.as_mut(), buf)?;
+                        let buf = call_filter(opts.input_filter.as_mut(), buf)?;
 
                         let exit = call_action(
                             opts.input_action.as_mut(),
@@ -707,43 +1250,70 @@ where
                             eof,
                         )?;
 
-                        if eof || exit {
-                            return Ok(true);
+                        if exit {
+                            return Ok(StopReason::Callback);
                         }
 
-                        let escape_char_pos =
-                            buf.iter().position(|c| *c == interact.escape_character);
-                        match escape_char_pos {
-                            Some(pos) => {
-                                interact.session.write_all(&buf[..pos]).await?;
-                                return Ok(true);
-                            }
-                            None => interact.session.write_all(&buf[..]).await?,
+                        if eof {
+                            return Ok(StopReason::InputEof);
+                        }
+
+                        if interact.send_input(&buf).await? {
+                            return Ok(StopReason::Escape);
                         }
                     }
                     Err(err) if err.kind() == io::ErrorKind::WouldBlock => {}
                     Err(err) => return Err(err.into()),
                 }
             }
+            ReadFrom::Handle => {}
             ReadFrom::Timeout => {
-                let exit = call_action(
-                    opts.idle_action.as_mut(),
-                    interact.session,
-                    &mut interact.input,
-                    &mut interact.output,
-                    &mut opts.state,
-                    &[],
-                    false,
-                )?;
+                if let Some(message) = interact.inactivity.keepalive() {
+                    interact.session.write_all(message).await?;
+                    interact.bytes_sent += message.len() as u64;
+                }
+
+                let exit = interact.inactivity.is_idle()
+                    && call_action(
+                        opts.idle_action.as_mut(),
+                        interact.session,
+                        &mut interact.input,
+                        &mut interact.output,
+                        &mut opts.state,
+                        &[],
+                        false,
+                    )?;
 
                 if exit {
-                    return Ok(true);
+                    return Ok(StopReason::Callback);
                 }
             }
         }
     }
 }
 
+#[cfg(all(
+    feature = "crossterm",
+    not(feature = "async"),
+    not(feature = "polling")
+))]
+fn set_window_size<S>(
+    session: &mut Session<OsProcess, S>,
+    cols: u16,
+    rows: u16,
+) -> Result<(), Error> {
+    #[cfg(unix)]
+    session
+        .get_process_mut()
+        .set_window_size(cols, rows)
+        .map_err(|err| Error::unknown("failed to set a window size", err.to_string()))?;
+
+    #[cfg(windows)]
+    session.get_process_mut().resize(rows, cols)?;
+
+    Ok(())
+}
+
 fn spin_write<W>(mut writer: W, buf: &[u8]) -> std::io::Result<()>
 where
     W: Write,
@@ -798,6 +1368,291 @@ where
     }
 }
 
+fn record(recording: Option<&mut Recording>, buf: &[u8]) -> std::io::Result<()> {
+    match recording {
+        Some(recording) => {
+            recording.0.write_all(buf)?;
+            recording.0.flush()
+        }
+        None => Ok(()),
+    }
+}
+
+fn track_mouse(mouse: Option<&mut MouseModes>, buf: &[u8]) {
+    if let Some(mouse) = mouse {
+        mouse.on(buf);
+    }
+}
+
+fn check_break(break_on: Option<&mut BreakOn>, buf: &[u8]) -> Result<Option<Captures>, Error> {
+    match break_on {
+        Some(break_on) => break_on.lookup.on(buf, false, &break_on.needle),
+        None => Ok(None),
+    }
+}
+
+/// A max time the interact loop waits for IO before checking a process status.
+#[cfg(any(feature = "async", feature = "polling"))]
+const MAX_WAIT: Duration = Duration::from_secs(5);
+
+/// Inactivity tracks when any data was relayed last time.
+#[derive(Debug)]
+struct Inactivity {
+    idle_timeout: Option<Duration>,
+    keepalive: Option<(Duration, Vec<u8>)>,
+    idle_since: Instant,
+    keepalive_since: Instant,
+}
+
+impl Default for Inactivity {
+    fn default() -> Self {
+        let now = Instant::now();
+        Self {
+            idle_timeout: None,
+            keepalive: None,
+            idle_since: now,
+            keepalive_since: now,
+        }
+    }
+}
+
+impl Inactivity {
+    fn touch(&mut self) {
+        let now = Instant::now();
+        self.idle_since = now;
+        self.keepalive_since = now;
+    }
+
+    /// Verifies whether an idle action must be called.
+    fn is_idle(&mut self) -> bool {
+        match self.idle_timeout {
+            Some(timeout) if self.idle_since.elapsed() < timeout => false,
+            Some(_) => {
+                self.idle_since = Instant::now();
+                true
+            }
+            None => true,
+        }
+    }
+
+    /// Returns a keepalive message if it's time to send it.
+    fn keepalive(&mut self) -> Option<&[u8]> {
+        match &self.keepalive {
+            Some((interval, message)) if self.keepalive_since.elapsed() >= *interval => {
+                self.keepalive_since = Instant::now();
+                Some(message)
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns how long IO can be awaited so no timeout is missed.
+    #[cfg(any(feature = "async", feature = "polling"))]
+    fn wait_timeout(&self) -> Duration {
+        let idle = self
+            .idle_timeout
+            .map(|timeout| timeout.saturating_sub(self.idle_since.elapsed()));
+        let keepalive = self
+            .keepalive
+            .as_ref()
+            .map(|(interval, _)| interval.saturating_sub(self.keepalive_since.elapsed()));
+
+        [idle, keepalive]
+            .into_iter()
+            .flatten()
+            .fold(MAX_WAIT, std::cmp::min)
+    }
+}
+
+const PASTE_START: &[u8] = b"\x1b[200~";
+const PASTE_END: &[u8] = b"\x1b[201~";
+
+/// Paste keeps paste settings and tracks whether an input is inside of a bracketed paste.
+#[derive(Debug, Default)]
+struct Paste {
+    bracketed: bool,
+    throttle: Option<Throttle>,
+    in_paste: bool,
+    /// Last bytes of an input, so a marker split between reads is still found.
+    tail: Vec<u8>,
+    /// A pasted input which is waiting to be sent.
+    pending: VecDeque<u8>,
+    /// A time before which a next chunk of a pending input must not be sent.
+    next_chunk_at: Option<Instant>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Throttle {
+    chunk_size: usize,
+    delay: Duration,
+}
+
+impl Paste {
+    /// Looks for paste markers in an input.
+    ///
+    /// It returns whether the input is pasted
+    /// and a position of an escape character which is not a part of a paste if any.
+    fn scan(&mut self, buf: &[u8], escape: u8) -> (bool, Option<usize>) {
+        let mut is_paste = self.in_paste;
+        for (i, &b) in buf.iter().enumerate() {
+            if b == escape && !self.in_paste {
+                return (is_paste, Some(i));
+            }
+
+            self.tail.push(b);
+            if self.tail.len() > PASTE_START.len() {
+                let _ = self.tail.remove(0);
+            }
+
+            if self.tail.ends_with(PASTE_START) {
+                self.in_paste = true;
+                is_paste = true;
+            } else if self.tail.ends_with(PASTE_END) {
+                self.in_paste = false;
+            }
+        }
+
+        (is_paste, None)
+    }
+
+    /// Queues an input if it must be throttled.
+    ///
+    /// An input is throttled if it's pasted or it's bigger than a chunk size,
+    /// or if there's already a queued input, so the order is kept.
+    fn enqueue(&mut self, buf: &[u8], is_paste: bool) -> bool {
+        let throttle = match self.throttle {
+            Some(throttle) => throttle,
+            None => return false,
+        };
+
+        let is_throttled = is_paste || buf.len() > throttle.chunk_size || !self.pending.is_empty();
+        if is_throttled {
+            self.pending.extend(buf);
+        }
+
+        is_throttled
+    }
+
+    /// Returns a next chunk of a queued input if it's time to send it.
+    fn next_chunk(&mut self) -> Option<Vec<u8>> {
+        let throttle = self.throttle?;
+        if self.time_left()? > Duration::ZERO {
+            return None;
+        }
+
+        let size = std::cmp::min(throttle.chunk_size, self.pending.len());
+        let chunk = self.pending.drain(..size).collect();
+        self.next_chunk_at = Some(Instant::now() + throttle.delay);
+
+        Some(chunk)
+    }
+
+    /// Returns how long to wait before a next chunk of a queued input can be sent.
+    ///
+    /// None is returned if nothing is queued.
+    fn time_left(&self) -> Option<Duration> {
+        if self.pending.is_empty() {
+            return None;
+        }
+
+        let left = self.next_chunk_at.map_or(Duration::ZERO, |at| {
+            at.saturating_duration_since(Instant::now())
+        });
+
+        Some(left)
+    }
+}
+
+/// Xterm private modes which make a terminal report a mouse.
+const MOUSE_MODES: [u16; 7] = [9, 1000, 1002, 1003, 1005, 1006, 1015];
+
+/// MouseModes tracks which xterm mouse modes a process turned on.
+#[derive(Debug, Default)]
+struct MouseModes {
+    /// The modes in an order they were turned on.
+    enabled: Vec<u16>,
+    /// An unfinished `\x1b[?...h` or `\x1b[?...l` sequence, which may be split between reads.
+    seq: Vec<u8>,
+}
+
+impl MouseModes {
+    /// A max length of a sequence, longer ones are not considered to be mode changes.
+    const MAX_SEQ_LEN: usize = 32;
+
+    /// Looks for mode changes in an output of a process.
+    fn on(&mut self, buf: &[u8]) {
+        for &b in buf {
+            match (self.seq.len(), b) {
+                (_, 0x1b) => {
+                    self.seq.clear();
+                    self.seq.push(b);
+                }
+                (0, _) => {}
+                (1, b'[') | (2, b'?') => self.seq.push(b),
+                (3.., b'0'..=b'9' | b';') if self.seq.len() < Self::MAX_SEQ_LEN => self.seq.push(b),
+                (4.., b'h' | b'l') => {
+                    let seq = std::mem::take(&mut self.seq);
+                    self.set(&seq[3..], b == b'h');
+                }
+                _ => self.seq.clear(),
+            }
+        }
+    }
+
+    fn set(&mut self, params: &[u8], on: bool) {
+        let modes = params
+            .split(|&b| b == b';')
+            .filter_map(|param| std::str::from_utf8(param).ok()?.parse::<u16>().ok())
+            .filter(|mode| MOUSE_MODES.contains(mode));
+
+        for mode in modes {
+            self.enabled.retain(|&m| m != mode);
+            if on {
+                self.enabled.push(mode);
+            }
+        }
+    }
+
+    /// Writes sequences which turn the tracked modes on or off.
+    fn write_modes(&self, buf: &mut Vec<u8>, on: bool) {
+        if on {
+            for mode in &self.enabled {
+                buf.extend_from_slice(format!("\x1b[?{mode}h").as_bytes());
+            }
+        } else {
+            for mode in self.enabled.iter().rev() {
+                buf.extend_from_slice(format!("\x1b[?{mode}l").as_bytes());
+            }
+        }
+    }
+}
+
+/// A number of last output bytes a [BreakOn] needle is looked up in.
+const BREAK_ON_LIMIT: usize = 4096;
+
+/// BreakOn is a needle which stops an interaction once it's matched.
+struct BreakOn {
+    needle: Box<dyn Needle + Send>,
+    lookup: Lookup,
+}
+
+impl fmt::Debug for BreakOn {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BreakOn")
+            .field("lookup", &self.lookup)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Recording is a writer which gets an unfiltered output of a process.
+struct Recording(Box<dyn Write + Send>);
+
+impl fmt::Debug for Recording {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Recording").finish()
+    }
+}
+
 #[cfg(unix)]
 fn get_status<S>(session: &Session<OsProcess, S>) -> Result<Option<crate::WaitStatus>, Error> {
     match session.get_process().status() {
@@ -806,3 +1661,94 @@ fn get_status<S>(session: &Session<OsProcess, S>) -> Result<Option<crate::WaitSt
         Err(err) => Err(Error::IO(std::io::Error::new(ErrorKind::Other, err))),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_paste_throttle() {
+        let mut paste = Paste::default();
+        assert!(!paste.enqueue(b"a long line of text", false));
+
+        paste.throttle = Some(Throttle {
+            chunk_size: 4,
+            delay: Duration::from_secs(60),
+        });
+
+        assert!(!paste.enqueue(b"ls", false));
+        assert!(paste.enqueue(b"a long line", false));
+        assert!(paste.enqueue(b"ls", false));
+
+        assert_eq!(paste.next_chunk().unwrap(), b"a lo");
+        assert!(paste.next_chunk().is_none());
+        assert!(paste.time_left().unwrap() > Duration::from_secs(50));
+
+        paste.next_chunk_at = None;
+        assert_eq!(paste.next_chunk().unwrap(), b"ng l");
+        paste.next_chunk_at = None;
+        assert_eq!(paste.next_chunk().unwrap(), b"inel");
+        paste.next_chunk_at = None;
+        assert_eq!(paste.next_chunk().unwrap(), b"s");
+        assert!(paste.time_left().is_none());
+        assert!(!paste.enqueue(b"ls", false));
+    }
+
+    #[test]
+    fn test_paste_markers() {
+        let mut paste = Paste::default();
+        assert_eq!(paste.scan(b"ls", 29), (false, None));
+
+        assert_eq!(paste.scan(b"\x1b[200~ls", 29), (true, None));
+        assert_eq!(paste.scan(b"ls", 29), (true, None));
+        assert_eq!(paste.scan(b"ls\x1b[201~", 29), (true, None));
+        assert_eq!(paste.scan(b"ls", 29), (false, None));
+
+        assert_eq!(paste.scan(b"\x1b[200~ls\x1b[201~", 29), (true, None));
+        assert_eq!(paste.scan(b"ls", 29), (false, None));
+    }
+
+    #[test]
+    fn test_paste_markers_split_between_reads() {
+        let mut paste = Paste::default();
+        assert_eq!(paste.scan(b"\x1b[20", 29), (false, None));
+        assert_eq!(paste.scan(b"0~ls", 29), (true, None));
+        assert_eq!(paste.scan(b"ls\x1b", 29), (true, None));
+        assert_eq!(paste.scan(b"[201~", 29), (true, None));
+        assert_eq!(paste.scan(b"ls", 29), (false, None));
+    }
+
+    #[test]
+    fn test_paste_keeps_escape_character() {
+        let mut paste = Paste::default();
+        assert_eq!(paste.scan(b"\x1b[200~l\x1ds", 29), (true, None));
+        assert_eq!(paste.scan(b"\x1d\x1b[201~l\x1ds", 29), (true, Some(8)));
+    }
+
+    #[test]
+    fn test_mouse_modes() {
+        let mut mouse = MouseModes::default();
+        mouse.on(b"text\x1b[?1000h\x1b[?25l\x1b[?10");
+        mouse.on(b"06h\x1b[?1002;1006l\x1b[?1003h");
+        assert_eq!(mouse.enabled, [1000, 1003]);
+
+        let mut buf = Vec::new();
+        mouse.write_modes(&mut buf, true);
+        assert_eq!(buf, b"\x1b[?1000h\x1b[?1003h");
+
+        buf.clear();
+        mouse.write_modes(&mut buf, false);
+        assert_eq!(buf, b"\x1b[?1003l\x1b[?1000l");
+    }
+
+    #[test]
+    fn test_break_on_keeps_tail() {
+        let mut lookup = Lookup::with_limit(8);
+        assert!(lookup.on(&[b'x'; 100], false, "2FA").unwrap().is_none());
+        assert!(lookup.on(b"2F", false, "2FA").unwrap().is_none());
+
+        let found = lookup.on(b"A code:", false, "2FA").unwrap().unwrap();
+        assert_eq!(found.before(), b"xxxxxx");
+        assert_eq!(found.get(0).unwrap(), b"2FA");
+    }
+}
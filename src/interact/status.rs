@@ -0,0 +1,78 @@
+use crate::process::ExitStatus;
+
+/// StopReason is a reason why an [`InteractSession`] returned control to a caller.
+///
+/// [`InteractSession`]: crate::interact::InteractSession
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StopReason {
+    /// An escape character was typed.
+    Escape,
+    /// An input reached EOF.
+    InputEof,
+    /// A process closed its output.
+    Eof,
+    /// A process exited.
+    Exited,
+    /// A needle set by [`InteractSession::break_on`] was found in the output.
+    ///
+    /// [`InteractSession::break_on`]: crate::interact::InteractSession::break_on
+    BreakOn,
+    /// A pause was requested by a [`PauseHandle`].
+    ///
+    /// [`PauseHandle`]: crate::interact::PauseHandle
+    Paused,
+    /// One of callbacks returned `true`.
+    Callback,
+}
+
+/// InteractStatus is a summary of an [`InteractSession`] run.
+///
+/// [`InteractSession`]: crate::interact::InteractSession
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InteractStatus {
+    reason: StopReason,
+    exit_status: Option<ExitStatus>,
+    bytes_sent: u64,
+    bytes_received: u64,
+}
+
+impl InteractStatus {
+    pub(crate) fn new(
+        reason: StopReason,
+        exit_status: Option<ExitStatus>,
+        bytes_sent: u64,
+        bytes_received: u64,
+    ) -> Self {
+        Self {
+            reason,
+            exit_status,
+            bytes_sent,
+            bytes_received,
+        }
+    }
+
+    /// Returns a reason why the interaction was stopped.
+    pub fn reason(&self) -> StopReason {
+        self.reason
+    }
+
+    /// Returns an exit status of a process if it has exited.
+    pub fn exit_status(&self) -> Option<ExitStatus> {
+        self.exit_status
+    }
+
+    /// Verifies whether the interaction was stopped while the process was still running.
+    pub fn is_alive(&self) -> bool {
+        self.reason != StopReason::Exited
+    }
+
+    /// Returns a number of bytes sent to the process.
+    pub fn bytes_sent(&self) -> u64 {
+        self.bytes_sent
+    }
+
+    /// Returns a number of bytes of the process output written to the output.
+    pub fn bytes_received(&self) -> u64 {
+        self.bytes_received
+    }
+}
@@ -31,6 +31,17 @@
 //!
 //! - `async`: Enables a async/await public API.
 //! - `polling`: Enables polling backend in interact session. Be cautious to use it on windows.
+//! - `serde`: Implements `serde::Serialize` for [`Captures`], [`Error`] and [`Transcript`].
+//! - `scripts`: Enables an interpreter of expect like scripts, see `script` module.
+//! - `pretty-errors`: Enables a colored rendering of failed expectations, see `pretty` module.
+//! - `crossterm`: Enables a terminal input which is read via `crossterm`, see `stream::crossterm` module and `Session::interact_crossterm`.
+//! - `fancy-regex`: Enables a `FancyRegex` needle which supports backreferences and lookarounds.
+//! - `secrecy`: Lets passwords be passed as `secrecy::SecretString`, see `Password`. It turns on `zeroize` as well.
+//! - `zeroize`: Wipes a `Secret` from memory when it's dropped.
+//!
+//! ## Environment variables
+//!
+//! - `EXPECTRL_TIMEOUT`: Overrides a default expect timeout in seconds, see [`session::TIMEOUT_ENV`].
 //!
 //! ## Examples
 //!
@@ -115,25 +126,68 @@ mod check_macros;
 mod control_code;
 mod error;
 mod needle;
+mod stats;
+mod timeline;
+mod transcript;
 
 #[cfg(all(windows, feature = "polling"))]
 mod waiter;
 
+pub mod dialogue;
+#[cfg(unix)]
+pub mod handoff;
+pub mod harness;
 pub mod interact;
+pub mod matrix;
+pub mod policy;
+#[cfg(feature = "pretty-errors")]
+pub mod pretty;
 pub mod process;
+#[cfg(unix)]
+pub mod pty;
 pub mod repl;
+pub mod replay;
+pub mod screen;
+#[cfg(feature = "scripts")]
+pub mod script;
+pub mod secret;
 pub mod session;
+#[cfg(unix)]
+pub mod ssh;
 pub mod stream;
+pub mod supervisor;
+pub mod test;
 
-pub use captures::Captures;
+pub use captures::{Captures, CapturesRef};
 pub use control_code::ControlCode;
 pub use error::Error;
-pub use needle::{Any, Eof, NBytes, Needle, Regex};
+pub use needle::{
+    All, AnsiStripped, Any, ByteRange, CaseInsensitive, Eof, Glob, NBytes, Needle, NormalizedWs,
+    Not, Regex, ShellPrompt, Then, Utf8,
+};
+pub use secret::{Password, Secret};
+
+#[cfg(feature = "fancy-regex")]
+pub use needle::FancyRegex;
 
 #[cfg(unix)]
 pub use ptyprocess::{Signal, WaitStatus};
 
+#[cfg(windows)]
+pub use process::windows::Signal;
+
+#[cfg(unix)]
+pub use nix::sys::termios;
+
 pub use session::Session;
+pub use stats::Stats;
+pub use timeline::Timeline;
+pub use transcript::{Transcript, TranscriptEntry, TranscriptFormat};
+
+use std::{ffi::OsStr, process::Command};
+
+#[cfg(windows)]
+use std::ffi::OsString;
 
 /// Spawn spawnes a new session.
 ///
@@ -162,3 +216,40 @@ pub use session::Session;
 pub fn spawn<S: AsRef<str>>(cmd: S) -> Result<Session, Error> {
     Session::spawn_cmd(cmd.as_ref())
 }
+
+/// Spawns a new session with a given program and arguments.
+///
+/// Unlike [`spawn`] the arguments are passed to the program as they are,
+/// so there's no word splitting and no need to quote paths with spaces or quotes.
+///
+/// # Example
+///
+/// ```no_run
+/// let p = expectrl::spawn_args("ls", ["-l", "My Documents"]).unwrap();
+/// ```
+pub fn spawn_args<P, I, A>(program: P, args: I) -> Result<Session, Error>
+where
+    P: AsRef<OsStr>,
+    I: IntoIterator<Item = A>,
+    A: AsRef<OsStr>,
+{
+    // A program name is passed to ConPTY as a part of a commandline,
+    // so a name with spaces must be quoted.
+    #[cfg(windows)]
+    let program = {
+        let program = program.as_ref();
+        if program.to_string_lossy().contains(' ') {
+            let mut quoted = OsString::from("\"");
+            quoted.push(program);
+            quoted.push("\"");
+            quoted
+        } else {
+            program.to_owned()
+        }
+    };
+
+    let mut command = Command::new(program);
+    let _ = command.args(args);
+
+    Session::spawn(command)
+}
@@ -0,0 +1,229 @@
+//! This module contains a [`Matrix`] which runs a command
+//! in a set of different terminal environments.
+//!
+//! It's handy to verify that a CLI behaves well regardless of `TERM`, locale or a terminal size.
+//!
+//! # Example
+//!
+#![cfg_attr(all(unix, not(feature = "async")), doc = "```no_run")]
+#![cfg_attr(not(all(unix, not(feature = "async"))), doc = "```ignore")]
+//! use expectrl::matrix::Matrix;
+//!
+//! let results = Matrix::new("sh")
+//!     .args(["-c", "echo $TERM"])
+//!     .terms(["xterm", "dumb"])
+//!     .locales(["C", "en_US.UTF-8"])
+//!     .sizes([(80, 24), (40, 10)])
+//!     .run(|env, session| {
+//!         session.expect(env.term.as_deref().unwrap())?;
+//!         Ok(())
+//!     });
+//!
+//! for (env, result) in results {
+//!     assert!(result.is_ok(), "failed in {:?}", env);
+//! }
+//! ```
+
+use std::process::Command;
+
+use crate::{Error, Session};
+
+/// Matrix spawns the same command under each combination of configured
+/// `TERM` values, locales and terminal sizes.
+///
+/// If a dimension is not set the corresponding setting is inherited from the current process.
+#[derive(Debug, Clone, Default)]
+pub struct Matrix {
+    program: String,
+    args: Vec<String>,
+    terms: Vec<String>,
+    locales: Vec<String>,
+    sizes: Vec<(u16, u16)>,
+}
+
+/// Environment represents a single combination of a [`Matrix`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Environment {
+    /// A value of `TERM` environment variable.
+    pub term: Option<String>,
+    /// A value of `LANG` and `LC_ALL` environment variables.
+    pub locale: Option<String>,
+    /// A terminal size in columns and rows.
+    ///
+    /// The pty is resized only on `unix`,
+    /// but `COLUMNS` and `LINES` environment variables are set on all platforms.
+    pub size: Option<(u16, u16)>,
+}
+
+impl Matrix {
+    /// Creates a new matrix for a given program.
+    pub fn new<S: Into<String>>(program: S) -> Self {
+        Self {
+            program: program.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Sets a list of arguments for the program.
+    pub fn args<I, S>(mut self, args: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.args = args.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Sets a list of `TERM` values to check.
+    pub fn terms<I, S>(mut self, terms: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.terms = terms.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Sets a list of locales to check.
+    pub fn locales<I, S>(mut self, locales: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.locales = locales.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Sets a list of terminal sizes (columns, rows) to check.
+    pub fn sizes<I>(mut self, sizes: I) -> Self
+    where
+        I: IntoIterator<Item = (u16, u16)>,
+    {
+        self.sizes = sizes.into_iter().collect();
+        self
+    }
+
+    /// Returns all combinations of the matrix.
+    pub fn environments(&self) -> Vec<Environment> {
+        let terms = optional_list(&self.terms);
+        let locales = optional_list(&self.locales);
+        let sizes = optional_list(&self.sizes);
+
+        let mut list = Vec::with_capacity(terms.len() * locales.len() * sizes.len());
+        for term in &terms {
+            for locale in &locales {
+                for size in &sizes {
+                    list.push(Environment {
+                        term: term.cloned(),
+                        locale: locale.cloned(),
+                        size: size.copied(),
+                    });
+                }
+            }
+        }
+
+        list
+    }
+
+    /// Spawns a [`Session`] for each combination and calls a given function with it.
+    ///
+    /// It doesn't stop on a failure, a result of every run is returned.
+    pub fn run<F, R>(&self, mut f: F) -> Vec<(Environment, Result<R, Error>)>
+    where
+        F: FnMut(&Environment, &mut Session) -> Result<R, Error>,
+    {
+        self.environments()
+            .into_iter()
+            .map(|env| {
+                let result = self
+                    .spawn(&env)
+                    .and_then(|mut session| f(&env, &mut session));
+                (env, result)
+            })
+            .collect()
+    }
+
+    /// Spawns a [`Session`] in a given environment.
+    pub fn spawn(&self, env: &Environment) -> Result<Session, Error> {
+        let mut command = Command::new(&self.program);
+        let _ = command.args(&self.args);
+
+        if let Some(term) = &env.term {
+            let _ = command.env("TERM", term);
+        }
+
+        if let Some(locale) = &env.locale {
+            let _ = command.env("LANG", locale);
+            let _ = command.env("LC_ALL", locale);
+        }
+
+        if let Some((cols, rows)) = env.size {
+            let _ = command.env("COLUMNS", cols.to_string());
+            let _ = command.env("LINES", rows.to_string());
+        }
+
+        #[allow(unused_mut)]
+        let mut session = Session::spawn(command)?;
+
+        #[cfg(unix)]
+        if let Some((cols, rows)) = env.size {
+            session
+                .get_process_mut()
+                .set_window_size(cols, rows)
+                .map_err(|err| Error::unknown("Failed to set a window size", err.to_string()))?;
+        }
+
+        Ok(session)
+    }
+}
+
+fn optional_list<T>(list: &[T]) -> Vec<Option<&T>> {
+    match list.is_empty() {
+        true => vec![None],
+        false => list.iter().map(Some).collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_environments() {
+        let matrix = Matrix::new("sh")
+            .terms(["xterm", "dumb"])
+            .sizes([(80, 24), (40, 10)]);
+
+        let envs = matrix.environments();
+        assert_eq!(envs.len(), 4);
+        assert_eq!(
+            envs[1],
+            Environment {
+                term: Some("xterm".to_owned()),
+                locale: None,
+                size: Some((40, 10)),
+            }
+        );
+        assert_eq!(
+            envs[2],
+            Environment {
+                term: Some("dumb".to_owned()),
+                locale: None,
+                size: Some((80, 24)),
+            }
+        );
+    }
+
+    #[test]
+    fn test_environments_empty() {
+        let envs = Matrix::new("sh").environments();
+        assert_eq!(
+            envs,
+            vec![Environment {
+                term: None,
+                locale: None,
+                size: None,
+            }]
+        );
+    }
+}
@@ -3,12 +3,26 @@
 //!
 //! The list of provided implementations can be found in the documentation.
 
-use crate::error::Error;
+use std::{
+    cell::Cell,
+    ops::{Range, RangeInclusive},
+    time::{Duration, Instant},
+};
+
+use crate::{error::Error, Captures};
 
 /// Needle an interface for search of a match in a buffer.
 pub trait Needle {
     /// Function returns all matches that were occured.
     fn check(&self, buf: &[u8], eof: bool) -> Result<Vec<Match>, Error>;
+
+    /// Returns a human readable description of what is looked up,
+    /// e.g. to tell which pattern an expect call was waiting for.
+    ///
+    /// By default it's a name of the type.
+    fn description(&self) -> String {
+        std::any::type_name::<Self>().to_string()
+    }
 }
 
 /// Match structure represent a range of bytes where match was found.
@@ -16,12 +30,19 @@ pub trait Needle {
 pub struct Match {
     start: usize,
     end: usize,
+    alternative: Option<usize>,
+    group: Option<usize>,
 }
 
 impl Match {
     /// New construct's an intanse of a Match.
     pub fn new(start: usize, end: usize) -> Self {
-        Self { start, end }
+        Self {
+            start,
+            end,
+            alternative: None,
+            group: None,
+        }
     }
 
     /// Start returns a start index of a match.
@@ -33,6 +54,28 @@ impl Match {
     pub fn end(&self) -> usize {
         self.end
     }
+
+    /// Alternative returns an index of a needle in [Any] which produced the match.
+    pub fn alternative(&self) -> Option<usize> {
+        self.alternative
+    }
+
+    /// Group returns an index of a regex group which produced the match.
+    ///
+    /// It returns [None] if the match was not produced by a regex.
+    pub fn group(&self) -> Option<usize> {
+        self.group
+    }
+
+    fn with_alternative(mut self, index: usize) -> Self {
+        self.alternative = Some(index);
+        self
+    }
+
+    fn with_group(mut self, index: usize) -> Self {
+        self.group = Some(index);
+        self
+    }
 }
 
 impl From<regex::bytes::Match<'_>> for Match {
@@ -48,12 +91,234 @@ pub struct Regex<Re: AsRef<str>>(pub Re);
 impl<Re: AsRef<str>> Needle for Regex<Re> {
     fn check(&self, buf: &[u8], _: bool) -> Result<Vec<Match>, Error> {
         let regex = regex::bytes::Regex::new(self.0.as_ref()).map_err(|_| Error::RegexParsing)?;
-        let matches = regex
+        regex.check(buf, false)
+    }
+
+    fn description(&self) -> String {
+        format!("regex {:?}", self.0.as_ref())
+    }
+}
+
+/// A precompiled regex can be used as a needle,
+/// so a pattern is not compiled on each call and can be shared across sessions.
+///
+/// # Example
+///
+/// ```no_run,ignore
+/// use expectrl::spawn;
+///
+/// let prompt = regex::bytes::Regex::new(r"\[\w+@\w+\]\$ ").unwrap();
+///
+/// let mut p = spawn("bash").unwrap();
+/// p.expect(&prompt).unwrap();
+/// ```
+impl Needle for regex::bytes::Regex {
+    fn check(&self, buf: &[u8], _: bool) -> Result<Vec<Match>, Error> {
+        let matches = self
             .captures_iter(buf)
-            .flat_map(|c| c.iter().flatten().map(|m| m.into()).collect::<Vec<Match>>())
+            .flat_map(|c| {
+                c.iter()
+                    .enumerate()
+                    .filter_map(|(i, m)| m.map(|m| Match::from(m).with_group(i)))
+                    .collect::<Vec<Match>>()
+            })
             .collect();
         Ok(matches)
     }
+
+    fn description(&self) -> String {
+        format!("regex {:?}", self.as_str())
+    }
+}
+
+/// A precompiled regex can be used as a needle.
+///
+/// Only valid UTF-8 parts of a buffer are searched,
+/// so a match doesn't span across invalid bytes.
+/// Use [regex::bytes::Regex] to search in arbitrary bytes.
+impl Needle for regex::Regex {
+    fn check(&self, buf: &[u8], _: bool) -> Result<Vec<Match>, Error> {
+        find_in_utf8_chunks(buf, |text, found| {
+            for captures in self.captures_iter(text) {
+                found.push(captures.iter().map(|m| m.map(|m| m.range())));
+            }
+
+            Ok(())
+        })
+    }
+
+    fn description(&self) -> String {
+        format!("regex {:?}", self.as_str())
+    }
+}
+
+/// Looks up matches in valid UTF-8 parts of a buffer, so a match doesn't span across invalid bytes.
+///
+/// `find` is called for each part and reports groups of each match it finds in it.
+fn find_in_utf8_chunks<F>(buf: &[u8], mut find: F) -> Result<Vec<Match>, Error>
+where
+    F: FnMut(&str, &mut ChunkMatches) -> Result<(), Error>,
+{
+    let mut found = ChunkMatches {
+        matches: Vec::new(),
+        offset: 0,
+    };
+
+    for chunk in buf.utf8_chunks() {
+        let text = chunk.valid();
+        find(text, &mut found)?;
+        found.offset += text.len() + chunk.invalid().len();
+    }
+
+    Ok(found.matches)
+}
+
+/// ChunkMatches collects matches of a part of a buffer, see [find_in_utf8_chunks].
+struct ChunkMatches {
+    matches: Vec<Match>,
+    offset: usize,
+}
+
+impl ChunkMatches {
+    /// Adds groups of a match, where ranges are relative to the current part.
+    fn push<I>(&mut self, groups: I)
+    where
+        I: Iterator<Item = Option<Range<usize>>>,
+    {
+        let offset = self.offset;
+        self.matches
+            .extend(groups.enumerate().filter_map(|(i, range)| {
+                range
+                    .map(|range| Match::new(offset + range.start, offset + range.end).with_group(i))
+            }));
+    }
+}
+
+/// FancyRegex tries to look up a match by a regex which may use backreferences and lookarounds.
+///
+/// Unlike [Regex] it's backed by a backtracking engine, so it's not guaranteed to run in linear time.
+/// Prefer [Regex] unless such features are required.
+///
+/// Only valid UTF-8 parts of a buffer are searched,
+/// so a match doesn't span across invalid bytes.
+///
+/// It's available with the `fancy-regex` feature.
+///
+/// # Example
+///
+/// ```no_run,ignore
+/// use expectrl::{spawn, FancyRegex};
+///
+/// let mut p = spawn("telnet router").unwrap();
+/// p.expect(FancyRegex(r"(?<=Hostname: )(\w+)-\1")).unwrap();
+/// ```
+#[cfg(feature = "fancy-regex")]
+#[derive(Debug)]
+pub struct FancyRegex<Re: AsRef<str>>(pub Re);
+
+#[cfg(feature = "fancy-regex")]
+impl<Re: AsRef<str>> Needle for FancyRegex<Re> {
+    fn check(&self, buf: &[u8], _: bool) -> Result<Vec<Match>, Error> {
+        let regex = fancy_regex::Regex::new(self.0.as_ref()).map_err(|_| Error::RegexParsing)?;
+
+        find_in_utf8_chunks(buf, |text, found| {
+            for captures in regex.captures_iter(text) {
+                let captures = captures.map_err(|err| Error::Other {
+                    message: "regex matching failed".to_string(),
+                    err: err.to_string(),
+                })?;
+
+                found.push(captures.iter().map(|m| m.map(|m| m.range())));
+            }
+
+            Ok(())
+        })
+    }
+
+    fn description(&self) -> String {
+        format!("fancy regex {:?}", self.0.as_ref())
+    }
+}
+
+/// Glob tries to look up a match by a shell-style wildcard pattern.
+///
+/// Like `-gl` patterns in Tcl expect it supports:
+///
+/// - `*` matches any sequence of characters, including an empty one.
+/// - `?` matches any single character.
+/// - `[abc]`, `[a-z]` matches a single character from a class; `[!a-z]` or `[^a-z]` negates it.
+/// - `\\` escapes a next character.
+///
+/// The pattern is not anchored, so it may match in the middle of the output.
+///
+/// # Example
+///
+/// ```no_run,ignore
+/// use expectrl::{spawn, Glob};
+///
+/// let mut p = spawn("wget https://example.com/archive.tar.gz").unwrap();
+/// p.expect(Glob("*.tar.gz* saved")).unwrap();
+/// ```
+#[derive(Debug)]
+pub struct Glob<S: AsRef<str>>(pub S);
+
+impl<S: AsRef<str>> Needle for Glob<S> {
+    fn check(&self, buf: &[u8], _: bool) -> Result<Vec<Match>, Error> {
+        let pattern = glob_to_regex(self.0.as_ref());
+        let regex = regex::bytes::Regex::new(&pattern).map_err(|_| Error::RegexParsing)?;
+        let matches = regex.find(buf).map(|m| m.into()).into_iter().collect();
+        Ok(matches)
+    }
+
+    fn description(&self) -> String {
+        format!("glob {:?}", self.0.as_ref())
+    }
+}
+
+/// Translates a glob pattern into a regex.
+fn glob_to_regex(glob: &str) -> String {
+    let mut re = String::from("(?s)");
+    let mut chars = glob.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' => re.push_str(".*"),
+            '?' => re.push('.'),
+            '\\' => match chars.next() {
+                Some(c) => re.push_str(&regex::escape(&c.to_string())),
+                None => re.push_str(&regex::escape("\\")),
+            },
+            '[' => {
+                let class = chars.clone().take_while(|&c| c != ']').collect::<String>();
+                let is_closed = chars.clone().nth(class.chars().count()) == Some(']');
+                if !is_closed || class.is_empty() {
+                    re.push_str(&regex::escape("["));
+                    continue;
+                }
+
+                for _ in 0..=class.chars().count() {
+                    let _ = chars.next();
+                }
+
+                re.push('[');
+                let mut class = class.chars().peekable();
+                if matches!(class.peek(), Some('!' | '^')) {
+                    let _ = class.next();
+                    re.push('^');
+                }
+
+                for c in class {
+                    match c {
+                        '-' => re.push('-'),
+                        c => re.push_str(&regex::escape(&c.to_string())),
+                    }
+                }
+                re.push(']');
+            }
+            c => re.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+
+    re
 }
 
 /// Eof consider a match when an EOF is reached.
@@ -67,6 +332,10 @@ impl Needle for Eof {
             false => Ok(Vec::new()),
         }
     }
+
+    fn description(&self) -> String {
+        "EOF".to_string()
+    }
 }
 
 /// NBytes matches N bytes from the stream.
@@ -86,6 +355,90 @@ impl Needle for NBytes {
             false => Ok(Vec::new()),
         }
     }
+
+    fn description(&self) -> String {
+        format!("{} bytes", self.count())
+    }
+}
+
+/// ByteRange matches whatever is available once at least `min` bytes arrived, but not more than `max` bytes.
+///
+/// It's useful for length based binary protocols, e.g. over a serial line,
+/// where a message is delivered in chunks of an unknown size.
+///
+/// If a timeout is reached before `min` bytes arrived,
+/// the partial data can be taken by `Session::take_buffer`.
+///
+/// # Example
+///
+/// ```no_run,ignore
+/// use expectrl::{spawn, ByteRange};
+///
+/// let mut p = spawn("cat /dev/ttyUSB0").unwrap();
+/// let frame = p.expect(ByteRange(4..=64)).unwrap();
+/// ```
+#[derive(Debug)]
+pub struct ByteRange(pub RangeInclusive<usize>);
+
+impl Needle for ByteRange {
+    fn check(&self, buf: &[u8], _: bool) -> Result<Vec<Match>, Error> {
+        let (min, max) = (*self.0.start(), *self.0.end());
+        match buf.len() >= min && min <= max {
+            true => Ok(vec![Match::new(0, std::cmp::min(buf.len(), max))]),
+            false => Ok(Vec::new()),
+        }
+    }
+
+    fn description(&self) -> String {
+        format!("{} to {} bytes", self.0.start(), self.0.end())
+    }
+}
+
+/// Line matches a line ending.
+///
+/// On EOF the rest of the buffer is considered to be a line.
+#[derive(Debug)]
+pub(crate) struct Line;
+
+impl Line {
+    /// Returns a line from captures without a line ending.
+    pub(crate) fn text(captures: &Captures) -> String {
+        let line = captures.before();
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+        String::from_utf8_lossy(line).into_owned()
+    }
+
+    /// Splits a buffer into lines without line endings.
+    ///
+    /// The last line may be incomplete.
+    pub(crate) fn split(buf: &[u8]) -> Vec<String> {
+        let buf = buf.strip_suffix(b"\n").unwrap_or(buf);
+        if buf.is_empty() {
+            return Vec::new();
+        }
+
+        buf.split(|&b| b == b'\n')
+            .map(|line| line.strip_suffix(b"\r").unwrap_or(line))
+            .map(|line| String::from_utf8_lossy(line).into_owned())
+            .collect()
+    }
+}
+
+impl Needle for Line {
+    fn check(&self, buf: &[u8], eof: bool) -> Result<Vec<Match>, Error> {
+        if let Some(pos) = buf.iter().position(|&b| b == b'\n') {
+            return Ok(vec![Match::new(pos, pos + 1)]);
+        }
+
+        match eof && !buf.is_empty() {
+            true => Ok(vec![Match::new(buf.len(), buf.len())]),
+            false => Ok(Vec::new()),
+        }
+    }
+
+    fn description(&self) -> String {
+        "a line".to_string()
+    }
 }
 
 impl Needle for [u8] {
@@ -107,11 +460,9 @@ impl Needle for [u8] {
 
         Ok(Vec::new())
     }
-}
 
-impl Needle for &[u8] {
-    fn check(&self, buf: &[u8], eof: bool) -> Result<Vec<Match>, Error> {
-        (*self).check(buf, eof)
+    fn description(&self) -> String {
+        format!("\"{}\"", self.escape_ascii())
     }
 }
 
@@ -119,11 +470,9 @@ impl Needle for str {
     fn check(&self, buf: &[u8], eof: bool) -> Result<Vec<Match>, Error> {
         self.as_bytes().check(buf, eof)
     }
-}
 
-impl Needle for &str {
-    fn check(&self, buf: &[u8], eof: bool) -> Result<Vec<Match>, Error> {
-        self.as_bytes().check(buf, eof)
+    fn description(&self) -> String {
+        format!("{:?}", self)
     }
 }
 
@@ -131,18 +480,137 @@ impl Needle for String {
     fn check(&self, buf: &[u8], eof: bool) -> Result<Vec<Match>, Error> {
         self.as_bytes().check(buf, eof)
     }
+
+    fn description(&self) -> String {
+        self.as_str().description()
+    }
 }
 
 impl Needle for u8 {
     fn check(&self, buf: &[u8], eof: bool) -> Result<Vec<Match>, Error> {
         ([*self][..]).check(buf, eof)
     }
+
+    fn description(&self) -> String {
+        format!("byte {:#04x}", self)
+    }
 }
 
 impl Needle for char {
     fn check(&self, buf: &[u8], eof: bool) -> Result<Vec<Match>, Error> {
         char::to_string(self).check(buf, eof)
     }
+
+    fn description(&self) -> String {
+        format!("{:?}", self)
+    }
+}
+
+/// CaseInsensitive matches a string ignoring ASCII case differences.
+///
+/// # Example
+///
+/// ```no_run,ignore
+/// use expectrl::{spawn, CaseInsensitive};
+///
+/// let mut p = spawn("ftp speedtest.tele2.net").unwrap();
+/// p.expect(CaseInsensitive("name")).unwrap();
+/// ```
+#[derive(Debug)]
+pub struct CaseInsensitive<S: AsRef<str>>(pub S);
+
+impl<S: AsRef<str>> Needle for CaseInsensitive<S> {
+    fn check(&self, buf: &[u8], _: bool) -> Result<Vec<Match>, Error> {
+        let needle = self.0.as_ref().as_bytes();
+        if buf.len() < needle.len() {
+            return Ok(Vec::new());
+        }
+
+        for l_bound in 0..=buf.len() - needle.len() {
+            let r_bound = l_bound + needle.len();
+            if needle.eq_ignore_ascii_case(&buf[l_bound..r_bound]) {
+                return Ok(vec![Match::new(l_bound, r_bound)]);
+            }
+        }
+
+        Ok(Vec::new())
+    }
+
+    fn description(&self) -> String {
+        format!("case insensitive {:?}", self.0.as_ref())
+    }
+}
+
+/// NormalizedWs matches a string treating any run of whitespace as equal to any other run.
+///
+/// So `"yes or no"` will be matched against `"yes  or\r\nno"`.
+/// Whitespace is considered to be a space, a tab, `\r` and `\n`.
+///
+/// # Example
+///
+/// ```no_run,ignore
+/// use expectrl::{spawn, NormalizedWs};
+///
+/// let mut p = spawn("my-installer").unwrap();
+/// p.expect(NormalizedWs("Continue? yes or no")).unwrap();
+/// ```
+#[derive(Debug)]
+pub struct NormalizedWs<S: AsRef<str>>(pub S);
+
+impl<S: AsRef<str>> Needle for NormalizedWs<S> {
+    fn check(&self, buf: &[u8], _: bool) -> Result<Vec<Match>, Error> {
+        let needle = self.0.as_ref().as_bytes();
+
+        for l_bound in 0..=buf.len() {
+            if let Some(r_bound) = match_normalized_ws(needle, &buf[l_bound..]) {
+                return Ok(vec![Match::new(l_bound, l_bound + r_bound)]);
+            }
+        }
+
+        Ok(Vec::new())
+    }
+
+    fn description(&self) -> String {
+        format!("{:?} with normalized whitespace", self.0.as_ref())
+    }
+}
+
+/// Matches a needle at the beginning of a buffer,
+/// where each run of whitespace in the needle consumes a run of whitespace in the buffer.
+///
+/// Returns an end index of a match.
+fn match_normalized_ws(needle: &[u8], buf: &[u8]) -> Option<usize> {
+    fn is_ws(b: u8) -> bool {
+        matches!(b, b' ' | b'\t' | b'\r' | b'\n')
+    }
+
+    let mut i = 0;
+    let mut j = 0;
+    while i < needle.len() {
+        if is_ws(needle[i]) {
+            while i < needle.len() && is_ws(needle[i]) {
+                i += 1;
+            }
+
+            let start = j;
+            while j < buf.len() && is_ws(buf[j]) {
+                j += 1;
+            }
+
+            if start == j {
+                return None;
+            }
+        } else {
+            if j == buf.len() || buf[j] != needle[i] {
+                return None;
+            }
+
+            i += 1;
+            j += 1;
+        }
+    }
+
+    Some(j)
 }
 
 /// Any matches uses all provided lookups and returns a match
@@ -182,15 +650,20 @@ where
     T: Needle,
 {
     fn check(&self, buf: &[u8], eof: bool) -> Result<Vec<Match>, Error> {
-        for needle in self.0.iter() {
+        for (i, needle) in self.0.iter().enumerate() {
             let found = needle.check(buf, eof)?;
             if !found.is_empty() {
+                let found = found.into_iter().map(|m| m.with_alternative(i)).collect();
                 return Ok(found);
             }
         }
 
         Ok(Vec::new())
     }
+
+    fn description(&self) -> String {
+        describe_list("any", self.0)
+    }
 }
 
 impl<T> Needle for Any<Vec<T>>
@@ -200,6 +673,10 @@ where
     fn check(&self, buf: &[u8], eof: bool) -> Result<Vec<Match>, Error> {
         Any(self.0.as_slice()).check(buf, eof)
     }
+
+    fn description(&self) -> String {
+        describe_list("any", &self.0)
+    }
 }
 
 impl<T, const N: usize> Needle for Any<[T; N]>
@@ -209,6 +686,10 @@ where
     fn check(&self, buf: &[u8], eof: bool) -> Result<Vec<Match>, Error> {
         Any(&self.0[..]).check(buf, eof)
     }
+
+    fn description(&self) -> String {
+        describe_list("any", &self.0)
+    }
 }
 
 impl<T, const N: usize> Needle for Any<&'_ [T; N]>
@@ -218,62 +699,635 @@ where
     fn check(&self, buf: &[u8], eof: bool) -> Result<Vec<Match>, Error> {
         Any(&self.0[..]).check(buf, eof)
     }
-}
 
-impl<T: Needle> Needle for &T {
-    fn check(&self, buf: &[u8], eof: bool) -> Result<Vec<Match>, Error> {
-        T::check(self, buf, eof)
+    fn description(&self) -> String {
+        describe_list("any", self.0)
     }
 }
 
-impl Needle for Box<dyn Needle + '_> {
+/// AnsiStripped runs a needle against a buffer with ANSI escape sequences removed.
+///
+/// It strips CSI sequences (e.g. colors `\x1b[31m`), OSC sequences (e.g. a window title)
+/// and other 2 byte escape sequences. Returned matches refer to the original buffer.
+///
+/// # Example
+///
+/// ```no_run,ignore
+/// use expectrl::{spawn, AnsiStripped};
+///
+/// let mut p = spawn("ls --color=always").unwrap();
+/// p.expect(AnsiStripped("Cargo.toml")).unwrap();
+/// ```
+#[derive(Debug)]
+pub struct AnsiStripped<N>(pub N);
+
+impl<N: Needle> Needle for AnsiStripped<N> {
     fn check(&self, buf: &[u8], eof: bool) -> Result<Vec<Match>, Error> {
-        self.as_ref().check(buf, eof)
-    }
-}
+        let (stripped, positions) = strip_ansi(buf);
+        let found = self.0.check(&stripped, eof)?;
+        let found = found
+            .into_iter()
+            .map(|m| {
+                let start = positions[m.start()];
+                let end = match m.end() > m.start() {
+                    true => positions[m.end() - 1] + 1,
+                    false => start,
+                };
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+                Match { start, end, ..m }
+            })
+            .collect();
 
-    #[test]
-    fn test_regex() {
-        assert_eq!(
-            Regex("[0-9]+").check(b"+012345", false).unwrap(),
-            vec![Match::new(1, 7)]
-        );
-        assert_eq!(
-            Regex(r"\w+").check(b"What's Up Boys", false).unwrap(),
-            vec![
-                Match::new(0, 4),
-                Match::new(5, 6),
-                Match::new(7, 9),
-                Match::new(10, 14)
-            ]
-        );
-        assert_eq!(
-            Regex(r"((?:\w|')+)")
-                .check(b"What's Up Boys", false)
-                .unwrap(),
-            vec![
-                Match::new(0, 6),
-                Match::new(0, 6),
-                Match::new(7, 9),
-                Match::new(7, 9),
-                Match::new(10, 14),
-                Match::new(10, 14)
-            ]
-        );
-        assert_eq!(
-            Regex(r"(\w+)=(\w+)").check(b"asd=123", false).unwrap(),
-            vec![Match::new(0, 7), Match::new(0, 3), Match::new(4, 7)]
-        );
+        Ok(found)
     }
 
-    #[test]
-    fn test_eof() {
-        assert_eq!(Eof.check(b"qwe", true).unwrap(), vec![Match::new(0, 3)]);
-        assert_eq!(Eof.check(b"qwe", false).unwrap(), vec![]);
+    fn description(&self) -> String {
+        format!("{} without ANSI sequences", self.0.description())
+    }
+}
+
+/// Removes ANSI escape sequences from a buffer.
+///
+/// It returns a stripped buffer and a list of original positions of each byte left.
+/// The list contains one more element which points to the end of the original buffer.
+///
+/// An unfinished sequence at the end of the buffer is dropped,
+/// as the rest of it most likely hasn't been read yet.
+fn strip_ansi(buf: &[u8]) -> (Vec<u8>, Vec<usize>) {
+    const ESC: u8 = 0x1b;
+    const BEL: u8 = 0x07;
+
+    let mut stripped = Vec::with_capacity(buf.len());
+    let mut positions = Vec::with_capacity(buf.len() + 1);
+
+    let mut i = 0;
+    while i < buf.len() {
+        if buf[i] != ESC {
+            stripped.push(buf[i]);
+            positions.push(i);
+            i += 1;
+            continue;
+        }
+
+        i += 1;
+        match buf.get(i) {
+            Some(b'[') => {
+                // CSI: parameter and intermediate bytes followed by a final byte.
+                i += 1;
+                while i < buf.len() && !(0x40..=0x7e).contains(&buf[i]) {
+                    i += 1;
+                }
+                i += 1;
+            }
+            Some(b']') => {
+                // OSC: terminated either by BEL or by ST (`ESC \`).
+                i += 1;
+                while i < buf.len() {
+                    if buf[i] == BEL {
+                        i += 1;
+                        break;
+                    }
+
+                    if buf[i] == ESC && buf.get(i + 1) == Some(&b'\\') {
+                        i += 2;
+                        break;
+                    }
+
+                    i += 1;
+                }
+            }
+            Some(_) => i += 1,
+            None => {}
+        }
+    }
+
+    positions.push(buf.len());
+
+    (stripped, positions)
+}
+
+fn describe_list<T: Needle>(name: &str, needles: &[T]) -> String {
+    let list = needles
+        .iter()
+        .map(|needle| needle.description())
+        .collect::<Vec<_>>();
+    format!("{} of [{}]", name, list.join(", "))
+}
+
+/// All matches only when all provided lookups are found.
+///
+/// It returns matches of all lookups, so the match ends at the one which was found the latest.
+///
+/// # Example
+///
+/// ```no_run,ignore
+/// use expectrl::{spawn, All};
+///
+/// let mut p = spawn("cat").unwrap();
+/// p.expect(All(["we", "are", "here"])).unwrap();
+/// ```
+#[derive(Debug)]
+pub struct All<I>(pub I);
+
+impl All<Vec<Box<dyn Needle>>> {
+    /// Boxed expectes a list of [Box]ed lookups.
+    pub fn boxed(v: Vec<Box<dyn Needle>>) -> Self {
+        Self(v)
+    }
+}
+
+impl<T> Needle for All<&[T]>
+where
+    T: Needle,
+{
+    fn check(&self, buf: &[u8], eof: bool) -> Result<Vec<Match>, Error> {
+        let mut matches = Vec::new();
+        for needle in self.0.iter() {
+            let found = needle.check(buf, eof)?;
+            if found.is_empty() {
+                return Ok(Vec::new());
+            }
+
+            matches.extend(found);
+        }
+
+        Ok(matches)
+    }
+
+    fn description(&self) -> String {
+        describe_list("all", self.0)
+    }
+}
+
+impl<T> Needle for All<Vec<T>>
+where
+    T: Needle,
+{
+    fn check(&self, buf: &[u8], eof: bool) -> Result<Vec<Match>, Error> {
+        All(self.0.as_slice()).check(buf, eof)
+    }
+
+    fn description(&self) -> String {
+        describe_list("all", &self.0)
+    }
+}
+
+impl<T, const N: usize> Needle for All<[T; N]>
+where
+    T: Needle,
+{
+    fn check(&self, buf: &[u8], eof: bool) -> Result<Vec<Match>, Error> {
+        All(&self.0[..]).check(buf, eof)
+    }
+
+    fn description(&self) -> String {
+        describe_list("all", &self.0)
+    }
+}
+
+impl<T, const N: usize> Needle for All<&'_ [T; N]>
+where
+    T: Needle,
+{
+    fn check(&self, buf: &[u8], eof: bool) -> Result<Vec<Match>, Error> {
+        All(&self.0[..]).check(buf, eof)
+    }
+
+    fn description(&self) -> String {
+        describe_list("all", self.0)
+    }
+}
+
+/// Then matches the first lookup followed by the second one.
+///
+/// The second lookup is searched only in bytes after the first match.
+///
+/// # Example
+///
+/// ```no_run,ignore
+/// use expectrl::{spawn, Then};
+///
+/// let mut p = spawn("cat").unwrap();
+/// p.expect(Then("login:", "password:")).unwrap();
+/// ```
+#[derive(Debug)]
+pub struct Then<A, B>(pub A, pub B);
+
+impl<A: Needle, B: Needle> Needle for Then<A, B> {
+    fn check(&self, buf: &[u8], eof: bool) -> Result<Vec<Match>, Error> {
+        let mut found = self.0.check(buf, eof)?;
+        if found.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let offset = found.iter().map(|m| m.end()).max().unwrap_or_default();
+        let next = self.1.check(&buf[offset..], eof)?;
+        if next.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        found.extend(next.into_iter().map(|m| Match {
+            start: m.start + offset,
+            end: m.end + offset,
+            ..m
+        }));
+
+        Ok(found)
+    }
+
+    fn description(&self) -> String {
+        format!("{} then {}", self.0.description(), self.1.description())
+    }
+}
+
+/// Not matches when a lookup is absent for a given window of time.
+///
+/// The window starts on a first check,
+/// so a new instance must be created for each expect call.
+/// It succeeds earlier if EOF is reached and the lookup wasn't found.
+///
+/// On success it returns an empty match at the beginning of a buffer,
+/// so no bytes are consumed.
+///
+/// # Example
+///
+/// ```no_run,ignore
+/// use std::time::Duration;
+/// use expectrl::{spawn, Not};
+///
+/// let mut p = spawn("cat").unwrap();
+/// p.expect(Not::new("error", Duration::from_secs(1))).unwrap();
+/// ```
+#[derive(Debug)]
+pub struct Not<N> {
+    needle: N,
+    window: Duration,
+    start: Cell<Option<Instant>>,
+}
+
+impl<N> Not<N> {
+    /// Creates a new [Not] lookup with a given window.
+    pub fn new(needle: N, window: Duration) -> Self {
+        Self {
+            needle,
+            window,
+            start: Cell::new(None),
+        }
+    }
+}
+
+impl<N: Needle> Needle for Not<N> {
+    fn check(&self, buf: &[u8], eof: bool) -> Result<Vec<Match>, Error> {
+        let start = match self.start.get() {
+            Some(start) => start,
+            None => {
+                let now = Instant::now();
+                self.start.set(Some(now));
+                now
+            }
+        };
+
+        let found = self.needle.check(buf, eof)?;
+        if !found.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        if eof || start.elapsed() >= self.window {
+            return Ok(vec![Match::new(0, 0)]);
+        }
+
+        Ok(Vec::new())
+    }
+
+    fn description(&self) -> String {
+        format!("no {} for {:?}", self.needle.description(), self.window)
+    }
+}
+
+/// ShellPrompt matches a common shell prompt ending at the end of the output,
+/// once nothing new arrived for a quiet period.
+///
+/// By default it's one of `$`, `#`, `>` and `%` followed by a space,
+/// and the output must stay the same for 100 milliseconds.
+/// The quiet period makes it less likely to match a `$` in the middle of a slow output.
+///
+/// It's a "good enough" prompt matcher for quick scripts against unknown devices,
+/// a known prompt is always more reliable.
+///
+/// The quiet period starts on a check which sees a prompt,
+/// so a new instance must be created for each expect call.
+///
+/// # Example
+///
+/// ```no_run,ignore
+/// use expectrl::{spawn, ShellPrompt};
+///
+/// let mut p = spawn("telnet router").unwrap();
+/// p.expect(ShellPrompt::new().endings("#>")).unwrap();
+/// ```
+#[derive(Debug)]
+pub struct ShellPrompt {
+    endings: Vec<u8>,
+    trailing_space: bool,
+    quiet: Duration,
+    seen: Cell<Option<(usize, Instant)>>,
+}
+
+impl ShellPrompt {
+    /// Creates a new [ShellPrompt] lookup with default settings.
+    pub fn new() -> Self {
+        Self {
+            endings: b"$#>%".to_vec(),
+            trailing_space: true,
+            quiet: Duration::from_millis(100),
+            seen: Cell::new(None),
+        }
+    }
+
+    /// Sets characters a prompt may end with.
+    pub fn endings<E: AsRef<[u8]>>(mut self, endings: E) -> Self {
+        self.endings = endings.as_ref().to_vec();
+        self
+    }
+
+    /// Sets whether a space after a prompt ending is required.
+    pub fn trailing_space(mut self, on: bool) -> Self {
+        self.trailing_space = on;
+        self
+    }
+
+    /// Sets a period the output must stay the same for.
+    pub fn quiet(mut self, period: Duration) -> Self {
+        self.quiet = period;
+        self
+    }
+
+    fn find(&self, buf: &[u8]) -> Option<usize> {
+        let buf = match self.trailing_space {
+            true => buf.strip_suffix(b" ")?,
+            false => buf,
+        };
+
+        let last = *buf.last()?;
+        match self.endings.contains(&last) {
+            true => Some(buf.len() - 1),
+            false => None,
+        }
+    }
+}
+
+impl Default for ShellPrompt {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Needle for ShellPrompt {
+    fn check(&self, buf: &[u8], eof: bool) -> Result<Vec<Match>, Error> {
+        let start = match self.find(buf) {
+            Some(start) => start,
+            None => {
+                self.seen.set(None);
+                return Ok(Vec::new());
+            }
+        };
+
+        let since = match self.seen.get() {
+            Some((len, since)) if len == buf.len() => since,
+            _ => {
+                let now = Instant::now();
+                self.seen.set(Some((buf.len(), now)));
+                now
+            }
+        };
+
+        if eof || since.elapsed() >= self.quiet {
+            return Ok(vec![Match::new(start, buf.len())]);
+        }
+
+        Ok(Vec::new())
+    }
+
+    fn description(&self) -> String {
+        "a shell prompt".to_string()
+    }
+}
+
+/// Utf8 checks a lookup only against complete UTF-8 characters.
+///
+/// A trailing incomplete character is not given to the lookup until the rest of it is read,
+/// and a match which splits a character is ignored.
+/// So [Captures::before] and matches are always valid UTF-8 if the output is.
+///
+/// It's useful for lookups like [Regex] or [NBytes].
+/// A `&str` lookup doesn't need it as a valid UTF-8 string can't match a part of a character.
+///
+/// # Example
+///
+/// ```no_run,ignore
+/// use expectrl::{spawn, Regex, Utf8};
+///
+/// let mut p = spawn("cat").unwrap();
+/// let captures = p.expect(Utf8(Regex("Hello .+"))).unwrap();
+/// println!("{}", captures.before_str_valid());
+/// ```
+#[derive(Debug)]
+pub struct Utf8<N>(pub N);
+
+impl<N: Needle> Needle for Utf8<N> {
+    fn check(&self, buf: &[u8], eof: bool) -> Result<Vec<Match>, Error> {
+        let len = match eof {
+            true => buf.len(),
+            false => complete_utf8_len(buf),
+        };
+
+        let buf = &buf[..len];
+        let found = self.0.check(buf, eof)?;
+
+        let is_boundary = |i: usize| i >= buf.len() || !matches!(buf[i], 0x80..=0xBF);
+        if found
+            .iter()
+            .any(|m| !is_boundary(m.start()) || !is_boundary(m.end()))
+        {
+            return Ok(Vec::new());
+        }
+
+        Ok(found)
+    }
+
+    fn description(&self) -> String {
+        self.0.description()
+    }
+}
+
+/// Returns a length of the buffer without a trailing incomplete UTF-8 character.
+pub(crate) fn complete_utf8_len(buf: &[u8]) -> usize {
+    // A character is at most 4 bytes long so only the last 3 bytes can be a part of an incomplete one.
+    for i in 1..=std::cmp::min(3, buf.len()) {
+        let len = match buf[buf.len() - i] {
+            0xF0..=0xF7 => 4,
+            0xE0..=0xEF => 3,
+            0xC0..=0xDF => 2,
+            0x80..=0xBF => continue,
+            _ => return buf.len(),
+        };
+
+        if len > i {
+            return buf.len() - i;
+        }
+
+        return buf.len();
+    }
+
+    buf.len()
+}
+
+impl<T: Needle + ?Sized> Needle for &T {
+    fn check(&self, buf: &[u8], eof: bool) -> Result<Vec<Match>, Error> {
+        T::check(self, buf, eof)
+    }
+
+    fn description(&self) -> String {
+        T::description(self)
+    }
+}
+
+impl<T: Needle + ?Sized> Needle for Box<T> {
+    fn check(&self, buf: &[u8], eof: bool) -> Result<Vec<Match>, Error> {
+        T::check(self, buf, eof)
+    }
+
+    fn description(&self) -> String {
+        T::description(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_regex() {
+        assert_eq!(
+            Regex("[0-9]+").check(b"+012345", false).unwrap(),
+            vec![Match::new(1, 7).with_group(0)]
+        );
+        assert_eq!(
+            Regex(r"\w+").check(b"What's Up Boys", false).unwrap(),
+            vec![
+                Match::new(0, 4).with_group(0),
+                Match::new(5, 6).with_group(0),
+                Match::new(7, 9).with_group(0),
+                Match::new(10, 14).with_group(0)
+            ]
+        );
+        assert_eq!(
+            Regex(r"((?:\w|')+)")
+                .check(b"What's Up Boys", false)
+                .unwrap(),
+            vec![
+                Match::new(0, 6).with_group(0),
+                Match::new(0, 6).with_group(1),
+                Match::new(7, 9).with_group(0),
+                Match::new(7, 9).with_group(1),
+                Match::new(10, 14).with_group(0),
+                Match::new(10, 14).with_group(1)
+            ]
+        );
+        assert_eq!(
+            Regex(r"(\w+)=(\w+)").check(b"asd=123", false).unwrap(),
+            vec![
+                Match::new(0, 7).with_group(0),
+                Match::new(0, 3).with_group(1),
+                Match::new(4, 7).with_group(2)
+            ]
+        );
+    }
+
+    #[cfg(feature = "fancy-regex")]
+    #[test]
+    fn test_fancy_regex() {
+        assert_eq!(
+            FancyRegex(r"(\w+) \1")
+                .check(b"say hello hello", false)
+                .unwrap(),
+            vec![
+                Match::new(4, 15).with_group(0),
+                Match::new(4, 9).with_group(1)
+            ]
+        );
+        assert_eq!(
+            FancyRegex(r"(?<=\$ )\d+")
+                .check(b"# 1 $ 23", false)
+                .unwrap(),
+            vec![Match::new(6, 8).with_group(0)]
+        );
+        assert_eq!(
+            FancyRegex(r"\d+").check(b"1\xFF\xFE23", false).unwrap(),
+            vec![
+                Match::new(0, 1).with_group(0),
+                Match::new(3, 5).with_group(0)
+            ]
+        );
+        assert!(FancyRegex(r"(").check(b"", false).is_err());
+    }
+
+    #[test]
+    fn test_precompiled_regex() {
+        let regex = regex::bytes::Regex::new(r"(\d+)\.").unwrap();
+        assert_eq!(
+            regex.check(b"v12.3", false).unwrap(),
+            vec![
+                Match::new(1, 4).with_group(0),
+                Match::new(1, 3).with_group(1)
+            ]
+        );
+
+        let regex = regex::Regex::new(r"\d+").unwrap();
+        assert_eq!(
+            regex.check(b"1\xFF\xFE23", false).unwrap(),
+            vec![
+                Match::new(0, 1).with_group(0),
+                Match::new(3, 5).with_group(0)
+            ]
+        );
+        assert_eq!(
+            regex.check("привет 1".as_bytes(), false).unwrap(),
+            vec![Match::new(13, 14).with_group(0)]
+        );
+    }
+
+    #[test]
+    fn test_glob() {
+        assert_eq!(
+            Glob("*.tar.gz saved")
+                .check(b"'archive.tar.gz saved'", false)
+                .unwrap(),
+            vec![Match::new(0, 21)]
+        );
+        assert_eq!(
+            Glob("file?.txt").check(b"ls: file1.txt", false).unwrap(),
+            vec![Match::new(4, 13)]
+        );
+        assert_eq!(
+            Glob("[0-9][!a-z]").check(b"a1b23", false).unwrap(),
+            vec![Match::new(3, 5)]
+        );
+        assert_eq!(
+            Glob(r"\*[ab]").check(b"a*b", false).unwrap(),
+            vec![Match::new(1, 3)]
+        );
+        assert_eq!(
+            Glob("[]x").check(b"[]x", false).unwrap(),
+            vec![Match::new(0, 3)]
+        );
+        assert_eq!(Glob("a?c").check(b"ac", false).unwrap(), vec![]);
+    }
+
+    #[test]
+    fn test_eof() {
+        assert_eq!(Eof.check(b"qwe", true).unwrap(), vec![Match::new(0, 3)]);
+        assert_eq!(Eof.check(b"qwe", false).unwrap(), vec![]);
     }
 
     #[test]
@@ -289,6 +1343,22 @@ mod tests {
         assert_eq!(NBytes(10).check(b"qwe", false).unwrap(), vec![]);
     }
 
+    #[test]
+    fn test_byte_range() {
+        assert_eq!(ByteRange(2..=4).check(b"1", false).unwrap(), vec![]);
+        assert_eq!(
+            ByteRange(2..=4).check(b"123", false).unwrap(),
+            vec![Match::new(0, 3)]
+        );
+        assert_eq!(
+            ByteRange(2..=4).check(b"123456", false).unwrap(),
+            vec![Match::new(0, 4)]
+        );
+        #[allow(clippy::reversed_empty_ranges)]
+        let empty = ByteRange(4..=2);
+        assert_eq!(empty.check(b"123456", false).unwrap(), vec![]);
+    }
+
     #[test]
     fn test_str() {
         assert_eq!(
@@ -346,13 +1416,230 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_complete_utf8_len() {
+        assert_eq!(complete_utf8_len(b""), 0);
+        assert_eq!(complete_utf8_len(b"abc"), 3);
+        assert_eq!(complete_utf8_len("Ж".as_bytes()), 2);
+        assert_eq!(complete_utf8_len(&"Ж".as_bytes()[..1]), 0);
+        assert_eq!(complete_utf8_len(&"a😀".as_bytes()[..4]), 1);
+        assert_eq!(complete_utf8_len(b"a\xff"), 2);
+    }
+
+    #[test]
+    fn test_utf8() {
+        let buf = "Hello Жук".as_bytes();
+        assert_eq!(
+            Utf8(Regex("Hello .+")).check(&buf[..7], false).unwrap(),
+            vec![]
+        );
+        assert_eq!(
+            Utf8(Regex("Hello .+")).check(&buf[..9], false).unwrap(),
+            vec![Match::new(0, 8).with_group(0)]
+        );
+        assert_eq!(
+            Utf8(Regex("Hello .+")).check(buf, false).unwrap(),
+            vec![Match::new(0, 12).with_group(0)]
+        );
+        assert_eq!(Utf8(NBytes(7)).check(buf, false).unwrap(), vec![]);
+        assert_eq!(
+            Utf8(NBytes(8)).check(buf, false).unwrap(),
+            vec![Match::new(0, 8)]
+        );
+        assert_eq!(
+            Utf8(Eof).check(&buf[..9], true).unwrap(),
+            vec![Match::new(0, 9)]
+        );
+    }
+
+    #[test]
+    fn test_ansi_stripped() {
+        assert_eq!(
+            AnsiStripped("Hello World")
+                .check(b"\x1b[1;31mHello\x1b[0m World", false)
+                .unwrap(),
+            vec![Match::new(7, 22)]
+        );
+        assert_eq!(
+            AnsiStripped("title")
+                .check(b"\x1b]0;title\x07prompt$ ", false)
+                .unwrap(),
+            vec![]
+        );
+        assert_eq!(
+            AnsiStripped("prompt$")
+                .check(b"\x1b]0;title\x1b\\prompt$ ", false)
+                .unwrap(),
+            vec![Match::new(11, 18)]
+        );
+        assert_eq!(
+            AnsiStripped(Regex(r"\d+"))
+                .check(b"\x1b[32m12\x1b[0m3\x1b[", false)
+                .unwrap(),
+            vec![Match::new(5, 12).with_group(0)]
+        );
+        assert_eq!(
+            AnsiStripped(Eof).check(b"\x1b[0mqwe", true).unwrap(),
+            vec![Match::new(4, 7)]
+        );
+    }
+
+    #[test]
+    fn test_case_insensitive() {
+        assert_eq!(
+            CaseInsensitive("login:")
+                .check(b"Last LOGIN: today\r\nLogin:", false)
+                .unwrap(),
+            vec![Match::new(5, 11)]
+        );
+        assert_eq!(
+            CaseInsensitive("password")
+                .check(b"passwor", false)
+                .unwrap(),
+            vec![]
+        );
+        assert_eq!(
+            CaseInsensitive("").check(b"qwerty", false).unwrap(),
+            vec![Match::new(0, 0)]
+        );
+    }
+
+    #[test]
+    fn test_normalized_ws() {
+        assert_eq!(
+            NormalizedWs("yes or no")
+                .check(b"Answer yes  or\r\nno?", false)
+                .unwrap(),
+            vec![Match::new(7, 18)]
+        );
+        assert_eq!(
+            NormalizedWs("yes or no").check(b"yesor no", false).unwrap(),
+            vec![]
+        );
+        assert_eq!(
+            NormalizedWs("yes \n").check(b"yes\t", false).unwrap(),
+            vec![Match::new(0, 4)]
+        );
+        assert_eq!(
+            NormalizedWs("").check(b"qwerty", false).unwrap(),
+            vec![Match::new(0, 0)]
+        );
+    }
+
+    #[test]
+    fn test_all() {
+        assert_eq!(
+            All(["rty", "we"]).check(b"qwerty", false).unwrap(),
+            vec![Match::new(3, 6), Match::new(1, 3)]
+        );
+        assert_eq!(All(["rty", "123"]).check(b"qwerty", false).unwrap(), vec![]);
+        assert_eq!(
+            All::boxed(vec![Box::new("we"), Box::new(NBytes(1))])
+                .check(b"qwerty", false)
+                .unwrap(),
+            vec![Match::new(1, 3), Match::new(0, 1)]
+        );
+        assert_eq!(
+            All(vec!["q", "y"]).check(b"qwerty", false).unwrap(),
+            vec![Match::new(0, 1), Match::new(5, 6)]
+        );
+    }
+
+    #[test]
+    fn test_then() {
+        assert_eq!(
+            Then("login:", "password:")
+                .check(b"password: login: password:", false)
+                .unwrap(),
+            vec![Match::new(10, 16), Match::new(17, 26)]
+        );
+        assert_eq!(
+            Then("login:", "password:")
+                .check(b"password: login:", false)
+                .unwrap(),
+            vec![]
+        );
+        assert_eq!(Then("a", "b").check(b"b", false).unwrap(), vec![]);
+    }
+
+    #[test]
+    fn test_not() {
+        let not = Not::new("error", Duration::from_secs(60));
+        assert_eq!(not.check(b"ok", false).unwrap(), vec![]);
+        assert_eq!(not.check(b"ok", true).unwrap(), vec![Match::new(0, 0)]);
+        assert_eq!(not.check(b"an error", true).unwrap(), vec![]);
+
+        let not = Not::new("error", Duration::from_millis(10));
+        assert_eq!(not.check(b"ok", false).unwrap(), vec![]);
+        std::thread::sleep(Duration::from_millis(20));
+        assert_eq!(not.check(b"ok", false).unwrap(), vec![Match::new(0, 0)]);
+        assert_eq!(not.check(b"error", false).unwrap(), vec![]);
+    }
+
+    #[test]
+    fn test_shell_prompt() {
+        let prompt = ShellPrompt::new().quiet(Duration::ZERO);
+        assert_eq!(
+            prompt.check(b"user@host:~$ ", false).unwrap(),
+            vec![Match::new(11, 13)]
+        );
+        assert_eq!(
+            prompt.check(b"root# ", false).unwrap(),
+            vec![Match::new(4, 6)]
+        );
+        assert_eq!(prompt.check(b"costs 5$", false).unwrap(), vec![]);
+        assert_eq!(prompt.check(b"$ ls\r\n", false).unwrap(), vec![]);
+
+        let prompt = ShellPrompt::new().endings(":").trailing_space(false);
+        assert_eq!(prompt.check(b"$ ", true).unwrap(), vec![]);
+        assert_eq!(
+            prompt.check(b"Router:", true).unwrap(),
+            vec![Match::new(6, 7)]
+        );
+
+        let prompt = ShellPrompt::new().quiet(Duration::from_millis(10));
+        assert_eq!(prompt.check(b"$ ", false).unwrap(), vec![]);
+        std::thread::sleep(Duration::from_millis(20));
+        assert_eq!(prompt.check(b"$ ls", false).unwrap(), vec![]);
+        assert_eq!(prompt.check(b"$ ls $ ", false).unwrap(), vec![]);
+        std::thread::sleep(Duration::from_millis(20));
+        assert_eq!(
+            prompt.check(b"$ ls $ ", false).unwrap(),
+            vec![Match::new(5, 7)]
+        );
+    }
+
+    #[test]
+    fn test_description() {
+        assert_eq!("login:".description(), "\"login:\"");
+        assert_eq!(Regex("\\d+").description(), "regex \"\\\\d+\"");
+        assert_eq!(
+            Any(["login:", "password:"]).description(),
+            "any of [\"login:\", \"password:\"]"
+        );
+        assert_eq!(Then(NBytes(2), Eof).description(), "2 bytes then EOF");
+        assert_eq!(b"\r\n"[..].description(), "\"\\r\\n\"");
+
+        struct Custom;
+
+        impl Needle for Custom {
+            fn check(&self, _: &[u8], _: bool) -> Result<Vec<Match>, Error> {
+                Ok(Vec::new())
+            }
+        }
+
+        assert!(Custom.description().ends_with("Custom"));
+        let boxed: Box<dyn Needle> = Box::new(Custom);
+        assert!(boxed.description().ends_with("Custom"));
+    }
+
     #[test]
     fn test_any() {
         assert_eq!(
             Any::<Vec<Box<dyn Needle>>>(vec![Box::new("we"), Box::new(NBytes(3))])
                 .check(b"qwerty", false)
                 .unwrap(),
-            vec![Match::new(1, 3)]
+            vec![Match::new(1, 3).with_alternative(0)]
         );
         assert_eq!(
             Any::boxed(vec![Box::new("123"), Box::new(NBytes(100))])
@@ -362,17 +1649,60 @@ mod tests {
         );
         assert_eq!(
             Any(["123", "234", "rty"]).check(b"qwerty", false).unwrap(),
-            vec![Match::new(3, 6)]
+            vec![Match::new(3, 6).with_alternative(2)]
         );
         assert_eq!(
             Any(&["123", "234", "rty"][..])
                 .check(b"qwerty", false)
                 .unwrap(),
-            vec![Match::new(3, 6)]
+            vec![Match::new(3, 6).with_alternative(2)]
         );
         assert_eq!(
             Any(&["123", "234", "rty"]).check(b"qwerty", false).unwrap(),
+            vec![Match::new(3, 6).with_alternative(2)]
+        );
+    }
+
+    #[test]
+    fn test_dyn_needle() {
+        let needles: Vec<Box<dyn Needle>> = vec![Box::new("rty"), Box::new(Regex("[0-9]+"))];
+        assert_eq!(
+            needles[0].check(b"qwerty", false).unwrap(),
             vec![Match::new(3, 6)]
         );
+
+        let needle: &dyn Needle = &NBytes(2);
+        assert_eq!(
+            needle.check(b"qwerty", false).unwrap(),
+            vec![Match::new(0, 2)]
+        );
+        assert_eq!(
+            (&needle).check(b"qwerty", false).unwrap(),
+            vec![Match::new(0, 2)]
+        );
+
+        let needle: Box<dyn Needle + Send> = Box::new("we");
+        assert_eq!(
+            needle.check(b"qwerty", false).unwrap(),
+            vec![Match::new(1, 3)]
+        );
+    }
+
+    #[test]
+    fn test_line() {
+        assert_eq!(
+            Line.check(b"Hello\r\nWorld", false).unwrap(),
+            vec![Match::new(6, 7)]
+        );
+        assert_eq!(Line.check(b"Hello", false).unwrap(), vec![]);
+        assert_eq!(Line.check(b"Hello", true).unwrap(), vec![Match::new(5, 5)]);
+        assert_eq!(Line.check(b"", true).unwrap(), vec![]);
+
+        let captures = Captures::new(b"Hello\r\n".to_vec(), vec![Match::new(6, 7)]);
+        assert_eq!(Line::text(&captures), "Hello");
+
+        assert_eq!(Line::split(b"Hello\r\nWorld"), vec!["Hello", "World"]);
+        assert_eq!(Line::split(b"Hello\n\n"), vec!["Hello", ""]);
+        assert_eq!(Line::split(b""), Vec::<String>::new());
     }
 }
@@ -0,0 +1,214 @@
+//! This module contains an [`AnswerPolicy`] which answers confirmation prompts automatically
+//! and a [`RetryPolicy`] which repeats an expect which was timed out.
+//!
+//! # Example
+//!
+#![cfg_attr(all(unix, not(feature = "async")), doc = "```no_run")]
+#![cfg_attr(not(all(unix, not(feature = "async"))), doc = "```ignore")]
+//! use expectrl::{policy::AnswerPolicy, spawn, Regex};
+//!
+//! let mut policy = AnswerPolicy::new()
+//!     .confirm(true)
+//!     .on(Regex("(?i)do you accept the license"), "accept");
+//!
+//! let mut session = spawn("./install.sh").unwrap();
+//! session.expect_with_policy("Installation finished", &mut policy).unwrap();
+//! ```
+
+use std::time::Duration;
+
+use crate::{Captures, Error, Needle, Regex};
+
+/// AnswerPolicy holds a list of rules,
+/// each of them is a prompt and an answer which must be sent once the prompt is seen.
+///
+/// It's used by `Session::expect_with_policy`.
+///
+/// Rules are checked in the order they were added.
+#[derive(Default)]
+pub struct AnswerPolicy {
+    rules: Vec<Rule>,
+    answered: usize,
+}
+
+struct Rule {
+    prompt: Box<dyn Needle>,
+    answer: String,
+}
+
+impl AnswerPolicy {
+    /// Creates a policy without any rules.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a rule which sends an answer as a line once a prompt is matched.
+    pub fn on<N, A>(mut self, prompt: N, answer: A) -> Self
+    where
+        N: Needle + 'static,
+        A: Into<String>,
+    {
+        self.rules.push(Rule {
+            prompt: Box::new(prompt),
+            answer: answer.into(),
+        });
+        self
+    }
+
+    /// Adds rules for common confirmation prompts.
+    ///
+    /// Prompts like `[y/N]`, `[Y/n]`, `(y/n)` are answered by `y` or `n`,
+    /// and `(yes/no)`, `[yes/no]` are answered by `yes` or `no`.
+    pub fn confirm(self, yes: bool) -> Self {
+        let (short, long) = match yes {
+            true => ("y", "yes"),
+            false => ("n", "no"),
+        };
+
+        self.on(Regex(r"(?i)[\[(]yes/no[\])]\??\s*$"), long)
+            .on(Regex(r"(?i)[\[(]y/n[\])]\??\s*:?\s*$"), short)
+    }
+
+    /// Returns a number of answers which were sent.
+    pub fn answered(&self) -> usize {
+        self.answered
+    }
+
+    /// Checks whether one of the prompts is matched.
+    ///
+    /// It returns an end index of the prompt and an answer.
+    pub(crate) fn check(&mut self, buf: &[u8], eof: bool) -> Result<Option<(usize, &str)>, Error> {
+        for rule in &self.rules {
+            let found = rule.prompt.check(buf, eof)?;
+            if !found.is_empty() {
+                self.answered += 1;
+                let end = Captures::right_most_index(&found);
+                return Ok(Some((end, &rule.answer)));
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+impl std::fmt::Debug for AnswerPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AnswerPolicy")
+            .field(
+                "answers",
+                &self.rules.iter().map(|r| &r.answer).collect::<Vec<_>>(),
+            )
+            .field("answered", &self.answered)
+            .finish()
+    }
+}
+
+/// RetryPolicy determines how many times an expect is repeated after it's timed out.
+///
+/// It's used by `Session::expect_with_retry`.
+///
+/// # Example
+///
+#[cfg_attr(all(unix, not(feature = "async")), doc = "```no_run")]
+#[cfg_attr(not(all(unix, not(feature = "async"))), doc = "```ignore")]
+/// use std::time::Duration;
+/// use expectrl::{policy::RetryPolicy, spawn};
+///
+/// let policy = RetryPolicy::new(3)
+///     .backoff(Duration::from_millis(500))
+///     .probe("\n");
+///
+/// let mut session = spawn("telnet 192.168.0.1").unwrap();
+/// session.expect_with_retry("login:", &policy).unwrap();
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RetryPolicy {
+    retries: usize,
+    backoff: Duration,
+    probe: Option<Vec<u8>>,
+}
+
+impl RetryPolicy {
+    /// Creates a policy which repeats an expect a given number of times.
+    pub fn new(retries: usize) -> Self {
+        Self {
+            retries,
+            ..Default::default()
+        }
+    }
+
+    /// Sets a delay which is made before each retry.
+    pub fn backoff(mut self, backoff: Duration) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    /// Sets a probe which is sent before each retry,
+    /// e.g. a new line to make a device print a prompt again.
+    pub fn probe<B: AsRef<[u8]>>(mut self, probe: B) -> Self {
+        self.probe = Some(probe.as_ref().to_vec());
+        self
+    }
+
+    /// Returns a number of retries.
+    pub fn get_retries(&self) -> usize {
+        self.retries
+    }
+
+    /// Returns a delay which is made before each retry.
+    pub fn get_backoff(&self) -> Duration {
+        self.backoff
+    }
+
+    /// Returns a probe which is sent before each retry.
+    pub fn get_probe(&self) -> Option<&[u8]> {
+        self.probe.as_deref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_confirm() {
+        let mut policy = AnswerPolicy::new().confirm(true);
+        assert_eq!(
+            policy.check(b"Continue? [y/N] ", false).unwrap(),
+            Some((16, "y"))
+        );
+        assert_eq!(
+            policy.check(b"Are you sure (yes/no)? ", false).unwrap(),
+            Some((23, "yes"))
+        );
+        assert_eq!(policy.check(b"Hello World", false).unwrap(), None);
+        assert_eq!(policy.answered(), 2);
+
+        let mut policy = AnswerPolicy::new().confirm(false);
+        assert_eq!(
+            policy.check(b"Remove file? (Y/n):", false).unwrap(),
+            Some((19, "n"))
+        );
+    }
+
+    #[test]
+    fn test_rules_order() {
+        let mut policy = AnswerPolicy::new().on("Select [1-3]:", "2").confirm(true);
+        assert_eq!(
+            policy.check(b"Select [1-3]: ", false).unwrap(),
+            Some((13, "2"))
+        );
+    }
+
+    #[test]
+    fn test_retry_policy() {
+        let policy = RetryPolicy::new(2)
+            .backoff(Duration::from_millis(100))
+            .probe("\n");
+        assert_eq!(policy.get_retries(), 2);
+        assert_eq!(policy.get_backoff(), Duration::from_millis(100));
+        assert_eq!(policy.get_probe(), Some(&b"\n"[..]));
+
+        assert_eq!(RetryPolicy::new(1).get_probe(), None);
+    }
+}
@@ -0,0 +1,228 @@
+//! This module contains a [`Report`] which renders a failed expectation in a human readable way.
+//!
+//! It's available with the `pretty-errors` feature.
+//!
+//! # Example
+//!
+//! ```no_run,ignore
+//! use expectrl::{spawn, pretty::Report};
+//!
+//! let mut p = spawn("login").unwrap();
+//! if let Err(err) = p.expect("Password:") {
+//!     panic!("{}", Report::new(err, "Password:", p.buffer()));
+//! }
+//! ```
+
+use std::{error, fmt};
+
+use crate::Error;
+
+const TAIL_LENGTH: usize = 256;
+
+const GREEN: &str = "\x1b[32m";
+const RED: &str = "\x1b[31m";
+const DIM: &str = "\x1b[2m";
+const RESET: &str = "\x1b[0m";
+
+/// Report is an error of an expect call together with what was expected and what was actually read.
+///
+/// Control characters are shown as escape sequences, e.g. `\r` or `\x1b`.
+/// The longest beginning of the expected text which is found in the output is highlighted,
+/// so it's easy to see how close the output was.
+#[derive(Debug)]
+pub struct Report {
+    error: Error,
+    expected: Vec<u8>,
+    tail: Vec<u8>,
+    truncated: bool,
+    colored: bool,
+}
+
+impl Report {
+    /// Creates a report.
+    ///
+    /// Only the last 256 bytes of the buffer are kept.
+    pub fn new<E, B>(error: Error, expected: E, buffer: B) -> Self
+    where
+        E: AsRef<[u8]>,
+        B: AsRef<[u8]>,
+    {
+        let buffer = buffer.as_ref();
+        let start = buffer.len().saturating_sub(TAIL_LENGTH);
+
+        Self {
+            error,
+            expected: expected.as_ref().to_vec(),
+            tail: buffer[start..].to_vec(),
+            truncated: start > 0,
+            colored: true,
+        }
+    }
+
+    /// Sets whether ANSI colors are used.
+    ///
+    /// They're used by default.
+    pub fn colored(mut self, on: bool) -> Self {
+        self.colored = on;
+        self
+    }
+
+    /// Returns an original error.
+    pub fn error(&self) -> &Error {
+        &self.error
+    }
+
+    /// Returns an original error, dropping the report.
+    pub fn into_error(self) -> Error {
+        self.error
+    }
+
+    fn paint(&self, f: &mut fmt::Formatter<'_>, color: &str, buf: &[u8]) -> fmt::Result {
+        if buf.is_empty() {
+            return Ok(());
+        }
+
+        match self.colored {
+            true => write!(f, "{}{}{}", color, Escaped(buf), RESET),
+            false => write!(f, "{}", Escaped(buf)),
+        }
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let partial = closest_partial_match(&self.expected, &self.tail);
+        let matched = partial.as_ref().map_or(0, |(_, len)| *len);
+
+        writeln!(f, "{}", self.error)?;
+
+        write!(f, "  expected: ")?;
+        self.paint(f, GREEN, &self.expected[..matched])?;
+        self.paint(f, RED, &self.expected[matched..])?;
+        writeln!(f)?;
+
+        write!(f, "  actual:   ")?;
+        if self.truncated {
+            self.paint(f, DIM, b"...")?;
+        }
+
+        match partial {
+            Some((start, len)) => {
+                self.paint(f, DIM, &self.tail[..start])?;
+                self.paint(f, GREEN, &self.tail[start..start + len])?;
+                self.paint(f, DIM, &self.tail[start + len..])?;
+            }
+            None => self.paint(f, DIM, &self.tail)?,
+        }
+
+        Ok(())
+    }
+}
+
+impl error::Error for Report {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+impl From<Report> for Error {
+    fn from(report: Report) -> Self {
+        report.error
+    }
+}
+
+/// Looks for the longest prefix of the expected text in the buffer.
+///
+/// It returns a position of its right most occurrence and its length.
+fn closest_partial_match(expected: &[u8], buf: &[u8]) -> Option<(usize, usize)> {
+    (1..=expected.len()).rev().find_map(|len| {
+        let prefix = &expected[..len];
+        buf.windows(len)
+            .rposition(|window| window == prefix)
+            .map(|start| (start, len))
+    })
+}
+
+/// Escaped shows control characters and not printable bytes as escape sequences.
+struct Escaped<'a>(&'a [u8]);
+
+impl fmt::Display for Escaped<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for chunk in self.0.utf8_chunks() {
+            for c in chunk.valid().chars() {
+                match c {
+                    '\r' => f.write_str("\\r")?,
+                    '\n' => f.write_str("\\n")?,
+                    '\t' => f.write_str("\\t")?,
+                    '\\' => f.write_str("\\\\")?,
+                    c if c.is_control() => write!(f, "\\x{:02x}", c as u32)?,
+                    c => write!(f, "{}", c)?,
+                }
+            }
+
+            for b in chunk.invalid() {
+                write!(f, "\\x{:02x}", b)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_closest_partial_match() {
+        assert_eq!(
+            closest_partial_match(b"login:", b"Last login\r\n"),
+            Some((5, 5))
+        );
+        assert_eq!(closest_partial_match(b"login:", b"log log"), Some((4, 3)));
+        assert_eq!(closest_partial_match(b"login:", b"password"), None);
+        assert_eq!(closest_partial_match(b"", b"password"), None);
+    }
+
+    #[test]
+    fn test_escaped() {
+        assert_eq!(
+            Escaped(b"a\r\n\t\x1b[0m\\\xFF\x80").to_string(),
+            "a\\r\\n\\t\\x1b[0m\\\\\\xff\\x80"
+        );
+        assert_eq!(Escaped("привет".as_bytes()).to_string(), "привет");
+    }
+
+    #[test]
+    fn test_report() {
+        let report = Report::new(Error::ExpectTimeout, "login:", "Last login\r\n").colored(false);
+        assert_eq!(
+            report.to_string(),
+            "Reached a timeout for expect type of command\n  \
+             expected: login:\n  \
+             actual:   Last login\\r\\n"
+        );
+
+        let report = Report::new(Error::Eof, "login:", "Last login\r\n");
+        assert_eq!(
+            report.to_string(),
+            "EOF was reached; the read may successed later\n  \
+             expected: \x1b[32mlogin\x1b[0m\x1b[31m:\x1b[0m\n  \
+             actual:   \x1b[2mLast \x1b[0m\x1b[32mlogin\x1b[0m\x1b[2m\\r\\n\x1b[0m"
+        );
+    }
+
+    #[test]
+    fn test_report_truncates_buffer() {
+        let buffer = "a".repeat(1000);
+        let report = Report::new(Error::ExpectTimeout, "b", &buffer).colored(false);
+        assert_eq!(
+            report.to_string(),
+            format!(
+                "Reached a timeout for expect type of command\n  expected: b\n  actual:   ...{}",
+                "a".repeat(256)
+            )
+        );
+        assert!(matches!(report.into_error(), Error::ExpectTimeout));
+    }
+}
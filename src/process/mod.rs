@@ -1,12 +1,20 @@
 //! This module contains a platform independent abstraction over an os process.
 
-use std::io::Result;
+use std::{fmt, io::Result, thread, time::Duration};
 
+#[cfg(unix)]
+use ptyprocess::Signal;
+
+#[cfg(unix)]
+pub mod piped;
 #[cfg(unix)]
 pub mod unix;
 #[cfg(windows)]
 pub mod windows;
 
+/// A wait used when there's no way to be notified about an event.
+const WAIT_TICK: Duration = Duration::from_millis(10);
+
 /// This trait represents a platform independent process which runs a program.
 pub trait Process: Sized {
     /// A command which process can run.
@@ -29,6 +37,115 @@ pub trait Healthcheck {
     fn is_alive(&mut self) -> Result<bool>;
 }
 
+/// Termination represents an ability to stop a spawned process and get its exit status.
+pub trait Termination {
+    /// Asks a process to terminate.
+    ///
+    /// If `force` is set the process is killed right away.
+    ///
+    /// A process which has already exited and was reaped must not be signaled,
+    /// as its id may be reused by another process.
+    fn terminate(&mut self, force: bool) -> Result<()>;
+    /// Returns an exit status of a process if it has exited.
+    ///
+    /// It doesn't block.
+    fn try_wait(&mut self) -> Result<Option<ExitStatus>>;
+
+    /// Blocks until a process exits or a timeout is reached.
+    ///
+    /// [None] means there's no timeout.
+    /// It may return early, so a caller must check an exit status by [Termination::try_wait].
+    ///
+    /// The default implementation sleeps for a short tick.
+    fn wait_exit(&mut self, timeout: Option<Duration>) -> Result<()> {
+        sleep_tick(timeout);
+        Ok(())
+    }
+
+    /// An async version of [Termination::wait_exit].
+    ///
+    /// The default implementation sleeps for a short tick.
+    #[cfg(feature = "async")]
+    fn wait_exit_async(
+        &mut self,
+        timeout: Option<Duration>,
+    ) -> futures_lite::future::Boxed<Result<()>> {
+        let wait = tick(timeout);
+        Box::pin(async move {
+            futures_timer::Delay::new(wait).await;
+            Ok(())
+        })
+    }
+}
+
+/// ExitStatus is a platform independent status a process exited with.
+///
+/// On unix it distinguishes an exit code and a signal which terminated the process.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ExitStatus(ExitKind);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum ExitKind {
+    Code(i32),
+    #[cfg(unix)]
+    Signal(Signal),
+}
+
+impl ExitStatus {
+    /// Creates a status of a process exited with a code.
+    pub fn from_code(code: i32) -> Self {
+        Self(ExitKind::Code(code))
+    }
+
+    /// Creates a status of a process terminated by a signal.
+    #[cfg(unix)]
+    pub fn from_signal(signal: Signal) -> Self {
+        Self(ExitKind::Signal(signal))
+    }
+
+    /// Verifies whether a process exited with 0 code.
+    pub fn success(&self) -> bool {
+        self.code() == Some(0)
+    }
+
+    /// Returns an exit code of a process.
+    ///
+    /// It returns [None] if the process was terminated by a signal.
+    pub fn code(&self) -> Option<i32> {
+        match self.0 {
+            ExitKind::Code(code) => Some(code),
+            #[cfg(unix)]
+            ExitKind::Signal(_) => None,
+        }
+    }
+
+    /// Returns a signal which terminated a process.
+    #[cfg(unix)]
+    pub fn signal(&self) -> Option<Signal> {
+        match self.0 {
+            ExitKind::Code(_) => None,
+            ExitKind::Signal(signal) => Some(signal),
+        }
+    }
+
+    /// Returns a raw exit code of a process.
+    #[cfg(windows)]
+    pub fn raw_code(&self) -> u32 {
+        let ExitKind::Code(code) = self.0;
+        code as u32
+    }
+}
+
+impl fmt::Display for ExitStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            ExitKind::Code(code) => write!(f, "exit code: {}", code),
+            #[cfg(unix)]
+            ExitKind::Signal(signal) => write!(f, "signal: {}", signal),
+        }
+    }
+}
+
 /// NonBlocking interface represens a [std::io::Read]er which can be turned in a non blocking mode
 /// so its read operations will return imideately.
 pub trait NonBlocking {
@@ -36,6 +153,27 @@ pub trait NonBlocking {
     fn set_non_blocking(&mut self) -> Result<()>;
     /// Sets a [std::io::Read]er back into a blocking mode.
     fn set_blocking(&mut self) -> Result<()>;
+
+    /// Blocks until there's something to read or a timeout is reached.
+    ///
+    /// [None] means there's no timeout.
+    /// It may return early, so a caller must check whether something was actually read.
+    ///
+    /// The default implementation sleeps for a short tick.
+    fn wait_readable(&mut self, timeout: Option<Duration>) -> Result<()> {
+        sleep_tick(timeout);
+        Ok(())
+    }
+}
+
+/// Sleeps for a short tick but not longer than a given timeout.
+pub(crate) fn sleep_tick(timeout: Option<Duration>) {
+    thread::sleep(tick(timeout));
+}
+
+/// Limits a given timeout by a short tick.
+pub(crate) fn tick(timeout: Option<Duration>) -> Duration {
+    timeout.map_or(WAIT_TICK, |timeout| timeout.min(WAIT_TICK))
 }
 
 #[cfg(feature = "async")]
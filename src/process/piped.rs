@@ -0,0 +1,175 @@
+//! This module contains a [PipedProcess] which is a [Process] run without a pseudo terminal.
+
+use std::{
+    fs::File,
+    io::{self, Result},
+    os::unix::{
+        io::{AsRawFd, FromRawFd, OwnedFd, RawFd},
+        process::ExitStatusExt,
+    },
+    process::{Child, Command, Stdio},
+    time::Duration,
+};
+
+use nix::{
+    fcntl::{fcntl, FcntlArg, FdFlag},
+    sys::signal::{kill, Signal},
+    unistd::Pid,
+};
+
+#[cfg(feature = "async")]
+use super::unix::wait_exit_async;
+use super::{
+    unix::{tokenize_command, wait_exit},
+    ExitStatus, Healthcheck, Process, Termination,
+};
+use crate::{error::to_io_error, stream::pipe::PipeStream};
+
+/// PipedProcess is a process which stdin, stdout and stderr are plain pipes.
+///
+/// Programs often behave differently when they're not attached to a terminal,
+/// e.g. they don't print prompts, disable colors and buffer the output.
+///
+/// The stderr is merged with the stdout into a single pipe,
+/// the same way a terminal merges them.
+#[derive(Debug)]
+pub struct PipedProcess {
+    child: Child,
+    output: Option<File>,
+}
+
+impl PipedProcess {
+    /// Returns a pid of the process.
+    pub fn pid(&self) -> u32 {
+        self.child.id()
+    }
+
+    /// Returns an underlying child.
+    pub fn get_child(&self) -> &Child {
+        &self.child
+    }
+
+    /// Returns a mut underlying child.
+    pub fn get_child_mut(&mut self) -> &mut Child {
+        &mut self.child
+    }
+}
+
+impl Process for PipedProcess {
+    type Command = Command;
+    type Stream = PipeStream;
+
+    fn spawn<S: AsRef<str>>(cmd: S) -> Result<Self> {
+        let args = tokenize_command(cmd.as_ref());
+        if args.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Failed to parse a command",
+            ));
+        }
+
+        let mut command = Command::new(&args[0]);
+        let _ = command.args(args.iter().skip(1));
+
+        Self::spawn_command(command)
+    }
+
+    fn spawn_command(mut command: Self::Command) -> Result<Self> {
+        let (reader, writer) = pipe()?;
+
+        let _ = command
+            .stdin(Stdio::piped())
+            .stdout(Stdio::from(writer.try_clone()?))
+            .stderr(Stdio::from(writer));
+
+        let child = command.spawn()?;
+
+        // The command keeps the write ends,
+        // so it must be dropped for EOF to be reached after the child exits.
+        drop(command);
+
+        Ok(Self {
+            child,
+            output: Some(reader),
+        })
+    }
+
+    fn open_stream(&mut self) -> Result<Self::Stream> {
+        let input = self.child.stdin.take();
+        let output = self.output.take();
+        match (output, input) {
+            (Some(output), Some(input)) => {
+                let input = File::from(OwnedFd::from(input));
+                Ok(PipeStream::new(output, input))
+            }
+            _ => Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                "A stream was already opened",
+            )),
+        }
+    }
+}
+
+impl Healthcheck for PipedProcess {
+    fn is_alive(&mut self) -> Result<bool> {
+        self.child.try_wait().map(|status| status.is_none())
+    }
+}
+
+impl Termination for PipedProcess {
+    fn terminate(&mut self, force: bool) -> Result<()> {
+        if force {
+            return self.child.kill();
+        }
+
+        let pid = Pid::from_raw(self.child.id() as i32);
+        kill(pid, Signal::SIGTERM).map_err(to_io_error("Failed to send a signal"))
+    }
+
+    fn try_wait(&mut self) -> Result<Option<ExitStatus>> {
+        let status = match self.child.try_wait()? {
+            Some(status) => status,
+            None => return Ok(None),
+        };
+
+        if let Some(code) = status.code() {
+            return Ok(Some(ExitStatus::from_code(code)));
+        }
+
+        match status.signal().map(Signal::try_from) {
+            Some(Ok(signal)) => Ok(Some(ExitStatus::from_signal(signal))),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Unexpected exit status",
+            )),
+        }
+    }
+    fn wait_exit(&mut self, timeout: Option<Duration>) -> Result<()> {
+        wait_exit(Pid::from_raw(self.child.id() as i32), timeout)
+    }
+
+    #[cfg(feature = "async")]
+    fn wait_exit_async(
+        &mut self,
+        timeout: Option<Duration>,
+    ) -> futures_lite::future::Boxed<Result<()>> {
+        wait_exit_async(Pid::from_raw(self.child.id() as i32), timeout)
+    }
+}
+
+/// Creates a pipe which descriptors are not inherited by other children.
+fn pipe() -> Result<(File, File)> {
+    let (reader, writer) = nix::unistd::pipe()?;
+    // SAFETY: the descriptors are just created and owned by nobody else.
+    let (reader, writer) = unsafe { (File::from_raw_fd(reader), File::from_raw_fd(writer)) };
+
+    set_cloexec(reader.as_raw_fd())?;
+    set_cloexec(writer.as_raw_fd())?;
+
+    Ok((reader, writer))
+}
+
+fn set_cloexec(fd: RawFd) -> Result<()> {
+    let _ = fcntl(fd, FcntlArg::F_SETFD(FdFlag::FD_CLOEXEC))?;
+    Ok(())
+}
@@ -1,8 +1,11 @@
 //! This module contains a Unix implementation of [crate::process::Process].
 
-use super::{Healthcheck, NonBlocking, Process};
+#[cfg(feature = "async")]
+use super::tick;
+use super::{sleep_tick, ExitStatus, Healthcheck, NonBlocking, Process, Termination};
 use crate::error::to_io_error;
-use ptyprocess::{stream::Stream, PtyProcess};
+use nix::{libc, unistd::Pid};
+use ptyprocess::{stream::Stream, PtyProcess, Signal, WaitStatus};
 
 #[cfg(feature = "async")]
 use super::IntoAsyncStream;
@@ -17,14 +20,17 @@ use std::{
 use std::{
     io::{self, Read, Result, Write},
     ops::{Deref, DerefMut},
-    os::unix::prelude::{AsRawFd, RawFd},
+    os::unix::prelude::{AsFd, AsRawFd, BorrowedFd, FromRawFd, OwnedFd, RawFd},
     process::Command,
+    sync::OnceLock,
+    time::Duration,
 };
 
 /// A Unix representation of a [Process] via [PtyProcess]
 #[derive(Debug)]
 pub struct UnixProcess {
     proc: PtyProcess,
+    exit_status: OnceLock<WaitStatus>,
 }
 
 impl Process for UnixProcess {
@@ -49,7 +55,10 @@ impl Process for UnixProcess {
     fn spawn_command(command: Self::Command) -> Result<Self> {
         let proc = PtyProcess::spawn(command).map_err(to_io_error("Failed to spawn a command"))?;
 
-        Ok(Self { proc })
+        Ok(Self {
+            proc,
+            exit_status: OnceLock::new(),
+        })
     }
 
     fn open_stream(&mut self) -> Result<Self::Stream> {
@@ -62,11 +71,98 @@ impl Process for UnixProcess {
     }
 }
 
+impl UnixProcess {
+    /// Returns a status of the process without blocking.
+    ///
+    /// Unlike [PtyProcess::status] it remembers an exit status once the process is reaped,
+    /// so it's safe to be called repeatedly.
+    pub fn status(&self) -> nix::Result<WaitStatus> {
+        if let Some(status) = self.exit_status.get() {
+            return Ok(*status);
+        }
+
+        let status = self.proc.status()?;
+        self.save_exit_status(status);
+
+        Ok(status)
+    }
+
+    /// Waits until the process changes its state.
+    ///
+    /// If the process was already reaped by [UnixProcess::status] or [UnixProcess::is_alive]
+    /// its exit status is returned.
+    pub fn wait(&self) -> nix::Result<WaitStatus> {
+        if let Some(status) = self.exit_status.get() {
+            return Ok(*status);
+        }
+
+        let status = self.proc.wait()?;
+        self.save_exit_status(status);
+
+        Ok(status)
+    }
+
+    /// Verifies whether the process is still alive.
+    ///
+    /// See [UnixProcess::status].
+    pub fn is_alive(&self) -> nix::Result<bool> {
+        match self.status() {
+            Ok(WaitStatus::StillAlive) => Ok(true),
+            Ok(_) | Err(nix::Error::ECHILD) | Err(nix::Error::ESRCH) => Ok(false),
+            Err(err) => Err(err),
+        }
+    }
+
+    fn save_exit_status(&self, status: WaitStatus) {
+        if let WaitStatus::Exited(..) | WaitStatus::Signaled(..) = status {
+            let _ = self.exit_status.set(status);
+        }
+    }
+}
+
 impl Healthcheck for UnixProcess {
     fn is_alive(&mut self) -> Result<bool> {
+        UnixProcess::is_alive(self).map_err(to_io_error("Failed to call pty.is_alive()"))
+    }
+}
+
+impl Termination for UnixProcess {
+    fn terminate(&mut self, force: bool) -> Result<()> {
+        if self.exit_status.get().is_some() {
+            return Ok(());
+        }
+
+        let signal = match force {
+            true => Signal::SIGKILL,
+            false => Signal::SIGTERM,
+        };
+
         self.proc
-            .is_alive()
-            .map_err(to_io_error("Failed to call pty.is_alive()"))
+            .signal(signal)
+            .map_err(to_io_error("Failed to call pty.signal()"))
+    }
+
+    fn try_wait(&mut self) -> Result<Option<ExitStatus>> {
+        let status =
+            UnixProcess::status(self).map_err(to_io_error("Failed to call pty.status()"))?;
+
+        match status {
+            WaitStatus::Exited(_, code) => Ok(Some(ExitStatus::from_code(code))),
+            WaitStatus::Signaled(_, signal, _) => Ok(Some(ExitStatus::from_signal(signal))),
+            _ => Ok(None),
+        }
+    }
+
+    fn wait_exit(&mut self, timeout: Option<Duration>) -> Result<()> {
+        wait_exit(self.proc.pid(), timeout)
+    }
+
+    #[cfg(feature = "async")]
+    fn wait_exit_async(
+        &mut self,
+        timeout: Option<Duration>,
+    ) -> futures_lite::future::Boxed<Result<()>> {
+        wait_exit_async(self.proc.pid(), timeout)
     }
 }
 
@@ -84,21 +180,85 @@ impl DerefMut for UnixProcess {
     }
 }
 
+/// A maximum length of a line a terminal keeps in canonical mode.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+const MAX_CANON: usize = 4095;
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+const MAX_CANON: usize = 255;
+
 /// A IO stream (write/read) of [UnixProcess].
+///
+/// In canonical mode a terminal discards characters of a line which is longer than its buffer.
+/// So a long line is split into chunks which are passed to the process by an EOF character,
+/// which is the way a terminal flushes a not finished line.
+///
+/// A terminal mode is checked only once a line gets too long,
+/// and writes in non canonical mode are passed as they are.
 #[derive(Debug)]
 pub struct PtyStream {
     handle: Stream,
+    line_length: usize,
 }
 
 impl PtyStream {
     fn new(stream: Stream) -> Self {
-        Self { handle: stream }
+        Self {
+            handle: stream,
+            line_length: 0,
+        }
+    }
+
+    fn count_line_length(&mut self, written: &[u8]) {
+        let length = match written.iter().rposition(|&b| b == b'\n') {
+            Some(i) => written.len() - i - 1,
+            None => self.line_length + written.len(),
+        };
+
+        self.line_length = std::cmp::min(length, MAX_CANON);
+    }
+
+    /// Returns an EOF character if the terminal is in canonical mode.
+    fn canonical_eof_char(&self) -> Result<Option<u8>> {
+        use nix::sys::termios::{tcgetattr, LocalFlags, SpecialCharacterIndices};
+
+        let termios = tcgetattr(self.handle.as_raw_fd()).map_err(nix_error_to_io)?;
+        if !termios.local_flags.contains(LocalFlags::ICANON) {
+            return Ok(None);
+        }
+
+        Ok(Some(
+            termios.control_chars[SpecialCharacterIndices::VEOF as usize],
+        ))
     }
 }
 
 impl Write for PtyStream {
     fn write(&mut self, buf: &[u8]) -> Result<usize> {
-        self.handle.write(buf)
+        let line = buf.iter().position(|&b| b == b'\n').unwrap_or(buf.len());
+        if self.line_length + line > MAX_CANON {
+            if let Some(eof) = self.canonical_eof_char()? {
+                if self.line_length == MAX_CANON {
+                    self.handle.write_all(&[eof])?;
+                    self.line_length = 0;
+                }
+
+                let length = std::cmp::min(buf.len(), MAX_CANON.saturating_sub(self.line_length));
+                let n = self.handle.write(&buf[..length])?;
+                self.line_length += n;
+
+                return Ok(n);
+            }
+
+            // there's no line buffer in non canonical mode,
+            // so the line isn't tracked till it gets too long again
+            self.line_length = 0;
+            return self.handle.write(buf);
+        }
+
+        let n = self.handle.write(buf)?;
+        self.count_line_length(&buf[..n]);
+
+        Ok(n)
     }
 
     fn flush(&mut self) -> Result<()> {
@@ -106,7 +266,27 @@ impl Write for PtyStream {
     }
 
     fn write_vectored(&mut self, bufs: &[io::IoSlice<'_>]) -> Result<usize> {
-        self.handle.write_vectored(bufs)
+        let length = bufs.iter().map(|buf| buf.len()).sum::<usize>();
+        if self.line_length + length > MAX_CANON {
+            if self.canonical_eof_char()?.is_some() {
+                let buf = bufs.iter().find(|buf| !buf.is_empty());
+                return self.write(buf.map_or(&[], |buf| buf));
+            }
+
+            self.line_length = 0;
+            return self.handle.write_vectored(bufs);
+        }
+
+        let n = self.handle.write_vectored(bufs)?;
+
+        let mut rest = n;
+        for buf in bufs {
+            let written = std::cmp::min(rest, buf.len());
+            self.count_line_length(&buf[..written]);
+            rest -= written;
+        }
+
+        Ok(n)
     }
 }
 
@@ -126,6 +306,10 @@ impl NonBlocking for PtyStream {
         let fd = self.handle.as_raw_fd();
         make_non_blocking(fd, false)
     }
+
+    fn wait_readable(&mut self, timeout: Option<Duration>) -> Result<()> {
+        wait_readable(self.handle.as_raw_fd(), timeout)
+    }
 }
 
 impl AsRawFd for PtyStream {
@@ -134,6 +318,13 @@ impl AsRawFd for PtyStream {
     }
 }
 
+impl AsFd for PtyStream {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        // SAFETY: the descriptor is owned by the stream and it's valid while the stream is alive.
+        unsafe { BorrowedFd::borrow_raw(self.as_raw_fd()) }
+    }
+}
+
 #[cfg(feature = "async")]
 impl IntoAsyncStream for PtyStream {
     type AsyncStream = AsyncPtyStream;
@@ -158,6 +349,20 @@ impl AsyncPtyStream {
     }
 }
 
+#[cfg(feature = "async")]
+impl AsRawFd for AsyncPtyStream {
+    fn as_raw_fd(&self) -> RawFd {
+        self.stream.as_raw_fd()
+    }
+}
+
+#[cfg(feature = "async")]
+impl AsFd for AsyncPtyStream {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.stream.get_ref().as_fd()
+    }
+}
+
 #[cfg(feature = "async")]
 impl AsyncWrite for AsyncPtyStream {
     fn poll_write(
@@ -205,6 +410,136 @@ pub(crate) fn make_non_blocking(fd: RawFd, blocking: bool) -> Result<()> {
     Ok(())
 }
 
+/// Blocks until a file descriptor becomes readable or a timeout is reached.
+///
+/// A hang up is reported as readable, so a following read can observe EOF.
+pub(crate) fn wait_readable(fd: RawFd, timeout: Option<Duration>) -> Result<()> {
+    use nix::poll::{poll, PollFd, PollFlags};
+
+    let timeout = match timeout {
+        // round up so a timeout less than a millisecond doesn't turn into a busy loop
+        Some(timeout) => timeout
+            .as_nanos()
+            .div_ceil(1_000_000)
+            .try_into()
+            .unwrap_or(i32::MAX),
+        None => -1,
+    };
+
+    let mut fds = [PollFd::new(fd, PollFlags::POLLIN)];
+    match poll(&mut fds, timeout) {
+        Ok(_) | Err(nix::errno::Errno::EINTR) => Ok(()),
+        Err(err) => Err(nix_error_to_io(err)),
+    }
+}
+
+/// Blocks until a process exits or a timeout is reached.
+///
+/// It may return early.
+/// If a process can't be watched on the platform it sleeps for a short tick.
+pub(crate) fn wait_exit(pid: Pid, timeout: Option<Duration>) -> Result<()> {
+    match open_exit_fd(pid) {
+        Ok(fd) => wait_readable(fd.as_raw_fd(), timeout),
+        // the process has already exited and was reaped
+        Err(err) if err.raw_os_error() == Some(libc::ESRCH) => Ok(()),
+        Err(_) => {
+            sleep_tick(timeout);
+            Ok(())
+        }
+    }
+}
+
+/// An async version of [wait_exit].
+#[cfg(feature = "async")]
+pub(crate) fn wait_exit_async(
+    pid: Pid,
+    timeout: Option<Duration>,
+) -> futures_lite::future::Boxed<Result<()>> {
+    let fd = open_exit_fd(pid).and_then(async_io::Async::new);
+    let exit = async move {
+        match fd {
+            Ok(fd) => fd.readable().await,
+            Err(err) if err.raw_os_error() == Some(libc::ESRCH) => Ok(()),
+            Err(_) => {
+                futures_timer::Delay::new(tick(None)).await;
+                Ok(())
+            }
+        }
+    };
+
+    match timeout {
+        Some(timeout) => Box::pin(futures_lite::future::or(exit, async move {
+            futures_timer::Delay::new(timeout).await;
+            Ok(())
+        })),
+        None => Box::pin(exit),
+    }
+}
+
+/// Opens a descriptor which becomes readable once a process exits.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn open_exit_fd(pid: Pid) -> Result<OwnedFd> {
+    // SAFETY: the call takes a pid and flags and returns a new descriptor.
+    let fd = unsafe { libc::syscall(libc::SYS_pidfd_open, pid.as_raw(), 0) };
+    if fd == -1 {
+        return Err(io::Error::last_os_error());
+    }
+
+    // SAFETY: the descriptor is just created and owned by nobody else.
+    Ok(unsafe { OwnedFd::from_raw_fd(fd as RawFd) })
+}
+
+/// Opens a descriptor which becomes readable once a process exits.
+#[cfg(any(target_os = "macos", target_os = "ios", target_os = "freebsd"))]
+fn open_exit_fd(pid: Pid) -> Result<OwnedFd> {
+    // SAFETY: the call takes no arguments and returns a new descriptor.
+    let kq = unsafe { libc::kqueue() };
+    if kq == -1 {
+        return Err(io::Error::last_os_error());
+    }
+
+    // SAFETY: the descriptor is just created and owned by nobody else.
+    let kq = unsafe { OwnedFd::from_raw_fd(kq) };
+
+    // SAFETY: all fields of the struct are plain numbers and pointers, so zeros are valid.
+    let mut event: libc::kevent = unsafe { std::mem::zeroed() };
+    event.ident = pid.as_raw() as libc::uintptr_t;
+    event.filter = libc::EVFILT_PROC;
+    event.flags = libc::EV_ADD | libc::EV_ONESHOT;
+    event.fflags = libc::NOTE_EXIT;
+
+    // SAFETY: the event outlives the call and no events are asked to be returned.
+    let ret = unsafe {
+        libc::kevent(
+            kq.as_raw_fd(),
+            &event,
+            1,
+            std::ptr::null_mut(),
+            0,
+            std::ptr::null(),
+        )
+    };
+    if ret == -1 {
+        return Err(io::Error::last_os_error());
+    }
+
+    Ok(kq)
+}
+
+#[cfg(not(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "freebsd"
+)))]
+fn open_exit_fd(_: Pid) -> Result<OwnedFd> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "A process exit can't be watched on the platform",
+    ))
+}
+
 fn nix_error_to_io(err: nix::Error) -> io::Error {
     io::Error::new(io::ErrorKind::Other, err)
 }
@@ -214,7 +549,7 @@ fn nix_error_to_io(err: nix::Error) -> io::Error {
 ///
 /// It doesn't cover all edge cases.
 /// So it may not be compatible with real shell arguments parsing.
-fn tokenize_command(program: &str) -> Vec<String> {
+pub(crate) fn tokenize_command(program: &str) -> Vec<String> {
     let re = regex::Regex::new(r#""[^"]+"|'[^']+'|[^'" ]+"#).unwrap();
     let mut res = vec![];
     for cap in re.captures_iter(program) {
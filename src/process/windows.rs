@@ -1,9 +1,14 @@
 //! This module contains a Windows implementation of [crate::process::Process].
 
 use std::{
+    collections::HashMap,
+    ffi::{c_void, OsStr, OsString},
     io::{self, Read, Result, Write},
     ops::{Deref, DerefMut},
+    os::windows::ffi::{OsStrExt, OsStringExt},
     process::Command,
+    sync::{Condvar, Mutex},
+    time::Duration,
 };
 
 use conpty::{
@@ -11,7 +16,9 @@ use conpty::{
     spawn, Process,
 };
 
-use super::{Healthcheck, NonBlocking, Process as ProcessTrait};
+use super::{
+    sleep_tick, ExitStatus, Healthcheck, NonBlocking, Process as ProcessTrait, Termination,
+};
 use crate::error::to_io_error;
 
 #[cfg(feature = "async")]
@@ -20,10 +27,59 @@ use super::IntoAsyncStream;
 use futures_lite::{AsyncRead, AsyncWrite};
 #[cfg(feature = "async")]
 use std::{
+    os::windows::io::{AsRawHandle, FromRawHandle, OwnedHandle, RawHandle},
     pin::Pin,
     task::{Context, Poll},
 };
 
+const CTRL_C_EVENT: u32 = 0;
+const CTRL_BREAK_EVENT: u32 = 1;
+const ATTACH_PARENT_PROCESS: u32 = u32::MAX;
+// How long a control event may take to reach the current process.
+const CTRL_EVENT_TIMEOUT: Duration = Duration::from_secs(1);
+
+#[cfg(feature = "async")]
+const SYNCHRONIZE: u32 = 0x0010_0000;
+#[cfg(feature = "async")]
+const INFINITE: u32 = u32::MAX;
+#[cfg(feature = "async")]
+const WAIT_FAILED: u32 = u32::MAX;
+
+type CtrlHandler = unsafe extern "system" fn(event: u32) -> i32;
+
+#[link(name = "kernel32")]
+extern "system" {
+    fn FreeConsole() -> i32;
+    fn AttachConsole(pid: u32) -> i32;
+    fn GetConsoleWindow() -> *mut c_void;
+    fn SetConsoleCtrlHandler(handler: Option<CtrlHandler>, add: i32) -> i32;
+    fn GenerateConsoleCtrlEvent(event: u32, process_group: u32) -> i32;
+    #[cfg(feature = "async")]
+    fn OpenProcess(access: u32, inherit: i32, pid: u32) -> RawHandle;
+    #[cfg(feature = "async")]
+    fn WaitForSingleObject(handle: RawHandle, millis: u32) -> u32;
+}
+
+/// Serializes control events, as the current process is moved to a console of a target process meanwhile.
+static CONSOLE_LOCK: Mutex<()> = Mutex::new(());
+
+/// Set once a generated control event reaches the current process.
+static CTRL_EVENT_DELIVERED: Mutex<bool> = Mutex::new(false);
+static CTRL_EVENT_CONDVAR: Condvar = Condvar::new();
+
+/// A console control event which can be delivered to a [WinProcess].
+///
+/// The variants are named after unix signals,
+/// so `session.signal(Signal::SIGINT)` works the same way on both platforms.
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Signal {
+    /// `CTRL_C_EVENT`.
+    SIGINT,
+    /// `CTRL_BREAK_EVENT`.
+    SIGBREAK,
+}
+
 /// A windows representation of a [Process] via [conpty::Process].
 #[derive(Debug)]
 pub struct WinProcess {
@@ -41,7 +97,7 @@ impl ProcessTrait for WinProcess {
     }
 
     fn spawn_command(command: Self::Command) -> Result<Self> {
-        conpty::Process::spawn(command)
+        conpty::Process::spawn(translate_command(&command))
             .map_err(to_io_error(""))
             .map(|proc| WinProcess { proc })
     }
@@ -53,12 +109,93 @@ impl ProcessTrait for WinProcess {
     }
 }
 
+impl WinProcess {
+    /// Resizes a pseudo console of the process.
+    ///
+    /// Sizes which don't fit into a console coordinate are clamped.
+    pub fn resize(&mut self, rows: u16, cols: u16) -> Result<()> {
+        let rows = i16::try_from(rows).unwrap_or(i16::MAX);
+        let cols = i16::try_from(cols).unwrap_or(i16::MAX);
+        self.proc
+            .resize(cols, rows)
+            .map_err(to_io_error("Failed to resize a pseudo console"))
+    }
+
+    /// Generates a console control event for all processes attached to the pseudo console of the process.
+    ///
+    /// Unlike writing `0x03` to the console input,
+    /// it works for programs which turned processed input off.
+    ///
+    /// A control event can be generated only for a console the caller is attached to,
+    /// so the current process is attached to the pseudo console for a short time.
+    /// Meanwhile the standard handles of the current process don't refer to its console.
+    pub fn signal(&mut self, signal: Signal) -> Result<()> {
+        let event = match signal {
+            Signal::SIGINT => CTRL_C_EVENT,
+            Signal::SIGBREAK => CTRL_BREAK_EVENT,
+        };
+
+        generate_ctrl_event(self.proc.pid(), event)
+    }
+}
+
 impl Healthcheck for WinProcess {
     fn is_alive(&mut self) -> Result<bool> {
         Ok(self.proc.is_alive())
     }
 }
 
+impl Termination for WinProcess {
+    /// A process is asked to terminate by `CTRL_BREAK_EVENT`,
+    /// and it's terminated right away if `force` is set.
+    fn terminate(&mut self, force: bool) -> Result<()> {
+        if !self.proc.is_alive() {
+            return Ok(());
+        }
+
+        if !force {
+            return self.signal(Signal::SIGBREAK);
+        }
+
+        self.proc
+            .exit(1)
+            .map_err(to_io_error("Failed to terminate a process"))
+    }
+
+    fn try_wait(&mut self) -> Result<Option<ExitStatus>> {
+        match self.proc.wait(Some(0)) {
+            Ok(code) => Ok(Some(ExitStatus::from_code(code as i32))),
+            Err(conpty::error::Error::Timeout(_)) => Ok(None),
+            Err(err) => Err(to_io_error("Failed to wait for a process")(err)),
+        }
+    }
+
+    fn wait_exit(&mut self, timeout: Option<Duration>) -> Result<()> {
+        match self.proc.wait(timeout.map(duration_to_millis)) {
+            Ok(_) | Err(conpty::error::Error::Timeout(_)) => Ok(()),
+            Err(err) => Err(to_io_error("Failed to wait for a process")(err)),
+        }
+    }
+
+    /// A process handle is waited on by a blocking thread.
+    #[cfg(feature = "async")]
+    fn wait_exit_async(
+        &mut self,
+        timeout: Option<Duration>,
+    ) -> futures_lite::future::Boxed<Result<()>> {
+        let handle = open_process(self.proc.pid());
+        let timeout = timeout.map_or(INFINITE, duration_to_millis);
+        Box::pin(blocking::unblock(move || {
+            let handle = handle?;
+            // SAFETY: the handle is valid till it's dropped at the end of the closure.
+            match unsafe { WaitForSingleObject(handle.as_raw_handle(), timeout) } {
+                WAIT_FAILED => Err(io::Error::last_os_error()),
+                _ => Ok(()),
+            }
+        }))
+    }
+}
+
 impl Deref for WinProcess {
     type Target = Process;
 
@@ -73,6 +210,188 @@ impl DerefMut for WinProcess {
     }
 }
 
+/// Makes a command which conpty spawns the same way as [Command::spawn] would.
+///
+/// conpty joins arguments with spaces as they are,
+/// and if any variable is set it passes only the set variables as an environment.
+/// So arguments are quoted here and the inherited environment is merged into the command.
+///
+/// A program is left as it is, because a commandline in a program name is commonly used,
+/// e.g. `Command::new("python ./main.py")`.
+/// [Command::env_clear] can't be observed and so it has no effect.
+fn translate_command(command: &Command) -> Command {
+    let mut cmd = Command::new(command.get_program());
+
+    for arg in command.get_args() {
+        let _ = cmd.arg(quote_arg(arg));
+    }
+
+    if let Some(dir) = command.get_current_dir() {
+        let _ = cmd.current_dir(dir);
+    }
+
+    if command.get_envs().next().is_some() {
+        // Variable names are case insensitive on Windows.
+        let key = |name: &OsStr| name.to_string_lossy().to_uppercase();
+
+        let mut envs = std::env::vars_os()
+            .map(|(name, value)| (key(&name), (name, value)))
+            .collect::<HashMap<_, _>>();
+        for (name, value) in command.get_envs() {
+            match value {
+                Some(value) => {
+                    let _ = envs.insert(key(name), (name.to_owned(), value.to_owned()));
+                }
+                None => {
+                    let _ = envs.remove(&key(name));
+                }
+            }
+        }
+
+        let _ = cmd.envs(envs.into_values());
+    }
+
+    cmd
+}
+
+/// Quotes an argument the way `CommandLineToArgvW` and MSVC runtime split it back.
+fn quote_arg(arg: &OsStr) -> OsString {
+    const QUOTE: u16 = b'"' as u16;
+    const BACKSLASH: u16 = b'\\' as u16;
+
+    let arg = arg.encode_wide().collect::<Vec<_>>();
+    let needs_quotes = arg.is_empty()
+        || arg
+            .iter()
+            .any(|&c| c == b' ' as u16 || c == b'\t' as u16 || c == QUOTE);
+    if !needs_quotes {
+        return OsString::from_wide(&arg);
+    }
+
+    let mut buf = vec![QUOTE];
+    let mut backslashes = 0;
+    for &c in &arg {
+        match c {
+            BACKSLASH => backslashes += 1,
+            QUOTE => {
+                // Backslashes before a quote are escaped as well as the quote itself.
+                buf.extend(std::iter::repeat(BACKSLASH).take(backslashes + 1));
+                backslashes = 0;
+            }
+            _ => backslashes = 0,
+        }
+
+        buf.push(c);
+    }
+
+    // Trailing backslashes are doubled so they don't escape the closing quote.
+    buf.extend(std::iter::repeat(BACKSLASH).take(backslashes));
+    buf.push(QUOTE);
+
+    OsString::from_wide(&buf)
+}
+
+/// Generates a control event for a console of a given process.
+///
+/// An event can be generated only for a console the caller is attached to,
+/// so the current process is detached from its console and attached to the one of the process.
+/// Then it's attached back to a console of its parent.
+///
+/// The event is delivered to the current process as well,
+/// so a handler which ignores it is set till the event is received.
+fn generate_ctrl_event(pid: u32, event: u32) -> Result<()> {
+    let _guard = CONSOLE_LOCK.lock().unwrap_or_else(|err| err.into_inner());
+    *CTRL_EVENT_DELIVERED
+        .lock()
+        .unwrap_or_else(|err| err.into_inner()) = false;
+
+    // SAFETY: the calls take plain values and a handler which lives as long as the program.
+    unsafe {
+        let had_console = !GetConsoleWindow().is_null();
+
+        if SetConsoleCtrlHandler(Some(ignore_ctrl_event), 1) == 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        let _ = FreeConsole();
+
+        let result = if AttachConsole(pid) == 0 {
+            Err(io::Error::new(
+                io::ErrorKind::Other,
+                format!(
+                    "Failed to attach to a console of the process: {}",
+                    io::Error::last_os_error()
+                ),
+            ))
+        } else if GenerateConsoleCtrlEvent(event, 0) == 0 {
+            Err(io::Error::new(
+                io::ErrorKind::Other,
+                format!(
+                    "Failed to generate a control event: {}",
+                    io::Error::last_os_error()
+                ),
+            ))
+        } else {
+            wait_ctrl_event();
+            Ok(())
+        };
+
+        let _ = FreeConsole();
+        if had_console {
+            let _ = AttachConsole(ATTACH_PARENT_PROCESS);
+        }
+
+        let _ = SetConsoleCtrlHandler(Some(ignore_ctrl_event), 0);
+
+        result
+    }
+}
+
+/// A handler which ignores a control event generated by [generate_ctrl_event].
+///
+/// `CTRL_BREAK_EVENT` can't be ignored by a null handler,
+/// and by default it terminates the process.
+unsafe extern "system" fn ignore_ctrl_event(_: u32) -> i32 {
+    let mut delivered = CTRL_EVENT_DELIVERED
+        .lock()
+        .unwrap_or_else(|err| err.into_inner());
+    *delivered = true;
+    CTRL_EVENT_CONDVAR.notify_all();
+
+    1
+}
+
+/// Waits till a generated control event reaches the current process,
+/// as it's handled by a separate thread after [GenerateConsoleCtrlEvent] returns.
+fn wait_ctrl_event() {
+    let delivered = CTRL_EVENT_DELIVERED
+        .lock()
+        .unwrap_or_else(|err| err.into_inner());
+    let _ = CTRL_EVENT_CONDVAR
+        .wait_timeout_while(delivered, CTRL_EVENT_TIMEOUT, |delivered| !*delivered)
+        .unwrap_or_else(|err| err.into_inner());
+}
+
+/// Converts a duration to milliseconds for Win32 waits, where [u32::MAX] means an infinite wait.
+fn duration_to_millis(timeout: Duration) -> u32 {
+    // round up so a timeout less than a millisecond doesn't turn into a busy loop
+    let millis = timeout.as_nanos().div_ceil(1_000_000);
+    u32::try_from(millis).unwrap_or(u32::MAX - 1)
+}
+
+/// Opens a handle of a process which can be waited on.
+#[cfg(feature = "async")]
+fn open_process(pid: u32) -> Result<OwnedHandle> {
+    // SAFETY: the call takes plain numbers and returns a new handle or null.
+    let handle = unsafe { OpenProcess(SYNCHRONIZE, 0, pid) };
+    if handle.is_null() {
+        return Err(io::Error::last_os_error());
+    }
+
+    // SAFETY: the handle is just opened and owned by nobody else.
+    Ok(unsafe { OwnedHandle::from_raw_handle(handle) })
+}
+
 /// An IO stream of [WinProcess].
 #[derive(Debug)]
 pub struct ProcessStream {
@@ -124,6 +443,13 @@ impl NonBlocking for ProcessStream {
         self.output.blocking(true);
         Ok(())
     }
+
+    /// ConPTY pipes are anonymous pipes, which are not waitable objects,
+    /// so it sleeps for a short tick and a caller checks the pipe again.
+    fn wait_readable(&mut self, timeout: Option<Duration>) -> Result<()> {
+        sleep_tick(timeout);
+        Ok(())
+    }
 }
 
 #[cfg(feature = "async")]
@@ -136,6 +462,10 @@ impl IntoAsyncStream for ProcessStream {
 }
 
 /// An async version of IO stream of [WinProcess].
+///
+/// ConPTY pipes are not opened for overlapped IO,
+/// so the pipes are served by blocking threads via [blocking::Unblock].
+/// Because of that a first poll may be pending even if the process already wrote something.
 #[cfg(feature = "async")]
 #[derive(Debug)]
 pub struct AsyncProcessStream {
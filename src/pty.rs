@@ -0,0 +1,343 @@
+//! This module contains low level pseudo terminal primitives.
+//!
+//! They're used for cases where [`Session::spawn`] is not flexible enough,
+//! so you can wire a process and a terminal yourself.
+//!
+//! # Example
+//!
+#![cfg_attr(not(feature = "async"), doc = "```no_run")]
+#![cfg_attr(feature = "async", doc = "```ignore")]
+//! use std::process::Command;
+//! use expectrl::{pty::{Master, WinSize}, Session};
+//!
+//! let master = Master::open().unwrap();
+//! let slave = master.open_slave().unwrap();
+//! slave.set_window_size(WinSize::new(24, 80)).unwrap();
+//!
+//! let child = slave.spawn(Command::new("cat")).unwrap();
+//! drop(slave);
+//!
+//! let mut session = Session::new(child, master).unwrap();
+//! session.send_line("Hello World").unwrap();
+//! session.expect("Hello World").unwrap();
+//! ```
+//!
+//! [`Session::spawn`]: crate::Session::spawn
+
+use std::{
+    fs::{File, OpenOptions},
+    io::{self, Read, Result, Write},
+    os::unix::{
+        fs::OpenOptionsExt,
+        io::{AsRawFd, FromRawFd, IntoRawFd, RawFd},
+        process::CommandExt,
+    },
+    process::{Child, Command, Stdio},
+    time::Duration,
+};
+
+use nix::{
+    fcntl::OFlag,
+    libc,
+    pty::{grantpt, posix_openpt, unlockpt},
+};
+
+use crate::process::{
+    unix::{make_non_blocking, wait_readable},
+    NonBlocking,
+};
+
+/// A size of a terminal window.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct WinSize {
+    /// A number of rows.
+    pub rows: u16,
+    /// A number of columns.
+    pub cols: u16,
+}
+
+impl WinSize {
+    /// Creates a new [`WinSize`].
+    pub fn new(rows: u16, cols: u16) -> Self {
+        Self { rows, cols }
+    }
+}
+
+/// A master side of a pseudo terminal.
+///
+/// Everything written to it is an input of a process attached to the [`Slave`],
+/// and everything read from it is its output.
+#[derive(Debug)]
+pub struct Master {
+    file: File,
+    slave_name: String,
+}
+
+impl Master {
+    /// Opens a new pseudo terminal.
+    pub fn open() -> Result<Self> {
+        let master = posix_openpt(OFlag::O_RDWR | OFlag::O_NOCTTY).map_err(io::Error::from)?;
+        grantpt(&master).map_err(io::Error::from)?;
+        unlockpt(&master).map_err(io::Error::from)?;
+
+        // SAFETY: the descriptor is owned by PtyMaster and it's released by into_raw_fd.
+        let file = unsafe { File::from_raw_fd(master.into_raw_fd()) };
+
+        Self::from_file(file)
+    }
+
+    /// Creates a [`Master`] from an already opened master side of a pseudo terminal.
+    ///
+    /// It returns an error if the file is not a pseudo terminal master.
+    pub fn from_file(file: File) -> Result<Self> {
+        let slave_name = slave_name(file.as_raw_fd())?;
+        Ok(Self { file, slave_name })
+    }
+
+    /// Returns a path of a slave device, e.g. `/dev/pts/3`.
+    pub fn slave_name(&self) -> &str {
+        &self.slave_name
+    }
+
+    /// Opens a slave side of the pseudo terminal.
+    pub fn open_slave(&self) -> Result<Slave> {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .custom_flags(libc::O_NOCTTY)
+            .open(&self.slave_name)?;
+
+        Ok(Slave { file })
+    }
+
+    /// Returns a size of the terminal window.
+    pub fn get_window_size(&self) -> Result<WinSize> {
+        get_window_size(self.as_raw_fd())
+    }
+
+    /// Resizes the terminal window.
+    pub fn set_window_size(&self, size: WinSize) -> Result<()> {
+        set_window_size(self.as_raw_fd(), size)
+    }
+
+    /// Creates a new handle to the same master.
+    pub fn try_clone(&self) -> Result<Self> {
+        Ok(Self {
+            file: self.file.try_clone()?,
+            slave_name: self.slave_name.clone(),
+        })
+    }
+
+    /// Returns an underlying file.
+    pub fn into_file(self) -> File {
+        self.file
+    }
+}
+
+impl Read for Master {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        self.file.read(buf)
+    }
+}
+
+impl Write for Master {
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        self.file.write(buf)
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        self.file.flush()
+    }
+}
+
+impl NonBlocking for Master {
+    fn set_non_blocking(&mut self) -> Result<()> {
+        make_non_blocking(self.as_raw_fd(), true)
+    }
+
+    fn set_blocking(&mut self) -> Result<()> {
+        make_non_blocking(self.as_raw_fd(), false)
+    }
+
+    fn wait_readable(&mut self, timeout: Option<Duration>) -> Result<()> {
+        wait_readable(self.as_raw_fd(), timeout)
+    }
+}
+
+impl AsRawFd for Master {
+    fn as_raw_fd(&self) -> RawFd {
+        self.file.as_raw_fd()
+    }
+}
+
+/// A slave side of a pseudo terminal.
+///
+/// It's a terminal a child process is attached to.
+#[derive(Debug)]
+pub struct Slave {
+    file: File,
+}
+
+impl Slave {
+    /// Returns a size of the terminal window.
+    pub fn get_window_size(&self) -> Result<WinSize> {
+        get_window_size(self.as_raw_fd())
+    }
+
+    /// Resizes the terminal window.
+    pub fn set_window_size(&self, size: WinSize) -> Result<()> {
+        set_window_size(self.as_raw_fd(), size)
+    }
+
+    /// Spawns a command with the slave as its controlling terminal and stdin, stdout and stderr.
+    ///
+    /// The child is started in a new session.
+    ///
+    /// You may want to drop the [`Slave`] afterwards,
+    /// otherwise EOF won't be reached on the master side after the child exits.
+    pub fn spawn(&self, mut command: Command) -> Result<Child> {
+        let _ = command
+            .stdin(Stdio::from(self.file.try_clone()?))
+            .stdout(Stdio::from(self.file.try_clone()?))
+            .stderr(Stdio::from(self.file.try_clone()?));
+
+        // SAFETY: only async-signal-safe functions are called in the closure.
+        unsafe {
+            let _ = command.pre_exec(|| {
+                if libc::setsid() == -1 {
+                    return Err(io::Error::last_os_error());
+                }
+
+                #[allow(clippy::useless_conversion)]
+                if libc::ioctl(0, libc::TIOCSCTTY.into(), 0) == -1 {
+                    return Err(io::Error::last_os_error());
+                }
+
+                Ok(())
+            });
+        }
+
+        command.spawn()
+    }
+
+    /// Creates a new handle to the same slave.
+    pub fn try_clone(&self) -> Result<Self> {
+        Ok(Self {
+            file: self.file.try_clone()?,
+        })
+    }
+
+    /// Returns an underlying file.
+    pub fn into_file(self) -> File {
+        self.file
+    }
+}
+
+impl AsRawFd for Slave {
+    fn as_raw_fd(&self) -> RawFd {
+        self.file.as_raw_fd()
+    }
+}
+
+fn get_window_size(fd: RawFd) -> Result<WinSize> {
+    let mut size = libc::winsize {
+        ws_row: 0,
+        ws_col: 0,
+        ws_xpixel: 0,
+        ws_ypixel: 0,
+    };
+
+    // SAFETY: the pointer is valid for the duration of the call.
+    #[allow(clippy::useless_conversion)]
+    let result = unsafe { libc::ioctl(fd, libc::TIOCGWINSZ.into(), &mut size) };
+    if result == -1 {
+        return Err(io::Error::last_os_error());
+    }
+
+    Ok(WinSize::new(size.ws_row, size.ws_col))
+}
+
+fn set_window_size(fd: RawFd, size: WinSize) -> Result<()> {
+    let size = libc::winsize {
+        ws_row: size.rows,
+        ws_col: size.cols,
+        ws_xpixel: 0,
+        ws_ypixel: 0,
+    };
+
+    // SAFETY: the pointer is valid for the duration of the call.
+    #[allow(clippy::useless_conversion)]
+    let result = unsafe { libc::ioctl(fd, libc::TIOCSWINSZ.into(), &size) };
+    if result == -1 {
+        return Err(io::Error::last_os_error());
+    }
+
+    Ok(())
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub(crate) fn slave_name(fd: RawFd) -> Result<String> {
+    let mut name = [0; 128];
+    // SAFETY: the buffer is valid for the duration of the call.
+    let result = unsafe { libc::ptsname_r(fd, name.as_mut_ptr(), name.len()) };
+    if result != 0 {
+        return Err(io::Error::from_raw_os_error(result));
+    }
+
+    // SAFETY: ptsname_r guarantees the name is null terminated.
+    let name = unsafe { std::ffi::CStr::from_ptr(name.as_ptr()) };
+    Ok(name.to_string_lossy().into_owned())
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+pub(crate) fn slave_name(fd: RawFd) -> Result<String> {
+    // SAFETY: ptsname is not thread safe, the name is copied right after the call.
+    let name = unsafe { libc::ptsname(fd) };
+    if name.is_null() {
+        return Err(io::Error::last_os_error());
+    }
+
+    // SAFETY: the pointer is checked to be not null.
+    let name = unsafe { std::ffi::CStr::from_ptr(name) };
+    Ok(name.to_string_lossy().into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_window_size() {
+        let master = Master::open().unwrap();
+        let slave = master.open_slave().unwrap();
+
+        master.set_window_size(WinSize::new(10, 20)).unwrap();
+        assert_eq!(slave.get_window_size().unwrap(), WinSize::new(10, 20));
+
+        slave.set_window_size(WinSize::new(30, 40)).unwrap();
+        assert_eq!(master.get_window_size().unwrap(), WinSize::new(30, 40));
+    }
+
+    #[test]
+    fn test_slave_name() {
+        let master = Master::open().unwrap();
+        assert!(master.slave_name().starts_with("/dev/"));
+    }
+
+    #[test]
+    fn test_spawn() {
+        let mut master = Master::open().unwrap();
+        let slave = master.open_slave().unwrap();
+
+        let mut command = Command::new("echo");
+        let _ = command.arg("Hello World");
+        let mut child = slave.spawn(command).unwrap();
+        drop(slave);
+
+        let _ = child.wait().unwrap();
+
+        let mut buf = [0; 64];
+        let n = master.read(&mut buf).unwrap();
+        assert_eq!(&buf[..n], b"Hello World\r\n");
+    }
+}
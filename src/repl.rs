@@ -2,10 +2,17 @@
 
 use crate::{
     error::Error,
+    needle::{Match, Needle},
+    secret::Password,
     session::{OsProcess, OsProcessStream},
-    Captures, Session,
+    AnsiStripped, Any, Captures, ControlCode, Session, Transcript, TranscriptFormat,
+};
+use std::{
+    collections::HashMap,
+    ops::{Deref, DerefMut},
+    path::Path,
+    time::Instant,
 };
-use std::ops::{Deref, DerefMut};
 
 #[cfg(not(feature = "async"))]
 use crate::process::NonBlocking;
@@ -38,7 +45,7 @@ pub fn spawn_bash() -> Result<ReplSession, Error> {
     // We might need to turn it off optionally?
     let _ = cmd.env(
         "PROMPT_COMMAND",
-        "PS1=EXPECT_PROMPT; unset PROMPT_COMMAND; bind 'set enable-bracketed-paste off'",
+        "PS1=EXPECT_PROMPT; PS2=EXPECT_CONTINUATION; unset PROMPT_COMMAND; bind 'set enable-bracketed-paste off'",
     );
 
     let session = crate::session::Session::spawn(cmd)?;
@@ -49,6 +56,7 @@ pub fn spawn_bash() -> Result<ReplSession, Error> {
         Some("quit".to_string()),
         false,
     );
+    bash.set_continuation_prompt("EXPECT_CONTINUATION");
 
     // read a prompt to make it not available on next read.
     //
@@ -76,7 +84,7 @@ pub async fn spawn_bash() -> Result<ReplSession, Error> {
     // We might need to turn it off optionally?
     let _ = cmd.env(
         "PROMPT_COMMAND",
-        "PS1=EXPECT_PROMPT; unset PROMPT_COMMAND; bind 'set enable-bracketed-paste off'",
+        "PS1=EXPECT_PROMPT; PS2=EXPECT_CONTINUATION; unset PROMPT_COMMAND; bind 'set enable-bracketed-paste off'",
     );
 
     let session = crate::session::Session::spawn(cmd)?;
@@ -87,6 +95,7 @@ pub async fn spawn_bash() -> Result<ReplSession, Error> {
         Some("quit".to_string()),
         false,
     );
+    bash.set_continuation_prompt("EXPECT_CONTINUATION");
 
     // read a prompt to make it not available on next read.
     bash.expect_prompt().await?;
@@ -94,6 +103,128 @@ pub async fn spawn_bash() -> Result<ReplSession, Error> {
     Ok(bash)
 }
 
+/// A prompt used by zsh and fish sessions.
+#[cfg(unix)]
+pub(crate) const SHELL_PROMPT: &str = "EXPECT_PROMPT";
+
+// The prompt in setup commands is split by quotes,
+// so an echo of a command isn't mistaken for the prompt itself.
+
+/// A zsh setup which sets prompts, turns off a right prompt,
+/// a mark of a partial line and bracketed paste.
+#[cfg(unix)]
+const ZSH_SETUP: &str = "PS1='EXPECT_PROMP''T'; PS2='EXPECT_CONTINUATIO''N'; RPS1=''; PROMPT_EOL_MARK=''; unsetopt PROMPT_SP; unset zle_bracketed_paste";
+
+/// A fish setup which sets a prompt, turns off a right prompt, a title and autosuggestions.
+#[cfg(unix)]
+const FISH_SETUP: &str = "function fish_prompt; printf 'EXPECT_PROMP''T'; end; function fish_right_prompt; end; function fish_title; end; set -g fish_autosuggestion_enabled 0";
+
+#[cfg(unix)]
+fn zsh_command() -> Command {
+    let mut cmd = Command::new("zsh");
+    let _ = cmd.arg("--no-rcs");
+    cmd
+}
+
+#[cfg(unix)]
+fn fish_command() -> Command {
+    let mut cmd = Command::new("fish");
+    let _ = cmd.arg("--no-config");
+    // A dumb terminal makes fish not to print cursor movements and colors.
+    let _ = cmd.env("TERM", "dumb");
+    cmd
+}
+
+/// Spawn a zsh session.
+///
+/// It starts zsh without startup files and uses a custom prompt to be able to controll the shell.
+/// A right prompt and a mark of a partial line are turned off as they clutter the output.
+#[cfg(unix)]
+#[cfg(not(feature = "async"))]
+pub fn spawn_zsh() -> Result<ReplSession, Error> {
+    let session = Session::spawn(zsh_command())?;
+    let mut zsh = ReplSession::new(
+        session,
+        SHELL_PROMPT.to_string(),
+        Some("exit".to_string()),
+        false,
+    );
+    zsh.set_continuation_prompt("EXPECT_CONTINUATION");
+
+    zsh.send_line(ZSH_SETUP)?;
+    zsh.expect_prompt()?;
+
+    Ok(zsh)
+}
+
+/// Spawn a zsh session.
+///
+/// It starts zsh without startup files and uses a custom prompt to be able to controll the shell.
+/// A right prompt and a mark of a partial line are turned off as they clutter the output.
+#[cfg(unix)]
+#[cfg(feature = "async")]
+pub async fn spawn_zsh() -> Result<ReplSession, Error> {
+    let session = Session::spawn(zsh_command())?;
+    let mut zsh = ReplSession::new(
+        session,
+        SHELL_PROMPT.to_string(),
+        Some("exit".to_string()),
+        false,
+    );
+    zsh.set_continuation_prompt("EXPECT_CONTINUATION");
+
+    zsh.send_line(ZSH_SETUP).await?;
+    zsh.expect_prompt().await?;
+
+    Ok(zsh)
+}
+
+/// Spawn a fish session.
+///
+/// It starts fish without configuration files and uses a custom prompt to be able to controll the shell.
+/// A right prompt, a title and autosuggestions are turned off as they clutter the output.
+#[cfg(unix)]
+#[cfg(not(feature = "async"))]
+pub fn spawn_fish() -> Result<ReplSession, Error> {
+    let session = Session::spawn(fish_command())?;
+    let mut fish = ReplSession::new(
+        session,
+        SHELL_PROMPT.to_string(),
+        Some("exit".to_string()),
+        false,
+    );
+
+    fish.send_line(FISH_SETUP)?;
+    fish.expect_prompt()?;
+
+    Ok(fish)
+}
+
+/// Spawn a fish session.
+///
+/// It starts fish without configuration files and uses a custom prompt to be able to controll the shell.
+/// A right prompt, a title and autosuggestions are turned off as they clutter the output.
+#[cfg(unix)]
+#[cfg(feature = "async")]
+pub async fn spawn_fish() -> Result<ReplSession, Error> {
+    let session = Session::spawn(fish_command())?;
+    let mut fish = ReplSession::new(
+        session,
+        SHELL_PROMPT.to_string(),
+        Some("exit".to_string()),
+        false,
+    );
+
+    fish.send_line(FISH_SETUP).await?;
+    fish.expect_prompt().await?;
+
+    Ok(fish)
+}
+
+/// A python setup which sets a continuation prompt,
+/// as the default "... " can be a part of an output.
+const PYTHON_SETUP: &str = "__import__('sys').ps2 = 'EXPECT_CONTINUATIO''N'";
+
 /// Spawn default python's IDLE.
 #[cfg(not(feature = "async"))]
 pub fn spawn_python() -> Result<ReplSession, Error> {
@@ -103,7 +234,12 @@ pub fn spawn_python() -> Result<ReplSession, Error> {
     let session = spawn("python")?;
 
     let mut idle = ReplSession::new(session, ">>> ".to_owned(), Some("quit()".to_owned()), false);
+    idle.set_continuation_prompt("EXPECT_CONTINUATION");
     idle.expect_prompt()?;
+
+    idle.send_line(PYTHON_SETUP)?;
+    idle.expect_prompt()?;
+
     Ok(idle)
 }
 
@@ -116,7 +252,12 @@ pub async fn spawn_python() -> Result<ReplSession, Error> {
     let session = spawn("python")?;
 
     let mut idle = ReplSession::new(session, ">>> ".to_owned(), Some("quit()".to_owned()), false);
+    idle.set_continuation_prompt("EXPECT_CONTINUATION");
     idle.expect_prompt().await?;
+
+    idle.send_line(PYTHON_SETUP).await?;
+    idle.expect_prompt().await?;
+
     Ok(idle)
 }
 
@@ -200,6 +341,13 @@ pub struct ReplSession<P = OsProcess, S = OsProcessStream> {
     quit_command: Option<String>,
     /// Flag to see if a echo is turned on.
     is_echo_on: bool,
+    /// A prompt which is shown when a command is not complete,
+    /// e.g. "... " for python.
+    continuation_prompt: Option<String>,
+    /// Flag to see if escape sequences are ignored while looking for a prompt.
+    is_ansi_aware: bool,
+    /// A record of executed commands.
+    transcript: Transcript,
 }
 
 impl<P, S> ReplSession<P, S> {
@@ -221,9 +369,74 @@ impl<P, S> ReplSession<P, S> {
             prompt,
             quit_command,
             is_echo_on: is_echo,
+            continuation_prompt: None,
+            is_ansi_aware: true,
+            transcript: Transcript::default(),
         }
     }
 
+    /// Sets whether ANSI escape sequences are ignored while looking for a prompt.
+    ///
+    /// It's on by default, so a colored prompt or the one which sets a window title is still found.
+    /// Returned output is left as it is.
+    pub fn set_ansi_aware(&mut self, on: bool) {
+        self.is_ansi_aware = on;
+    }
+
+    /// Get a setting whether ANSI escape sequences are ignored while looking for a prompt.
+    pub fn is_ansi_aware(&self) -> bool {
+        self.is_ansi_aware
+    }
+
+    /// Sets a prompt which a repl shows when a command is not complete,
+    /// e.g. an unclosed quote or a trailing backslash.
+    ///
+    /// When it's set a command is sent line by line.
+    /// If the prompt is shown after the last line [`ReplSession::execute`] returns an error
+    /// instead of hanging, and the incomplete command is interrupted.
+    pub fn set_continuation_prompt(&mut self, prompt: impl Into<String>) {
+        self.continuation_prompt = Some(prompt.into());
+    }
+
+    /// Get a used continuation prompt.
+    pub fn get_continuation_prompt(&self) -> Option<&str> {
+        self.continuation_prompt.as_deref()
+    }
+
+    /// Returns a record of commands run by [`ReplSession::execute`] and alike methods so far.
+    ///
+    /// A command which failed is recorded with its error.
+    /// Nothing is recorded unless it's turned on by [`ReplSession::set_transcript_limit`].
+    pub fn transcript(&self) -> &Transcript {
+        &self.transcript
+    }
+
+    /// Sets a number of last commands which are kept in a transcript, see [`ReplSession::transcript`].
+    ///
+    /// It's 0 by default, so commands are not recorded.
+    pub fn set_transcript_limit(&mut self, limit: usize) {
+        self.transcript.set_limit(limit);
+    }
+
+    /// Saves a record of executed commands to a file, see [`ReplSession::transcript`].
+    pub fn save_transcript<PP: AsRef<Path>>(
+        &self,
+        path: PP,
+        format: TranscriptFormat,
+    ) -> Result<(), Error> {
+        self.transcript.save(path, format)
+    }
+
+    /// Removes all recorded commands.
+    pub fn clear_transcript(&mut self) {
+        self.transcript.clear();
+    }
+
+    fn record(&mut self, cmd: &str, result: &Result<Vec<u8>, Error>, start: Instant) {
+        let result = result.as_ref().map(Vec::as_slice);
+        self.transcript.push(cmd, result, start.elapsed());
+    }
+
     /// Get a used prompt.
     pub fn get_prompt(&self) -> &str {
         &self.prompt
@@ -254,7 +467,8 @@ impl<P, S: Read + NonBlocking> ReplSession<P, S> {
     }
 
     fn _expect_prompt(&mut self) -> Result<Captures, Error> {
-        self.session.expect(&self.prompt)
+        self.session
+            .expect(Prompt::new(self.prompt.as_str(), self.is_ansi_aware))
     }
 }
 
@@ -267,7 +481,9 @@ impl<P, S: AsyncRead + Unpin> ReplSession<P, S> {
     }
 
     async fn _expect_prompt(&mut self) -> Result<Captures, Error> {
-        self.session.expect(&self.prompt).await
+        self.session
+            .expect(Prompt::new(self.prompt.as_str(), self.is_ansi_aware))
+            .await
     }
 }
 
@@ -276,11 +492,102 @@ impl<P, S: Read + NonBlocking + Write> ReplSession<P, S> {
     /// Send a command to a repl and verifies that it exited.
     /// Returning it's output.
     pub fn execute<SS: AsRef<str> + Clone>(&mut self, cmd: SS) -> Result<Vec<u8>, Error> {
+        let start = Instant::now();
+        let result = self._execute(cmd.as_ref());
+        self.record(cmd.as_ref(), &result, start);
+        result
+    }
+
+    fn _execute(&mut self, cmd: &str) -> Result<Vec<u8>, Error> {
+        if self.continuation_prompt.is_some() {
+            return self.execute_lines(cmd);
+        }
+
         self.send_line(cmd)?;
         let found = self._expect_prompt()?;
         Ok(found.before().to_vec())
     }
 
+    fn execute_lines(&mut self, cmd: &str) -> Result<Vec<u8>, Error> {
+        let mut output = Vec::new();
+        let mut lines = cmd.split('\n').peekable();
+        while let Some(line) = lines.next() {
+            self.send_line(line)?;
+
+            let continuation = self.continuation_prompt.as_deref().unwrap_or_default();
+            let found = self.session.expect(Prompt::new(
+                Any([self.prompt.as_str(), continuation]),
+                self.is_ansi_aware,
+            ))?;
+            output.extend_from_slice(found.before());
+
+            let is_continuation = found.matched_index() == Some(1);
+            if is_continuation && lines.peek().is_none() {
+                self.session.send(ControlCode::EndOfText)?;
+                self.expect_prompt()?;
+                return Err(continuation_error(cmd));
+            }
+        }
+
+        Ok(output)
+    }
+
+    /// Runs a command via `sudo` and returns its output.
+    ///
+    /// A password is sent only if `sudo` asks for it,
+    /// so it works when credentials are cached as well.
+    /// If the password is rejected the command is interrupted and an error is returned.
+    ///
+    /// It's supposed to be used with a shell repl, e.g. [spawn_bash].
+    pub fn execute_sudo<SS, PW>(&mut self, cmd: SS, password: PW) -> Result<Vec<u8>, Error>
+    where
+        SS: AsRef<str>,
+        PW: Password,
+    {
+        let start = Instant::now();
+        let result = self._execute_sudo(cmd.as_ref(), &password);
+        self.record(&format!("sudo {}", cmd.as_ref()), &result, start);
+        result
+    }
+
+    fn _execute_sudo(&mut self, cmd: &str, password: &impl Password) -> Result<Vec<u8>, Error> {
+        self.send_line(sudo_command(cmd))?;
+
+        let mut found = self.session.expect(Prompt::new(
+            Any([self.prompt.as_str(), SUDO_PROMPT]),
+            self.is_ansi_aware,
+        ))?;
+        if found.matched_index() == Some(1) {
+            self.session.send_secret(password)?;
+            found = self.session.expect(Prompt::new(
+                Any([self.prompt.as_str(), SUDO_PROMPT]),
+                self.is_ansi_aware,
+            ))?;
+
+            if found.matched_index() == Some(1) {
+                self.session.send(ControlCode::EndOfText)?;
+                self.expect_prompt()?;
+                return Err(sudo_error());
+            }
+        }
+
+        Ok(found.before().to_vec())
+    }
+
+    /// Send a command to a repl and parses it's output as a table.
+    ///
+    /// See [parse_table] for supported formats.
+    ///
+    /// Notice that if a terminal echoes input the command itself will be taken as a header,
+    /// so you may want to turn echo off beforehand.
+    pub fn execute_table<SS: AsRef<str> + Clone>(
+        &mut self,
+        cmd: SS,
+    ) -> Result<Vec<HashMap<String, String>>, Error> {
+        let output = self.execute(cmd)?;
+        Ok(parse_table(&String::from_utf8_lossy(&output)))
+    }
+
     /// Sends line to repl (and flush the output).
     ///
     /// If echo_on=true wait for the input to appear.
@@ -311,11 +618,115 @@ impl<P, S: Read + NonBlocking + Write> ReplSession<P, S> {
 impl<P, S: AsyncRead + AsyncWrite + Unpin> ReplSession<P, S> {
     /// Send a command to a repl and verifies that it exited.
     pub async fn execute(&mut self, cmd: impl AsRef<str>) -> Result<Vec<u8>, Error> {
+        let start = Instant::now();
+        let result = self._execute(cmd.as_ref()).await;
+        self.record(cmd.as_ref(), &result, start);
+        result
+    }
+
+    async fn _execute(&mut self, cmd: &str) -> Result<Vec<u8>, Error> {
+        if self.continuation_prompt.is_some() {
+            return self.execute_lines(cmd).await;
+        }
+
         self.send_line(cmd).await?;
         let found = self._expect_prompt().await?;
         Ok(found.before().to_vec())
     }
 
+    async fn execute_lines(&mut self, cmd: &str) -> Result<Vec<u8>, Error> {
+        let mut output = Vec::new();
+        let mut lines = cmd.split('\n').peekable();
+        while let Some(line) = lines.next() {
+            self.send_line(line).await?;
+
+            let continuation = self.continuation_prompt.as_deref().unwrap_or_default();
+            let found = self
+                .session
+                .expect(Prompt::new(
+                    Any([self.prompt.as_str(), continuation]),
+                    self.is_ansi_aware,
+                ))
+                .await?;
+            output.extend_from_slice(found.before());
+
+            let is_continuation = found.matched_index() == Some(1);
+            if is_continuation && lines.peek().is_none() {
+                self.session.send(ControlCode::EndOfText).await?;
+                self.expect_prompt().await?;
+                return Err(continuation_error(cmd));
+            }
+        }
+
+        Ok(output)
+    }
+
+    /// Runs a command via `sudo` and returns its output.
+    ///
+    /// A password is sent only if `sudo` asks for it,
+    /// so it works when credentials are cached as well.
+    /// If the password is rejected the command is interrupted and an error is returned.
+    ///
+    /// It's supposed to be used with a shell repl, e.g. [spawn_bash].
+    pub async fn execute_sudo(
+        &mut self,
+        cmd: impl AsRef<str>,
+        password: impl Password,
+    ) -> Result<Vec<u8>, Error> {
+        let start = Instant::now();
+        let result = self._execute_sudo(cmd.as_ref(), &password).await;
+        self.record(&format!("sudo {}", cmd.as_ref()), &result, start);
+        result
+    }
+
+    async fn _execute_sudo(
+        &mut self,
+        cmd: &str,
+        password: &impl Password,
+    ) -> Result<Vec<u8>, Error> {
+        self.send_line(sudo_command(cmd)).await?;
+
+        let mut found = self
+            .session
+            .expect(Prompt::new(
+                Any([self.prompt.as_str(), SUDO_PROMPT]),
+                self.is_ansi_aware,
+            ))
+            .await?;
+        if found.matched_index() == Some(1) {
+            self.session.send_secret(password).await?;
+            found = self
+                .session
+                .expect(Prompt::new(
+                    Any([self.prompt.as_str(), SUDO_PROMPT]),
+                    self.is_ansi_aware,
+                ))
+                .await?;
+
+            if found.matched_index() == Some(1) {
+                self.session.send(ControlCode::EndOfText).await?;
+                self.expect_prompt().await?;
+                return Err(sudo_error());
+            }
+        }
+
+        Ok(found.before().to_vec())
+    }
+
+    /// Send a command to a repl and parses it's output as a table.
+    ///
+    /// See [parse_table] for supported formats.
+    ///
+    /// Notice that if a terminal echoes input the command itself will be taken as a header,
+    /// so you may want to turn echo off beforehand.
+    pub async fn execute_table(
+        &mut self,
+        cmd: impl AsRef<str>,
+    ) -> Result<Vec<HashMap<String, String>>, Error> {
+        let output = self.execute(cmd).await?;
+        Ok(parse_table(&String::from_utf8_lossy(&output)))
+    }
+
     /// Sends line to repl (and flush the output).
     ///
     /// If echo_on=true wait for the input to appear.
@@ -353,3 +764,206 @@ impl<P, S> DerefMut for ReplSession<P, S> {
         &mut self.session
     }
 }
+
+/// Prompt is a needle of a repl prompt which optionally ignores ANSI escape sequences.
+struct Prompt<N> {
+    needle: N,
+    strip_ansi: bool,
+}
+
+impl<N> Prompt<N> {
+    fn new(needle: N, strip_ansi: bool) -> Self {
+        Self { needle, strip_ansi }
+    }
+}
+
+impl<N: Needle> Needle for Prompt<N> {
+    fn check(&self, buf: &[u8], eof: bool) -> Result<Vec<Match>, Error> {
+        match self.strip_ansi {
+            true => AnsiStripped(&self.needle).check(buf, eof),
+            false => self.needle.check(buf, eof),
+        }
+    }
+
+    fn description(&self) -> String {
+        self.needle.description()
+    }
+}
+
+/// A password prompt which is set for `sudo` so it doesn't depend on a locale.
+const SUDO_PROMPT: &str = "EXPECT_SUDO_PASSWORD:";
+
+/// Builds a `sudo` call with a custom password prompt.
+///
+/// The prompt is split by quotes, so an echo of the command isn't mistaken for the prompt itself.
+fn sudo_command(cmd: &str) -> String {
+    format!("sudo -p 'EXPECT_SUDO_PASSWOR''D:' {}", cmd)
+}
+
+fn sudo_error() -> Error {
+    Error::Other {
+        message: "sudo failed".to_string(),
+        err: "the password was rejected".to_string(),
+    }
+}
+
+fn continuation_error(cmd: &str) -> Error {
+    Error::Other {
+        message: "continuation prompt detected".to_string(),
+        err: format!("the command is not complete: {:?}", cmd),
+    }
+}
+
+/// Parses a tabular output of a command into a list of rows.
+///
+/// The first non empty line is considered to be a header,
+/// each row is a map from a header name to a cell value.
+///
+/// Two formats are supported:
+///     - pipe delimited tables (`| NAME | SIZE |`), where border lines like `+------+` or `|---|` are skipped.
+///     - whitespace delimited tables (`NAME SIZE`), where the last column takes the rest of a line.
+///
+/// Missing cells are set to an empty string.
+///
+/// ```
+/// use expectrl::repl::parse_table;
+///
+/// let table = parse_table("NAME   SIZE\nfoo    10\nbar    20\n");
+/// assert_eq!(table.len(), 2);
+/// assert_eq!(table[1]["NAME"], "bar");
+/// assert_eq!(table[1]["SIZE"], "20");
+/// ```
+pub fn parse_table(text: &str) -> Vec<HashMap<String, String>> {
+    let mut lines = text
+        .lines()
+        .map(|line| line.trim_end_matches('\r'))
+        .filter(|line| !line.trim().is_empty() && !is_table_border(line));
+
+    let header = match lines.next() {
+        Some(header) => header,
+        None => return Vec::new(),
+    };
+
+    let is_piped = header.contains('|');
+    let columns = match is_piped {
+        true => split_piped_row(header),
+        false => header.split_whitespace().collect(),
+    };
+
+    lines
+        .map(|line| {
+            let cells = match is_piped {
+                true => split_piped_row(line),
+                false => split_whitespace_row(line, columns.len()),
+            };
+
+            columns
+                .iter()
+                .enumerate()
+                .map(|(i, column)| {
+                    let cell = cells.get(i).map_or("", |cell| cell.trim());
+                    (column.to_string(), cell.to_string())
+                })
+                .collect()
+        })
+        .collect()
+}
+
+fn split_piped_row(line: &str) -> Vec<&str> {
+    let line = line.trim();
+    let line = line.strip_prefix('|').unwrap_or(line);
+    let line = line.strip_suffix('|').unwrap_or(line);
+    line.split('|').map(str::trim).collect()
+}
+
+fn split_whitespace_row(line: &str, count: usize) -> Vec<&str> {
+    let mut cells = Vec::with_capacity(count);
+    let mut rest = line.trim();
+    while !rest.is_empty() {
+        if cells.len() + 1 == count {
+            cells.push(rest);
+            break;
+        }
+
+        let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+        cells.push(&rest[..end]);
+        rest = rest[end..].trim_start();
+    }
+
+    cells
+}
+
+fn is_table_border(line: &str) -> bool {
+    line.trim()
+        .chars()
+        .all(|c| matches!(c, '-' | '+' | '=' | '|' | ':' | ' '))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(cells: &[(&str, &str)]) -> HashMap<String, String> {
+        cells
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_table_whitespace() {
+        let table = parse_table("\r\nNAME  SIZE  DESCRIPTION\r\nfoo   10    first file\r\nbar\r\n");
+        assert_eq!(
+            table,
+            vec![
+                row(&[
+                    ("NAME", "foo"),
+                    ("SIZE", "10"),
+                    ("DESCRIPTION", "first file")
+                ]),
+                row(&[("NAME", "bar"), ("SIZE", ""), ("DESCRIPTION", "")]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_table_piped() {
+        let table = parse_table(
+            "+----+---------+\n| id | name    |\n+----+---------+\n| 1  | John D. |\n| 2  |         |\n+----+---------+\n",
+        );
+        assert_eq!(
+            table,
+            vec![
+                row(&[("id", "1"), ("name", "John D.")]),
+                row(&[("id", "2"), ("name", "")]),
+            ]
+        );
+
+        let table = parse_table("a|b\n---|---\n1|2\n");
+        assert_eq!(table, vec![row(&[("a", "1"), ("b", "2")])]);
+    }
+
+    #[test]
+    fn test_prompt_ignores_ansi() {
+        let buf = b"output\r\n\x1b]0;title\x07\x1b[32m>>\x1b[0m> ";
+
+        let found = Prompt::new(">>> ", true).check(buf, false).unwrap();
+        assert_eq!(found, vec![Match::new(23, buf.len())]);
+
+        let found = Prompt::new(">>> ", false).check(buf, false).unwrap();
+        assert!(found.is_empty());
+    }
+
+    #[test]
+    fn test_sudo_command() {
+        let cmd = sudo_command("whoami");
+        assert_eq!(cmd, "sudo -p 'EXPECT_SUDO_PASSWOR''D:' whoami");
+        assert!(!cmd.contains(SUDO_PROMPT));
+    }
+
+    #[test]
+    fn test_parse_table_empty() {
+        assert!(parse_table("").is_empty());
+        assert!(parse_table("NAME SIZE\n").is_empty());
+    }
+}
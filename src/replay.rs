@@ -0,0 +1,449 @@
+//! This module contains a [`ReplaySession`] which serves a previously recorded output
+//! instead of running a process.
+//!
+//! It allows to test expect logic without spawning real processes.
+//! A [`Transcript`] can be loaded from an asciicast file,
+//! see [`Session::record_asciicast`], or built by hand.
+//!
+//! # Example
+//!
+#![cfg_attr(not(feature = "async"), doc = "```no_run")]
+#![cfg_attr(feature = "async", doc = "```ignore")]
+//! use expectrl::replay::{self, Transcript};
+//!
+//! let transcript = Transcript::new()
+//!     .output("login: ")
+//!     .output("Password: ");
+//!
+//! let mut session = replay::ReplaySession::replay(transcript).unwrap();
+//! session.expect("login:").unwrap();
+//! session.send_line("root").unwrap();
+//! session.expect("Password:").unwrap();
+//!
+//! assert_eq!(session.get_stream().get_written(), b"root\n");
+//!
+//! let mut session = replay::load("session.cast").unwrap();
+//! session.expect("$ ").unwrap();
+//! ```
+
+use std::{
+    collections::VecDeque,
+    fs::File,
+    io::{self, BufRead, BufReader, Read, Write},
+    path::Path,
+    time::{Duration, Instant},
+};
+
+#[cfg(feature = "async")]
+use futures_lite::{AsyncRead, AsyncWrite};
+#[cfg(feature = "async")]
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use crate::{process::NonBlocking, Error, Session};
+
+/// A session which replays a [`Transcript`].
+///
+/// There's no process behind it so the process type is `()`.
+pub type ReplaySession = Session<(), ReplayStream>;
+
+impl Session<(), ReplayStream> {
+    /// Creates a session which replays a transcript as fast as possible.
+    pub fn replay(transcript: Transcript) -> Result<Self, Error> {
+        let session = Self::new((), ReplayStream::new(transcript))?;
+        Ok(session)
+    }
+}
+
+/// Loads an asciicast file and creates a [`ReplaySession`] which replays it as fast as possible.
+pub fn load<A: AsRef<Path>>(path: A) -> Result<ReplaySession, Error> {
+    let transcript = Transcript::load(path)?;
+    ReplaySession::replay(transcript)
+}
+
+/// Transcript is a list of output chunks with a time they were produced at.
+#[derive(Debug, Default, Clone)]
+pub struct Transcript {
+    events: Vec<(Duration, Vec<u8>)>,
+}
+
+impl Transcript {
+    /// Creates an empty transcript.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds an output chunk which is available right after the previous one.
+    pub fn output<B: AsRef<[u8]>>(self, buf: B) -> Self {
+        let at = self.duration();
+        self.output_at(at, buf)
+    }
+
+    /// Adds an output chunk which is available at a given time since the start of a replay.
+    ///
+    /// A time is never less than a time of the previous chunk.
+    pub fn output_at<B: AsRef<[u8]>>(mut self, at: Duration, buf: B) -> Self {
+        let at = std::cmp::max(at, self.duration());
+        self.events.push((at, buf.as_ref().to_vec()));
+        self
+    }
+
+    /// Loads output events of an asciicast v2 file.
+    pub fn load<A: AsRef<Path>>(path: A) -> Result<Self, Error> {
+        let file = File::open(path)?;
+        Self::from_asciicast(BufReader::new(file))
+    }
+
+    /// Parses output events of an asciicast v2 stream.
+    ///
+    /// Input events are ignored.
+    pub fn from_asciicast<R: BufRead>(reader: R) -> Result<Self, Error> {
+        let mut transcript = Self::new();
+        for (i, line) in reader.lines().enumerate() {
+            let line = line?;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('{') {
+                continue;
+            }
+
+            let (at, code, data) = parse_event(line).ok_or_else(|| Error::Other {
+                message: format!("Failed to parse a transcript at line {}", i + 1),
+                err: format!("unexpected event {:?}", line),
+            })?;
+
+            if code == "o" {
+                transcript = transcript.output_at(at, data);
+            }
+        }
+
+        Ok(transcript)
+    }
+
+    /// Returns a time of the last chunk.
+    pub fn duration(&self) -> Duration {
+        self.events
+            .last()
+            .map(|(at, _)| *at)
+            .unwrap_or(Duration::ZERO)
+    }
+
+    /// Returns a number of chunks.
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    /// Verifies whether there's no chunks.
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+}
+
+/// ReplayStream serves chunks of a [`Transcript`] one by one
+/// and collects everything written to it.
+///
+/// When all chunks are served it returns EOF.
+#[derive(Debug)]
+pub struct ReplayStream {
+    events: VecDeque<(Duration, Vec<u8>)>,
+    written: Vec<u8>,
+    timing: bool,
+    start: Option<Instant>,
+    non_blocking: bool,
+    #[cfg(feature = "async")]
+    delay: Option<futures_timer::Delay>,
+}
+
+impl ReplayStream {
+    /// Creates a new stream which serves chunks as fast as possible.
+    pub fn new(transcript: Transcript) -> Self {
+        Self {
+            events: transcript.events.into(),
+            written: Vec::new(),
+            timing: false,
+            start: None,
+            non_blocking: false,
+            #[cfg(feature = "async")]
+            delay: None,
+        }
+    }
+
+    /// Sets whether chunks are served according to their time or as fast as possible.
+    ///
+    /// The time is measured since the first read.
+    pub fn with_timing(mut self, on: bool) -> Self {
+        self.timing = on;
+        self
+    }
+
+    /// Returns all bytes which were written to the stream.
+    pub fn get_written(&self) -> &[u8] {
+        &self.written
+    }
+
+    /// Returns a time left until the next chunk is available.
+    fn time_left(&mut self) -> Option<Duration> {
+        let (at, _) = self.events.front()?;
+        if !self.timing {
+            return None;
+        }
+
+        let start = *self.start.get_or_insert_with(Instant::now);
+        at.checked_sub(start.elapsed()).filter(|d| !d.is_zero())
+    }
+
+    fn read_chunk(&mut self, buf: &mut [u8]) -> usize {
+        let (_, chunk) = match self.events.front_mut() {
+            Some(event) => event,
+            None => return 0,
+        };
+
+        let n = std::cmp::min(buf.len(), chunk.len());
+        buf[..n].copy_from_slice(&chunk[..n]);
+        let _ = chunk.drain(..n);
+
+        if chunk.is_empty() {
+            let _ = self.events.pop_front();
+        }
+
+        n
+    }
+}
+
+impl Read for ReplayStream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        if let Some(left) = self.time_left() {
+            if self.non_blocking {
+                return Err(io::Error::new(io::ErrorKind::WouldBlock, ""));
+            }
+
+            std::thread::sleep(left);
+        }
+
+        Ok(self.read_chunk(buf))
+    }
+}
+
+impl Write for ReplayStream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.written.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl NonBlocking for ReplayStream {
+    fn set_non_blocking(&mut self) -> io::Result<()> {
+        self.non_blocking = true;
+        Ok(())
+    }
+
+    fn set_blocking(&mut self) -> io::Result<()> {
+        self.non_blocking = false;
+        Ok(())
+    }
+}
+
+#[cfg(feature = "async")]
+impl AsyncRead for ReplayStream {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        if buf.is_empty() {
+            return Poll::Ready(Ok(0));
+        }
+
+        if let Some(left) = self.time_left() {
+            let delay = self
+                .delay
+                .get_or_insert_with(|| futures_timer::Delay::new(left));
+            if Pin::new(delay).poll(cx).is_pending() {
+                return Poll::Pending;
+            }
+        }
+
+        self.delay = None;
+        Poll::Ready(Ok(self.read_chunk(buf)))
+    }
+}
+
+#[cfg(feature = "async")]
+impl AsyncWrite for ReplayStream {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        _: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Poll::Ready(self.write(buf))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+}
+
+/// Parses an event line, e.g. `[0.5, "o", "data"]`.
+fn parse_event(line: &str) -> Option<(Duration, String, String)> {
+    let line = line.strip_prefix('[')?.strip_suffix(']')?;
+    let (time, rest) = line.split_once(',')?;
+    let time = time.trim().parse::<f64>().ok()?;
+    let time = Duration::try_from_secs_f64(time).ok()?;
+
+    let (code, rest) = parse_json_string(rest.trim_start())?;
+    let rest = rest.trim_start().strip_prefix(',')?;
+    let (data, rest) = parse_json_string(rest.trim_start())?;
+    if !rest.trim().is_empty() {
+        return None;
+    }
+
+    Some((time, code, data))
+}
+
+/// Parses a JSON string at the beginning of the input and returns the rest of the input.
+fn parse_json_string(s: &str) -> Option<(String, &str)> {
+    let mut chars = s.strip_prefix('"')?.char_indices();
+    let mut out = String::new();
+    let mut surrogate = None;
+    let body = &s[1..];
+
+    while let Some((i, c)) = chars.next() {
+        let c = match c {
+            '"' => return Some((out, &body[i + 1..])),
+            '\\' => match chars.next()?.1 {
+                'n' => '\n',
+                'r' => '\r',
+                't' => '\t',
+                'b' => '\u{8}',
+                'f' => '\u{c}',
+                'u' => {
+                    let hex = (0..4)
+                        .map(|_| chars.next().map(|(_, c)| c))
+                        .collect::<Option<String>>()?;
+                    let code = u32::from_str_radix(&hex, 16).ok()?;
+                    match (surrogate.take(), code) {
+                        (None, 0xD800..=0xDBFF) => {
+                            surrogate = Some(code);
+                            continue;
+                        }
+                        (Some(high), 0xDC00..=0xDFFF) => {
+                            char::from_u32(0x10000 + ((high - 0xD800) << 10) + (code - 0xDC00))?
+                        }
+                        (_, code) => char::from_u32(code).unwrap_or(char::REPLACEMENT_CHARACTER),
+                    }
+                }
+                c => c,
+            },
+            c => c,
+        };
+
+        out.push(c);
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_asciicast() {
+        let cast = concat!(
+            "{\"version\": 2, \"width\": 80, \"height\": 24}\n",
+            "[0.100000, \"o\", \"login: \\u001b[1m\"]\n",
+            "[0.200000, \"i\", \"root\\n\"]\n",
+            "[0.300000, \"o\", \"\\\"\\ud83d\\ude00\\\"\\r\\n\"]\n",
+        );
+
+        let transcript = Transcript::from_asciicast(cast.as_bytes()).unwrap();
+        assert_eq!(
+            transcript.events,
+            vec![
+                (Duration::from_millis(100), b"login: \x1b[1m".to_vec()),
+                (Duration::from_millis(300), "\"😀\"\r\n".as_bytes().to_vec()),
+            ]
+        );
+
+        let err = Transcript::from_asciicast("[0.1, \"o\"]".as_bytes()).unwrap_err();
+        assert!(err.to_string().contains("at line 1"));
+
+        for line in [
+            "[1e300, \"o\", \"\"]",
+            "[NaN, \"o\", \"\"]",
+            "[-1, \"o\", \"\"]",
+        ] {
+            let err = Transcript::from_asciicast(line.as_bytes()).unwrap_err();
+            assert!(err.to_string().contains("at line 1"));
+        }
+    }
+
+    #[test]
+    fn test_read_chunks() {
+        let transcript = Transcript::new().output("Hello").output(" World");
+        let mut stream = ReplayStream::new(transcript);
+
+        let mut buf = [0; 3];
+        assert_eq!(stream.read(&mut buf).unwrap(), 3);
+        assert_eq!(&buf, b"Hel");
+        assert_eq!(stream.read(&mut buf).unwrap(), 2);
+        assert_eq!(&buf[..2], b"lo");
+        assert_eq!(stream.read(&mut buf).unwrap(), 3);
+        assert_eq!(stream.read(&mut buf).unwrap(), 3);
+        assert_eq!(stream.read(&mut buf).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_timing() {
+        let transcript = Transcript::new().output_at(Duration::from_millis(100), "Hello");
+        let mut stream = ReplayStream::new(transcript).with_timing(true);
+
+        let mut buf = [0; 8];
+        stream.set_non_blocking().unwrap();
+        let err = stream.read(&mut buf).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::WouldBlock);
+
+        stream.set_blocking().unwrap();
+        let now = Instant::now();
+        assert_eq!(stream.read(&mut buf).unwrap(), 5);
+        assert!(now.elapsed() >= Duration::from_millis(50));
+    }
+
+    #[test]
+    fn test_session() {
+        let transcript = Transcript::new().output("login: ").output("Password: ");
+        let mut session = ReplaySession::replay(transcript).unwrap();
+
+        #[cfg(not(feature = "async"))]
+        {
+            let _ = session.expect("login:").unwrap();
+            session.send_line("root").unwrap();
+            let _ = session.expect("Password:").unwrap();
+            assert!(matches!(session.expect("$"), Err(Error::Eof)));
+        }
+
+        #[cfg(feature = "async")]
+        futures_lite::future::block_on(async {
+            let _ = session.expect("login:").await.unwrap();
+            session.send_line("root").await.unwrap();
+            let _ = session.expect("Password:").await.unwrap();
+            assert!(matches!(session.expect("$").await, Err(Error::Eof)));
+        });
+
+        assert_eq!(session.get_stream().get_written(), b"root\n");
+    }
+}
@@ -0,0 +1,458 @@
+//! This module contains a [`Screen`] which renders a terminal output into a grid of characters.
+//!
+//! It implements a small subset of VT100 control sequences:
+//! cursor movement, line/screen erasing and scrolling.
+//! Other sequences (e.g. colors) are recognized and ignored.
+//!
+//! It lets you look at what a user would see, instead of raw bytes full of escape sequences.
+//!
+//! # Example
+//!
+//! ```
+//! use expectrl::screen::{Rect, Screen};
+//!
+//! let mut screen = Screen::new(3, 20);
+//! screen.process(b"name   age\r\n\x1b[1mjohn\x1b[0m   30");
+//!
+//! assert_eq!(screen.find_text("age"), Some((0, 7)));
+//! assert_eq!(screen.region_text(Rect::new(1, 7, 1, 3)), "30");
+//! ```
+
+use std::io;
+
+/// A rectangular area of a [`Screen`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rect {
+    /// A first row of the area.
+    pub row: usize,
+    /// A first column of the area.
+    pub col: usize,
+    /// A number of rows in the area.
+    pub rows: usize,
+    /// A number of columns in the area.
+    pub cols: usize,
+}
+
+impl Rect {
+    /// Creates a new area.
+    pub fn new(row: usize, col: usize, rows: usize, cols: usize) -> Self {
+        Self {
+            row,
+            col,
+            rows,
+            cols,
+        }
+    }
+}
+
+/// Screen is a virtual terminal which keeps a rendered state of the output.
+#[derive(Debug, Clone)]
+pub struct Screen {
+    rows: usize,
+    cols: usize,
+    cells: Vec<Vec<char>>,
+    cursor: (usize, usize),
+    state: State,
+    utf8: Vec<u8>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum State {
+    Ground,
+    Escape,
+    EscapeIntermediate,
+    Csi(Vec<u8>),
+    Osc,
+    OscEscape,
+}
+
+impl Screen {
+    /// Creates an empty screen of a given size.
+    pub fn new(rows: usize, cols: usize) -> Self {
+        Self {
+            rows,
+            cols,
+            cells: vec![vec![' '; cols]; rows],
+            cursor: (0, 0),
+            state: State::Ground,
+            utf8: Vec::new(),
+        }
+    }
+
+    /// Returns a number of rows.
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// Returns a number of columns.
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    /// Returns a cursor position as (row, column).
+    pub fn cursor(&self) -> (usize, usize) {
+        self.cursor
+    }
+
+    /// Feeds a terminal output to the screen.
+    pub fn process(&mut self, bytes: &[u8]) {
+        for &b in bytes {
+            self.process_byte(b);
+        }
+    }
+
+    /// Returns a text of a given row with trailing whitespace trimmed.
+    ///
+    /// Returns an empty string if the row is out of the screen.
+    pub fn row_text(&self, row: usize) -> String {
+        self.region_text(Rect::new(row, 0, 1, self.cols))
+    }
+
+    /// Returns a text of the whole screen.
+    ///
+    /// Rows are separated by `\n` and trailing whitespace is trimmed.
+    pub fn text(&self) -> String {
+        self.region_text(Rect::new(0, 0, self.rows, self.cols))
+    }
+
+    /// Returns a text of a given area.
+    ///
+    /// Rows are separated by `\n` and trailing whitespace of each row is trimmed.
+    /// Parts of the area which are out of the screen are ignored.
+    pub fn region_text(&self, rect: Rect) -> String {
+        let rows = self.cells.iter().skip(rect.row).take(rect.rows);
+        let lines = rows
+            .map(|row| {
+                let line = row
+                    .iter()
+                    .skip(rect.col)
+                    .take(rect.cols)
+                    .collect::<String>();
+                line.trim_end().to_owned()
+            })
+            .collect::<Vec<_>>();
+
+        lines.join("\n")
+    }
+
+    /// Looks up a text on the screen and returns a position of its first occurrence.
+    ///
+    /// The search is done row by row, so a text wrapped to a next line won't be found.
+    pub fn find_text(&self, text: &str) -> Option<(usize, usize)> {
+        let needle = text.chars().collect::<Vec<_>>();
+        if needle.is_empty() {
+            return Some((0, 0));
+        }
+
+        for (i, row) in self.cells.iter().enumerate() {
+            if let Some(col) = row.windows(needle.len()).position(|w| w == needle) {
+                return Some((i, col));
+            }
+        }
+
+        None
+    }
+
+    /// Clears the screen and moves the cursor to the top left corner.
+    pub fn clear(&mut self) {
+        self.cells = vec![vec![' '; self.cols]; self.rows];
+        self.cursor = (0, 0);
+    }
+
+    fn process_byte(&mut self, b: u8) {
+        match std::mem::replace(&mut self.state, State::Ground) {
+            State::Ground => self.ground(b),
+            State::Escape => match b {
+                b'[' => self.state = State::Csi(Vec::new()),
+                b']' => self.state = State::Osc,
+                0x20..=0x2f => self.state = State::EscapeIntermediate,
+                b'D' => self.line_feed(),
+                b'E' => {
+                    self.cursor.1 = 0;
+                    self.line_feed();
+                }
+                b'M' => self.reverse_line_feed(),
+                b'c' => self.clear(),
+                _ => {}
+            },
+            State::EscapeIntermediate => {
+                if (0x20..=0x2f).contains(&b) {
+                    self.state = State::EscapeIntermediate;
+                }
+            }
+            State::Csi(mut params) => match b {
+                0x40..=0x7e => self.csi(&params, b),
+                _ => {
+                    params.push(b);
+                    self.state = State::Csi(params);
+                }
+            },
+            State::Osc => match b {
+                0x07 => {}
+                0x1b => self.state = State::OscEscape,
+                _ => self.state = State::Osc,
+            },
+            State::OscEscape => {}
+        }
+    }
+
+    fn ground(&mut self, b: u8) {
+        match b {
+            0x1b => {
+                self.utf8.clear();
+                self.state = State::Escape;
+            }
+            b'\r' => self.cursor.1 = 0,
+            b'\n' | 0x0b | 0x0c => self.line_feed(),
+            0x08 => self.cursor.1 = self.cursor.1.saturating_sub(1),
+            b'\t' => {
+                let col = (self.cursor.1 / 8 + 1) * 8;
+                self.cursor.1 = col.min(self.cols.saturating_sub(1));
+            }
+            0x00..=0x1f | 0x7f => {}
+            _ => {
+                self.utf8.push(b);
+                match std::str::from_utf8(&self.utf8) {
+                    Ok(s) => {
+                        let c = s.chars().next().unwrap_or(char::REPLACEMENT_CHARACTER);
+                        self.utf8.clear();
+                        self.print(c);
+                    }
+                    Err(err) if err.error_len().is_some() || self.utf8.len() >= 4 => {
+                        self.utf8.clear();
+                        self.print(char::REPLACEMENT_CHARACTER);
+                    }
+                    Err(_) => {}
+                }
+            }
+        }
+    }
+
+    fn print(&mut self, c: char) {
+        if self.rows == 0 || self.cols == 0 {
+            return;
+        }
+
+        if self.cursor.1 >= self.cols {
+            self.cursor.1 = 0;
+            self.line_feed();
+        }
+
+        let (row, col) = self.cursor;
+        self.cells[row][col] = c;
+        self.cursor.1 += 1;
+    }
+
+    fn line_feed(&mut self) {
+        if self.cursor.0 + 1 < self.rows {
+            self.cursor.0 += 1;
+        } else if self.rows > 0 {
+            let _ = self.cells.remove(0);
+            self.cells.push(vec![' '; self.cols]);
+        }
+    }
+
+    fn reverse_line_feed(&mut self) {
+        if self.cursor.0 > 0 {
+            self.cursor.0 -= 1;
+        } else if self.rows > 0 {
+            let _ = self.cells.pop();
+            self.cells.insert(0, vec![' '; self.cols]);
+        }
+    }
+
+    fn csi(&mut self, params: &[u8], action: u8) {
+        if matches!(params.first(), Some(0x3c..=0x3f)) {
+            // private sequences e.g. `ESC[?25l` don't affect a text
+            return;
+        }
+
+        let args = parse_params(params);
+        let arg = |i: usize, default: usize| match args.get(i) {
+            Some(&n) if n > 0 => n,
+            _ => default,
+        };
+
+        let last_row = self.rows.saturating_sub(1);
+        let last_col = self.cols.saturating_sub(1);
+        let (row, col) = self.cursor;
+
+        match action {
+            b'A' => self.cursor.0 = row.saturating_sub(arg(0, 1)),
+            b'B' | b'e' => self.cursor.0 = row.saturating_add(arg(0, 1)).min(last_row),
+            b'C' | b'a' => self.cursor.1 = col.saturating_add(arg(0, 1)).min(last_col),
+            b'D' => self.cursor.1 = col.min(last_col).saturating_sub(arg(0, 1)),
+            b'E' => self.cursor = (row.saturating_add(arg(0, 1)).min(last_row), 0),
+            b'F' => self.cursor = (row.saturating_sub(arg(0, 1)), 0),
+            b'G' | b'`' => self.cursor.1 = (arg(0, 1) - 1).min(last_col),
+            b'd' => self.cursor.0 = (arg(0, 1) - 1).min(last_row),
+            b'H' | b'f' => {
+                self.cursor = ((arg(0, 1) - 1).min(last_row), (arg(1, 1) - 1).min(last_col))
+            }
+            b'J' => match args.first().copied().unwrap_or(0) {
+                0 => {
+                    self.erase_line(row, col, self.cols);
+                    for r in row + 1..self.rows {
+                        self.erase_line(r, 0, self.cols);
+                    }
+                }
+                1 => {
+                    for r in 0..row {
+                        self.erase_line(r, 0, self.cols);
+                    }
+                    self.erase_line(row, 0, col + 1);
+                }
+                2 | 3 => {
+                    for r in 0..self.rows {
+                        self.erase_line(r, 0, self.cols);
+                    }
+                }
+                _ => {}
+            },
+            b'K' => match args.first().copied().unwrap_or(0) {
+                0 => self.erase_line(row, col, self.cols),
+                1 => self.erase_line(row, 0, col + 1),
+                2 => self.erase_line(row, 0, self.cols),
+                _ => {}
+            },
+            b'X' => self.erase_line(row, col, col.saturating_add(arg(0, 1))),
+            b'P' => {
+                if let Some(line) = self.cells.get_mut(row) {
+                    let n = arg(0, 1).min(self.cols.saturating_sub(col));
+                    if col < line.len() {
+                        let _ = line.drain(col..col + n);
+                        line.resize(line.len() + n, ' ');
+                    }
+                }
+            }
+            b'@' => {
+                if let Some(line) = self.cells.get_mut(row) {
+                    let n = arg(0, 1).min(self.cols.saturating_sub(col));
+                    for _ in 0..n {
+                        if col < line.len() {
+                            line.insert(col, ' ');
+                            let _ = line.pop();
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn erase_line(&mut self, row: usize, from: usize, to: usize) {
+        if let Some(line) = self.cells.get_mut(row) {
+            let to = to.min(line.len());
+            for cell in line.iter_mut().take(to).skip(from) {
+                *cell = ' ';
+            }
+        }
+    }
+}
+
+impl io::Write for Screen {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.process(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+fn parse_params(params: &[u8]) -> Vec<usize> {
+    params
+        .split(|&b| b == b';' || b == b':')
+        .map(|p| {
+            p.iter()
+                .filter(|b| b.is_ascii_digit())
+                .fold(0usize, |n, b| {
+                    n.saturating_mul(10).saturating_add((b - b'0') as usize)
+                })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_screen_text() {
+        let mut screen = Screen::new(3, 10);
+        screen.process(b"Hello\r\nWorld");
+        assert_eq!(screen.text(), "Hello\nWorld\n");
+        assert_eq!(screen.cursor(), (1, 5));
+    }
+
+    #[test]
+    fn test_screen_wrap_and_scroll() {
+        let mut screen = Screen::new(2, 4);
+        screen.process(b"abcdef\r\nxyz");
+        assert_eq!(screen.text(), "ef\nxyz");
+    }
+
+    #[test]
+    fn test_screen_cursor_movement() {
+        let mut screen = Screen::new(3, 10);
+        screen.process(b"\x1b[2;3Hx\x1b[1;1Hy\x1b[2Cz");
+        assert_eq!(screen.text(), "y  z\n  x\n");
+
+        screen.process(b"\x1b[2J\x1b[H\x1b[31mred\x1b[0m");
+        assert_eq!(screen.text(), "red\n\n");
+    }
+
+    #[test]
+    fn test_screen_cursor_movement_saturates() {
+        let huge = usize::MAX.to_string();
+
+        let mut screen = Screen::new(3, 10);
+        screen.process(format!("\x1b[{0}B\x1b[{0}Cx", huge).as_bytes());
+        screen.process(format!("\x1b[{0}E\x1b[{0}e\x1b[{0}ay", huge).as_bytes());
+        screen.process(format!("\x1b[3;1H\x1b[{}X", huge).as_bytes());
+        assert_eq!(screen.text(), "\n\n");
+    }
+
+    #[test]
+    fn test_screen_erase_line() {
+        let mut screen = Screen::new(1, 10);
+        screen.process(b"Hello World\r");
+        screen.process(b"Bye\x1b[K");
+        assert_eq!(screen.text(), "Bye");
+    }
+
+    #[test]
+    fn test_screen_ignores_osc() {
+        let mut screen = Screen::new(1, 10);
+        screen.process(b"\x1b]0;title\x07$ \x1b]2;t\x1b\\ls");
+        assert_eq!(screen.text(), "$ ls");
+    }
+
+    #[test]
+    fn test_screen_utf8() {
+        let mut screen = Screen::new(1, 10);
+        let text = "привет".as_bytes();
+        screen.process(&text[..3]);
+        screen.process(&text[3..]);
+        assert_eq!(screen.text(), "привет");
+    }
+
+    #[test]
+    fn test_region_text() {
+        let mut screen = Screen::new(3, 12);
+        screen.process(b"id  name\r\n1   john\r\n2   jane");
+        assert_eq!(screen.region_text(Rect::new(1, 4, 2, 4)), "john\njane");
+        assert_eq!(screen.region_text(Rect::new(2, 0, 5, 1)), "2");
+        assert_eq!(screen.region_text(Rect::new(10, 0, 5, 1)), "");
+    }
+
+    #[test]
+    fn test_find_text() {
+        let mut screen = Screen::new(3, 12);
+        screen.process(b"id  name\r\n1   john\r\n2   jane");
+        assert_eq!(screen.find_text("jane"), Some((2, 4)));
+        assert_eq!(screen.find_text("name"), Some((0, 4)));
+        assert_eq!(screen.find_text("bob"), None);
+    }
+}
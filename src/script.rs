@@ -0,0 +1,588 @@
+//! This module contains an interpreter of simple expect like scripts.
+//!
+//! A script is a list of instructions, one per line.
+//! Everything after `#` is a comment unless it's inside a string.
+//!
+//! - `spawn <command>` spawns a command; it must be the first instruction.
+//! - `timeout <seconds>` or `timeout none` sets an expect timeout.
+//! - `expect <pattern>` waits until a pattern is matched.
+//! - `expect <pattern> -> <label>, <pattern> -> <label>` waits until one of the patterns is matched
+//!   and jumps to a corresponding label.
+//! - `send <string>` sends a string, `send_line <string>` sends it followed by a line ending.
+//! - `sleep <seconds>` makes a pause.
+//! - `label <name>` marks a place to jump to, and `goto <name>` jumps to it.
+//! - `exit` stops the script.
+//!
+//! A pattern is either a quoted string, `regex "<regex>"` or `eof`.
+//! Strings support `\n`, `\r`, `\t`, `\\`, `\"` and `\xNN` escapes.
+//!
+//! # Example
+//!
+#![cfg_attr(all(unix, not(feature = "async")), doc = "```no_run")]
+#![cfg_attr(not(all(unix, not(feature = "async"))), doc = "```ignore")]
+//! use expectrl::script::Script;
+//!
+//! let script: Script = r#"
+//!     spawn ssh user@localhost
+//!     timeout 10
+//!
+//!     label login
+//!     expect "(yes/no)" -> confirm, "password:" -> password
+//!
+//!     label confirm
+//!     send_line "yes"
+//!     goto login
+//!
+//!     label password
+//!     send_line "qwerty"
+//!     expect regex "\$ $"
+//! "#
+//! .parse()
+//! .unwrap();
+//!
+//! let session = script.run().unwrap();
+//! ```
+
+use std::{collections::HashMap, str::FromStr, time::Duration};
+
+use crate::{needle::Match, Any, Eof, Error, Needle, Regex, Session};
+
+#[cfg(not(feature = "async"))]
+use crate::process::NonBlocking;
+#[cfg(not(feature = "async"))]
+use std::io::{Read, Write};
+
+#[cfg(feature = "async")]
+use futures_lite::{AsyncRead, AsyncWrite};
+
+/// Script is a parsed list of instructions.
+///
+/// It can be created via [`FromStr`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Script {
+    command: Option<String>,
+    instructions: Vec<(usize, Instruction)>,
+    labels: HashMap<String, usize>,
+}
+
+/// An instruction of a [`Script`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Instruction {
+    /// Sets an expect timeout.
+    Timeout(Option<Duration>),
+    /// Waits until one of the patterns is matched and jumps to a corresponding label if it's set.
+    Expect(Vec<(Pattern, Option<String>)>),
+    /// Sends bytes.
+    Send(Vec<u8>),
+    /// Sends a line, see [`Session::send_line`].
+    SendLine(Vec<u8>),
+    /// Makes a pause.
+    Sleep(Duration),
+    /// Jumps to a label.
+    Goto(String),
+    /// Stops the script.
+    Exit,
+}
+
+/// A pattern of an expect instruction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Pattern {
+    /// A string.
+    Str(Vec<u8>),
+    /// A regex.
+    Regex(String),
+    /// An end of file.
+    Eof,
+}
+
+impl Needle for Pattern {
+    fn check(&self, buf: &[u8], eof: bool) -> Result<Vec<Match>, Error> {
+        match self {
+            Pattern::Str(s) => s.as_slice().check(buf, eof),
+            Pattern::Regex(re) => Regex(re).check(buf, eof),
+            Pattern::Eof => Eof.check(buf, eof),
+        }
+    }
+
+    fn description(&self) -> String {
+        match self {
+            Pattern::Str(s) => s.as_slice().description(),
+            Pattern::Regex(re) => Regex(re).description(),
+            Pattern::Eof => Eof.description(),
+        }
+    }
+}
+
+impl Script {
+    /// Returns a command of a `spawn` instruction.
+    pub fn command(&self) -> Option<&str> {
+        self.command.as_deref()
+    }
+
+    /// Returns a list of instructions with their line numbers.
+    pub fn instructions(&self) -> &[(usize, Instruction)] {
+        &self.instructions
+    }
+
+    /// Spawns a command of the script and runs the script against it.
+    ///
+    /// It returns the session so it can be used further.
+    #[cfg(not(feature = "async"))]
+    pub fn run(&self) -> Result<Session, Error> {
+        let mut session = self.spawn()?;
+        self.run_on(&mut session)?;
+        Ok(session)
+    }
+
+    /// Spawns a command of the script and runs the script against it.
+    ///
+    /// It returns the session so it can be used further.
+    #[cfg(feature = "async")]
+    pub async fn run(&self) -> Result<Session, Error> {
+        let mut session = self.spawn()?;
+        self.run_on(&mut session).await?;
+        Ok(session)
+    }
+
+    /// Runs the script against a session.
+    ///
+    /// A `spawn` instruction is ignored.
+    #[cfg(not(feature = "async"))]
+    pub fn run_on<P, S>(&self, session: &mut Session<P, S>) -> Result<(), Error>
+    where
+        S: Read + Write + NonBlocking,
+    {
+        let mut pc = 0;
+        while let Some((line, instruction)) = self.instructions.get(pc) {
+            pc += 1;
+
+            let result = match instruction {
+                Instruction::Timeout(timeout) => {
+                    session.set_expect_timeout(*timeout);
+                    Ok(None)
+                }
+                Instruction::Expect(patterns) => {
+                    let needle = Any(patterns.iter().map(|(p, _)| p).collect::<Vec<_>>());
+                    session.expect(needle).map(|captures| {
+                        captures
+                            .matched_index()
+                            .and_then(|i| patterns[i].1.as_ref())
+                    })
+                }
+                Instruction::Send(buf) => session.send(buf).map(|_| None).map_err(Error::from),
+                Instruction::SendLine(buf) => {
+                    session.send_line(buf).map(|_| None).map_err(Error::from)
+                }
+                Instruction::Sleep(duration) => {
+                    std::thread::sleep(*duration);
+                    Ok(None)
+                }
+                Instruction::Goto(label) => Ok(Some(label)),
+                Instruction::Exit => return Ok(()),
+            };
+
+            match result {
+                Ok(Some(label)) => pc = self.labels[label],
+                Ok(None) => {}
+                Err(err) => return Err(line_error(*line, err)),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Runs the script against a session.
+    ///
+    /// A `spawn` instruction is ignored.
+    #[cfg(feature = "async")]
+    pub async fn run_on<P, S>(&self, session: &mut Session<P, S>) -> Result<(), Error>
+    where
+        S: AsyncRead + AsyncWrite + Unpin,
+    {
+        let mut pc = 0;
+        while let Some((line, instruction)) = self.instructions.get(pc) {
+            pc += 1;
+
+            let result = match instruction {
+                Instruction::Timeout(timeout) => {
+                    session.set_expect_timeout(*timeout);
+                    Ok(None)
+                }
+                Instruction::Expect(patterns) => {
+                    let needle = Any(patterns.iter().map(|(p, _)| p).collect::<Vec<_>>());
+                    session.expect(needle).await.map(|captures| {
+                        captures
+                            .matched_index()
+                            .and_then(|i| patterns[i].1.as_ref())
+                    })
+                }
+                Instruction::Send(buf) => {
+                    session.send(buf).await.map(|_| None).map_err(Error::from)
+                }
+                Instruction::SendLine(buf) => session
+                    .send_line(buf)
+                    .await
+                    .map(|_| None)
+                    .map_err(Error::from),
+                Instruction::Sleep(duration) => {
+                    futures_timer::Delay::new(*duration).await;
+                    Ok(None)
+                }
+                Instruction::Goto(label) => Ok(Some(label)),
+                Instruction::Exit => return Ok(()),
+            };
+
+            match result {
+                Ok(Some(label)) => pc = self.labels[label],
+                Ok(None) => {}
+                Err(err) => return Err(line_error(*line, err)),
+            }
+        }
+
+        Ok(())
+    }
+
+    fn spawn(&self) -> Result<Session, Error> {
+        match &self.command {
+            Some(command) => crate::spawn(command),
+            None => Err(Error::Other {
+                message: "Failed to run a script".to_owned(),
+                err: "there's no spawn instruction".to_owned(),
+            }),
+        }
+    }
+}
+
+impl FromStr for Script {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut command = None;
+        let mut instructions = Vec::new();
+        let mut labels = HashMap::new();
+        let mut gotos = Vec::new();
+
+        for (i, line) in s.lines().enumerate() {
+            let number = i + 1;
+            let line = strip_comment(line).trim();
+
+            if let Some(cmd) = line.strip_prefix("spawn ") {
+                if command.is_some() || !instructions.is_empty() {
+                    return Err(parse_error(number, "spawn must be the first instruction"));
+                }
+
+                command = Some(cmd.trim().to_owned());
+                continue;
+            }
+
+            let tokens = tokenize(line).map_err(|err| parse_error(number, err))?;
+            let mut tokens = tokens.into_iter();
+
+            let keyword = match tokens.next() {
+                Some(Token::Word(keyword)) => keyword,
+                Some(_) => return Err(parse_error(number, "expected an instruction")),
+                None => continue,
+            };
+
+            let instruction = match keyword.as_str() {
+                "timeout" => match tokens.next() {
+                    Some(Token::Word(w)) if w == "none" => Instruction::Timeout(None),
+                    Some(Token::Word(w)) => Instruction::Timeout(Some(
+                        parse_duration(&w).map_err(|e| parse_error(number, e))?,
+                    )),
+                    _ => return Err(parse_error(number, "expected a timeout")),
+                },
+                "sleep" => match tokens.next() {
+                    Some(Token::Word(w)) => {
+                        Instruction::Sleep(parse_duration(&w).map_err(|e| parse_error(number, e))?)
+                    }
+                    _ => return Err(parse_error(number, "expected a duration")),
+                },
+                "send" => match tokens.next() {
+                    Some(Token::Str(buf)) => Instruction::Send(buf),
+                    _ => return Err(parse_error(number, "expected a string")),
+                },
+                "send_line" => match tokens.next() {
+                    Some(Token::Str(buf)) => Instruction::SendLine(buf),
+                    _ => return Err(parse_error(number, "expected a string")),
+                },
+                "expect" => {
+                    let patterns =
+                        parse_patterns(&mut tokens).map_err(|e| parse_error(number, e))?;
+                    for (_, label) in &patterns {
+                        if let Some(label) = label {
+                            gotos.push((number, label.clone()));
+                        }
+                    }
+
+                    Instruction::Expect(patterns)
+                }
+                "goto" => match tokens.next() {
+                    Some(Token::Word(label)) => {
+                        gotos.push((number, label.clone()));
+                        Instruction::Goto(label)
+                    }
+                    _ => return Err(parse_error(number, "expected a label")),
+                },
+                "label" => match tokens.next() {
+                    Some(Token::Word(label)) => {
+                        if labels.insert(label, instructions.len()).is_some() {
+                            return Err(parse_error(number, "a label is already defined"));
+                        }
+
+                        continue;
+                    }
+                    _ => return Err(parse_error(number, "expected a label")),
+                },
+                "exit" => Instruction::Exit,
+                _ => return Err(parse_error(number, "unknown instruction")),
+            };
+
+            if tokens.next().is_some() {
+                return Err(parse_error(number, "unexpected token"));
+            }
+
+            instructions.push((number, instruction));
+        }
+
+        for (number, label) in gotos {
+            if !labels.contains_key(&label) {
+                return Err(parse_error(number, "undefined label"));
+            }
+        }
+
+        Ok(Self {
+            command,
+            instructions,
+            labels,
+        })
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Token {
+    Word(String),
+    Str(Vec<u8>),
+    Arrow,
+    Comma,
+}
+
+fn tokenize(line: &str) -> Result<Vec<Token>, &'static str> {
+    let mut tokens = Vec::new();
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '#' => break,
+            ',' => tokens.push(Token::Comma),
+            '-' if chars.peek() == Some(&'>') => {
+                let _ = chars.next();
+                tokens.push(Token::Arrow);
+            }
+            '"' => {
+                let mut buf = Vec::new();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => {
+                            let escaped = match chars.next() {
+                                Some('n') => b'\n',
+                                Some('r') => b'\r',
+                                Some('t') => b'\t',
+                                Some('\\') => b'\\',
+                                Some('"') => b'"',
+                                Some('x') => {
+                                    let hex =
+                                        chars.next().zip(chars.next()).ok_or("invalid escape")?;
+                                    let hex = format!("{}{}", hex.0, hex.1);
+                                    u8::from_str_radix(&hex, 16).map_err(|_| "invalid escape")?
+                                }
+                                // keep unknown escapes as is, so regex escapes like `\d` work
+                                Some(c) => {
+                                    buf.push(b'\\');
+                                    let mut tmp = [0; 4];
+                                    buf.extend(c.encode_utf8(&mut tmp).as_bytes());
+                                    continue;
+                                }
+                                None => return Err("not closed string"),
+                            };
+
+                            buf.push(escaped);
+                        }
+                        Some(c) => {
+                            let mut tmp = [0; 4];
+                            buf.extend(c.encode_utf8(&mut tmp).as_bytes());
+                        }
+                        None => return Err("not closed string"),
+                    }
+                }
+
+                tokens.push(Token::Str(buf));
+            }
+            c if c.is_whitespace() => {}
+            c => {
+                let mut word = String::from(c);
+                while let Some(&c) = chars.peek() {
+                    if c.is_whitespace() || c == ',' || c == '"' || c == '#' {
+                        break;
+                    }
+
+                    word.push(c);
+                    let _ = chars.next();
+                }
+
+                tokens.push(Token::Word(word));
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+fn parse_patterns(
+    tokens: &mut impl Iterator<Item = Token>,
+) -> Result<Vec<(Pattern, Option<String>)>, &'static str> {
+    let mut patterns = Vec::new();
+    loop {
+        let pattern = match tokens.next() {
+            Some(Token::Str(s)) => Pattern::Str(s),
+            Some(Token::Word(w)) if w == "eof" => Pattern::Eof,
+            Some(Token::Word(w)) if w == "regex" => match tokens.next() {
+                Some(Token::Str(s)) => Pattern::Regex(
+                    String::from_utf8(s).map_err(|_| "a regex must be a valid utf8")?,
+                ),
+                _ => return Err("expected a regex string"),
+            },
+            _ => return Err("expected a pattern"),
+        };
+
+        match tokens.next() {
+            None => {
+                patterns.push((pattern, None));
+                return Ok(patterns);
+            }
+            Some(Token::Comma) => patterns.push((pattern, None)),
+            Some(Token::Arrow) => {
+                let label = match tokens.next() {
+                    Some(Token::Word(label)) => label,
+                    _ => return Err("expected a label"),
+                };
+
+                patterns.push((pattern, Some(label)));
+
+                match tokens.next() {
+                    None => return Ok(patterns),
+                    Some(Token::Comma) => {}
+                    Some(_) => return Err("expected a comma"),
+                }
+            }
+            Some(_) => return Err("expected a comma or an arrow"),
+        }
+    }
+}
+
+/// Cuts a comment off a line, a `#` inside a string doesn't start a comment.
+fn strip_comment(line: &str) -> &str {
+    let mut in_string = false;
+    let mut escaped = false;
+    for (i, c) in line.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if in_string => escaped = true,
+            '"' => in_string = !in_string,
+            '#' if !in_string => return &line[..i],
+            _ => {}
+        }
+    }
+
+    line
+}
+
+fn parse_duration(s: &str) -> Result<Duration, &'static str> {
+    s.parse::<f64>()
+        .ok()
+        .and_then(|secs| Duration::try_from_secs_f64(secs).ok())
+        .ok_or("invalid number of seconds")
+}
+
+fn parse_error(line: usize, message: &str) -> Error {
+    Error::Other {
+        message: format!("Failed to parse a script at line {}", line),
+        err: message.to_owned(),
+    }
+}
+
+fn line_error(line: usize, err: Error) -> Error {
+    Error::Other {
+        message: format!("Script failed at line {}", line),
+        err: err.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let script: Script = r#"
+            spawn sh -c "echo '#hello'" # a comment
+            timeout 1.5 # a comment
+            label start
+            expect "a\"b\x41" -> start, regex "\d+", eof -> end
+            send "yes\n"
+            send_line "no"
+            sleep 0
+            goto start
+            label end
+            exit
+        "#
+        .parse()
+        .unwrap();
+
+        assert_eq!(script.command(), Some(r#"sh -c "echo '#hello'""#));
+        assert_eq!(
+            script.instructions(),
+            [
+                (3, Instruction::Timeout(Some(Duration::from_millis(1500)))),
+                (
+                    5,
+                    Instruction::Expect(vec![
+                        (Pattern::Str(b"a\"bA".to_vec()), Some("start".to_owned())),
+                        (Pattern::Regex(r"\d+".to_owned()), None),
+                        (Pattern::Eof, Some("end".to_owned())),
+                    ])
+                ),
+                (6, Instruction::Send(b"yes\n".to_vec())),
+                (7, Instruction::SendLine(b"no".to_vec())),
+                (8, Instruction::Sleep(Duration::ZERO)),
+                (9, Instruction::Goto("start".to_owned())),
+                (11, Instruction::Exit),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        for (script, line) in [
+            ("send", 1),
+            ("\nexpect", 2),
+            ("goto nowhere", 1),
+            ("send \"not closed", 1),
+            ("label a\nlabel a", 2),
+            ("exit\nspawn cat", 2),
+            ("unknown", 1),
+            ("timeout -1", 1),
+            ("timeout 1e300", 1),
+            ("sleep NaN", 1),
+        ] {
+            let err = script.parse::<Script>().unwrap_err();
+            assert!(
+                err.to_string()
+                    .contains(&format!("Failed to parse a script at line {}", line)),
+                "{:?} {}",
+                script,
+                err
+            );
+        }
+    }
+}
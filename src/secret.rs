@@ -0,0 +1,145 @@
+//! This module contains a [`Password`] trait for secrets which are sent to a process
+//! and an owned [`Secret`].
+//!
+//! A secret sent by [`Session::send_secret`] is not passed to a session logger,
+//! nor to [`LogStream`] and [`AsciicastStream`] wrappers.
+//!
+//! With the `zeroize` feature a [`Secret`] is wiped from memory when it's dropped.
+//! The `secrecy` feature turns it on as well.
+//!
+//! [`Session::send_secret`]: crate::Session::send_secret
+//! [`LogStream`]: crate::stream::log::LogStream
+//! [`AsciicastStream`]: crate::stream::asciicast::AsciicastStream
+
+use std::{cell::Cell, fmt};
+
+/// Password is a secret which can be sent to a process,
+/// see [`Session::send_secret`] and [`ReplSession::execute_sudo`].
+///
+/// With the `secrecy` feature it's implemented for `secrecy::SecretString`,
+/// and with the `zeroize` feature for `zeroize::Zeroizing<String>`.
+///
+/// [`Session::send_secret`]: crate::Session::send_secret
+/// [`ReplSession::execute_sudo`]: crate::repl::ReplSession::execute_sudo
+pub trait Password {
+    /// Returns the password.
+    fn expose(&self) -> &str;
+}
+
+impl Password for str {
+    fn expose(&self) -> &str {
+        self
+    }
+}
+
+impl Password for String {
+    fn expose(&self) -> &str {
+        self
+    }
+}
+
+impl<T: Password + ?Sized> Password for &T {
+    fn expose(&self) -> &str {
+        T::expose(self)
+    }
+}
+
+#[cfg(feature = "secrecy")]
+impl Password for secrecy::SecretString {
+    fn expose(&self) -> &str {
+        secrecy::ExposeSecret::expose_secret(self)
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl Password for zeroize::Zeroizing<String> {
+    fn expose(&self) -> &str {
+        self
+    }
+}
+
+/// Secret is an owned password.
+///
+/// It's hidden from a debug output,
+/// and with the `zeroize` feature it's wiped from memory when dropped.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Secret(String);
+
+impl Password for Secret {
+    fn expose(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<String> for Secret {
+    fn from(secret: String) -> Self {
+        Self(secret)
+    }
+}
+
+impl<P: Password + ?Sized> From<&P> for Secret {
+    fn from(secret: &P) -> Self {
+        Self(secret.expose().to_owned())
+    }
+}
+
+#[cfg(feature = "secrecy")]
+impl From<secrecy::SecretString> for Secret {
+    fn from(secret: secrecy::SecretString) -> Self {
+        Self::from(&secret)
+    }
+}
+
+impl fmt::Debug for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("***")
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl Drop for Secret {
+    fn drop(&mut self) {
+        zeroize::Zeroize::zeroize(&mut self.0);
+    }
+}
+
+thread_local! {
+    static IS_SECRET_WRITE: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Runs a write of a secret, so stream wrappers don't record it.
+///
+/// An async write must be wrapped on each poll, so the mark doesn't leak to other tasks.
+pub(crate) fn write_secret<F, R>(write: F) -> R
+where
+    F: FnOnce() -> R,
+{
+    let was_secret = IS_SECRET_WRITE.with(|is_secret| is_secret.replace(true));
+    let result = write();
+    IS_SECRET_WRITE.with(|is_secret| is_secret.set(was_secret));
+    result
+}
+
+/// Verifies whether a secret is being written.
+pub(crate) fn is_secret_write() -> bool {
+    IS_SECRET_WRITE.with(Cell::get)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_secret_is_hidden() {
+        let secret = Secret::from("password");
+        assert_eq!(secret.expose(), "password");
+        assert_eq!(format!("{:?}", secret), "***");
+    }
+
+    #[test]
+    fn test_write_secret() {
+        assert!(!is_secret_write());
+        assert!(write_secret(is_secret_write));
+        assert!(!is_secret_write());
+    }
+}
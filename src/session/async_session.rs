@@ -12,7 +12,25 @@ use futures_lite::{
     ready, AsyncBufRead, AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt,
 };
 
-use crate::{process::Healthcheck, Captures, Error, Needle};
+use super::{
+    event::{EventStream, Events, SessionEvent},
+    watchdog::{Watch, Watchdog},
+    Echo, EchoMatch, Found, Tee,
+};
+use crate::{
+    captures::CapturesRef,
+    needle::Line,
+    policy::{AnswerPolicy, RetryPolicy},
+    process::{ExitStatus, Healthcheck, Termination},
+    secret::Password,
+    stream::log::{written_bytes, LogClock, LogFormat, Logger},
+    Captures, ControlCode, Error, Needle, Stats, Timeline,
+};
+
+#[cfg(windows)]
+const LINE_ENDING: &[u8] = b"\r\n";
+#[cfg(not(windows))]
+const LINE_ENDING: &[u8] = b"\n";
 
 /// Session represents a spawned process and its streams.
 /// It controlls process and communication with it.
@@ -20,6 +38,9 @@ use crate::{process::Healthcheck, Captures, Error, Needle};
 pub struct Session<P = super::OsProcess, S = super::OsProcessStream> {
     process: P,
     stream: Stream<S>,
+    name: Option<String>,
+    #[cfg(unix)]
+    stderr: Option<Box<super::StderrSession>>,
 }
 
 // GEt back to the solution where Logger is just dyn Write instead of all these magic with type system.....
@@ -30,6 +51,9 @@ impl<P, S> Session<P, S> {
         Ok(Self {
             process,
             stream: Stream::new(stream),
+            name: None,
+            #[cfg(unix)]
+            stderr: None,
         })
     }
 
@@ -53,11 +77,137 @@ impl<P, S> Session<P, S> {
         &mut self.process
     }
 
+    /// Sets a name of the session.
+    ///
+    /// The name is added to each line of a log set by [Session::set_log] or [log](crate::session::log)
+    /// and to messages of errors returned by [Session::expect], [Session::expect_ref] and [Session::expect_ctx].
+    /// It helps to tell sessions apart when a few of them are run at once.
+    ///
+    /// A kind of an error is not changed, e.g. [Error::Eof] and [Error::ExpectTimeout] are returned as they are.
+    pub fn set_name<N: Into<String>>(&mut self, name: N) {
+        let name = name.into();
+        self.stream.stream.logger.set_name(Some(name.clone()));
+        self.name = Some(name);
+    }
+
+    /// Returns a name of the session.
+    ///
+    /// See [Session::set_name].
+    pub fn get_name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Adds a name of the session to a message of an error if it's set.
+    fn named<T>(&self, result: Result<T, Error>) -> Result<T, Error> {
+        match &self.name {
+            Some(name) => result.map_err(|err| err.with_name(name)),
+            None => result,
+        }
+    }
+
+    /// Returns counters of the session activity.
+    ///
+    /// See [Stats].
+    pub fn stats(&self) -> &Stats {
+        &self.stream.stream.stats
+    }
+
+    /// Resets all counters of the session activity.
+    pub fn reset_stats(&mut self) {
+        self.stream.stream.stats = Stats::default();
+    }
+
+    /// Sets a logger which writes each read/write operation of the session into a given writer.
+    ///
+    /// It replaces a previously set logger.
+    /// Unlike [crate::session::log] it doesn't change a type of the session,
+    /// so the logger can be removed later by [Session::unset_log].
+    pub fn set_log<W>(&mut self, dst: W)
+    where
+        W: io::Write + Send + 'static,
+    {
+        self.stream.stream.logger.set_sink(Box::new(dst));
+    }
+
+    /// Mirrors all output read from a process to a given writer, e.g. a file or a channel.
+    ///
+    /// Unlike [Session::set_log] bytes are written as they are,
+    /// and matching proceeds as usual.
+    /// It replaces a previously set writer.
+    ///
+    /// The writer is dropped after the first error it returns.
+    pub fn tee_output<W>(&mut self, dst: W)
+    where
+        W: io::Write + Send + 'static,
+    {
+        self.stream.stream.tee.set_sink(Some(Box::new(dst)));
+    }
+
+    /// Stops mirroring the output set by [Session::tee_output].
+    pub fn unset_tee_output(&mut self) {
+        self.stream.stream.tee.set_sink(None);
+    }
+
+    /// Sets a format of the log.
+    ///
+    /// See [Session::set_log].
+    pub fn set_log_format(&mut self, format: LogFormat) {
+        self.stream.stream.logger.set_format(format);
+    }
+
+    /// Sets a clock each line of the log is timestamped by.
+    ///
+    /// See [Session::set_log].
+    pub fn set_log_clock(&mut self, clock: LogClock) {
+        self.stream.stream.logger.set_clock(clock);
+    }
+
+    /// Removes a logger set by [Session::set_log].
+    pub fn unset_log(&mut self) {
+        self.stream.stream.logger.unset_sink();
+    }
+
+    /// Stops logging until [Session::resume_log] is called.
+    ///
+    /// It's useful to exclude noisy or sensitive parts, e.g. a password entry.
+    pub fn pause_log(&mut self) {
+        self.stream.stream.logger.set_paused(true);
+    }
+
+    /// Resumes logging stopped by [Session::pause_log].
+    pub fn resume_log(&mut self) {
+        self.stream.stream.logger.set_paused(false);
+    }
+
+    /// Registers a callback which is called on each [SessionEvent].
+    ///
+    /// Events are reported as soon as the session observes them:
+    /// EOF by any read, e.g. [Session::expect] or [Session::check],
+    /// and an exit by [Session::wait_timeout], [Session::shutdown] or [Session::poll_event].
+    pub fn on_event<F>(&mut self, callback: F)
+    where
+        F: FnMut(&SessionEvent) + Send + 'static,
+    {
+        self.stream.stream.events.set_callback(Box::new(callback));
+    }
+
+    /// Sets a [Watchdog] which warns about slow expect calls.
+    ///
+    /// It's checked by [Session::expect] and [Session::expect_ref].
+    pub fn set_watchdog(&mut self, watchdog: Watchdog) {
+        self.stream.watchdog = watchdog;
+    }
+
     /// Set the pty session's expect timeout.
     pub fn set_expect_timeout(&mut self, expect_timeout: Option<Duration>) {
         self.stream.set_expect_timeout(expect_timeout);
     }
 
+    /// Get the pty session's expect timeout.
+    pub fn get_expect_timeout(&self) -> Option<Duration> {
+        self.stream.expect_timeout
+    }
+
     /// Set a expect algorithm to be either gready or lazy.
     ///
     /// Default algorithm is gready.
@@ -72,13 +222,106 @@ impl<P, S> Session<P, S> {
         new_stream: F,
     ) -> Result<Session<P, R>, Error> {
         let buf = self.stream.get_available().to_owned();
+        let stats = self.stream.stream.stats;
+        let logger = std::mem::take(&mut self.stream.stream.logger);
+        let watchdog = std::mem::take(&mut self.stream.watchdog);
+        let tee = std::mem::take(&mut self.stream.stream.tee);
+        let events = std::mem::take(&mut self.stream.stream.events);
 
         let stream = self.stream.into_inner();
         let stream = new_stream(stream);
         let mut session = Session::new(self.process, stream)?;
         session.stream.keep(&buf);
+        session.stream.stream.stats = stats;
+        session.stream.stream.logger = logger;
+        session.stream.watchdog = watchdog;
+        session.stream.stream.tee = tee;
+        session.stream.stream.events = events;
+        session.name = self.name;
+        #[cfg(unix)]
+        {
+            session.stderr = self.stderr;
+        }
         Ok(session)
     }
+
+    #[cfg(unix)]
+    pub(crate) fn set_stderr(&mut self, stderr: super::StderrSession) {
+        self.stderr = Some(Box::new(stderr));
+    }
+
+    #[cfg(unix)]
+    pub(crate) fn stderr_mut(&mut self) -> Option<&mut super::StderrSession> {
+        self.stderr.as_deref_mut()
+    }
+
+    /// Splits the session into a process, a stream and not yet consumed bytes.
+    #[cfg(unix)]
+    pub(crate) fn into_parts(mut self) -> (P, S, Vec<u8>) {
+        let buf = self.stream.get_available().to_owned();
+        (self.process, self.stream.into_inner(), buf)
+    }
+
+    /// Puts bytes back in the buffer so they will be read first.
+    #[cfg(unix)]
+    pub(crate) fn keep_in_buffer(&mut self, buf: &[u8]) {
+        self.stream.keep(buf);
+    }
+
+    #[cfg(unix)]
+    pub(crate) fn is_expect_lazy(&self) -> bool {
+        self.stream.expect_lazy
+    }
+
+    /// Sets whether an echo of lines sent by [Session::send_line] must be skipped.
+    ///
+    /// If it's set, the echo is consumed before matching or reading the output,
+    /// so a needle can't be accidentally matched against a sent line.
+    /// It applies to expect, check and read calls.
+    ///
+    /// The echo is consumed only if the output starts with it,
+    /// otherwise it's ignored.
+    /// A line ending of the echo matches both `\n` and `\r\n`.
+    pub fn set_skip_echo(&mut self, skip: bool) {
+        self.stream.skip_echo = skip;
+        self.stream.echo.clear();
+    }
+
+    /// Sets whether `\r\n` in the output is replaced by `\n`.
+    ///
+    /// A terminal translates `\n` to `\r\n` on output,
+    /// so it allows to write needles with `\n` and get normalized [Captures].
+    ///
+    /// It affects only the output read after the call and the output which is already buffered.
+    ///
+    /// By default it's off, so the output is byte exact.
+    pub fn set_normalize_newlines(&mut self, on: bool) {
+        self.stream.stream.set_normalize_newlines(on);
+    }
+
+    /// Sets a maximum number of bytes read from a process at once.
+    ///
+    /// A read buffer of this size is allocated once and reused by all reads.
+    /// A size of `0` is treated as `1`.
+    ///
+    /// By default it's 4096 bytes.
+    pub fn set_read_chunk_size(&mut self, size: usize) {
+        self.stream.stream.set_read_chunk_size(size);
+    }
+
+    /// Sets a delay which is made after each character sent by [Session::send] and [Session::send_line].
+    ///
+    /// By default there's no delay.
+    ///
+    /// See [Session::send_slow].
+    pub fn set_send_delay(&mut self, delay: Option<Duration>) {
+        self.stream.send_delay = delay;
+    }
+
+    /// Get a delay which is made after each sent character.
+    pub fn get_send_delay(&self) -> Option<Duration> {
+        self.stream.send_delay
+    }
 }
 
 impl<P: Healthcheck, S> Session<P, S> {
@@ -88,6 +331,127 @@ impl<P: Healthcheck, S> Session<P, S> {
     }
 }
 
+impl<P: Termination, S: AsyncRead + Unpin> Session<P, S> {
+    /// Checks whether a process closed its side of the stream or exited.
+    ///
+    /// It doesn't block.
+    /// Each event is returned only once, and a callback registered by [Session::on_event] is called for it.
+    ///
+    /// Output read while checking for EOF is kept in the buffer.
+    pub async fn poll_event(&mut self) -> Result<Option<SessionEvent>, Error> {
+        if !self.stream.stream.events.is_eof_reported() {
+            loop {
+                match futures_lite::future::poll_once(self.stream.stream.fill()).await {
+                    Some(Ok(0)) => return Ok(Some(SessionEvent::Eof)),
+                    Some(Ok(_)) => continue,
+                    Some(Err(err)) => return Err(err.into()),
+                    None => break,
+                }
+            }
+        }
+
+        if !self.stream.stream.events.is_exit_reported() {
+            if let Some(status) = self.check_exit()? {
+                return Ok(Some(SessionEvent::Exited(status)));
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+impl<P, S> Session<P, S> {
+    /// Returns a stream of session events.
+    ///
+    /// The stream doesn't borrow the session,
+    /// it gets events as the session observes them,
+    /// e.g. by [Session::expect], [Session::wait_timeout] or [Session::poll_event].
+    /// Events which were reported before the call are delivered first.
+    ///
+    /// The stream ends once both EOF and an exit of the process are delivered,
+    /// or when the session is dropped.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use futures_lite::StreamExt;
+    /// use expectrl::{session::SessionEvent, Eof};
+    /// use std::time::Duration;
+    ///
+    /// # futures_lite::future::block_on(async {
+    /// let mut p = expectrl::spawn("echo hello").unwrap();
+    /// let mut events = p.events();
+    ///
+    /// p.expect(Eof).await.unwrap();
+    /// p.wait_timeout(Duration::from_secs(1)).await.unwrap();
+    ///
+    /// assert_eq!(events.next().await, Some(SessionEvent::Eof));
+    /// assert!(matches!(events.next().await, Some(SessionEvent::Exited(_))));
+    /// # });
+    /// ```
+    pub fn events(&mut self) -> EventStream {
+        self.stream.stream.events.subscribe()
+    }
+}
+
+impl<P: Termination, S> Session<P, S> {
+    /// Checks whether a process exited and reports it once it did.
+    fn check_exit(&mut self) -> io::Result<Option<ExitStatus>> {
+        let status = self.process.try_wait()?;
+        if let Some(status) = status {
+            self.stream.stream.events.report_exit(status);
+        }
+
+        Ok(status)
+    }
+
+    /// Waits until a process exits, but not longer than a given timeout.
+    ///
+    /// It returns [None] if the process is still running.
+    pub async fn wait_timeout(&mut self, timeout: Duration) -> Result<Option<ExitStatus>, Error> {
+        let start = std::time::Instant::now();
+        loop {
+            if let Some(status) = self.check_exit()? {
+                return Ok(Some(status));
+            }
+
+            let left = timeout.saturating_sub(start.elapsed());
+            if left.is_zero() {
+                return Ok(None);
+            }
+
+            self.process.wait_exit_async(Some(left)).await?;
+        }
+    }
+
+    /// Shutdown asks a process to terminate and waits until it exits.
+    ///
+    /// If the process is still alive after a given timeout it's killed.
+    ///
+    /// It returns an exit status of the process.
+    /// If the process has already exited it's not signaled.
+    pub async fn shutdown(&mut self, timeout: Duration) -> Result<ExitStatus, Error> {
+        if let Some(status) = self.check_exit()? {
+            return Ok(status);
+        }
+
+        self.process.terminate(false)?;
+        if let Some(status) = self.wait_timeout(timeout).await? {
+            return Ok(status);
+        }
+
+        self.process.terminate(true)?;
+
+        loop {
+            if let Some(status) = self.check_exit()? {
+                return Ok(status);
+            }
+
+            self.process.wait_exit_async(None).await?;
+        }
+    }
+}
+
 impl<P, S: AsyncRead + Unpin> Session<P, S> {
     /// Expect waits until a pattern is matched.
     ///
@@ -133,10 +497,319 @@ impl<P, S: AsyncRead + Unpin> Session<P, S> {
     /// It returns an error if timeout is reached.
     /// You can specify a timeout value by [Session::set_expect_timeout] method.
     pub async fn expect<N: Needle>(&mut self, needle: N) -> Result<Captures, Error> {
-        match self.stream.expect_lazy {
+        let result = self.expect_captures(&needle).await;
+        self.named(result)
+    }
+
+    /// Expects a needle and accounts the result, see [Session::expect].
+    async fn expect_captures<N: Needle>(&mut self, needle: &N) -> Result<Captures, Error> {
+        let start = std::time::Instant::now();
+
+        let result = match self.stream.expect_lazy {
             true => self.stream.expect_lazy(needle).await,
             false => self.stream.expect_gready(needle).await,
         }
+        .map(|captures| captures.with_elapsed(start.elapsed()));
+
+        self.on_expect(needle, &result, start.elapsed());
+
+        result
+    }
+
+    /// Expect waits until a pattern is matched, just like [Session::expect],
+    /// but the returned [CapturesRef] borrows bytes from the session buffer instead of copying them.
+    ///
+    /// It's handy when a match is big, e.g. a content of a large file.
+    /// The bytes are consumed once [CapturesRef] is dropped.
+    ///
+    /// # Example
+    ///
+    #[cfg_attr(windows, doc = "```no_run")]
+    #[cfg_attr(unix, doc = "```")]
+    /// # futures_lite::future::block_on(async {
+    /// let mut p = expectrl::spawn("echo 123").unwrap();
+    /// let m = p.expect_ref(expectrl::Regex("\\d+")).await.unwrap();
+    /// assert_eq!(m.get(0).unwrap(), b"123");
+    /// # });
+    /// ```
+    pub async fn expect_ref<N: Needle>(&mut self, needle: N) -> Result<CapturesRef<'_>, Error> {
+        let start = std::time::Instant::now();
+
+        let result = match self.stream.expect_lazy {
+            true => self.stream.find_lazy(&needle).await,
+            false => self.stream.find_gready(&needle).await,
+        };
+
+        self.on_expect(&needle, &result, start.elapsed());
+
+        let found = self.named(result)?;
+        Ok(found
+            .into_captures_ref(&mut self.stream.stream)
+            .with_elapsed(start.elapsed()))
+    }
+
+    /// Accounts a result of an expect call.
+    fn on_expect<N: Needle, T>(
+        &mut self,
+        needle: &N,
+        result: &Result<T, Error>,
+        elapsed: Duration,
+    ) {
+        self.stream.stream.stats.add_expect(result, elapsed);
+
+        if let Err(Error::ExpectTimeout) = result {
+            let text = format!("waiting for {}", needle.description());
+            self.stream.stream.logger.log_event("timeout", &text);
+        }
+    }
+
+    /// Expect waits until a pattern is matched, just like [Session::expect],
+    /// but an error is labeled with a given name of the step.
+    ///
+    /// It helps to find out which step of a long dialogue failed.
+    /// An underlying error can be obtained by [Error::root].
+    ///
+    /// # Example
+    ///
+    /// ```no_run,ignore
+    /// let mut p = expectrl::spawn("login").unwrap();
+    /// p.expect_ctx("waiting for login prompt", "login:").unwrap();
+    /// ```
+    pub async fn expect_ctx<L, N>(&mut self, label: L, needle: N) -> Result<Captures, Error>
+    where
+        L: Into<String>,
+        N: Needle,
+    {
+        let result = self
+            .expect_captures(&needle)
+            .await
+            .map_err(|err| err.context(label));
+        self.named(result)
+    }
+
+    /// Expect idle waits until no new output arrives for a given period of time.
+    ///
+    /// It's useful to wait until a TUI application finishes redrawing.
+    /// The output is not consumed, so it can be inspected afterwards, e.g. by [Session::buffer].
+    ///
+    /// It returns once EOF is reached as no more output can arrive.
+    ///
+    /// It returns an error if timeout is reached.
+    /// You can specify a timeout value by [Session::set_expect_timeout] method.
+    pub async fn expect_idle(&mut self, idle: Duration) -> Result<(), Error> {
+        self.stream.expect_idle(idle).await
+    }
+
+    /// Expect timeline waits until all needles registered in a [Timeline] are matched.
+    ///
+    /// Needles may be matched in any order,
+    /// the time of each match is recorded in the timeline.
+    ///
+    /// On success all bytes up to the right most match are consumed.
+    ///
+    /// It returns an error if timeout is reached.
+    /// You can specify a timeout value by [Session::set_expect_timeout] method.
+    pub async fn expect_timeline(&mut self, timeline: &mut Timeline) -> Result<Captures, Error> {
+        self.stream.expect_timeline(timeline).await
+    }
+
+    /// Expect line waits until a complete line is available and returns it.
+    ///
+    /// A line ending (`\n` or `\r\n`) is not included in the returned line.
+    /// If EOF is reached the rest of the output is considered to be the last line.
+    ///
+    /// # Example
+    ///
+    #[cfg_attr(windows, doc = "```no_run")]
+    #[cfg_attr(unix, doc = "```")]
+    /// # futures_lite::future::block_on(async {
+    /// let mut p = expectrl::spawn("echo 123").unwrap();
+    /// assert_eq!(p.expect_line().await.unwrap(), "123");
+    /// # });
+    /// ```
+    ///
+    /// It returns an error if timeout is reached.
+    /// You can specify a timeout value by [Session::set_expect_timeout] method.
+    pub async fn expect_line(&mut self) -> Result<String, Error> {
+        let captures = self.expect(Line).await?;
+        Ok(Line::text(&captures))
+    }
+
+    /// Returns a stream of lines of the output.
+    ///
+    /// Each line is read via [Session::expect_line] so the timeout is applied per line.
+    /// The stream ends when EOF is reached.
+    ///
+    /// # Example
+    ///
+    #[cfg_attr(windows, doc = "```no_run")]
+    #[cfg_attr(unix, doc = "```")]
+    /// use futures_lite::StreamExt;
+    ///
+    /// # futures_lite::future::block_on(async {
+    /// let mut p = expectrl::spawn("printf '1\\n2\\n3'").unwrap();
+    /// let lines = p.lines().try_collect::<_, _, Vec<_>>().await.unwrap();
+    /// assert_eq!(lines, ["1", "2", "3"]);
+    /// # });
+    /// ```
+    pub fn lines(&mut self) -> impl futures_lite::Stream<Item = Result<String, Error>> + '_ {
+        futures_lite::stream::unfold(self, |session| async move {
+            match session.expect_line().await {
+                Ok(line) => Some((Ok(line), session)),
+                Err(err) if matches!(err.root(), Error::Eof) => None,
+                Err(err) => Some((Err(err), session)),
+            }
+        })
+    }
+
+    /// Returns a stream of output chunks.
+    ///
+    /// Each chunk is what was read from a process at once,
+    /// the first one also includes bytes which were already buffered.
+    /// Chunks are consumed as they're yielded,
+    /// so the output can be forwarded elsewhere, e.g. to a channel or a websocket.
+    ///
+    /// No timeout is applied.
+    /// The stream ends when EOF is reached.
+    ///
+    /// # Example
+    ///
+    #[cfg_attr(windows, doc = "```no_run")]
+    #[cfg_attr(unix, doc = "```")]
+    /// use futures_lite::StreamExt;
+    ///
+    /// # futures_lite::future::block_on(async {
+    /// let mut p = expectrl::spawn("echo Hello World").unwrap();
+    /// let chunks = p.chunks().try_collect::<_, _, Vec<_>>().await.unwrap();
+    /// assert_eq!(chunks.concat(), b"Hello World\r\n");
+    /// # });
+    /// ```
+    pub fn chunks(&mut self) -> impl futures_lite::Stream<Item = io::Result<Vec<u8>>> + '_ {
+        futures_lite::stream::unfold(self, |session| async move {
+            let chunk = session.read_chunk().await?;
+            Some((chunk, session))
+        })
+    }
+
+    /// Turns a session into a stream of output chunks.
+    ///
+    /// See [Session::chunks].
+    pub fn into_chunk_stream(self) -> impl futures_lite::Stream<Item = io::Result<Vec<u8>>> {
+        futures_lite::stream::unfold(self, |mut session| async move {
+            let chunk = session.read_chunk().await?;
+            Some((chunk, session))
+        })
+    }
+
+    /// Reads a chunk of the output.
+    ///
+    /// It returns [None] if EOF is reached.
+    async fn read_chunk(&mut self) -> Option<io::Result<Vec<u8>>> {
+        if self.stream.stream.buffer().is_empty() {
+            match self.stream.stream.fill().await {
+                Ok(0) => return None,
+                Ok(_) => {}
+                Err(err) => return Some(Err(err)),
+            }
+        }
+
+        Some(Ok(self.take_buffer()))
+    }
+
+    /// Returns a stream of successive matches of a needle.
+    ///
+    /// Each match is looked up via [Session::expect] so the timeout is applied per match.
+    ///
+    /// Notice that by default a needle may report a few matches found in the output read at once,
+    /// e.g. [Regex](crate::Regex) does, and all of them end up in a single [Captures].
+    /// Use [Session::set_expect_lazy] to get them one by one.
+    /// The stream ends when EOF is reached.
+    ///
+    /// # Example
+    ///
+    #[cfg_attr(windows, doc = "```no_run")]
+    #[cfg_attr(unix, doc = "```")]
+    /// use expectrl::Regex;
+    /// use futures_lite::StreamExt;
+    ///
+    /// # futures_lite::future::block_on(async {
+    /// let mut p = expectrl::spawn("echo 'ok: 1 ok: 2 failed: 3 ok: 4'").unwrap();
+    /// p.set_expect_lazy(true);
+    ///
+    /// let found = p
+    ///     .matches(Regex("ok: \\d"))
+    ///     .map(|m| m.map(|m| m.get(0).unwrap().to_vec()))
+    ///     .try_collect::<_, _, Vec<_>>()
+    ///     .await
+    ///     .unwrap();
+    /// assert_eq!(found, [b"ok: 1", b"ok: 2", b"ok: 4"]);
+    /// # });
+    /// ```
+    pub fn matches<'a, N: Needle + 'a>(
+        &'a mut self,
+        needle: N,
+    ) -> impl futures_lite::Stream<Item = Result<Captures, Error>> + 'a {
+        futures_lite::stream::unfold((self, needle), |(session, needle)| async move {
+            match session.expect(&needle).await {
+                Ok(captures) => Some((Ok(captures), (session, needle))),
+                Err(err) if matches!(err.root(), Error::Eof) => None,
+                Err(err) => Some((Err(err), (session, needle))),
+            }
+        })
+    }
+
+    /// Reads until a delimiter is found.
+    ///
+    /// The returned bytes include the delimiter.
+    /// If EOF is reached the rest of the output is returned.
+    ///
+    /// If a timeout is not set the one from [Session::set_expect_timeout] is used.
+    /// On timeout nothing is consumed.
+    pub async fn read_until_timeout(
+        &mut self,
+        delim: u8,
+        timeout: Option<Duration>,
+    ) -> Result<Vec<u8>, Error> {
+        self.stream.read_until_timeout(delim, timeout).await
+    }
+
+    /// Reads exactly enough bytes to fill a buffer.
+    ///
+    /// If a timeout is not set the one from [Session::set_expect_timeout] is used.
+    /// On timeout or EOF nothing is consumed.
+    pub async fn read_exact_timeout(
+        &mut self,
+        buf: &mut [u8],
+        timeout: Option<Duration>,
+    ) -> Result<(), Error> {
+        self.stream.read_exact_timeout(buf, timeout).await
+    }
+
+    /// Poll expect advances an expect by reading all bytes which are ready.
+    ///
+    /// It returns [Poll::Pending] if there's no match yet and nothing more can be read,
+    /// in which case a waker from a given context is registered by an underlying stream.
+    ///
+    /// It's meant for driving a session from a custom event loop or a hand written future.
+    /// A timeout is not applied, it's up to the caller.
+    ///
+    /// It returns [Error::Eof] if nothing is matched and EOF is reached.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # futures_lite::future::block_on(async {
+    /// let mut p = expectrl::spawn("echo 123").unwrap();
+    /// let m = futures_lite::future::poll_fn(|cx| p.poll_expect(cx, &"123")).await.unwrap();
+    /// assert_eq!(m.get(0).unwrap(), b"123");
+    /// # });
+    /// ```
+    pub fn poll_expect<N: Needle>(
+        &mut self,
+        cx: &mut Context<'_>,
+        needle: &N,
+    ) -> Poll<Result<Captures, Error>> {
+        self.stream.poll_expect(cx, needle)
     }
 
     /// Check checks if a pattern is matched.
@@ -160,6 +833,33 @@ impl<P, S: AsyncRead + Unpin> Session<P, S> {
         self.stream.check(needle).await
     }
 
+    /// Check all looks up every match of a pattern in the bytes available at the moment.
+    ///
+    /// It's the same as calling [Session::check] until nothing is found,
+    /// but it reads only once.
+    /// All bytes up to the last match are consumed.
+    ///
+    /// Notice that a needle may report a few matches at once, e.g. [Regex](crate::Regex) does,
+    /// then all of them end up in a single [Captures].
+    ///
+    /// It returns [Error::Eof] if nothing is found and EOF is reached.
+    ///
+    /// # Example
+    ///
+    #[cfg_attr(any(target_os = "macos", windows), doc = "```no_run")]
+    #[cfg_attr(not(any(target_os = "macos", windows)), doc = "```")]
+    /// # futures_lite::future::block_on(async {
+    /// let mut p = expectrl::spawn("echo 'id=1 id=2 id=3'").unwrap();
+    /// // wait to guarantee that check will successed (most likely)
+    /// std::thread::sleep(std::time::Duration::from_secs(1));
+    /// let found = p.check_all("id=").await.unwrap();
+    /// assert_eq!(found.len(), 3);
+    /// # });
+    /// ```
+    pub async fn check_all<E: Needle>(&mut self, needle: E) -> Result<Vec<Captures>, Error> {
+        self.stream.check_all(needle).await
+    }
+
     /// Is matched checks if a pattern is matched.
     /// It doesn't consumes bytes from stream.
     pub async fn is_matched<E: Needle>(&mut self, needle: E) -> Result<bool, Error> {
@@ -170,6 +870,77 @@ impl<P, S: AsyncRead + Unpin> Session<P, S> {
     pub async fn is_empty(&mut self) -> io::Result<bool> {
         self.stream.is_empty().await
     }
+
+    /// Waits until an output arrives but not longer than a given limit and a short tick,
+    /// so needles which depend on time are checked again in time.
+    ///
+    /// It's used by [crate::expect!] between rounds of checks.
+    #[doc(hidden)]
+    pub async fn __wait_output(&mut self, limit: Option<Duration>) -> io::Result<()> {
+        let wait = limit.map_or(super::CHECK_INTERVAL, |limit| {
+            std::cmp::min(limit, super::CHECK_INTERVAL)
+        });
+
+        let fill = async { self.stream.stream.fill().await.map(|_| ()) };
+        let tick = async {
+            futures_timer::Delay::new(wait).await;
+            Ok(())
+        };
+
+        futures_lite::future::or(fill, tick).await
+    }
+
+    /// Returns bytes which were read but not yet matched by any `expect` call.
+    ///
+    /// It doesn't make any IO calls.
+    pub fn buffer(&self) -> &[u8] {
+        self.stream.stream.buffer()
+    }
+
+    /// Returns bytes which were read but not yet matched, without consuming them.
+    ///
+    /// It's the same as [Session::buffer].
+    pub fn peek_buffer(&self) -> &[u8] {
+        self.buffer()
+    }
+
+    /// Returns lines which were read but not yet matched, without consuming them.
+    ///
+    /// Line endings are stripped and the last line may be incomplete.
+    pub fn peek_lines(&self) -> Vec<String> {
+        Line::split(self.buffer())
+    }
+
+    /// Removes and returns all bytes which were read but not yet matched.
+    ///
+    /// It's useful to get a partial data after a timeout, e.g. of a [ByteRange](crate::ByteRange) needle.
+    pub fn take_buffer(&mut self) -> Vec<u8> {
+        let buf = self.stream.stream.buffer().to_vec();
+        self.stream.stream.consume(buf.len());
+        buf
+    }
+
+    /// Discards all bytes which were read but not yet matched.
+    pub fn clear_buffer(&mut self) {
+        let n = self.stream.stream.buffer().len();
+        self.stream.stream.consume(n);
+    }
+
+    /// Reads everything which is available at the moment, without blocking,
+    /// and returns the whole buffer.
+    ///
+    /// The bytes are not consumed, so they will be available for a following `expect` call.
+    pub async fn peek(&mut self) -> Result<&[u8], Error> {
+        loop {
+            match futures_lite::future::poll_once(self.stream.stream.fill()).await {
+                Some(Ok(0)) | None => break,
+                Some(Ok(_)) => continue,
+                Some(Err(err)) => return Err(err.into()),
+            }
+        }
+
+        Ok(self.stream.stream.buffer())
+    }
 }
 
 impl<Proc, S: AsyncWrite + Unpin> Session<Proc, S> {
@@ -191,34 +962,204 @@ impl<Proc, S: AsyncWrite + Unpin> Session<Proc, S> {
     /// # });
     /// ```
     pub async fn send<B: AsRef<[u8]>>(&mut self, buf: B) -> io::Result<()> {
-        self.stream.write_all(buf.as_ref()).await
+        match self.stream.send_delay {
+            Some(delay) => self.send_slow(buf, delay).await,
+            None => self.stream.write_all(buf.as_ref()).await,
+        }
+    }
+
+    /// Sends text to child's STDIN one character at a time,
+    /// each character is followed by a given delay.
+    ///
+    /// It's useful for serial consoles and bootloaders which drop input arrived in a single burst.
+    ///
+    /// See [Session::set_send_delay] to pace all writes made by [Session::send] and [Session::send_line].
+    pub async fn send_slow<B: AsRef<[u8]>>(&mut self, buf: B, delay: Duration) -> io::Result<()> {
+        for c in super::split_chars(buf.as_ref()) {
+            self.stream.write_all(c).await?;
+            self.stream.flush().await?;
+            futures_timer::Delay::new(delay).await;
+        }
+
+        Ok(())
+    }
+
+    /// Sends a list of pieces coalescing them into a single write.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mut proc = expectrl::spawn("cat").unwrap();
+    /// # futures_lite::future::block_on(async {
+    /// proc.send_all(&["ls", " -l", "\n"]).await.unwrap();
+    /// # });
+    /// ```
+    pub async fn send_all<B: AsRef<[u8]>>(&mut self, bufs: &[B]) -> io::Result<()> {
+        let buf = bufs
+            .iter()
+            .flat_map(|buf| buf.as_ref())
+            .copied()
+            .collect::<Vec<_>>();
+        self.send(buf).await
+    }
+
+    /// Send a line to child’s STDIN.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use expectrl::{spawn, ControlCode};
+    ///
+    /// let mut proc = spawn("cat").unwrap();
+    ///
+    /// # futures_lite::future::block_on(async {
+    /// proc.send_line("Hello");
+    /// proc.send_line(b"World");
+    /// proc.send_line(ControlCode::try_from("^C").unwrap());
+    /// # });
+    /// ```
+    pub async fn send_line<B: AsRef<[u8]>>(&mut self, buf: B) -> io::Result<()> {
+        self.send(buf.as_ref()).await?;
+        self.send(LINE_ENDING).await?;
+
+        if self.stream.skip_echo {
+            self.stream.echo.push_line(buf.as_ref());
+        }
+
+        Ok(())
+    }
+
+    /// Sends a secret, e.g. a password, followed by a line ending.
+    ///
+    /// Unlike [Session::send_line] the secret is never passed to a logger set by [Session::set_log],
+    /// nor recorded by a [`LogStream`] or an [`AsciicastStream`] the session is built on,
+    /// and it's not kept in memory to skip its echo, as passwords are usually not echoed.
+    /// It's written right from a given reference, so no copies of it are left behind.
+    ///
+    /// With the `secrecy` feature a `secrecy::SecretString` can be passed.
+    ///
+    /// [`LogStream`]: crate::stream::log::LogStream
+    /// [`AsciicastStream`]: crate::stream::asciicast::AsciicastStream
+    pub async fn send_secret<PW: Password + ?Sized>(&mut self, secret: &PW) -> io::Result<()> {
+        {
+            // the mark is set on each poll, so it's not left behind while the task is suspended
+            let mut send = std::pin::pin!(self.send(secret.expose()));
+            futures_lite::future::poll_fn(|cx| {
+                crate::secret::write_secret(|| std::future::Future::poll(send.as_mut(), cx))
+            })
+            .await?;
+        }
+
+        self.send(LINE_ENDING).await
+    }
+
+    /// Sends an interrupt character (`^C`) to the child,
+    /// which usually makes a terminal deliver `SIGINT` to a foreground process.
+    pub async fn send_intr(&mut self) -> io::Result<()> {
+        self.send(ControlCode::EndOfText).await
+    }
+
+    /// Sends an end of file character to the child.
+    ///
+    /// It's `^D` on unix and `^Z` on windows.
+    /// A child's `read` returns 0 bytes if it's sent at the beginning of a line.
+    pub async fn send_eof(&mut self) -> io::Result<()> {
+        #[cfg(windows)]
+        const EOF: ControlCode = ControlCode::Substitute;
+        #[cfg(not(windows))]
+        const EOF: ControlCode = ControlCode::EndOfTransmission;
+
+        self.send(EOF).await
     }
+}
 
-    /// Send a line to child’s STDIN.
+impl<P, S: AsyncRead + AsyncWrite + Unpin> Session<P, S> {
+    /// Expect waits until a pattern is matched,
+    /// answering prompts recognized by a [AnswerPolicy] in the meantime.
     ///
-    /// # Example
+    /// A prompt is consumed once it's answered.
+    /// The pattern itself has a priority over prompts.
     ///
-    /// ```
-    /// use expectrl::{spawn, ControlCode};
+    /// It returns an error if timeout is reached.
+    /// You can specify a timeout value by [Session::set_expect_timeout] method.
+    pub async fn expect_with_policy<N: Needle>(
+        &mut self,
+        needle: N,
+        policy: &mut AnswerPolicy,
+    ) -> Result<Captures, Error> {
+        let expect_timeout = self.stream.expect_timeout;
+
+        let expect_future = async {
+            let mut eof = false;
+            loop {
+                // an answer is sent by send_line so its echo is skipped too
+                self.stream.consume_echo().await?;
+
+                let data = self.stream.stream.buffer();
+
+                let found = Needle::check(&needle, data, eof)?;
+                if !found.is_empty() {
+                    let end_index = Captures::right_most_index(&found);
+                    let involved_bytes = data[..end_index].to_vec();
+                    self.stream.stream.consume(end_index);
+
+                    return Ok(Captures::new(involved_bytes, found));
+                }
+
+                if let Some((end_index, answer)) = policy.check(data, eof)? {
+                    let answer = answer.to_owned();
+                    self.stream.stream.consume(end_index);
+                    self.send_line(answer).await?;
+                    continue;
+                }
+
+                if eof {
+                    return Err(Error::Eof);
+                }
+
+                eof = self.stream.stream.fill().await? == 0;
+            }
+        };
+
+        if let Some(timeout) = expect_timeout {
+            let timeout_future = futures_timer::Delay::new(timeout);
+            futures_lite::future::or(expect_future, async {
+                timeout_future.await;
+                Err(Error::ExpectTimeout)
+            })
+            .await
+        } else {
+            expect_future.await
+        }
+    }
+
+    /// Expect waits until a pattern is matched,
+    /// repeating the attempt according to a [RetryPolicy] if it's timed out.
     ///
-    /// let mut proc = spawn("cat").unwrap();
+    /// Before each retry the policy's backoff delay is made and its probe is sent if it's set.
     ///
-    /// # futures_lite::future::block_on(async {
-    /// proc.send_line("Hello");
-    /// proc.send_line(b"World");
-    /// proc.send_line(ControlCode::try_from("^C").unwrap());
-    /// # });
-    /// ```
-    pub async fn send_line<B: AsRef<[u8]>>(&mut self, buf: B) -> io::Result<()> {
-        #[cfg(windows)]
-        const LINE_ENDING: &[u8] = b"\r\n";
-        #[cfg(not(windows))]
-        const LINE_ENDING: &[u8] = b"\n";
+    /// Each attempt has its own timeout which you can set by [Session::set_expect_timeout].
+    /// It returns an error of the last attempt if all of them failed.
+    pub async fn expect_with_retry<N: Needle>(
+        &mut self,
+        needle: N,
+        policy: &RetryPolicy,
+    ) -> Result<Captures, Error> {
+        let mut retries = policy.get_retries();
+        loop {
+            match self.expect(&needle).await {
+                Err(err) if matches!(err.root(), Error::ExpectTimeout) && retries > 0 => {
+                    retries -= 1
+                }
+                result => return result,
+            }
 
-        self.stream.write_all(buf.as_ref()).await?;
-        self.stream.write_all(LINE_ENDING).await?;
+            futures_timer::Delay::new(policy.get_backoff()).await;
 
-        Ok(())
+            if let Some(probe) = policy.get_probe() {
+                self.send(probe).await?;
+            }
+        }
     }
 }
 
@@ -289,6 +1230,10 @@ struct Stream<S> {
     stream: BufferedStream<S>,
     expect_timeout: Option<Duration>,
     expect_lazy: bool,
+    skip_echo: bool,
+    echo: Echo,
+    send_delay: Option<Duration>,
+    watchdog: Watchdog,
 }
 
 impl<S> Stream<S> {
@@ -296,8 +1241,12 @@ impl<S> Stream<S> {
     fn new(stream: S) -> Self {
         Self {
             stream: BufferedStream::new(stream),
-            expect_timeout: Some(Duration::from_millis(10000)),
+            expect_timeout: super::default_expect_timeout(),
             expect_lazy: false,
+            skip_echo: false,
+            echo: Echo::default(),
+            send_delay: None,
+            watchdog: Watchdog::default(),
         }
     }
 
@@ -334,10 +1283,96 @@ impl<S> Stream<S> {
 }
 
 impl<S: AsyncRead + Unpin> Stream<S> {
+    /// Consumes an echo of sent lines if the output starts with it.
+    ///
+    /// It waits for the rest of the echo, a timeout is up to the caller.
+    async fn consume_echo(&mut self) -> Result<(), Error> {
+        let mut eof = false;
+        while !self.skip_available_echo(eof) {
+            eof = self.stream.fill().await? == 0;
+        }
+
+        Ok(())
+    }
+
+    /// Consumes an echo of sent lines if the available output starts with it.
+    ///
+    /// It returns `false` if only a part of the echo is available yet.
+    fn skip_available_echo(&mut self, eof: bool) -> bool {
+        if self.echo.is_empty() {
+            return true;
+        }
+
+        match self.echo.check(self.stream.buffer()) {
+            EchoMatch::Complete(n) => {
+                self.stream.consume(n);
+                true
+            }
+            EchoMatch::Mismatch => true,
+            EchoMatch::Partial if eof => {
+                self.echo.clear();
+                true
+            }
+            EchoMatch::Partial => false,
+        }
+    }
+
     async fn expect_gready<N: Needle>(&mut self, needle: N) -> Result<Captures, Error> {
+        let found = self.find_gready(needle).await?;
+        Ok(self.take_found(found))
+    }
+
+    async fn expect_lazy<N: Needle>(&mut self, needle: N) -> Result<Captures, Error> {
+        let found = self.find_lazy(needle).await?;
+        Ok(self.take_found(found))
+    }
+
+    /// Copies bytes involved in a match and consumes them.
+    fn take_found(&mut self, found: Found) -> Captures {
+        let end = found.end;
+        let captures = found.into_captures(self.stream.buffer());
+        self.stream.consume(end);
+        captures
+    }
+
+    fn poll_expect<N: Needle>(
+        &mut self,
+        cx: &mut Context<'_>,
+        needle: &N,
+    ) -> Poll<Result<Captures, Error>> {
+        let mut eof = false;
+        loop {
+            if !self.skip_available_echo(eof) {
+                eof = ready!(self.stream.poll_fill(cx))? == 0;
+                continue;
+            }
+
+            let data = self.stream.buffer();
+
+            let found = needle.check(data, eof)?;
+            if !found.is_empty() {
+                let found = Found::new(found, data.len());
+                return Poll::Ready(Ok(self.take_found(found)));
+            }
+
+            if eof {
+                return Poll::Ready(Err(Error::Eof));
+            }
+
+            eof = ready!(self.stream.poll_fill(cx))? == 0;
+        }
+    }
+
+    /// Looks for a match filling as much as possible to the buffer.
+    ///
+    /// The match is not consumed.
+    async fn find_gready<N: Needle>(&mut self, needle: N) -> Result<Found, Error> {
         let expect_timeout = self.expect_timeout;
 
         let expect_future = async {
+            self.consume_echo().await?;
+
+            let mut watch = Watch::new();
             let mut eof = false;
             loop {
                 let data = self.stream.buffer();
@@ -345,18 +1380,16 @@ impl<S: AsyncRead + Unpin> Stream<S> {
                 let found = Needle::check(&needle, data, eof)?;
 
                 if !found.is_empty() {
-                    let end_index = Captures::right_most_index(&found);
-                    let involved_bytes = data[..end_index].to_vec();
-                    self.stream.consume(end_index);
-
-                    return Ok(Captures::new(involved_bytes, found));
+                    return Ok(Found::new(found, data.len()));
                 }
 
                 if eof {
                     return Err(Error::Eof);
                 }
 
-                eof = self.stream.fill().await? == 0;
+                if let Some(n) = self.fill_watched(&mut watch, &needle).await? {
+                    eof = n == 0;
+                }
             }
         };
 
@@ -372,7 +1405,10 @@ impl<S: AsyncRead + Unpin> Stream<S> {
         }
     }
 
-    async fn expect_lazy<N: Needle>(&mut self, needle: N) -> Result<Captures, Error> {
+    /// Looks for a match reading byte by byte.
+    ///
+    /// The match is not consumed.
+    async fn find_lazy<N: Needle>(&mut self, needle: N) -> Result<Found, Error> {
         let expect_timeout = self.expect_timeout;
         let expect_future = async {
             // We read by byte to make things as lazy as possible.
@@ -389,14 +1425,18 @@ impl<S: AsyncRead + Unpin> Stream<S> {
             // but in such case we would need to keep a EOF indicator internally in stream,
             // which is OK if EOF happens onces, but I am not sure if this is a case.
 
+            self.consume_echo().await?;
+
             let mut checked_length = 0;
             let mut eof = false;
+            let mut watch = Watch::new();
             loop {
                 let available = self.stream.buffer();
                 let is_buffer_checked = checked_length == available.len();
                 if is_buffer_checked {
-                    let n = self.stream.fill().await?;
-                    eof = n == 0;
+                    if let Some(n) = self.fill_watched(&mut watch, &needle).await? {
+                        eof = n == 0;
+                    }
                 }
 
                 // We intentinally not increase the counter
@@ -410,15 +1450,110 @@ impl<S: AsyncRead + Unpin> Stream<S> {
                 let data = &available[..checked_length];
                 let found = Needle::check(&needle, data, eof)?;
                 if !found.is_empty() {
+                    return Ok(Found::new(found, checked_length));
+                }
+
+                if eof {
+                    return Err(Error::Eof);
+                }
+            }
+        };
+
+        if let Some(timeout) = expect_timeout {
+            let timeout_future = futures_timer::Delay::new(timeout);
+            futures_lite::future::or(expect_future, async {
+                timeout_future.await;
+                Err(Error::ExpectTimeout)
+            })
+            .await
+        } else {
+            expect_future.await
+        }
+    }
+
+    /// Fills the buffer, meanwhile checking a watchdog.
+    ///
+    /// It returns `None` if nothing arrived for a while, so a needle can be checked again,
+    /// see [super::CHECK_INTERVAL].
+    async fn fill_watched<N: Needle>(
+        &mut self,
+        watch: &mut Watch,
+        needle: &N,
+    ) -> io::Result<Option<usize>> {
+        let buffered = self.stream.buffer().len();
+        self.watchdog
+            .check(watch, needle, buffered, &mut self.stream.logger);
+
+        let wait = match self.watchdog.time_left(watch) {
+            Some(left) => std::cmp::min(left, super::CHECK_INTERVAL),
+            None => super::CHECK_INTERVAL,
+        };
+
+        let fill = async { Some(self.stream.fill().await) };
+        let tick = async {
+            futures_timer::Delay::new(wait).await;
+            None
+        };
+
+        futures_lite::future::or(fill, tick).await.transpose()
+    }
+
+    async fn expect_idle(&mut self, idle: Duration) -> Result<(), Error> {
+        let expect_timeout = self.expect_timeout;
+
+        let idle_future = async {
+            loop {
+                let fill = async { Some(self.stream.fill().await) };
+                let wait = async {
+                    futures_timer::Delay::new(idle).await;
+                    None
+                };
+
+                match futures_lite::future::or(fill, wait).await {
+                    Some(Ok(0)) | None => return Ok(()),
+                    Some(Ok(_)) => continue,
+                    Some(Err(err)) => return Err(err.into()),
+                }
+            }
+        };
+
+        if let Some(timeout) = expect_timeout {
+            let timeout_future = futures_timer::Delay::new(timeout);
+            futures_lite::future::or(idle_future, async {
+                timeout_future.await;
+                Err(Error::ExpectTimeout)
+            })
+            .await
+        } else {
+            idle_future.await
+        }
+    }
+
+    async fn expect_timeline(&mut self, timeline: &mut Timeline) -> Result<Captures, Error> {
+        let expect_timeout = self.expect_timeout;
+
+        let expect_future = async {
+            self.consume_echo().await?;
+
+            let mut eof = false;
+            loop {
+                let data = self.stream.buffer();
+
+                timeline.check(data, eof)?;
+                if timeline.is_complete() {
+                    let found = timeline.matches();
                     let end_index = Captures::right_most_index(&found);
                     let involved_bytes = data[..end_index].to_vec();
                     self.stream.consume(end_index);
+
                     return Ok(Captures::new(involved_bytes, found));
                 }
 
                 if eof {
                     return Err(Error::Eof);
                 }
+
+                eof = self.stream.fill().await? == 0;
             }
         };
 
@@ -434,6 +1569,91 @@ impl<S: AsyncRead + Unpin> Stream<S> {
         }
     }
 
+    async fn read_until_timeout(
+        &mut self,
+        delim: u8,
+        timeout: Option<Duration>,
+    ) -> Result<Vec<u8>, Error> {
+        let timeout = timeout.or(self.expect_timeout);
+
+        let read_future = async {
+            self.consume_echo().await?;
+
+            let mut eof = false;
+            loop {
+                let data = self.stream.buffer();
+
+                let end = match data.iter().position(|&b| b == delim) {
+                    Some(pos) => Some(pos + 1),
+                    None if eof && !data.is_empty() => Some(data.len()),
+                    None => None,
+                };
+
+                if let Some(end) = end {
+                    let buf = data[..end].to_vec();
+                    self.stream.consume(end);
+                    return Ok(buf);
+                }
+
+                if eof {
+                    return Err(Error::Eof);
+                }
+
+                eof = self.stream.fill().await? == 0;
+            }
+        };
+
+        if let Some(timeout) = timeout {
+            let timeout_future = futures_timer::Delay::new(timeout);
+            futures_lite::future::or(read_future, async {
+                timeout_future.await;
+                Err(Error::ExpectTimeout)
+            })
+            .await
+        } else {
+            read_future.await
+        }
+    }
+
+    async fn read_exact_timeout(
+        &mut self,
+        buf: &mut [u8],
+        timeout: Option<Duration>,
+    ) -> Result<(), Error> {
+        let timeout = timeout.or(self.expect_timeout);
+
+        let read_future = async {
+            self.consume_echo().await?;
+
+            let mut eof = false;
+            loop {
+                let data = self.stream.buffer();
+                if data.len() >= buf.len() {
+                    buf.copy_from_slice(&data[..buf.len()]);
+                    self.stream.consume(buf.len());
+                    return Ok(());
+                }
+
+                if eof {
+                    return Err(Error::Eof);
+                }
+
+                eof = self.stream.fill().await? == 0;
+            }
+        };
+
+        if let Some(timeout) = timeout {
+            let timeout_future = futures_timer::Delay::new(timeout);
+            futures_lite::future::or(read_future, async {
+                timeout_future.await;
+                Err(Error::ExpectTimeout)
+            })
+            .await
+        } else {
+            read_future.await
+        }
+    }
+
     /// Is matched checks if a pattern is matched.
     /// It doesn't consumes bytes from stream.
     async fn is_matched<E: Needle>(&mut self, needle: E) -> Result<bool, Error> {
@@ -456,6 +1676,9 @@ impl<S: AsyncRead + Unpin> Stream<S> {
     /// Returns empty found structure if nothing found.
     async fn check<E: Needle>(&mut self, needle: E) -> Result<Captures, Error> {
         let eof = self.try_fill().await?;
+        if !self.skip_available_echo(eof) {
+            return Ok(Captures::new(Vec::new(), Vec::new()));
+        }
 
         let buf = self.stream.buffer();
         let found = needle.check(buf, eof)?;
@@ -473,6 +1696,23 @@ impl<S: AsyncRead + Unpin> Stream<S> {
         Ok(Captures::new(Vec::new(), Vec::new()))
     }
 
+    async fn check_all<E: Needle>(&mut self, needle: E) -> Result<Vec<Captures>, Error> {
+        let eof = self.try_fill().await?;
+        if !self.skip_available_echo(eof) {
+            return Ok(Vec::new());
+        }
+
+        let buf = self.stream.buffer();
+        let (found, consumed) = super::find_all(&needle, buf, eof)?;
+        self.stream.consume(consumed);
+
+        if found.is_empty() && eof {
+            return Err(Error::Eof);
+        }
+
+        Ok(found)
+    }
+
     /// Verifyes if stream is empty or not.
     async fn is_empty(&mut self) -> io::Result<bool> {
         match futures_lite::future::poll_once(self.read(&mut [])).await {
@@ -498,7 +1738,9 @@ impl<S: AsyncWrite + Unpin> AsyncWrite for Stream<S> {
         cx: &mut Context<'_>,
         buf: &[u8],
     ) -> Poll<io::Result<usize>> {
-        Pin::new(&mut *self.stream.get_mut()).poll_write(cx, buf)
+        let n = ready!(Pin::new(&mut *self.stream.get_mut()).poll_write(cx, buf))?;
+        self.stream.on_write(&buf[..n]);
+        Poll::Ready(Ok(n))
     }
 
     fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
@@ -514,7 +1756,9 @@ impl<S: AsyncWrite + Unpin> AsyncWrite for Stream<S> {
         cx: &mut Context<'_>,
         bufs: &[io::IoSlice<'_>],
     ) -> Poll<io::Result<usize>> {
-        Pin::new(&mut *self.stream.get_mut()).poll_write_vectored(cx, bufs)
+        let n = ready!(Pin::new(&mut *self.stream.get_mut()).poll_write_vectored(cx, bufs))?;
+        self.stream.on_write(&written_bytes(bufs, n));
+        Poll::Ready(Ok(n))
     }
 }
 
@@ -545,6 +1789,12 @@ struct BufferedStream<S> {
     stream: S,
     buffer: Vec<u8>,
     length: usize,
+    read_buffer: Vec<u8>,
+    normalize_newlines: bool,
+    stats: Stats,
+    logger: Logger,
+    tee: Tee,
+    events: Events,
 }
 
 impl<S> BufferedStream<S> {
@@ -553,12 +1803,50 @@ impl<S> BufferedStream<S> {
             stream,
             buffer: Vec::new(),
             length: 0,
+            read_buffer: vec![0; super::DEFAULT_READ_CHUNK_SIZE],
+            normalize_newlines: false,
+            stats: Stats::default(),
+            logger: Logger::default(),
+            tee: Tee::default(),
+            events: Events::default(),
         }
     }
 
+    /// Accounts bytes read from an underlying stream.
+    fn on_read(&mut self, buf: &[u8]) {
+        self.stats.add_read(buf.len());
+        self.logger.log_read(buf);
+        self.tee.write(buf);
+    }
+
+    /// Accounts bytes written to an underlying stream.
+    fn on_write(&mut self, buf: &[u8]) {
+        self.stats.add_written(buf.len());
+        self.logger.log_write(buf);
+    }
+
     fn keep(&mut self, buf: &[u8]) {
-        self.buffer.extend(buf);
-        self.length += buf.len();
+        let start = self.buffer.len();
+        self.buffer.extend_from_slice(buf);
+
+        if self.normalize_newlines {
+            super::normalize_newlines(&mut self.buffer, start);
+        }
+
+        self.length = self.buffer.len();
+    }
+
+    fn set_read_chunk_size(&mut self, size: usize) {
+        self.read_buffer = vec![0; std::cmp::max(size, 1)];
+    }
+
+    fn set_normalize_newlines(&mut self, on: bool) {
+        self.normalize_newlines = on;
+
+        if on {
+            super::normalize_newlines(&mut self.buffer, 0);
+            self.length = self.buffer.len();
+        }
     }
 
     fn buffer(&self) -> &[u8] {
@@ -570,12 +1858,38 @@ impl<S> BufferedStream<S> {
     }
 }
 
+impl<S> crate::captures::Buffer for BufferedStream<S> {
+    fn bytes(&self) -> &[u8] {
+        self.buffer()
+    }
+
+    fn consume(&mut self, n: usize) {
+        let _ = self.buffer.drain(..n);
+        self.length -= n;
+    }
+}
+
 impl<S: AsyncRead + Unpin> BufferedStream<S> {
     async fn fill(&mut self) -> io::Result<usize> {
-        let mut buf = [0; 128];
-        let n = self.stream.read(&mut buf).await?;
-        self.keep(&buf[..n]);
-        Ok(n)
+        futures_lite::future::poll_fn(|cx| self.poll_fill(cx)).await
+    }
+
+    /// Reads a chunk from an underlying stream to the buffer.
+    fn poll_fill(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<usize>> {
+        // the buffer is taken out only to satisfy the borrow checker
+        let mut buf = std::mem::take(&mut self.read_buffer);
+        let result = Pin::new(&mut self.stream).poll_read(cx, &mut buf);
+        if let Poll::Ready(Ok(n)) = result {
+            self.on_read(&buf[..n]);
+            self.keep(&buf[..n]);
+
+            if n == 0 {
+                self.events.report_eof();
+            }
+        }
+        self.read_buffer = buf;
+
+        result
     }
 }
 
@@ -606,9 +1920,7 @@ impl<S: AsyncRead + Unpin> AsyncRead for BufferedStream<S> {
 impl<S: AsyncRead + Unpin> AsyncBufRead for BufferedStream<S> {
     fn poll_fill_buf(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<&[u8]>> {
         if self.buffer.is_empty() {
-            let mut buf = [0; 128];
-            let n = ready!(Pin::new(&mut self.stream).poll_read(cx, &mut buf))?;
-            self.keep(&buf[..n]);
+            let _ = ready!(self.poll_fill(cx))?;
         }
 
         let buf = self.get_mut().buffer();
@@ -733,6 +2045,24 @@ mod tests {
         });
     }
 
+    #[test]
+    fn test_expect_timeline() {
+        let buf = b"Hello World".to_vec();
+        let cursor = futures_lite::io::Cursor::new(buf);
+        let mut stream = Stream::new(cursor);
+
+        futures_lite::future::block_on(async {
+            let mut timeline = Timeline::new().on("world", "World").on("hello", "Hello");
+            let found = stream.expect_timeline(&mut timeline).await.unwrap();
+            assert_eq!(b"", found.before());
+            assert_eq!(
+                vec![b"World", b"Hello"],
+                found.matches().collect::<Vec<_>>()
+            );
+            assert!(timeline.is_complete());
+        });
+    }
+
     #[test]
     fn test_check() {
         let buf = b"Hello World".to_vec();
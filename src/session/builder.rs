@@ -0,0 +1,376 @@
+//! This module contains a [SessionBuilder] which allows to customize how a [Session] is spawned.
+
+use std::{process::Command, time::Duration};
+
+#[cfg(unix)]
+use std::{
+    ffi::{CString, OsString},
+    fs::{self, File, OpenOptions},
+    io,
+    os::unix::{
+        ffi::{OsStrExt, OsStringExt},
+        fs::OpenOptionsExt,
+        io::AsRawFd,
+        process::CommandExt,
+    },
+    path::PathBuf,
+};
+
+#[cfg(unix)]
+use nix::{
+    libc,
+    sys::stat::Mode,
+    unistd::{Gid, Uid},
+};
+
+use super::Session;
+use crate::Error;
+#[cfg(unix)]
+use crate::{process::unix::make_non_blocking, stream::pipe::PipeStream};
+
+/// SessionBuilder spawns a [Session] on a platform process
+/// with settings which can't be set by [Command] itself.
+///
+/// # Example
+///
+#[cfg_attr(unix, doc = "```no_run")]
+#[cfg_attr(not(unix), doc = "```ignore")]
+/// use std::process::Command;
+/// use expectrl::Session;
+///
+/// let mut builder = Session::builder(Command::new("cat"));
+/// unsafe {
+///     builder = builder.pre_exec(|| Ok(()));
+/// }
+///
+/// let session = builder.spawn().unwrap();
+/// ```
+#[derive(Debug)]
+pub struct SessionBuilder {
+    command: Command,
+    send_delay: Option<Duration>,
+    expect_timeout: Option<Option<Duration>>,
+    #[cfg(unix)]
+    separate_stderr: bool,
+    #[cfg(unix)]
+    uid: Option<u32>,
+    #[cfg(unix)]
+    gid: Option<u32>,
+    #[cfg(windows)]
+    console_size: Option<(u16, u16)>,
+}
+
+impl SessionBuilder {
+    /// Creates a new builder for a given command.
+    pub fn new(command: Command) -> Self {
+        Self {
+            command,
+            send_delay: None,
+            expect_timeout: None,
+            #[cfg(unix)]
+            separate_stderr: false,
+            #[cfg(unix)]
+            uid: None,
+            #[cfg(unix)]
+            gid: None,
+            #[cfg(windows)]
+            console_size: None,
+        }
+    }
+
+    /// Returns a reference to an underlying command.
+    pub fn get_command(&self) -> &Command {
+        &self.command
+    }
+
+    /// Returns a mut reference to an underlying command.
+    pub fn get_command_mut(&mut self) -> &mut Command {
+        &mut self.command
+    }
+
+    /// Registers a closure which is called in a child process right before `exec`.
+    ///
+    /// It's called after a pseudo terminal is set as a controlling terminal of the child
+    /// and after all descriptors except stdin, stdout and stderr are closed.
+    /// So it can be used to place the child into a cgroup or to apply rlimits.
+    ///
+    /// If you need to pass an extra descriptor to the child,
+    /// use [`pty::Slave::spawn`] instead, which doesn't close inherited descriptors.
+    ///
+    /// Closures are called in the order they were registered.
+    ///
+    /// # Safety
+    ///
+    /// The same rules as for [`CommandExt::pre_exec`] apply.
+    /// In particular only async-signal-safe functions must be called in the closure.
+    ///
+    /// [`pty::Slave::spawn`]: crate::pty::Slave::spawn
+    #[cfg(unix)]
+    pub unsafe fn pre_exec<F>(mut self, f: F) -> Self
+    where
+        F: FnMut() -> io::Result<()> + Send + Sync + 'static,
+    {
+        let _ = self.command.pre_exec(f);
+        self
+    }
+
+    /// Sets a user id of the child process.
+    ///
+    /// It's applied in the child between `fork` and `exec`,
+    /// so a pseudo terminal is still set up with the privileges of the current process.
+    #[cfg(unix)]
+    pub fn uid(mut self, id: u32) -> Self {
+        let _ = self.command.uid(id);
+        self.uid = Some(id);
+        self
+    }
+
+    /// Sets a group id of the child process.
+    ///
+    /// See [SessionBuilder::uid].
+    #[cfg(unix)]
+    pub fn gid(mut self, id: u32) -> Self {
+        let _ = self.command.gid(id);
+        self.gid = Some(id);
+        self
+    }
+
+    /// Connects a stderr of the process to a pipe instead of the terminal.
+    ///
+    /// The stderr is available via [`Session::stderr`] as a separate session,
+    /// so it's possible to verify that a message goes to stderr and not to stdout.
+    ///
+    /// By default stderr is merged into the terminal.
+    #[cfg(unix)]
+    pub fn separate_stderr(mut self, on: bool) -> Self {
+        self.separate_stderr = on;
+        self
+    }
+
+    /// Sets a delay which is made after each sent character.
+    ///
+    /// See [Session::set_send_delay].
+    pub fn send_delay(mut self, delay: Duration) -> Self {
+        self.send_delay = Some(delay);
+        self
+    }
+
+    /// Sets an expect timeout of the session.
+    ///
+    /// It overrides a default timeout, including the one set by [`TIMEOUT_ENV`].
+    ///
+    /// See [Session::set_expect_timeout].
+    ///
+    /// [`TIMEOUT_ENV`]: super::TIMEOUT_ENV
+    pub fn expect_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.expect_timeout = Some(timeout);
+        self
+    }
+
+    /// Sets a size of a pseudo console the process is attached to.
+    ///
+    /// ConPTY doesn't take a size on spawn,
+    /// so the console is resized right after the process is created.
+    /// A process which reads a size on startup may still observe the default one.
+    ///
+    /// A console can be resized later via [`WinProcess::resize`].
+    ///
+    /// [`WinProcess::resize`]: crate::process::windows::WinProcess::resize
+    #[cfg(windows)]
+    pub fn console_size(mut self, rows: u16, cols: u16) -> Self {
+        self.console_size = Some((rows, cols));
+        self
+    }
+
+    /// Spawns a session.
+    pub fn spawn(mut self) -> Result<Session, Error> {
+        #[cfg(unix)]
+        let stderr = match self.separate_stderr {
+            true => Some(StderrFifo::new(&mut self.command, self.uid, self.gid)?),
+            false => None,
+        };
+
+        let session = Session::spawn(self.command);
+
+        #[cfg(unix)]
+        if let Some(stderr) = &stderr {
+            stderr.remove();
+        }
+
+        let mut session = session?;
+        session.set_send_delay(self.send_delay);
+
+        if let Some(timeout) = self.expect_timeout {
+            session.set_expect_timeout(timeout);
+        }
+
+        #[cfg(unix)]
+        if let Some(stderr) = stderr {
+            let stream = stderr.into_stream()?;
+
+            #[cfg(feature = "async")]
+            let stream = crate::process::IntoAsyncStream::into_async_stream(stream)?;
+
+            let stderr = Session::new((), stream)?;
+            session.set_stderr(stderr);
+        }
+
+        #[cfg(windows)]
+        if let Some((rows, cols)) = self.console_size {
+            session.get_process_mut().resize(rows, cols)?;
+        }
+
+        Ok(session)
+    }
+}
+
+/// A named pipe which a stderr of a child is redirected to.
+///
+/// A child closes all inherited descriptors except standard ones before `exec`,
+/// so a pipe can't be passed by a descriptor and the child opens it by a path instead.
+/// The pipe is created in a private directory, so no one else can open it.
+/// If the child runs under another user or group the directory is handed over to them,
+/// as the pipe is opened after the child has changed its ids.
+#[cfg(unix)]
+#[derive(Debug)]
+struct StderrFifo {
+    dir: PathBuf,
+    path: PathBuf,
+    reader: File,
+    // A write end is kept open till the child opens its own one,
+    // otherwise the reader could get EOF right away.
+    writer: File,
+}
+
+#[cfg(unix)]
+impl StderrFifo {
+    fn new(command: &mut Command, uid: Option<u32>, gid: Option<u32>) -> io::Result<Self> {
+        let dir = make_private_dir()?;
+        let path = dir.join("stderr");
+
+        match Self::open(dir.clone(), path, command, uid, gid) {
+            Ok(fifo) => Ok(fifo),
+            Err(err) => {
+                let _ = fs::remove_dir_all(&dir);
+                Err(err)
+            }
+        }
+    }
+
+    fn open(
+        dir: PathBuf,
+        path: PathBuf,
+        command: &mut Command,
+        uid: Option<u32>,
+        gid: Option<u32>,
+    ) -> io::Result<Self> {
+        nix::unistd::mkfifo(&path, Mode::S_IRUSR | Mode::S_IWUSR)?;
+
+        if uid.is_some() || gid.is_some() {
+            let uid = uid.map(Uid::from_raw);
+            let gid = gid.map(Gid::from_raw);
+            nix::unistd::chown(&dir, uid, gid)?;
+            nix::unistd::chown(&path, uid, gid)?;
+        }
+
+        // A reader is opened in a non-blocking mode,
+        // otherwise the call would wait for a writer.
+        let reader = OpenOptions::new()
+            .read(true)
+            .custom_flags(libc::O_NONBLOCK)
+            .open(&path)?;
+        let writer = OpenOptions::new()
+            .write(true)
+            .custom_flags(libc::O_NONBLOCK)
+            .open(&path)?;
+
+        let c_path = CString::new(path.as_os_str().as_bytes())?;
+
+        // SAFETY: only async-signal-safe functions are called in the closure.
+        unsafe {
+            let _ = command.pre_exec(move || {
+                let fd = libc::open(c_path.as_ptr(), libc::O_WRONLY);
+                if fd == -1 {
+                    return Err(io::Error::last_os_error());
+                }
+
+                if libc::dup2(fd, libc::STDERR_FILENO) == -1 {
+                    return Err(io::Error::last_os_error());
+                }
+
+                let _ = libc::close(fd);
+
+                Ok(())
+            });
+        }
+
+        Ok(Self {
+            dir,
+            path,
+            reader,
+            writer,
+        })
+    }
+
+    /// Removes the pipe from a file system.
+    ///
+    /// Already opened ends keep working.
+    fn remove(&self) {
+        let _ = fs::remove_file(&self.path);
+        let _ = fs::remove_dir(&self.dir);
+    }
+
+    /// Returns a read end of the pipe.
+    ///
+    /// It must be called once the child is spawned,
+    /// as the write end of the current process is closed.
+    fn into_stream(self) -> io::Result<PipeStream> {
+        drop(self.writer);
+        make_non_blocking(self.reader.as_raw_fd(), false)?;
+        Ok(PipeStream::read_only(self.reader))
+    }
+}
+
+/// Creates a temporary directory which only the current user has access to.
+#[cfg(unix)]
+fn make_private_dir() -> io::Result<PathBuf> {
+    let template = std::env::temp_dir().join("expectrl-XXXXXX");
+    let mut template = CString::new(template.into_os_string().into_vec())?.into_bytes_with_nul();
+
+    // SAFETY: the template is a valid nul terminated string which outlives the call.
+    let dir = unsafe { libc::mkdtemp(template.as_mut_ptr().cast()) };
+    if dir.is_null() {
+        return Err(io::Error::last_os_error());
+    }
+
+    let _ = template.pop();
+    Ok(PathBuf::from(OsString::from_vec(template)))
+}
+
+impl From<Command> for SessionBuilder {
+    fn from(command: Command) -> Self {
+        Self::new(command)
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::{io::Read, os::unix::fs::PermissionsExt};
+
+    #[test]
+    fn test_stderr_fifo() {
+        let mut command = Command::new("true");
+        let mut fifo = StderrFifo::new(&mut command, None, None).unwrap();
+
+        let mode = fs::metadata(&fifo.dir).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o700);
+
+        // there's a writer so there's no EOF till the child opens the pipe
+        let err = fifo.reader.read(&mut [0; 1]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::WouldBlock);
+
+        fifo.remove();
+        assert!(!fifo.dir.exists());
+    }
+}
@@ -0,0 +1,206 @@
+//! This module contains a [SessionEvent] which notifies about changes of a session state.
+
+use std::fmt;
+
+#[cfg(feature = "async")]
+use std::{
+    collections::VecDeque,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll, Waker},
+};
+
+use crate::process::ExitStatus;
+
+/// SessionEvent represents a change of a session state.
+///
+/// Each event is reported only once per session.
+///
+/// See [`Session::poll_event`] and [`Session::on_event`].
+///
+/// [`Session::poll_event`]: crate::Session::poll_event
+/// [`Session::on_event`]: crate::Session::on_event
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SessionEvent {
+    /// A process closed its side of the stream.
+    Eof,
+    /// A process exited with a given status.
+    Exited(ExitStatus),
+}
+
+type Callback = Box<dyn FnMut(&SessionEvent) + Send>;
+
+/// Events keeps a registered callback and remembers which events were already reported.
+#[derive(Default)]
+pub(crate) struct Events {
+    callback: Option<Callback>,
+    eof: bool,
+    exit_status: Option<ExitStatus>,
+    #[cfg(feature = "async")]
+    subscribers: Vec<Arc<Mutex<Queue>>>,
+}
+
+impl Events {
+    pub(crate) fn set_callback(&mut self, callback: Callback) {
+        self.callback = Some(callback);
+    }
+
+    pub(crate) fn is_eof_reported(&self) -> bool {
+        self.eof
+    }
+
+    pub(crate) fn is_exit_reported(&self) -> bool {
+        self.exit_status.is_some()
+    }
+
+    /// Marks an event as reported and calls a callback if any.
+    pub(crate) fn emit(&mut self, event: SessionEvent) -> SessionEvent {
+        match event {
+            SessionEvent::Eof => self.eof = true,
+            SessionEvent::Exited(status) => self.exit_status = Some(status),
+        }
+
+        if let Some(callback) = &mut self.callback {
+            callback(&event);
+        }
+
+        #[cfg(feature = "async")]
+        {
+            // a subscriber is gone once its stream is dropped
+            self.subscribers
+                .retain(|queue| Arc::strong_count(queue) > 1);
+            for queue in &self.subscribers {
+                queue.lock().unwrap().push(event);
+            }
+        }
+
+        event
+    }
+
+    /// Emits [SessionEvent::Eof] unless it was already reported.
+    pub(crate) fn report_eof(&mut self) {
+        if !self.eof {
+            let _ = self.emit(SessionEvent::Eof);
+        }
+    }
+
+    /// Emits [SessionEvent::Exited] unless it was already reported.
+    pub(crate) fn report_exit(&mut self, status: ExitStatus) {
+        if self.exit_status.is_none() {
+            let _ = self.emit(SessionEvent::Exited(status));
+        }
+    }
+
+    /// Creates a stream which gets all events,
+    /// including the ones which were already reported.
+    #[cfg(feature = "async")]
+    pub(crate) fn subscribe(&mut self) -> EventStream {
+        let mut queue = Queue::default();
+        if self.eof {
+            queue.push(SessionEvent::Eof);
+        }
+
+        if let Some(status) = self.exit_status {
+            queue.push(SessionEvent::Exited(status));
+        }
+
+        let queue = Arc::new(Mutex::new(queue));
+        self.subscribers.push(queue.clone());
+
+        EventStream {
+            queue,
+            eof: false,
+            exited: false,
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+impl Drop for Events {
+    fn drop(&mut self) {
+        for queue in &self.subscribers {
+            queue.lock().unwrap().close();
+        }
+    }
+}
+
+impl fmt::Debug for Events {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Events")
+            .field("callback", &self.callback.is_some())
+            .field("eof", &self.eof)
+            .field("exit_status", &self.exit_status)
+            .finish()
+    }
+}
+
+/// A stream of [SessionEvent]s.
+///
+/// It's created by [`Session::events`](crate::Session::events).
+///
+/// It ends once both EOF and an exit of the process are delivered,
+/// or when the session is dropped.
+#[cfg(feature = "async")]
+#[derive(Debug)]
+pub struct EventStream {
+    queue: Arc<Mutex<Queue>>,
+    eof: bool,
+    exited: bool,
+}
+
+#[cfg(feature = "async")]
+impl futures_lite::Stream for EventStream {
+    type Item = SessionEvent;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        if self.eof && self.exited {
+            return Poll::Ready(None);
+        }
+
+        let mut queue = self.queue.lock().unwrap();
+        match queue.events.pop_front() {
+            Some(event) => {
+                drop(queue);
+                match event {
+                    SessionEvent::Eof => self.eof = true,
+                    SessionEvent::Exited(_) => self.exited = true,
+                }
+
+                Poll::Ready(Some(event))
+            }
+            None if queue.is_closed => Poll::Ready(None),
+            None => {
+                queue.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+/// Queue keeps events which are not yet taken by an [EventStream].
+#[cfg(feature = "async")]
+#[derive(Debug, Default)]
+struct Queue {
+    events: VecDeque<SessionEvent>,
+    waker: Option<Waker>,
+    is_closed: bool,
+}
+
+#[cfg(feature = "async")]
+impl Queue {
+    fn push(&mut self, event: SessionEvent) {
+        self.events.push_back(event);
+        self.wake();
+    }
+
+    fn close(&mut self) {
+        self.is_closed = true;
+        self.wake();
+    }
+
+    fn wake(&mut self) {
+        if let Some(waker) = self.waker.take() {
+            waker.wake();
+        }
+    }
+}
@@ -17,12 +17,25 @@
 
 #[cfg(feature = "async")]
 mod async_session;
+mod builder;
+mod event;
 #[cfg(not(feature = "async"))]
 mod sync_session;
+mod watchdog;
 
-use std::{io::Write, process::Command};
+use std::{fs::File, io::Write, path::Path, process::Command, time::Duration};
 
-use crate::{interact::InteractSession, process::Process, stream::log::LogStream, Error};
+use crate::{
+    captures::{Buffer, CapturesRef},
+    interact::{Duplex, InteractSession},
+    needle::Match,
+    process::Process,
+    stream::{
+        asciicast::AsciicastStream,
+        log::{LogFormat, LogStream},
+    },
+    Captures, Error, Needle,
+};
 
 #[cfg(not(feature = "async"))]
 use std::io::Read;
@@ -30,6 +43,17 @@ use std::io::Read;
 #[cfg(feature = "async")]
 use crate::process::IntoAsyncStream;
 
+#[cfg(unix)]
+use crate::{
+    termios::{self, Termios},
+    Signal,
+};
+#[cfg(unix)]
+use std::{
+    os::unix::io::{AsFd, AsRawFd, BorrowedFd, RawFd},
+    path::PathBuf,
+};
+
 #[cfg(unix)]
 type OsProc = crate::process::unix::UnixProcess;
 #[cfg(windows)]
@@ -44,16 +68,38 @@ type OsProcStream = crate::process::windows::ProcessStream;
 #[cfg(all(windows, feature = "async"))]
 type OsProcStream = crate::process::windows::AsyncProcessStream;
 
+#[cfg(all(unix, not(feature = "async")))]
+type PipeStream = crate::stream::pipe::PipeStream;
+#[cfg(all(unix, feature = "async"))]
+type PipeStream = crate::stream::pipe::AsyncPipeStream;
+
 /// A type alias for OS process which can run a [`Session`] and a default one.
 pub type OsProcess = OsProc;
 /// A type alias for OS process stream which is a default one for [`Session`].
 pub type OsProcessStream = OsProcStream;
+/// A session over a stderr of a process spawned with [`SessionBuilder::separate_stderr`].
+///
+/// It's read only.
+#[cfg(unix)]
+pub type StderrSession = Session<(), PipeStream>;
+
+/// A session which process is run without a pseudo terminal.
+///
+/// See [`Session::spawn_piped`].
+#[cfg(unix)]
+pub type PipedSession = Session<crate::process::piped::PipedProcess, PipeStream>;
 
 #[cfg(feature = "async")]
 pub use async_session::Session;
 
 #[cfg(not(feature = "async"))]
-pub use sync_session::Session;
+pub use sync_session::{Lines, Matches, Session};
+
+pub use builder::SessionBuilder;
+#[cfg(feature = "async")]
+pub use event::EventStream;
+pub use event::SessionEvent;
+pub use watchdog::{Watchdog, WatchdogWarning};
 
 impl Session {
     /// Spawns a session on a platform process.
@@ -78,6 +124,46 @@ impl Session {
         Ok(session)
     }
 
+    /// Spawns a session on a process which is connected via plain pipes instead of a pseudo terminal.
+    ///
+    /// It allows to test how a program behaves without a terminal
+    /// via the same API, e.g. [`Session::expect`] and [`Session::send_line`].
+    ///
+    /// There's no echo and no line discipline,
+    /// so control codes are passed as they are and the output has `\n` line endings.
+    /// Stderr is merged with stdout.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use std::process::Command;
+    /// use expectrl::Session;
+    ///
+    /// let mut p = Session::spawn_piped(Command::new("cat")).unwrap();
+    /// # #[cfg(not(feature = "async"))]
+    /// # {
+    /// p.send_line("Hello World").unwrap();
+    /// p.expect("Hello World\n").unwrap();
+    /// # }
+    /// ```
+    #[cfg(unix)]
+    pub fn spawn_piped(command: Command) -> Result<PipedSession, Error> {
+        let mut process = crate::process::piped::PipedProcess::spawn_command(command)?;
+        let stream = process.open_stream()?;
+
+        #[cfg(feature = "async")]
+        let stream = stream.into_async_stream()?;
+
+        let session = Session::new(process, stream)?;
+
+        Ok(session)
+    }
+
+    /// Creates a [SessionBuilder] which allows to customize how a process is spawned.
+    pub fn builder(command: Command) -> SessionBuilder {
+        SessionBuilder::new(command)
+    }
+
     /// Spawns a session on a platform process.
     /// Using a string commandline.
     pub(crate) fn spawn_cmd(cmd: &str) -> Result<Self, Error> {
@@ -144,6 +230,557 @@ impl<P, S> Session<P, S> {
     pub fn interact<I, O>(&mut self, input: I, output: O) -> InteractSession<&mut Self, I, O> {
         InteractSession::new(self, input, output)
     }
+
+    /// Interact gives control of the child process to a single stream which is used both as an input and an output,
+    /// e.g. a network socket or a web terminal bridge.
+    ///
+    /// It works the same way as [`Session::interact`].
+    /// Notice that in not `async` mode reads of the stream must not block,
+    /// so a socket must be put in a nonblocking mode.
+    ///
+    /// # Example
+    ///
+    /// ```no_run,ignore
+    /// use std::net::TcpListener;
+    /// use expectrl::{self, interact::InteractOptions};
+    ///
+    /// let mut p = expectrl::spawn("sh").unwrap();
+    ///
+    /// let listener = TcpListener::bind("127.0.0.1:8080").unwrap();
+    /// let (client, _) = listener.accept().unwrap();
+    /// client.set_nonblocking(true).unwrap();
+    ///
+    /// p.interact_with(client).spawn(InteractOptions::default()).unwrap();
+    /// ```
+    pub fn interact_with<T>(
+        &mut self,
+        stream: T,
+    ) -> InteractSession<&mut Self, Duplex<T>, Duplex<T>> {
+        let input = Duplex::new(stream);
+        let output = input.clone();
+        InteractSession::new(self, input, output)
+    }
+
+    /// Interact gives control of the child process to a terminal which is read via `crossterm`.
+    ///
+    /// The terminal is put into a raw mode until the returned [`InteractSession`] is dropped.
+    /// Keys, a mouse and a paste are converted to the escape sequences a process expects,
+    /// and the process is resized along with the terminal.
+    ///
+    /// It's available with the `crossterm` feature.
+    ///
+    /// # Example
+    ///
+    /// ```no_run,ignore
+    /// use std::io::stdout;
+    /// use expectrl::{self, interact::InteractOptions};
+    ///
+    /// let mut p = expectrl::spawn("sh").unwrap();
+    ///
+    /// p.interact_crossterm(stdout())
+    ///     .unwrap()
+    ///     .spawn(InteractOptions::default())
+    ///     .unwrap();
+    /// ```
+    #[cfg(all(
+        feature = "crossterm",
+        not(feature = "async"),
+        not(feature = "polling")
+    ))]
+    pub fn interact_crossterm<O>(
+        &mut self,
+        output: O,
+    ) -> Result<InteractSession<&mut Self, crate::stream::crossterm::CrosstermInput, O>, Error>
+    {
+        let input = crate::stream::crossterm::CrosstermInput::open()?;
+        let resize = input.resize_handle();
+        Ok(InteractSession::new(self, input, output).set_resize(resize))
+    }
+
+    /// Starts recording the session into an [asciicast v2] file.
+    ///
+    /// Everything read from the process, including what's read in [`Session::interact`],
+    /// is recorded with timing so it can be replayed by `asciinema play`.
+    ///
+    /// A terminal size in the header is 80x24,
+    /// use [`AsciicastStream::set_size`] via [`Session::get_stream_mut`] to change it before any IO.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// let p = expectrl::spawn("vim").unwrap();
+    /// let mut p = p.record_asciicast("session.cast").unwrap();
+    /// ```
+    ///
+    /// [asciicast v2]: https://docs.asciinema.org/manual/asciicast/v2/
+    #[cfg(not(feature = "async"))]
+    pub fn record_asciicast<A>(self, path: A) -> Result<Session<P, AsciicastStream<S, File>>, Error>
+    where
+        A: AsRef<Path>,
+        S: Read,
+    {
+        let file = File::create(path)?;
+        self.swap_stream(|s| AsciicastStream::new(s, file))
+    }
+
+    /// Starts recording the session into an [asciicast v2] file.
+    ///
+    /// Everything read from the process, including what's read in [`Session::interact`],
+    /// is recorded with timing so it can be replayed by `asciinema play`.
+    ///
+    /// A terminal size in the header is 80x24,
+    /// use [`AsciicastStream::set_size`] via [`Session::get_stream_mut`] to change it before any IO.
+    ///
+    /// [asciicast v2]: https://docs.asciinema.org/manual/asciicast/v2/
+    #[cfg(feature = "async")]
+    pub fn record_asciicast<A>(self, path: A) -> Result<Session<P, AsciicastStream<S, File>>, Error>
+    where
+        A: AsRef<Path>,
+    {
+        let file = File::create(path)?;
+        self.swap_stream(|s| AsciicastStream::new(s, file))
+    }
+
+    /// Makes the session decode an output from a given Windows codepage.
+    ///
+    /// Sent bytes are encoded into the codepage as well,
+    /// so UTF-8 needles and UTF-8 input work the same way as on unix.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use expectrl::stream::codepage;
+    ///
+    /// let p = expectrl::spawn("cmd /C dir").unwrap();
+    /// let mut p = p.with_codepage(codepage::OEM).unwrap();
+    /// ```
+    #[cfg(all(windows, not(feature = "async")))]
+    pub fn with_codepage(
+        self,
+        codepage: u32,
+    ) -> Result<Session<P, crate::stream::codepage::CodepageStream<S>>, Error>
+    where
+        S: Read,
+    {
+        self.swap_stream(|s| crate::stream::codepage::CodepageStream::new(s, codepage))
+    }
+
+    /// Makes the session decode an output from a given Windows codepage.
+    ///
+    /// Sent bytes are encoded into the codepage as well,
+    /// so UTF-8 needles and UTF-8 input work the same way as on unix.
+    #[cfg(all(windows, feature = "async"))]
+    pub fn with_codepage(
+        self,
+        codepage: u32,
+    ) -> Result<Session<P, crate::stream::codepage::CodepageStream<S>>, Error> {
+        self.swap_stream(|s| crate::stream::codepage::CodepageStream::new(s, codepage))
+    }
+}
+
+#[cfg(unix)]
+impl<S> Session<OsProcess, S> {
+    /// Sends a signal to the process.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use expectrl::{spawn, Signal};
+    ///
+    /// let mut p = spawn("cat").unwrap();
+    /// p.signal(Signal::SIGUSR1).unwrap();
+    /// ```
+    pub fn signal(&mut self, signal: Signal) -> Result<(), Error> {
+        self.get_process_mut()
+            .signal(signal)
+            .map_err(|err| Error::unknown("Failed to send a signal", err.to_string()))
+    }
+
+    /// Turns the terminal's echo on or off.
+    ///
+    /// See [Session::set_skip_echo] if you need the echo but don't want to match against it.
+    pub fn set_terminal_echo(&mut self, on: bool) -> Result<(), Error> {
+        let _ = self
+            .get_process_mut()
+            .set_echo(on, None)
+            .map_err(|err| Error::unknown("Failed to set echo", err.to_string()))?;
+
+        Ok(())
+    }
+
+    /// Verifies whether the terminal's echo is on.
+    pub fn is_terminal_echo(&self) -> Result<bool, Error> {
+        self.get_process()
+            .get_echo()
+            .map_err(|err| Error::unknown("Failed to get echo", err.to_string()))
+    }
+
+    /// Returns the terminal's settings.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use expectrl::{spawn, termios::LocalFlags};
+    ///
+    /// let mut p = spawn("cat").unwrap();
+    ///
+    /// let mut settings = p.tty_settings().unwrap();
+    /// settings.local_flags.remove(LocalFlags::ICANON | LocalFlags::ISIG);
+    /// p.set_tty_settings(&settings).unwrap();
+    /// ```
+    pub fn tty_settings(&self) -> Result<Termios, Error> {
+        let file = self.get_tty_handle()?;
+        termios::tcgetattr(file.as_raw_fd())
+            .map_err(|err| Error::unknown("Failed to call tcgetattr", err.to_string()))
+    }
+
+    /// Sets the terminal's settings.
+    ///
+    /// The settings are applied right away.
+    pub fn set_tty_settings(&mut self, settings: &Termios) -> Result<(), Error> {
+        let file = self.get_tty_handle()?;
+        termios::tcsetattr(file.as_raw_fd(), termios::SetArg::TCSANOW, settings)
+            .map_err(|err| Error::unknown("Failed to call tcsetattr", err.to_string()))
+    }
+
+    /// Puts the terminal into a raw mode.
+    ///
+    /// Input is available character by character,
+    /// echo and special processing of input and output characters are disabled.
+    pub fn set_raw_mode(&mut self) -> Result<(), Error> {
+        let mut settings = self.tty_settings()?;
+        termios::cfmakeraw(&mut settings);
+        self.set_tty_settings(&settings)
+    }
+
+    /// Returns a path of the terminal device the process is attached to, e.g. `/dev/pts/3`.
+    pub fn pty_name(&self) -> Result<PathBuf, Error> {
+        let file = self.get_tty_handle()?;
+        let name = crate::pty::slave_name(file.as_raw_fd())?;
+        Ok(PathBuf::from(name))
+    }
+
+    fn get_tty_handle(&self) -> Result<File, Error> {
+        self.get_process()
+            .get_raw_handle()
+            .map_err(|err| Error::unknown("Failed to get a pty handle", err.to_string()))
+    }
+
+    /// Sends a signal to the whole process group of the process.
+    ///
+    /// A process is always spawned in a new session, so it's a leader of its own process group,
+    /// and all its children are members of the group unless they created their own one,
+    /// like shells with job control do for background jobs.
+    ///
+    /// It can be used to not leave orphan processes behind.
+    pub fn kill_group(&mut self, signal: Signal) -> Result<(), Error> {
+        nix::sys::signal::killpg(self.get_process().pid(), signal)
+            .map_err(|err| Error::unknown("Failed to send a signal to a group", err.to_string()))
+    }
+
+    /// Returns a session over a stderr of the process,
+    /// which can be used to expect an output of the stderr separately from the terminal.
+    ///
+    /// It returns [None] unless the session was spawned with [`SessionBuilder::separate_stderr`].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use std::process::Command;
+    /// use expectrl::Session;
+    ///
+    /// let mut command = Command::new("sh");
+    /// command.args(["-c", "echo out; echo err >&2"]);
+    ///
+    /// let mut p = Session::builder(command).separate_stderr(true).spawn().unwrap();
+    /// # #[cfg(not(feature = "async"))]
+    /// # {
+    /// p.stderr().unwrap().expect("err").unwrap();
+    /// p.expect("out").unwrap();
+    /// # }
+    /// ```
+    pub fn stderr(&mut self) -> Option<&mut StderrSession> {
+        self.stderr_mut()
+    }
+}
+
+#[cfg(windows)]
+impl<S> Session<OsProcess, S> {
+    /// Sends a console control event to the process.
+    ///
+    /// [`Signal::SIGINT`] generates a real `CTRL_C_EVENT`,
+    /// which is delivered even to programs which ignore a `0x03` byte in their input.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use expectrl::{spawn, Signal};
+    ///
+    /// let mut p = spawn("ping -t localhost").unwrap();
+    /// p.signal(Signal::SIGINT).unwrap();
+    /// ```
+    ///
+    /// [`Signal::SIGINT`]: crate::Signal::SIGINT
+    pub fn signal(&mut self, signal: crate::Signal) -> Result<(), Error> {
+        self.get_process_mut()
+            .signal(signal)
+            .map_err(|err| Error::unknown("Failed to send a signal", err.to_string()))
+    }
+}
+
+#[cfg(unix)]
+impl<P, S: AsRawFd> AsRawFd for Session<P, S> {
+    fn as_raw_fd(&self) -> RawFd {
+        self.get_stream().as_raw_fd()
+    }
+}
+
+#[cfg(unix)]
+impl<P, S: AsFd> AsFd for Session<P, S> {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.get_stream().as_fd()
+    }
+}
+
+/// An environment variable which overrides a default expect timeout of new sessions.
+///
+/// The value is a number of seconds, e.g. `30` or `2.5`, or `none` to disable the timeout.
+/// An invalid value is ignored.
+///
+/// A timeout set in code, via [`Session::set_expect_timeout`] or [`SessionBuilder::expect_timeout`], takes precedence.
+pub const TIMEOUT_ENV: &str = "EXPECTRL_TIMEOUT";
+
+const DEFAULT_EXPECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Returns an expect timeout a new session is created with.
+///
+/// See [TIMEOUT_ENV].
+fn default_expect_timeout() -> Option<Duration> {
+    std::env::var(TIMEOUT_ENV)
+        .ok()
+        .and_then(|value| parse_timeout(&value))
+        .unwrap_or(Some(DEFAULT_EXPECT_TIMEOUT))
+}
+
+fn parse_timeout(value: &str) -> Option<Option<Duration>> {
+    let value = value.trim();
+    if value.eq_ignore_ascii_case("none") {
+        return Some(None);
+    }
+
+    let secs = value.parse::<f64>().ok()?;
+    Duration::try_from_secs_f64(secs).ok().map(Some)
+}
+
+/// Replaces `\r\n` with `\n` in a buffer starting from a given index.
+///
+/// A byte right before the index is checked as well,
+/// so a `\r\n` split across 2 reads is replaced too.
+fn normalize_newlines(buf: &mut Vec<u8>, start: usize) {
+    let start = start.saturating_sub(1);
+    let mut j = start;
+    for i in start..buf.len() {
+        if buf[i] == b'\r' && buf.get(i + 1) == Some(&b'\n') {
+            continue;
+        }
+
+        buf[j] = buf[i];
+        j += 1;
+    }
+
+    buf.truncate(j);
+}
+
+/// A default size of a chunk read from a stream at once, see `Session::set_read_chunk_size`.
+const DEFAULT_READ_CHUNK_SIZE: usize = 4096;
+
+/// A longest time an expect call waits for an output before it checks a needle again.
+///
+/// Some needles depend on time rather than on an output, e.g. [crate::Not],
+/// so they must be checked even if nothing arrives.
+const CHECK_INTERVAL: Duration = Duration::from_millis(20);
+
+/// Tee mirrors the output of a session to a writer.
+///
+/// The writer is dropped after the first error,
+/// so a closed sink doesn't disturb the session.
+#[derive(Default)]
+struct Tee {
+    sink: Option<Box<dyn Write + Send>>,
+}
+
+impl Tee {
+    fn set_sink(&mut self, sink: Option<Box<dyn Write + Send>>) {
+        self.sink = sink;
+    }
+
+    fn write(&mut self, buf: &[u8]) {
+        if buf.is_empty() {
+            return;
+        }
+
+        if let Some(sink) = &mut self.sink {
+            if sink.write_all(buf).and_then(|_| sink.flush()).is_err() {
+                self.sink = None;
+            }
+        }
+    }
+}
+
+impl std::fmt::Debug for Tee {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Tee")
+            .field("sink", &self.sink.is_some())
+            .finish()
+    }
+}
+
+/// Echo is a sent text which is expected to show up at the beginning of the output.
+///
+/// See `Session::set_skip_echo`.
+#[derive(Debug, Default)]
+struct Echo {
+    pending: Vec<u8>,
+}
+
+/// A result of matching an [Echo] against the output.
+#[derive(Debug, PartialEq, Eq)]
+enum EchoMatch {
+    /// The echo takes a given number of bytes at the beginning of the output.
+    Complete(usize),
+    /// The output is a beginning of the echo.
+    Partial,
+    /// The output doesn't start with the echo.
+    Mismatch,
+}
+
+impl Echo {
+    /// Adds a sent line.
+    fn push_line(&mut self, line: &[u8]) {
+        self.pending.extend(line);
+        self.pending.push(b'\n');
+    }
+
+    fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    fn clear(&mut self) {
+        self.pending.clear();
+    }
+
+    /// Matches the echo against the beginning of the output.
+    ///
+    /// A line ending of the echo matches both `\n` and `\r\n`,
+    /// as a terminal may translate it.
+    /// The echo is cleared unless the match is [EchoMatch::Partial].
+    fn check(&mut self, data: &[u8]) -> EchoMatch {
+        let mut pos = 0;
+        for &b in &self.pending {
+            if b == b'\n' && data.get(pos) == Some(&b'\r') {
+                pos += 1;
+            }
+
+            match data.get(pos) {
+                Some(&c) if c == b => pos += 1,
+                Some(_) => {
+                    self.pending.clear();
+                    return EchoMatch::Mismatch;
+                }
+                None => return EchoMatch::Partial,
+            }
+        }
+
+        self.pending.clear();
+        EchoMatch::Complete(pos)
+    }
+}
+
+/// Found is a match of an expect call which is not consumed from a session buffer yet.
+#[derive(Debug)]
+struct Found {
+    matches: Vec<Match>,
+    end: usize,
+    scanned: usize,
+}
+
+impl Found {
+    fn new(matches: Vec<Match>, scanned: usize) -> Self {
+        let end = Captures::right_most_index(&matches);
+        Self {
+            matches,
+            end,
+            scanned,
+        }
+    }
+
+    /// Copies involved bytes out of a buffer.
+    ///
+    /// The caller is responsible for consuming them.
+    fn into_captures(self, buf: &[u8]) -> Captures {
+        Captures::new(buf[..self.end].to_vec(), self.matches).with_scanned(self.scanned)
+    }
+
+    /// Borrows involved bytes from a buffer,
+    /// they're consumed once [CapturesRef] is dropped.
+    fn into_captures_ref<'a>(self, buf: &'a mut (dyn Buffer + 'a)) -> CapturesRef<'a> {
+        CapturesRef::new(buf, self.end, self.matches).with_scanned(self.scanned)
+    }
+}
+
+/// Looks up successive matches of a needle in a buffer.
+///
+/// It returns the matches and a number of bytes they span over.
+fn find_all<N: Needle>(needle: &N, buf: &[u8], eof: bool) -> Result<(Vec<Captures>, usize), Error> {
+    let mut found = Vec::new();
+    let mut pos = 0;
+    loop {
+        let rest = &buf[pos..];
+        let matches = needle.check(rest, eof)?;
+        if matches.is_empty() {
+            break;
+        }
+
+        let end = Captures::right_most_index(&matches);
+        found.push(Captures::new(rest[..end].to_vec(), matches));
+
+        // an empty match would be found over and over again
+        if end == 0 {
+            break;
+        }
+
+        pos += end;
+    }
+
+    Ok((found, pos))
+}
+
+/// Splits bytes into UTF-8 characters.
+///
+/// Bytes which are not a valid UTF-8 are returned one by one.
+fn split_chars(buf: &[u8]) -> impl Iterator<Item = &[u8]> {
+    let mut rest = buf;
+    std::iter::from_fn(move || {
+        if rest.is_empty() {
+            return None;
+        }
+
+        let len = match rest[0] {
+            0xF0..=0xF7 => 4,
+            0xE0..=0xEF => 3,
+            0xC0..=0xDF => 2,
+            _ => 1,
+        };
+
+        let len = match rest.get(..len).map(std::str::from_utf8) {
+            Some(Ok(_)) => len,
+            _ => 1,
+        };
+
+        let (c, tail) = rest.split_at(len);
+        rest = tail;
+        Some(c)
+    })
 }
 
 /// Set a logger which will write each Read/Write operation into the writter.
@@ -162,7 +799,40 @@ where
     W: Write,
     S: Read,
 {
-    session.swap_stream(|s| LogStream::new(s, dst))
+    log_with_format(session, dst, LogFormat::default())
+}
+
+/// Set a logger just like [log] but with a given format of the log.
+///
+/// Lines are tagged by a name of the session if it's set by [Session::set_name].
+///
+/// # Example
+///
+/// ```
+/// use expectrl::{spawn, session::log_with_format, stream::log::LogFormat};
+///
+/// let p = spawn("cat").unwrap();
+/// let p = log_with_format(p, std::io::stdout(), LogFormat::HexDump);
+/// ```
+#[cfg(not(feature = "async"))]
+pub fn log_with_format<W, P, S>(
+    session: Session<P, S>,
+    dst: W,
+    format: LogFormat,
+) -> Result<Session<P, LogStream<S, W>>, Error>
+where
+    W: Write,
+    S: Read,
+{
+    let name = session.get_name().map(str::to_owned);
+    session.swap_stream(|s| {
+        let mut stream = LogStream::with_format(s, dst, format);
+        if let Some(name) = name {
+            stream.set_name(name);
+        }
+
+        stream
+    })
 }
 
 /// Set a logger which will write each Read/Write operation into the writter.
@@ -180,5 +850,951 @@ pub fn log<W, P, S>(session: Session<P, S>, dst: W) -> Result<Session<P, LogStre
 where
     W: Write,
 {
-    session.swap_stream(|s| LogStream::new(s, dst))
+    log_with_format(session, dst, LogFormat::default())
+}
+
+/// Set a logger just like [log] but with a given format of the log.
+///
+/// Lines are tagged by a name of the session if it's set by [Session::set_name].
+///
+/// # Example
+///
+/// ```
+/// use expectrl::{spawn, session::log_with_format, stream::log::LogFormat};
+///
+/// let p = spawn("cat").unwrap();
+/// let p = log_with_format(p, std::io::stdout(), LogFormat::HexDump);
+/// ```
+#[cfg(feature = "async")]
+pub fn log_with_format<W, P, S>(
+    session: Session<P, S>,
+    dst: W,
+    format: LogFormat,
+) -> Result<Session<P, LogStream<S, W>>, Error>
+where
+    W: Write,
+{
+    let name = session.get_name().map(str::to_owned);
+    session.swap_stream(|s| {
+        let mut stream = LogStream::with_format(s, dst, format);
+        if let Some(name) = name {
+            stream.set_name(name);
+        }
+
+        stream
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_chars() {
+        assert_eq!(
+            split_chars("ab💖ф".as_bytes()).collect::<Vec<_>>(),
+            ["a".as_bytes(), b"b", "💖".as_bytes(), "ф".as_bytes()]
+        );
+        assert_eq!(
+            split_chars(b"a\xF0\x9F").collect::<Vec<_>>(),
+            [&b"a"[..], b"\xF0", b"\x9F"]
+        );
+        assert_eq!(split_chars(b"").count(), 0);
+    }
+
+    #[test]
+    fn test_parse_timeout() {
+        assert_eq!(parse_timeout("30"), Some(Some(Duration::from_secs(30))));
+        assert_eq!(
+            parse_timeout(" 2.5 "),
+            Some(Some(Duration::from_millis(2500)))
+        );
+        assert_eq!(parse_timeout("none"), Some(None));
+        assert_eq!(parse_timeout("NONE"), Some(None));
+        assert_eq!(parse_timeout("-1"), None);
+        assert_eq!(parse_timeout("10s"), None);
+        assert_eq!(parse_timeout(""), None);
+    }
+
+    #[test]
+    fn test_normalize_newlines() {
+        let mut buf = b"a\r\nb\r\r\n\r".to_vec();
+        normalize_newlines(&mut buf, 0);
+        assert_eq!(buf, b"a\nb\r\n\r");
+
+        buf.extend(b"\nc");
+        normalize_newlines(&mut buf, 6);
+        assert_eq!(buf, b"a\nb\r\n\nc");
+    }
+
+    #[test]
+    fn test_set_normalize_newlines() {
+        use crate::test::{MockSession, MockStream};
+
+        let stream = MockStream::new()
+            .output("Hello\r\n")
+            .on_receive("World\n")
+            .respond("World\r\nDone\r\n");
+        let mut session = MockSession::mock(stream).unwrap();
+        session.set_normalize_newlines(true);
+        session.set_skip_echo(true);
+
+        #[cfg(not(feature = "async"))]
+        {
+            let captures = session.expect("Hello\n").unwrap();
+            assert_eq!(captures.as_bytes(), b"Hello\n");
+            session.send_line("World").unwrap();
+            let captures = session.expect("\n").unwrap();
+            assert_eq!(captures.before(), b"Done");
+        }
+
+        #[cfg(feature = "async")]
+        futures_lite::future::block_on(async {
+            let captures = session.expect("Hello\n").await.unwrap();
+            assert_eq!(captures.as_bytes(), b"Hello\n");
+            session.send_line("World").await.unwrap();
+            let captures = session.expect("\n").await.unwrap();
+            assert_eq!(captures.before(), b"Done");
+        });
+    }
+
+    #[test]
+    fn test_stats() {
+        use crate::test::{MockSession, MockStream};
+
+        let stream = MockStream::new()
+            .output("Hello World")
+            .on_receive("ping\n")
+            .respond("pong");
+        let mut session = MockSession::mock(stream).unwrap();
+        session.set_expect_timeout(Some(Duration::from_millis(50)));
+
+        #[cfg(not(feature = "async"))]
+        {
+            let _ = session.expect("Hello").unwrap();
+            session.send_line("ping").unwrap();
+            let _ = session.expect("pong").unwrap();
+            let _ = session.expect("nothing").unwrap_err();
+        }
+
+        #[cfg(feature = "async")]
+        futures_lite::future::block_on(async {
+            let _ = session.expect("Hello").await.unwrap();
+            session.send_line("ping").await.unwrap();
+            let _ = session.expect("pong").await.unwrap();
+            let _ = session.expect("nothing").await.unwrap_err();
+        });
+
+        let stats = *session.stats();
+        assert_eq!(stats.bytes_read(), 15);
+        assert_eq!(stats.bytes_written(), 5);
+        assert_eq!(stats.expects(), 3);
+        assert_eq!(stats.matches(), 2);
+        assert_eq!(stats.timeouts(), 1);
+        assert!(stats.longest_match() < Duration::from_millis(50));
+
+        session.reset_stats();
+        assert_eq!(*session.stats(), crate::Stats::default());
+    }
+
+    /// A writer which can be inspected after it was given to a session.
+    #[derive(Debug, Clone, Default)]
+    struct SharedBuf(std::sync::Arc<std::sync::Mutex<Vec<u8>>>);
+
+    impl SharedBuf {
+        fn take(&self) -> String {
+            String::from_utf8(std::mem::take(&mut *self.0.lock().unwrap())).unwrap()
+        }
+    }
+
+    impl Write for SharedBuf {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_set_log() {
+        use crate::test::{MockSession, MockStream};
+
+        let stream = MockStream::new()
+            .output("login: ")
+            .on_receive("admin\n")
+            .respond("password: ")
+            .on_receive("secret\n")
+            .respond("$ ");
+        let mut session = MockSession::mock(stream).unwrap();
+
+        let log = SharedBuf::default();
+        session.set_log(log.clone());
+
+        #[cfg(not(feature = "async"))]
+        {
+            let _ = session.expect("login: ").unwrap();
+            session.send_line("admin").unwrap();
+            let _ = session.expect("password: ").unwrap();
+            assert_eq!(
+                log.take(),
+                "read: \"login: \"\nwrite: \"admin\"\nwrite: \"\\n\"\nread: \"password: \"\n"
+            );
+
+            session.pause_log();
+            session.send_line("secret").unwrap();
+            session.resume_log();
+            session.set_log_format(LogFormat::HexDump);
+            let _ = session.expect("$").unwrap();
+            assert_eq!(log.take(), "read: 24 20 |$ |\n");
+
+            session.unset_log();
+            let _ = session.expect(" ").unwrap();
+            assert_eq!(log.take(), "");
+        }
+
+        #[cfg(feature = "async")]
+        futures_lite::future::block_on(async {
+            let _ = session.expect("login: ").await.unwrap();
+            session.send_line("admin").await.unwrap();
+            let _ = session.expect("password: ").await.unwrap();
+            assert_eq!(
+                log.take(),
+                "read: \"login: \"\nwrite: \"admin\"\nwrite: \"\\n\"\nread: \"password: \"\n"
+            );
+
+            session.pause_log();
+            session.send_line("secret").await.unwrap();
+            session.resume_log();
+            session.set_log_format(LogFormat::HexDump);
+            let _ = session.expect("$").await.unwrap();
+            assert_eq!(log.take(), "read: 24 20 |$ |\n");
+
+            session.unset_log();
+            let _ = session.expect(" ").await.unwrap();
+            assert_eq!(log.take(), "");
+        });
+    }
+
+    #[test]
+    fn test_send_secret_is_not_logged() {
+        use crate::test::{MockSession, MockStream};
+
+        let stream = MockStream::new()
+            .output("password: ")
+            .on_receive("secret\n")
+            .respond("$ ");
+        let session = MockSession::mock(stream).unwrap();
+
+        let stream_log = SharedBuf::default();
+        let mut session = log(session, stream_log.clone()).unwrap();
+
+        let log = SharedBuf::default();
+        session.set_log(log.clone());
+
+        #[cfg(not(feature = "async"))]
+        {
+            let _ = session.expect("password: ").unwrap();
+            session.send_secret("secret").unwrap();
+            let _ = session.expect("$ ").unwrap();
+        }
+
+        #[cfg(feature = "async")]
+        futures_lite::future::block_on(async {
+            let _ = session.expect("password: ").await.unwrap();
+            session.send_secret("secret").await.unwrap();
+            let _ = session.expect("$ ").await.unwrap();
+        });
+
+        let expected = "read: \"password: \"\nwrite: \"\\n\"\nread: \"$ \"\n";
+        assert_eq!(log.take(), expected);
+        assert_eq!(stream_log.take(), expected);
+    }
+
+    #[test]
+    fn test_matches() {
+        use crate::test::{MockSession, MockStream};
+
+        let stream = MockStream::new().output("ok: 1 failed: 2 ok: 3").eof();
+        let mut session = MockSession::mock(stream).unwrap();
+        session.set_expect_lazy(true);
+
+        #[cfg(not(feature = "async"))]
+        let found = session
+            .matches(crate::Regex("ok: \\d"))
+            .map(|m| m.map(|m| m.get(0).unwrap().to_vec()))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        #[cfg(feature = "async")]
+        let found = futures_lite::future::block_on(async {
+            use futures_lite::StreamExt;
+
+            session
+                .matches(crate::Regex("ok: \\d"))
+                .map(|m| m.map(|m| m.get(0).unwrap().to_vec()))
+                .try_collect::<_, _, Vec<_>>()
+                .await
+                .unwrap()
+        });
+
+        assert_eq!(found, [b"ok: 1", b"ok: 3"]);
+    }
+
+    #[test]
+    fn test_take_buffer_after_timeout() {
+        use crate::test::{MockSession, MockStream};
+
+        let mut session = MockSession::mock(MockStream::new().output("12")).unwrap();
+        session.set_expect_timeout(Some(Duration::from_millis(100)));
+
+        #[cfg(not(feature = "async"))]
+        let err = session.expect(crate::ByteRange(4..=8)).unwrap_err();
+        #[cfg(feature = "async")]
+        let err =
+            futures_lite::future::block_on(session.expect(crate::ByteRange(4..=8))).unwrap_err();
+
+        assert!(matches!(err, Error::ExpectTimeout));
+        assert_eq!(session.take_buffer(), b"12");
+        assert_eq!(session.buffer(), b"");
+    }
+
+    #[test]
+    fn test_expect_ref() {
+        use crate::test::{MockSession, MockStream};
+
+        let mut session = MockSession::mock(MockStream::new().output("Hello World!")).unwrap();
+
+        {
+            #[cfg(not(feature = "async"))]
+            let m = session.expect_ref("World").unwrap();
+            #[cfg(feature = "async")]
+            let m = futures_lite::future::block_on(session.expect_ref("World")).unwrap();
+
+            assert_eq!(m.before(), b"Hello ");
+            assert_eq!(m.get(0), Some(b"World".as_ref()));
+            assert_eq!(m.as_bytes(), b"Hello World");
+        }
+
+        assert_eq!(session.buffer(), b"!");
+    }
+
+    #[test]
+    #[cfg(not(feature = "async"))]
+    fn test_write_buffering() {
+        use crate::test::{MockSession, MockStream};
+
+        let stream = MockStream::new()
+            .on_receive("Hello World\n")
+            .ignore()
+            .on_receive("!")
+            .ignore();
+        let mut session = MockSession::mock(stream).unwrap();
+        session.set_write_buffering(true);
+
+        session.send("Hello").unwrap();
+        session.send_line(" World").unwrap();
+        assert_eq!(session.get_stream().get_written(), b"");
+        assert_eq!(session.stats().bytes_written(), 0);
+
+        session.flush().unwrap();
+        assert_eq!(session.get_stream().get_written(), b"Hello World\n");
+        assert_eq!(session.stats().bytes_written(), 12);
+
+        session.set_write_buffering(false);
+        session.send("!").unwrap();
+        assert_eq!(session.get_stream().get_written(), b"Hello World\n!");
+    }
+
+    #[test]
+    #[cfg(not(feature = "async"))]
+    fn test_write_buffer_is_flushed() {
+        use crate::test::{MockSession, MockStream};
+
+        let stream = MockStream::new()
+            .on_receive("ls\n")
+            .respond("file\n$ ")
+            .on_receive("sudo ls\nsecret\nexit\n")
+            .ignore();
+        let session = MockSession::mock(stream).unwrap();
+
+        let stream_log = SharedBuf::default();
+        let mut session = log(session, stream_log.clone()).unwrap();
+        session.set_write_buffering(true);
+
+        session.send_line("ls").unwrap();
+        let _ = session.expect("$ ").unwrap();
+
+        session.send_line("sudo ls").unwrap();
+        session.send_secret("secret").unwrap();
+        session.send_line("exit").unwrap();
+        drop(session);
+
+        assert_eq!(
+            stream_log.take(),
+            "write: \"ls\\n\"\nread: \"file\\n$ \"\nwrite: \"sudo ls\\n\"\nwrite: \"\\nexit\\n\"\n"
+        );
+    }
+
+    #[test]
+    fn test_send_all() {
+        use crate::test::{MockSession, MockStream};
+
+        let stream = MockStream::new().on_receive("ls -l\n").ignore();
+        let mut session = MockSession::mock(stream).unwrap();
+
+        #[cfg(not(feature = "async"))]
+        session.send_all(&["", "ls", " -l", "", "\n"]).unwrap();
+        #[cfg(feature = "async")]
+        futures_lite::future::block_on(session.send_all(&["", "ls", " -l", "", "\n"])).unwrap();
+
+        assert_eq!(session.get_stream().get_written(), b"ls -l\n");
+    }
+
+    #[test]
+    fn test_echo_check() {
+        let mut echo = Echo::default();
+        echo.push_line(b"ls");
+        assert_eq!(echo.check(b"ls\r\nfile"), EchoMatch::Complete(4));
+        assert!(echo.is_empty());
+
+        echo.push_line(b"ls");
+        assert_eq!(echo.check(b"ls\nfile"), EchoMatch::Complete(3));
+
+        echo.push_line(b"ls");
+        assert_eq!(echo.check(b"ls\r"), EchoMatch::Partial);
+        assert_eq!(echo.check(b"ls\r\n"), EchoMatch::Complete(4));
+
+        echo.push_line(b"ls");
+        assert_eq!(echo.check(b"file"), EchoMatch::Mismatch);
+        assert!(echo.is_empty());
+    }
+
+    #[test]
+    fn test_skip_echo() {
+        use crate::test::{MockSession, MockStream};
+
+        let stream = MockStream::new()
+            .on_receive("abc\n")
+            .respond("abc\nok abc")
+            .on_receive("def\n")
+            .respond("def\r\nok def\r\n");
+        let mut session = MockSession::mock(stream).unwrap();
+        session.set_skip_echo(true);
+
+        #[cfg(not(feature = "async"))]
+        {
+            session.send_line("abc").unwrap();
+            let m = session.check("abc").unwrap();
+            assert_eq!(m.before(), b"ok ");
+
+            session.send_line("def").unwrap();
+            let m = session.check_all("def").unwrap();
+            assert_eq!(m.len(), 1);
+            assert_eq!(m[0].before(), b"ok ");
+        }
+
+        #[cfg(feature = "async")]
+        futures_lite::future::block_on(async {
+            session.send_line("abc").await.unwrap();
+            let m = session.check("abc").await.unwrap();
+            assert_eq!(m.before(), b"ok ");
+
+            session.send_line("def").await.unwrap();
+            let m = session.check_all("def").await.unwrap();
+            assert_eq!(m.len(), 1);
+            assert_eq!(m[0].before(), b"ok ");
+        });
+    }
+
+    #[test]
+    #[cfg(not(feature = "async"))]
+    fn test_expect_nonblocking_step() {
+        use crate::test::{MockSession, MockStream};
+
+        let stream = MockStream::new()
+            .output("Hello")
+            .on_receive("\n")
+            .respond(" World")
+            .eof();
+        let mut session = MockSession::mock(stream).unwrap();
+
+        assert!(session.expect_nonblocking_step("World").unwrap().is_none());
+
+        session.send("\n").unwrap();
+        let m = session.expect_nonblocking_step("World").unwrap().unwrap();
+        assert_eq!(m.before(), b"Hello ");
+
+        let err = session.expect_nonblocking_step("World").unwrap_err();
+        assert!(matches!(err, Error::Eof));
+    }
+
+    #[test]
+    #[cfg(feature = "async")]
+    fn test_poll_expect() {
+        use crate::test::{MockSession, MockStream};
+        use std::task::{Context, Poll, Waker};
+
+        let stream = MockStream::new()
+            .output("Hello")
+            .on_receive("\n")
+            .respond(" World")
+            .eof();
+        let mut session = MockSession::mock(stream).unwrap();
+
+        let mut cx = Context::from_waker(Waker::noop());
+        assert!(session.poll_expect(&mut cx, &"World").is_pending());
+
+        futures_lite::future::block_on(session.send("\n")).unwrap();
+        match session.poll_expect(&mut cx, &"World") {
+            Poll::Ready(Ok(m)) => assert_eq!(m.before(), b"Hello "),
+            result => panic!("unexpected result {:?}", result),
+        }
+
+        let result = session.poll_expect(&mut cx, &"World");
+        assert!(matches!(result, Poll::Ready(Err(Error::Eof))));
+    }
+
+    #[test]
+    #[cfg(feature = "async")]
+    fn test_chunks() {
+        use crate::test::{MockSession, MockStream};
+        use futures_lite::StreamExt;
+
+        let stream = MockStream::new().output("Hello World").eof();
+        let mut session = MockSession::mock(stream).unwrap();
+        futures_lite::future::block_on(async {
+            let chunks = session.chunks().try_collect::<_, _, Vec<_>>().await;
+            assert_eq!(chunks.unwrap().concat(), b"Hello World");
+
+            let chunks = session.chunks().try_collect::<_, _, Vec<_>>().await;
+            assert!(chunks.unwrap().is_empty());
+        });
+
+        let stream = MockStream::new().output("Hello World").eof();
+        let mut session = MockSession::mock(stream).unwrap();
+        session.keep_in_buffer(b"> ");
+        futures_lite::future::block_on(async {
+            let chunks = session.into_chunk_stream().try_collect::<_, _, Vec<_>>();
+            let chunks = chunks.await.unwrap();
+            assert_eq!(chunks.first().unwrap(), b"> ");
+            assert_eq!(chunks.concat(), b"> Hello World");
+        });
+    }
+
+    #[test]
+    fn test_read_chunk_size() {
+        use crate::test::{MockSession, MockStream};
+
+        let stream = MockStream::new().output("Hello World").eof();
+        let mut session = MockSession::mock(stream).unwrap();
+        session.set_read_chunk_size(0);
+
+        #[cfg(not(feature = "async"))]
+        let m = session.expect("World").unwrap();
+        #[cfg(feature = "async")]
+        let m = futures_lite::future::block_on(session.expect("World")).unwrap();
+
+        assert_eq!(m.before(), b"Hello ");
+        assert_eq!(session.stats().bytes_read(), 11);
+    }
+
+    #[test]
+    fn test_watchdog() {
+        use crate::test::{MockSession, MockStream};
+
+        let mut session = MockSession::mock(MockStream::new().output("Hello")).unwrap();
+        session.set_expect_timeout(Some(Duration::from_millis(200)));
+
+        let warnings = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let sink = warnings.clone();
+        session.set_watchdog(
+            Watchdog::new()
+                .elapsed(Duration::from_millis(50))
+                .buffer_size(3)
+                .on_warning(move |warning| sink.lock().unwrap().push(warning.clone())),
+        );
+
+        #[cfg(not(feature = "async"))]
+        let err = session.expect("World").unwrap_err();
+        #[cfg(feature = "async")]
+        let err = futures_lite::future::block_on(session.expect("World")).unwrap_err();
+        assert!(matches!(err, Error::ExpectTimeout));
+
+        let warnings = warnings.lock().unwrap();
+        assert_eq!(warnings.len(), 2);
+        assert_eq!(
+            warnings[0],
+            WatchdogWarning::BufferSize {
+                needle: String::from("\"World\""),
+                size: 5
+            }
+        );
+        assert!(matches!(
+            &warnings[1],
+            WatchdogWarning::Elapsed { elapsed, .. } if *elapsed < Duration::from_millis(200)
+        ));
+    }
+
+    #[test]
+    fn test_watchdog_is_logged() {
+        use crate::test::{MockSession, MockStream};
+
+        let mut session = MockSession::mock(MockStream::new().output("Hello")).unwrap();
+        session.set_expect_timeout(Some(Duration::from_millis(100)));
+        session.set_watchdog(Watchdog::new().buffer_size(3));
+
+        let log = SharedBuf::default();
+        session.set_log(log.clone());
+
+        #[cfg(not(feature = "async"))]
+        let err = session.expect("World").unwrap_err();
+        #[cfg(feature = "async")]
+        let err = futures_lite::future::block_on(session.expect("World")).unwrap_err();
+
+        assert!(matches!(err, Error::ExpectTimeout));
+        assert_eq!(
+            log.take(),
+            "read: \"Hello\"\n\
+             watchdog: 5 bytes are buffered without a match of \"World\"\n\
+             timeout: waiting for \"World\"\n"
+        );
+    }
+
+    #[test]
+    fn test_tee_output() {
+        use crate::test::{MockSession, MockStream};
+
+        let stream = MockStream::new()
+            .output("login: ")
+            .on_receive("admin\n")
+            .respond("\x1b[1mWelcome\x1b[0m\n$ ");
+        let mut session = MockSession::mock(stream).unwrap();
+
+        let tee = SharedBuf::default();
+        session.tee_output(tee.clone());
+
+        #[cfg(not(feature = "async"))]
+        {
+            let _ = session.expect("login: ").unwrap();
+            session.send("admin\n").unwrap();
+            let _ = session.expect("$ ").unwrap();
+        }
+        #[cfg(feature = "async")]
+        futures_lite::future::block_on(async {
+            let _ = session.expect("login: ").await.unwrap();
+            session.send("admin\n").await.unwrap();
+            let _ = session.expect("$ ").await.unwrap();
+        });
+
+        assert_eq!(tee.take(), "login: \x1b[1mWelcome\x1b[0m\n$ ");
+
+        session.unset_tee_output();
+        session.keep_in_buffer(b"ignored");
+        assert_eq!(tee.take(), "");
+    }
+
+    #[test]
+    fn test_time_based_needle_is_checked_without_output() {
+        use crate::{
+            test::{MockSession, MockStream},
+            Not,
+        };
+
+        let mut session = MockSession::mock(MockStream::new()).unwrap();
+        session.set_expect_timeout(Some(Duration::from_secs(5)));
+
+        let now = std::time::Instant::now();
+        let needle = Not::new("x", Duration::from_millis(50));
+
+        #[cfg(not(feature = "async"))]
+        let _ = session.expect(needle).unwrap();
+        #[cfg(feature = "async")]
+        let _ = futures_lite::future::block_on(session.expect(needle)).unwrap();
+
+        assert!(now.elapsed() < Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_timeout_is_logged() {
+        use crate::test::{MockSession, MockStream};
+
+        let mut session = MockSession::mock(MockStream::new().output("Hello")).unwrap();
+        session.set_expect_timeout(Some(Duration::from_millis(100)));
+
+        let log = SharedBuf::default();
+        session.set_log(log.clone());
+
+        #[cfg(not(feature = "async"))]
+        let err = session.expect("login:").unwrap_err();
+        #[cfg(feature = "async")]
+        let err = futures_lite::future::block_on(session.expect("login:")).unwrap_err();
+
+        assert!(matches!(err, Error::ExpectTimeout));
+        assert_eq!(
+            log.take(),
+            "read: \"Hello\"\ntimeout: waiting for \"login:\"\n"
+        );
+    }
+
+    #[test]
+    fn test_find_all() {
+        let (found, consumed) = find_all(&"id=", b"id=1 id=2 name", false).unwrap();
+        assert_eq!(consumed, 8);
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[0].before(), b"");
+        assert_eq!(found[1].before(), b"1 ");
+
+        let (found, consumed) = find_all(&crate::Regex("\\d"), b"id=1 id=2", false).unwrap();
+        assert_eq!(consumed, 9);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].matches().count(), 2);
+
+        let (found, consumed) = find_all(&"id=", b"name", false).unwrap();
+        assert_eq!(consumed, 0);
+        assert!(found.is_empty());
+    }
+
+    #[test]
+    fn test_expect_ctx() {
+        use crate::test::{MockSession, MockStream};
+
+        let mut session = MockSession::mock(MockStream::new().output("Hello").eof()).unwrap();
+
+        #[cfg(not(feature = "async"))]
+        let (ok, err) = (
+            session.expect_ctx("greeting", "Hello"),
+            session.expect_ctx("waiting for login prompt", "login:"),
+        );
+
+        #[cfg(feature = "async")]
+        let (ok, err) = futures_lite::future::block_on(async {
+            (
+                session.expect_ctx("greeting", "Hello").await,
+                session
+                    .expect_ctx("waiting for login prompt", "login:")
+                    .await,
+            )
+        });
+
+        assert_eq!(ok.unwrap().as_bytes(), b"Hello");
+
+        let err = err.unwrap_err();
+        assert!(matches!(err.root(), Error::Eof));
+        assert_eq!(
+            err.to_string(),
+            "waiting for login prompt: EOF was reached; the read may successed later"
+        );
+    }
+
+    #[test]
+    fn test_set_name() {
+        use crate::test::{MockSession, MockStream};
+
+        let mut session = MockSession::mock(MockStream::new().output("Hello").eof()).unwrap();
+        let log = SharedBuf::default();
+        session.set_log(log.clone());
+        session.set_name("router-1");
+        assert_eq!(session.get_name(), Some("router-1"));
+
+        #[cfg(not(feature = "async"))]
+        let err = session.expect_ctx("login", "login:").unwrap_err();
+        #[cfg(feature = "async")]
+        let err =
+            futures_lite::future::block_on(session.expect_ctx("login", "login:")).unwrap_err();
+
+        assert!(matches!(err.root(), Error::Eof));
+        assert_eq!(
+            err.to_string(),
+            "router-1: login: EOF was reached; the read may successed later"
+        );
+        assert_eq!(log.take(), "[router-1] read: \"Hello\"\n");
+
+        #[cfg(not(feature = "async"))]
+        let err = session.expect("login:").unwrap_err();
+        #[cfg(feature = "async")]
+        let err = futures_lite::future::block_on(session.expect("login:")).unwrap_err();
+
+        assert!(matches!(err, Error::Eof));
+
+        let mut session = MockSession::mock(MockStream::new().output("Hi")).unwrap();
+        session.set_name("router-2");
+        let mut session = super::log(session, log.clone()).unwrap();
+        assert_eq!(session.get_stream().get_name(), Some("router-2"));
+
+        #[cfg(not(feature = "async"))]
+        let _ = session.expect("Hi").unwrap();
+        #[cfg(feature = "async")]
+        let _ = futures_lite::future::block_on(session.expect("Hi")).unwrap();
+
+        assert_eq!(log.take(), "[router-2] read: \"Hi\"\n");
+    }
+
+    #[test]
+    fn test_captures_elapsed() {
+        use crate::test::{MockSession, MockStream};
+
+        let mut session = MockSession::mock(MockStream::new().output("Hello World")).unwrap();
+        let mut lazy = MockSession::mock(MockStream::new().output("Hello World")).unwrap();
+        lazy.set_expect_lazy(true);
+
+        #[cfg(not(feature = "async"))]
+        let (captures, lazy_captures) = (
+            session.expect("Hello").unwrap(),
+            lazy.expect("Hello").unwrap(),
+        );
+
+        #[cfg(feature = "async")]
+        let (captures, lazy_captures) = futures_lite::future::block_on(async {
+            (
+                session.expect("Hello").await.unwrap(),
+                lazy.expect("Hello").await.unwrap(),
+            )
+        });
+
+        assert_eq!(captures.scanned(), 11);
+        assert_eq!(lazy_captures.scanned(), 5);
+        assert!(captures.elapsed() < session.get_expect_timeout().unwrap());
+        assert!(captures.elapsed() <= session.stats().longest_match());
+    }
+
+    #[test]
+    fn test_poll_event() {
+        use crate::{
+            process::{ExitStatus, Termination},
+            test::MockStream,
+        };
+        use std::sync::{Arc, Mutex};
+
+        struct Exited;
+
+        impl Termination for Exited {
+            fn terminate(&mut self, _: bool) -> std::io::Result<()> {
+                Ok(())
+            }
+
+            fn try_wait(&mut self) -> std::io::Result<Option<ExitStatus>> {
+                Ok(Some(ExitStatus::from_code(1)))
+            }
+        }
+
+        let stream = MockStream::new().output("Hello").eof();
+        let mut session = Session::new(Exited, stream).unwrap();
+
+        let events = Arc::new(Mutex::new(Vec::new()));
+        session.on_event({
+            let events = events.clone();
+            move |event| events.lock().unwrap().push(*event)
+        });
+
+        #[cfg(not(feature = "async"))]
+        {
+            assert_eq!(session.poll_event().unwrap(), Some(SessionEvent::Eof));
+            assert_eq!(
+                session.poll_event().unwrap(),
+                Some(SessionEvent::Exited(ExitStatus::from_code(1)))
+            );
+            assert_eq!(session.poll_event().unwrap(), None);
+            assert_eq!(session.expect("Hello").unwrap().as_bytes(), b"Hello");
+        }
+
+        #[cfg(feature = "async")]
+        futures_lite::future::block_on(async {
+            use futures_lite::StreamExt;
+
+            let events = session.events();
+            assert_eq!(session.poll_event().await.unwrap(), Some(SessionEvent::Eof));
+            assert_eq!(
+                session.poll_event().await.unwrap(),
+                Some(SessionEvent::Exited(ExitStatus::from_code(1)))
+            );
+            assert_eq!(
+                events.collect::<Vec<_>>().await,
+                [
+                    SessionEvent::Eof,
+                    SessionEvent::Exited(ExitStatus::from_code(1))
+                ]
+            );
+            assert_eq!(session.poll_event().await.unwrap(), None);
+            assert_eq!(session.expect("Hello").await.unwrap().as_bytes(), b"Hello");
+        });
+
+        assert_eq!(
+            *events.lock().unwrap(),
+            [
+                SessionEvent::Eof,
+                SessionEvent::Exited(ExitStatus::from_code(1))
+            ]
+        );
+    }
+
+    #[test]
+    fn test_events_are_reported_by_any_call() {
+        use crate::{
+            process::{ExitStatus, Termination},
+            test::MockStream,
+        };
+        use std::sync::{Arc, Mutex};
+
+        struct Exited;
+
+        impl Termination for Exited {
+            fn terminate(&mut self, _: bool) -> std::io::Result<()> {
+                Ok(())
+            }
+
+            fn try_wait(&mut self) -> std::io::Result<Option<ExitStatus>> {
+                Ok(Some(ExitStatus::from_code(0)))
+            }
+        }
+
+        let stream = MockStream::new().output("Hello").eof();
+        let mut session = Session::new(Exited, stream).unwrap();
+
+        let events = Arc::new(Mutex::new(Vec::new()));
+        session.on_event({
+            let events = events.clone();
+            move |event| events.lock().unwrap().push(*event)
+        });
+
+        #[cfg(not(feature = "async"))]
+        {
+            assert!(matches!(session.check("World"), Err(Error::Eof)));
+            assert_eq!(*events.lock().unwrap(), [SessionEvent::Eof]);
+
+            let status = session.wait_timeout(Duration::from_secs(1)).unwrap();
+            assert_eq!(status, Some(ExitStatus::from_code(0)));
+            assert_eq!(session.poll_event().unwrap(), None);
+        }
+
+        #[cfg(feature = "async")]
+        futures_lite::future::block_on(async {
+            use futures_lite::StreamExt;
+
+            let mut stream = session.events();
+
+            let line = session.read_until_timeout(b'\n', None).await.unwrap();
+            assert_eq!(line, b"Hello");
+            assert!(matches!(session.check("World").await, Err(Error::Eof)));
+            assert_eq!(stream.next().await, Some(SessionEvent::Eof));
+
+            let status = session.wait_timeout(Duration::from_secs(1)).await.unwrap();
+            assert_eq!(status, Some(ExitStatus::from_code(0)));
+            assert_eq!(
+                stream.next().await,
+                Some(SessionEvent::Exited(ExitStatus::from_code(0)))
+            );
+            assert_eq!(stream.next().await, None);
+            assert_eq!(session.poll_event().await.unwrap(), None);
+        });
+
+        assert_eq!(
+            *events.lock().unwrap(),
+            [
+                SessionEvent::Eof,
+                SessionEvent::Exited(ExitStatus::from_code(0))
+            ]
+        );
+    }
 }
@@ -2,16 +2,34 @@
 
 use std::{
     io::{self, BufRead, BufReader, Read, Write},
+    thread,
     time::{self, Duration},
 };
 
+use super::{
+    event::{Events, SessionEvent},
+    watchdog::{Watch, Watchdog},
+    Echo, EchoMatch, Found, Tee,
+};
 use crate::{
+    captures::CapturesRef,
     error::Error,
-    needle::Needle,
-    process::{Healthcheck, NonBlocking},
-    Captures,
+    needle::{Line, Needle},
+    policy::{AnswerPolicy, RetryPolicy},
+    process::{ExitStatus, Healthcheck, NonBlocking, Termination},
+    secret::Password,
+    stream::log::{written_bytes, LogClock, LogFormat, Logger},
+    Captures, ControlCode, Stats, Timeline,
 };
 
+/// A size of a buffer used by [Session::set_write_buffering].
+const WRITE_BUFFER_CAPACITY: usize = 8 * 1024;
+
+#[cfg(windows)]
+const LINE_ENDING: &[u8] = b"\r\n";
+#[cfg(not(windows))]
+const LINE_ENDING: &[u8] = b"\n";
+
 /// Session represents a spawned process and its streams.
 /// It controlls process and communication with it.
 #[derive(Debug)]
@@ -20,6 +38,14 @@ pub struct Session<P = super::OsProcess, S = super::OsProcessStream> {
     stream: TryStream<S>,
     expect_timeout: Option<Duration>,
     expect_lazy: bool,
+    skip_echo: bool,
+    echo: Echo,
+    send_delay: Option<Duration>,
+    events: Events,
+    watchdog: Watchdog,
+    name: Option<String>,
+    #[cfg(unix)]
+    stderr: Option<Box<super::StderrSession>>,
 }
 
 impl<P, S> Session<P, S>
@@ -32,8 +58,16 @@ where
         Ok(Self {
             proc: process,
             stream,
-            expect_timeout: Some(Duration::from_millis(10000)),
+            expect_timeout: super::default_expect_timeout(),
             expect_lazy: false,
+            skip_echo: false,
+            echo: Echo::default(),
+            send_delay: None,
+            events: Events::default(),
+            watchdog: Watchdog::default(),
+            name: None,
+            #[cfg(unix)]
+            stderr: None,
         })
     }
 
@@ -42,24 +76,67 @@ where
         F: FnOnce(S) -> R,
         R: Read,
     {
+        self.stream.flush_pending_writes()?;
         self.stream.flush_in_buffer();
         let buf = self.stream.get_available().to_owned();
+        let stats = *self.stream.stats_mut();
+        let logger = std::mem::take(self.stream.logger_mut());
+        let tee = std::mem::take(self.stream.tee_mut());
 
         let stream = self.stream.into_inner();
         let new_stream = new_stream(stream);
 
         let mut session = Session::new(self.proc, new_stream)?;
         session.stream.keep_in_buffer(&buf);
+        *session.stream.stats_mut() = stats;
+        *session.stream.logger_mut() = logger;
+        *session.stream.tee_mut() = tee;
+        session.events = self.events;
+        session.watchdog = self.watchdog;
+        session.name = self.name;
+        #[cfg(unix)]
+        {
+            session.stderr = self.stderr;
+        }
         Ok(session)
     }
+
+    /// Splits the session into a process, a stream and not yet consumed bytes.
+    #[cfg(unix)]
+    pub(crate) fn into_parts(mut self) -> (P, S, Vec<u8>) {
+        self.stream.flush_in_buffer();
+        let buf = self.stream.get_available().to_owned();
+        (self.proc, self.stream.into_inner(), buf)
+    }
+
+    /// Puts bytes back in the buffer so they will be read first.
+    #[cfg(unix)]
+    pub(crate) fn keep_in_buffer(&mut self, buf: &[u8]) {
+        self.stream.keep_in_buffer(buf);
+    }
 }
 
 impl<P, S> Session<P, S> {
+    #[cfg(unix)]
+    pub(crate) fn set_stderr(&mut self, stderr: super::StderrSession) {
+        self.stderr = Some(Box::new(stderr));
+    }
+
+    #[cfg(unix)]
+    pub(crate) fn stderr_mut(&mut self) -> Option<&mut super::StderrSession> {
+        self.stderr.as_deref_mut()
+    }
+
     /// Set the pty session's expect timeout.
     pub fn set_expect_timeout(&mut self, expect_timeout: Option<Duration>) {
         self.expect_timeout = expect_timeout;
     }
 
+    /// Get the pty session's expect timeout.
+    pub fn get_expect_timeout(&self) -> Option<Duration> {
+        self.expect_timeout
+    }
+
     /// Set a expect algorithm to be either gready or lazy.
     ///
     /// Default algorithm is gready.
@@ -69,6 +146,183 @@ impl<P, S> Session<P, S> {
         self.expect_lazy = lazy;
     }
 
+    #[cfg(unix)]
+    pub(crate) fn is_expect_lazy(&self) -> bool {
+        self.expect_lazy
+    }
+
+    /// Sets whether an echo of lines sent by [Session::send_line] must be skipped.
+    ///
+    /// If it's set, the echo is consumed before matching or reading the output,
+    /// so a needle can't be accidentally matched against a sent line.
+    /// It applies to expect, check and read calls.
+    ///
+    /// The echo is consumed only if the output starts with it,
+    /// otherwise it's ignored.
+    /// A line ending of the echo matches both `\n` and `\r\n`.
+    pub fn set_skip_echo(&mut self, skip: bool) {
+        self.skip_echo = skip;
+        self.echo.clear();
+    }
+
+    /// Sets whether `\r\n` in the output is replaced by `\n`.
+    ///
+    /// A terminal translates `\n` to `\r\n` on output,
+    /// so it allows to write needles with `\n` and get normalized [Captures].
+    ///
+    /// It affects only the output read after the call and the output which is already buffered.
+    /// Bytes read directly via [Read] are not normalized.
+    ///
+    /// By default it's off, so the output is byte exact.
+    pub fn set_normalize_newlines(&mut self, on: bool) {
+        self.stream.set_normalize_newlines(on);
+    }
+
+    /// Sets a delay which is made after each character sent by [Session::send] and [Session::send_line].
+    ///
+    /// By default there's no delay.
+    ///
+    /// See [Session::send_slow].
+    pub fn set_send_delay(&mut self, delay: Option<Duration>) {
+        self.send_delay = delay;
+    }
+
+    /// Sets a maximum number of bytes read from a process at once.
+    ///
+    /// A read buffer of this size is allocated once and reused by all reads.
+    /// A size of `0` is treated as `1`.
+    ///
+    /// By default it's 4096 bytes.
+    pub fn set_read_chunk_size(&mut self, size: usize) {
+        self.stream.set_read_chunk_size(size);
+    }
+
+    /// Get a delay which is made after each sent character.
+    pub fn get_send_delay(&self) -> Option<Duration> {
+        self.send_delay
+    }
+
+    /// Sets a name of the session.
+    ///
+    /// The name is added to each line of a log set by [Session::set_log] or [log](crate::session::log)
+    /// and to messages of errors returned by [Session::expect], [Session::expect_ref] and [Session::expect_ctx].
+    /// It helps to tell sessions apart when a few of them are run at once.
+    ///
+    /// A kind of an error is not changed, e.g. [Error::Eof] and [Error::ExpectTimeout] are returned as they are.
+    pub fn set_name<N: Into<String>>(&mut self, name: N) {
+        let name = name.into();
+        self.stream.logger_mut().set_name(Some(name.clone()));
+        self.name = Some(name);
+    }
+
+    /// Returns a name of the session.
+    ///
+    /// See [Session::set_name].
+    pub fn get_name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Adds a name of the session to a message of an error if it's set.
+    fn named<T>(&self, result: Result<T, Error>) -> Result<T, Error> {
+        match &self.name {
+            Some(name) => result.map_err(|err| err.with_name(name)),
+            None => result,
+        }
+    }
+
+    /// Returns counters of the session activity.
+    ///
+    /// See [Stats].
+    pub fn stats(&self) -> &Stats {
+        self.stream.stats()
+    }
+
+    /// Resets all counters of the session activity.
+    pub fn reset_stats(&mut self) {
+        *self.stream.stats_mut() = Stats::default();
+    }
+
+    /// Sets a logger which writes each read/write operation of the session into a given writer.
+    ///
+    /// It replaces a previously set logger.
+    /// Unlike [crate::session::log] it doesn't change a type of the session,
+    /// so the logger can be removed later by [Session::unset_log].
+    pub fn set_log<W>(&mut self, dst: W)
+    where
+        W: Write + Send + 'static,
+    {
+        self.stream.logger_mut().set_sink(Box::new(dst));
+    }
+
+    /// Mirrors all output read from a process to a given writer, e.g. a file or a channel.
+    ///
+    /// Unlike [Session::set_log] bytes are written as they are,
+    /// and matching proceeds as usual.
+    /// It replaces a previously set writer.
+    ///
+    /// The writer is dropped after the first error it returns.
+    pub fn tee_output<W>(&mut self, dst: W)
+    where
+        W: Write + Send + 'static,
+    {
+        self.stream.tee_mut().set_sink(Some(Box::new(dst)));
+    }
+
+    /// Stops mirroring the output set by [Session::tee_output].
+    pub fn unset_tee_output(&mut self) {
+        self.stream.tee_mut().set_sink(None);
+    }
+
+    /// Sets a format of the log.
+    ///
+    /// See [Session::set_log].
+    pub fn set_log_format(&mut self, format: LogFormat) {
+        self.stream.logger_mut().set_format(format);
+    }
+
+    /// Sets a clock each line of the log is timestamped by.
+    ///
+    /// See [Session::set_log].
+    pub fn set_log_clock(&mut self, clock: LogClock) {
+        self.stream.logger_mut().set_clock(clock);
+    }
+
+    /// Removes a logger set by [Session::set_log].
+    pub fn unset_log(&mut self) {
+        self.stream.logger_mut().unset_sink();
+    }
+
+    /// Stops logging until [Session::resume_log] is called.
+    ///
+    /// It's useful to exclude noisy or sensitive parts, e.g. a password entry.
+    pub fn pause_log(&mut self) {
+        self.stream.logger_mut().set_paused(true);
+    }
+
+    /// Resumes logging stopped by [Session::pause_log].
+    pub fn resume_log(&mut self) {
+        self.stream.logger_mut().set_paused(false);
+    }
+
+    /// Registers a callback which is called on each [SessionEvent].
+    ///
+    /// Events are reported as soon as the session observes them:
+    /// EOF by any read, e.g. [Session::expect] or [Session::check],
+    /// and an exit by [Session::wait_timeout], [Session::shutdown] or [Session::poll_event].
+    pub fn on_event<F>(&mut self, callback: F)
+    where
+        F: FnMut(&SessionEvent) + Send + 'static,
+    {
+        self.events.set_callback(Box::new(callback));
+    }
+
+    /// Sets a [Watchdog] which warns about slow expect calls.
+    ///
+    /// It's checked by [Session::expect] and [Session::expect_ref].
+    pub fn set_watchdog(&mut self, watchdog: Watchdog) {
+        self.watchdog = watchdog;
+    }
+
     /// Get a reference to original stream.
     pub fn get_stream(&self) -> &S {
         self.stream.as_ref()
@@ -97,6 +351,86 @@ impl<P: Healthcheck, S> Session<P, S> {
     }
 }
 
+impl<P: Termination, S: Read + NonBlocking> Session<P, S> {
+    /// Checks whether a process closed its side of the stream or exited.
+    ///
+    /// It doesn't block.
+    /// Each event is returned only once, and a callback registered by [Session::on_event] is called for it.
+    ///
+    /// Output read while checking for EOF is kept in the buffer.
+    pub fn poll_event(&mut self) -> Result<Option<SessionEvent>, Error> {
+        if !self.events.is_eof_reported() && self.read_available()? {
+            return Ok(Some(SessionEvent::Eof));
+        }
+
+        if !self.events.is_exit_reported() {
+            if let Some(status) = self.check_exit()? {
+                return Ok(Some(SessionEvent::Exited(status)));
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+impl<P: Termination, S> Session<P, S> {
+    /// Checks whether a process exited and reports it once it did.
+    fn check_exit(&mut self) -> io::Result<Option<ExitStatus>> {
+        let status = self.proc.try_wait()?;
+        if let Some(status) = status {
+            self.events.report_exit(status);
+        }
+
+        Ok(status)
+    }
+
+    /// Waits until a process exits, but not longer than a given timeout.
+    ///
+    /// It returns [None] if the process is still running.
+    pub fn wait_timeout(&mut self, timeout: Duration) -> Result<Option<ExitStatus>, Error> {
+        let start = time::Instant::now();
+        loop {
+            if let Some(status) = self.check_exit()? {
+                return Ok(Some(status));
+            }
+
+            let left = timeout.saturating_sub(start.elapsed());
+            if left.is_zero() {
+                return Ok(None);
+            }
+
+            self.proc.wait_exit(Some(left))?;
+        }
+    }
+
+    /// Shutdown asks a process to terminate and waits until it exits.
+    ///
+    /// If the process is still alive after a given timeout it's killed.
+    ///
+    /// It returns an exit status of the process.
+    /// If the process has already exited it's not signaled.
+    pub fn shutdown(&mut self, timeout: Duration) -> Result<ExitStatus, Error> {
+        if let Some(status) = self.check_exit()? {
+            return Ok(status);
+        }
+
+        self.proc.terminate(false)?;
+        if let Some(status) = self.wait_timeout(timeout)? {
+            return Ok(status);
+        }
+
+        self.proc.terminate(true)?;
+
+        loop {
+            if let Some(status) = self.check_exit()? {
+                return Ok(status);
+            }
+
+            self.proc.wait_exit(None)?;
+        }
+    }
+}
+
 impl<P, S: Read + NonBlocking> Session<P, S> {
     /// Expect waits until a pattern is matched.
     ///
@@ -140,31 +474,224 @@ impl<P, S: Read + NonBlocking> Session<P, S> {
     where
         N: Needle,
     {
-        match self.expect_lazy {
-            true => self.expect_lazy(needle),
-            false => self.expect_gready(needle),
+        let result = self.expect_captures(&needle);
+        self.named(result)
+    }
+
+    /// Expects a needle and accounts the result, see [Session::expect].
+    fn expect_captures<N>(&mut self, needle: &N) -> Result<Captures, Error>
+    where
+        N: Needle,
+    {
+        let start = time::Instant::now();
+
+        let result = self
+            .consume_echo(start)
+            .and_then(|_| match self.expect_lazy {
+                true => self.expect_lazy(needle, start),
+                false => self.expect_gready(needle, start),
+            })
+            .map(|captures| captures.with_elapsed(start.elapsed()));
+
+        self.on_expect(needle, &result, start.elapsed());
+
+        result
+    }
+
+    /// Expect waits until a pattern is matched, just like [Session::expect],
+    /// but the returned [CapturesRef] borrows bytes from the session buffer instead of copying them.
+    ///
+    /// It's handy when a match is big, e.g. a content of a large file.
+    /// The bytes are consumed once [CapturesRef] is dropped.
+    ///
+    /// # Example
+    ///
+    #[cfg_attr(windows, doc = "```no_run")]
+    #[cfg_attr(unix, doc = "```")]
+    /// let mut p = expectrl::spawn("echo 123").unwrap();
+    /// let m = p.expect_ref(expectrl::Regex("\\d+")).unwrap();
+    /// assert_eq!(m.get(0).unwrap(), b"123");
+    /// ```
+    pub fn expect_ref<N>(&mut self, needle: N) -> Result<CapturesRef<'_>, Error>
+    where
+        N: Needle,
+    {
+        let start = time::Instant::now();
+
+        let result = self
+            .consume_echo(start)
+            .and_then(|_| match self.expect_lazy {
+                true => self.find_lazy(&needle, start),
+                false => self.find_gready(&needle, start),
+            });
+
+        self.on_expect(&needle, &result, start.elapsed());
+
+        let found = self.named(result)?;
+        let buffer = self.stream.buffer_mut();
+        Ok(found
+            .into_captures_ref(buffer)
+            .with_elapsed(start.elapsed()))
+    }
+
+    /// Returns how long an expect call may wait for the output,
+    /// so neither a timeout nor a watchdog warning are missed.
+    fn wait_limit(&self, start: time::Instant, watch: &Watch) -> Option<Duration> {
+        let timeout = remaining(start, self.expect_timeout);
+        match (timeout, self.watchdog.time_left(watch)) {
+            (Some(timeout), Some(left)) => Some(std::cmp::min(timeout, left)),
+            (timeout, left) => timeout.or(left),
+        }
+    }
+
+    /// Accounts a result of an expect call.
+    fn on_expect<N, T>(&mut self, needle: &N, result: &Result<T, Error>, elapsed: Duration)
+    where
+        N: Needle,
+    {
+        self.stream.stats_mut().add_expect(result, elapsed);
+
+        if let Err(Error::ExpectTimeout) = result {
+            let text = format!("waiting for {}", needle.description());
+            self.stream.logger_mut().log_event("timeout", &text);
         }
     }
 
+    /// Expect waits until a pattern is matched, just like [Session::expect],
+    /// but an error is labeled with a given name of the step.
+    ///
+    /// It helps to find out which step of a long dialogue failed.
+    /// An underlying error can be obtained by [Error::root].
+    ///
+    /// # Example
+    ///
+    /// ```no_run,ignore
+    /// let mut p = expectrl::spawn("login").unwrap();
+    /// p.expect_ctx("waiting for login prompt", "login:").unwrap();
+    /// ```
+    pub fn expect_ctx<L, N>(&mut self, label: L, needle: N) -> Result<Captures, Error>
+    where
+        L: Into<String>,
+        N: Needle,
+    {
+        let result = self
+            .expect_captures(&needle)
+            .map_err(|err| err.context(label));
+        self.named(result)
+    }
+
+    /// Consumes an echo of sent lines if the output starts with it.
+    ///
+    /// It waits for the rest of the echo until the expect timeout counted from `start` is reached.
+    ///
+    /// See [Session::set_skip_echo].
+    fn consume_echo(&mut self, start: time::Instant) -> Result<(), Error> {
+        self.consume_echo_until(start, self.expect_timeout)
+    }
+
+    /// Consumes an echo of sent lines waiting for it no longer than a given timeout.
+    fn consume_echo_until(
+        &mut self,
+        start: time::Instant,
+        timeout: Option<Duration>,
+    ) -> Result<(), Error> {
+        loop {
+            if self.echo.is_empty() {
+                return Ok(());
+            }
+
+            let eof = self.read_available()?;
+            if self.skip_available_echo(eof) {
+                return Ok(());
+            }
+
+            if let Some(timeout) = timeout {
+                if start.elapsed() > timeout {
+                    return Err(Error::ExpectTimeout);
+                }
+            }
+
+            self.stream.wait_readable(tick(remaining(start, timeout)))?;
+        }
+    }
+
+    /// Consumes an echo of sent lines if the available output starts with it.
+    ///
+    /// It returns `false` if only a part of the echo is available yet.
+    fn skip_available_echo(&mut self, eof: bool) -> bool {
+        if self.echo.is_empty() {
+            return true;
+        }
+
+        match self.echo.check(self.stream.get_available()) {
+            EchoMatch::Complete(n) => {
+                self.stream.consume_available(n);
+                true
+            }
+            EchoMatch::Mismatch => true,
+            EchoMatch::Partial if eof => {
+                self.echo.clear();
+                true
+            }
+            EchoMatch::Partial => false,
+        }
+    }
+
+    /// Reads all bytes available at the moment and reports EOF once it's reached.
+    fn read_available(&mut self) -> io::Result<bool> {
+        let eof = self.stream.read_available()?;
+        if eof {
+            self.events.report_eof();
+        }
+
+        Ok(eof)
+    }
+
     /// Expect which fills as much as possible to the buffer.
     ///
     /// See [Session::expect].
-    fn expect_gready<N>(&mut self, needle: N) -> Result<Captures, Error>
+    fn expect_gready<N>(&mut self, needle: N, start: time::Instant) -> Result<Captures, Error>
     where
         N: Needle,
     {
-        let start = time::Instant::now();
+        let found = self.find_gready(needle, start)?;
+        Ok(self.take_found(found))
+    }
+
+    /// Expect which reads byte by byte.
+    ///
+    /// See [Session::expect].
+    fn expect_lazy<N>(&mut self, needle: N, start: time::Instant) -> Result<Captures, Error>
+    where
+        N: Needle,
+    {
+        let found = self.find_lazy(needle, start)?;
+        Ok(self.take_found(found))
+    }
+
+    /// Copies bytes involved in a match and consumes them.
+    fn take_found(&mut self, found: Found) -> Captures {
+        let end = found.end;
+        let captures = found.into_captures(self.stream.get_available());
+        self.stream.consume_available(end);
+        captures
+    }
+
+    /// Looks for a match filling as much as possible to the buffer.
+    ///
+    /// The match is not consumed.
+    fn find_gready<N>(&mut self, needle: N, start: time::Instant) -> Result<Found, Error>
+    where
+        N: Needle,
+    {
+        let mut watch = Watch::new();
         loop {
-            let eof = self.stream.read_available()?;
+            let eof = self.read_available()?;
             let data = self.stream.get_available();
 
             let found = needle.check(data, eof)?;
             if !found.is_empty() {
-                let end_index = Captures::right_most_index(&found);
-                let involved_bytes = data[..end_index].to_vec();
-                self.stream.consume_available(end_index);
-
-                return Ok(Captures::new(involved_bytes, found));
+                return Ok(Found::new(found, data.len()));
             }
 
             if eof {
@@ -176,20 +703,28 @@ impl<P, S: Read + NonBlocking> Session<P, S> {
                     return Err(Error::ExpectTimeout);
                 }
             }
+
+            let buffered = data.len();
+            self.watchdog
+                .check(&mut watch, &needle, buffered, self.stream.logger_mut());
+
+            self.stream
+                .wait_readable(tick(self.wait_limit(start, &watch)))?;
         }
     }
 
-    /// Expect which reads byte by byte.
+    /// Looks for a match reading byte by byte.
     ///
-    /// See [Session::expect].
-    fn expect_lazy<N>(&mut self, needle: N) -> Result<Captures, Error>
+    /// The match is not consumed.
+    fn find_lazy<N>(&mut self, needle: N, start: time::Instant) -> Result<Found, Error>
     where
         N: Needle,
     {
         let mut checking_data_length = 0;
         let mut eof = false;
-        let start = time::Instant::now();
+        let mut watch = Watch::new();
         loop {
+            let mut is_drained = false;
             let mut available = self.stream.get_available();
             if checking_data_length == available.len() {
                 // We read by byte to make things as lazy as possible.
@@ -205,7 +740,12 @@ impl<P, S: Read + NonBlocking> Session<P, S> {
                 // We could read all data available via `read_available` to reduce IO operations,
                 // but in such case we would need to keep a EOF indicator internally in stream,
                 // which is OK if EOF happens onces, but I am not sure if this is a case.
-                eof = self.stream.read_available_once(&mut [0; 1])? == Some(0);
+                let n = self.stream.read_available_once(&mut [0; 1])?;
+                eof = n == Some(0);
+                if eof {
+                    self.events.report_eof();
+                }
+                is_drained = n.is_none();
                 available = self.stream.get_available();
             }
 
@@ -220,9 +760,95 @@ impl<P, S: Read + NonBlocking> Session<P, S> {
 
             let found = needle.check(data, eof)?;
             if !found.is_empty() {
+                return Ok(Found::new(found, checking_data_length));
+            }
+
+            if eof {
+                return Err(Error::Eof);
+            }
+
+            if let Some(timeout) = self.expect_timeout {
+                if start.elapsed() > timeout {
+                    return Err(Error::ExpectTimeout);
+                }
+            }
+
+            let buffered = available.len();
+            self.watchdog
+                .check(&mut watch, &needle, buffered, self.stream.logger_mut());
+
+            // there's no point to check the same data again till something arrives
+            if is_drained {
+                self.stream
+                    .wait_readable(tick(self.wait_limit(start, &watch)))?;
+            }
+        }
+    }
+
+    /// Expect idle waits until no new output arrives for a given period of time.
+    ///
+    /// It's useful to wait until a TUI application finishes redrawing.
+    /// The output is not consumed, so it can be inspected afterwards, e.g. by [Session::buffer].
+    ///
+    /// It returns once EOF is reached as no more output can arrive.
+    ///
+    /// It returns an error if timeout is reached.
+    /// You can specify a timeout value by [Session::set_expect_timeout] method.
+    pub fn expect_idle(&mut self, idle: Duration) -> Result<(), Error> {
+        let start = time::Instant::now();
+        let mut last_output = start;
+        let mut length = self.stream.get_available().len();
+        loop {
+            let eof = self.read_available()?;
+
+            let available = self.stream.get_available().len();
+            if available != length {
+                length = available;
+                last_output = time::Instant::now();
+            }
+
+            if eof || last_output.elapsed() >= idle {
+                return Ok(());
+            }
+
+            if let Some(timeout) = self.expect_timeout {
+                if start.elapsed() > timeout {
+                    return Err(Error::ExpectTimeout);
+                }
+            }
+
+            let idle_left = idle.saturating_sub(last_output.elapsed());
+            let wait = match remaining(start, self.expect_timeout) {
+                Some(timeout_left) => std::cmp::min(idle_left, timeout_left),
+                None => idle_left,
+            };
+            self.stream.wait_readable(Some(wait))?;
+        }
+    }
+
+    /// Expect timeline waits until all needles registered in a [Timeline] are matched.
+    ///
+    /// Needles may be matched in any order,
+    /// the time of each match is recorded in the timeline.
+    ///
+    /// On success all bytes up to the right most match are consumed.
+    ///
+    /// It returns an error if timeout is reached.
+    /// You can specify a timeout value by [Session::set_expect_timeout] method.
+    pub fn expect_timeline(&mut self, timeline: &mut Timeline) -> Result<Captures, Error> {
+        let start = time::Instant::now();
+        self.consume_echo(start)?;
+        loop {
+            let eof = self.read_available()?;
+            let data = self.stream.get_available();
+
+            timeline.check(data, eof)?;
+            if timeline.is_complete() {
+                let found = timeline.matches();
                 let end_index = Captures::right_most_index(&found);
                 let involved_bytes = data[..end_index].to_vec();
                 self.stream.consume_available(end_index);
+
                 return Ok(Captures::new(involved_bytes, found));
             }
 
@@ -235,6 +861,159 @@ impl<P, S: Read + NonBlocking> Session<P, S> {
                     return Err(Error::ExpectTimeout);
                 }
             }
+
+            self.stream
+                .wait_readable(tick(remaining(start, self.expect_timeout)))?;
+        }
+    }
+
+    /// Expect line waits until a complete line is available and returns it.
+    ///
+    /// A line ending (`\n` or `\r\n`) is not included in the returned line.
+    /// If EOF is reached the rest of the output is considered to be the last line.
+    ///
+    /// # Example
+    ///
+    #[cfg_attr(windows, doc = "```no_run")]
+    #[cfg_attr(unix, doc = "```")]
+    /// let mut p = expectrl::spawn("echo 123").unwrap();
+    /// assert_eq!(p.expect_line().unwrap(), "123");
+    /// ```
+    ///
+    /// It returns an error if timeout is reached.
+    /// You can specify a timeout value by [Session::set_expect_timeout] method.
+    pub fn expect_line(&mut self) -> Result<String, Error> {
+        let captures = self.expect(Line)?;
+        Ok(Line::text(&captures))
+    }
+
+    /// Returns an iterator over lines of the output.
+    ///
+    /// Each line is read via [Session::expect_line] so the timeout is applied per line.
+    /// The iterator ends when EOF is reached.
+    ///
+    /// # Example
+    ///
+    #[cfg_attr(windows, doc = "```no_run")]
+    #[cfg_attr(unix, doc = "```")]
+    /// let mut p = expectrl::spawn("printf '1\\n2\\n3'").unwrap();
+    /// let lines = p.lines().collect::<Result<Vec<_>, _>>().unwrap();
+    /// assert_eq!(lines, ["1", "2", "3"]);
+    /// ```
+    pub fn lines(&mut self) -> Lines<'_, P, S> {
+        Lines { session: self }
+    }
+
+    /// Returns an iterator over successive matches of a needle.
+    ///
+    /// Each match is looked up via [Session::expect] so the timeout is applied per match.
+    ///
+    /// Notice that by default a needle may report a few matches found in the output read at once,
+    /// e.g. [Regex](crate::Regex) does, and all of them end up in a single [Captures].
+    /// Use [Session::set_expect_lazy] to get them one by one.
+    /// The iterator ends when EOF is reached.
+    ///
+    /// # Example
+    ///
+    #[cfg_attr(windows, doc = "```no_run")]
+    #[cfg_attr(unix, doc = "```")]
+    /// use expectrl::Regex;
+    ///
+    /// let mut p = expectrl::spawn("echo 'ok: 1 ok: 2 failed: 3 ok: 4'").unwrap();
+    /// p.set_expect_lazy(true);
+    ///
+    /// let found = p
+    ///     .matches(Regex("ok: \\d"))
+    ///     .map(|m| m.map(|m| m.get(0).unwrap().to_vec()))
+    ///     .collect::<Result<Vec<_>, _>>()
+    ///     .unwrap();
+    /// assert_eq!(found, [b"ok: 1", b"ok: 2", b"ok: 4"]);
+    /// ```
+    pub fn matches<N: Needle>(&mut self, needle: N) -> Matches<'_, N, P, S> {
+        Matches {
+            session: self,
+            needle,
+        }
+    }
+
+    /// Reads until a delimiter is found.
+    ///
+    /// The returned bytes include the delimiter.
+    /// If EOF is reached the rest of the output is returned.
+    ///
+    /// If a timeout is not set the one from [Session::set_expect_timeout] is used.
+    /// On timeout nothing is consumed.
+    pub fn read_until_timeout(
+        &mut self,
+        delim: u8,
+        timeout: Option<Duration>,
+    ) -> Result<Vec<u8>, Error> {
+        let timeout = timeout.or(self.expect_timeout);
+        let start = time::Instant::now();
+        self.consume_echo_until(start, timeout)?;
+        loop {
+            let eof = self.read_available()?;
+            let data = self.stream.get_available();
+
+            let end = match data.iter().position(|&b| b == delim) {
+                Some(pos) => Some(pos + 1),
+                None if eof && !data.is_empty() => Some(data.len()),
+                None => None,
+            };
+
+            if let Some(end) = end {
+                let buf = data[..end].to_vec();
+                self.stream.consume_available(end);
+                return Ok(buf);
+            }
+
+            if eof {
+                return Err(Error::Eof);
+            }
+
+            if let Some(timeout) = timeout {
+                if start.elapsed() > timeout {
+                    return Err(Error::ExpectTimeout);
+                }
+            }
+
+            self.stream.wait_readable(remaining(start, timeout))?;
+        }
+    }
+
+    /// Reads exactly enough bytes to fill a buffer.
+    ///
+    /// If a timeout is not set the one from [Session::set_expect_timeout] is used.
+    /// On timeout or EOF nothing is consumed.
+    pub fn read_exact_timeout(
+        &mut self,
+        buf: &mut [u8],
+        timeout: Option<Duration>,
+    ) -> Result<(), Error> {
+        let timeout = timeout.or(self.expect_timeout);
+        let start = time::Instant::now();
+        self.consume_echo_until(start, timeout)?;
+        loop {
+            let eof = self.read_available()?;
+            let data = self.stream.get_available();
+
+            if data.len() >= buf.len() {
+                buf.copy_from_slice(&data[..buf.len()]);
+                self.stream.consume_available(buf.len());
+                return Ok(());
+            }
+
+            if eof {
+                return Err(Error::Eof);
+            }
+
+            if let Some(timeout) = timeout {
+                if start.elapsed() > timeout {
+                    return Err(Error::ExpectTimeout);
+                }
+            }
+
+            self.stream.wait_readable(remaining(start, timeout))?;
         }
     }
 
@@ -252,34 +1031,118 @@ impl<P, S: Read + NonBlocking> Session<P, S> {
     /// use expectrl::{spawn, Regex};
     /// use std::time::Duration;
     ///
-    /// let mut p = spawn("echo 123").unwrap();
+    /// let mut p = spawn("echo 123").unwrap();
+    /// #
+    /// # // wait to guarantee that check echo worked out (most likely)
+    /// # std::thread::sleep(Duration::from_millis(500));
+    /// #
+    /// let m = p.check(Regex("\\d+")).unwrap();
+    /// assert_eq!(m.get(0).unwrap(), b"123");
+    /// ```
+    pub fn check<N>(&mut self, needle: N) -> Result<Captures, Error>
+    where
+        N: Needle,
+    {
+        let eof = self.read_available()?;
+        if !self.skip_available_echo(eof) {
+            return Ok(Captures::new(Vec::new(), Vec::new()));
+        }
+
+        let buf = self.stream.get_available();
+
+        let found = needle.check(buf, eof)?;
+        if !found.is_empty() {
+            let end_index = Captures::right_most_index(&found);
+            let involved_bytes = buf[..end_index].to_vec();
+            self.stream.consume_available(end_index);
+            return Ok(Captures::new(involved_bytes, found));
+        }
+
+        if eof {
+            return Err(Error::Eof);
+        }
+
+        Ok(Captures::new(Vec::new(), Vec::new()))
+    }
+
+    /// Expect nonblocking step advances an expect by a single readiness cycle.
+    ///
+    /// It reads all bytes available at the moment without blocking
+    /// and checks a needle against the buffer.
+    /// If there's no match yet [None] is returned,
+    /// so it can be called again once the stream gets readable.
+    ///
+    /// It's meant for embedding a session into a custom event loop, e.g. `mio` or `epoll` based one,
+    /// where a descriptor of the session is registered for readiness notifications.
+    /// A timeout is not applied, it's up to the loop.
+    ///
+    /// It returns [Error::Eof] if nothing is matched and EOF is reached.
+    pub fn expect_nonblocking_step<N>(&mut self, needle: N) -> Result<Option<Captures>, Error>
+    where
+        N: Needle,
+    {
+        let eof = self.read_available()?;
+        if !self.skip_available_echo(eof) {
+            return Ok(None);
+        }
+
+        let data = self.stream.get_available();
+
+        let found = needle.check(data, eof)?;
+        if !found.is_empty() {
+            let found = Found::new(found, data.len());
+            return Ok(Some(self.take_found(found)));
+        }
+
+        if eof {
+            return Err(Error::Eof);
+        }
+
+        Ok(None)
+    }
+
+    /// Check all looks up every match of a pattern in the bytes available at the moment.
+    ///
+    /// It's the same as calling [Session::check] until nothing is found,
+    /// but it reads only once.
+    /// All bytes up to the last match are consumed.
+    ///
+    /// Notice that a needle may report a few matches at once, e.g. [Regex](crate::Regex) does,
+    /// then all of them end up in a single [Captures].
+    ///
+    /// It returns [Error::Eof] if nothing is found and EOF is reached.
+    ///
+    /// # Example
+    ///
+    #[cfg_attr(any(windows, target_os = "macos"), doc = "```no_run")]
+    #[cfg_attr(not(any(target_os = "macos", windows)), doc = "```")]
+    /// let mut p = expectrl::spawn("echo 'id=1 id=2 id=3'").unwrap();
     /// #
     /// # // wait to guarantee that check echo worked out (most likely)
-    /// # std::thread::sleep(Duration::from_millis(500));
+    /// # std::thread::sleep(std::time::Duration::from_millis(500));
     /// #
-    /// let m = p.check(Regex("\\d+")).unwrap();
-    /// assert_eq!(m.get(0).unwrap(), b"123");
+    /// let found = p.check_all("id=").unwrap();
+    /// assert_eq!(found.len(), 3);
     /// ```
-    pub fn check<N>(&mut self, needle: N) -> Result<Captures, Error>
+    pub fn check_all<N>(&mut self, needle: N) -> Result<Vec<Captures>, Error>
     where
         N: Needle,
     {
-        let eof = self.stream.read_available()?;
+        let eof = self.read_available()?;
+        if !self.skip_available_echo(eof) {
+            return Ok(Vec::new());
+        }
+
         let buf = self.stream.get_available();
 
-        let found = needle.check(buf, eof)?;
-        if !found.is_empty() {
-            let end_index = Captures::right_most_index(&found);
-            let involved_bytes = buf[..end_index].to_vec();
-            self.stream.consume_available(end_index);
-            return Ok(Captures::new(involved_bytes, found));
-        }
+        let (found, consumed) = super::find_all(&needle, buf, eof)?;
+        self.stream.consume_available(consumed);
 
-        if eof {
+        if found.is_empty() && eof {
             return Err(Error::Eof);
         }
 
-        Ok(Captures::new(Vec::new(), Vec::new()))
+        Ok(found)
     }
 
     /// The functions checks if a pattern is matched.
@@ -318,7 +1181,7 @@ impl<P, S: Read + NonBlocking> Session<P, S> {
     where
         N: Needle,
     {
-        let eof = self.stream.read_available()?;
+        let eof = self.read_available()?;
         let buf = self.stream.get_available();
 
         let found = needle.check(buf, eof)?;
@@ -334,7 +1197,63 @@ impl<P, S: Read + NonBlocking> Session<P, S> {
     }
 }
 
+/// An iterator over lines of a [Session] output.
+///
+/// It's created by [Session::lines].
+#[derive(Debug)]
+pub struct Lines<'a, P, S> {
+    session: &'a mut Session<P, S>,
+}
+
+impl<P, S: Read + NonBlocking> Iterator for Lines<'_, P, S> {
+    type Item = Result<String, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.session.expect_line() {
+            Ok(line) => Some(Ok(line)),
+            Err(err) if matches!(err.root(), Error::Eof) => None,
+            Err(err) => Some(Err(err)),
+        }
+    }
+}
+
+/// An iterator over successive matches of a needle in a [Session] output.
+///
+/// It's created by [Session::matches].
+#[derive(Debug)]
+pub struct Matches<'a, N, P, S> {
+    session: &'a mut Session<P, S>,
+    needle: N,
+}
+
+impl<N: Needle, P, S: Read + NonBlocking> Iterator for Matches<'_, N, P, S> {
+    type Item = Result<Captures, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.session.expect(&self.needle) {
+            Ok(captures) => Some(Ok(captures)),
+            Err(err) if matches!(err.root(), Error::Eof) => None,
+            Err(err) => Some(Err(err)),
+        }
+    }
+}
+
 impl<Proc, Stream: Write> Session<Proc, Stream> {
+    /// Sets whether writes are collected in an internal buffer.
+    ///
+    /// It's useful for dialogues which send many small pieces,
+    /// so they're written to a process at once instead of one syscall per piece.
+    ///
+    /// The buffer is written on [Write::flush], once it gets full,
+    /// before the session reads an output and when it's dropped.
+    /// Turning buffering off doesn't discard already buffered bytes,
+    /// they're written on the next write or flush.
+    ///
+    /// By default it's off.
+    pub fn set_write_buffering(&mut self, on: bool) {
+        self.stream.set_write_buffering(on);
+    }
+
     /// Send text to child’s STDIN.
     ///
     /// You can also use methods from [std::io::Write] instead.
@@ -351,7 +1270,62 @@ impl<Proc, Stream: Write> Session<Proc, Stream> {
     /// proc.send(ControlCode::try_from("^C").unwrap());
     /// ```
     pub fn send<B: AsRef<[u8]>>(&mut self, buf: B) -> io::Result<()> {
-        self.stream.write_all(buf.as_ref())
+        match self.send_delay {
+            Some(delay) => self.send_slow(buf, delay),
+            None => self.stream.write_all(buf.as_ref()),
+        }
+    }
+
+    /// Sends text to child's STDIN one character at a time,
+    /// each character is followed by a given delay.
+    ///
+    /// It's useful for serial consoles and bootloaders which drop input arrived in a single burst.
+    ///
+    /// See [Session::set_send_delay] to pace all writes made by [Session::send] and [Session::send_line].
+    pub fn send_slow<B: AsRef<[u8]>>(&mut self, buf: B, delay: Duration) -> io::Result<()> {
+        for c in super::split_chars(buf.as_ref()) {
+            self.stream.write_all(c)?;
+            self.stream.flush()?;
+            thread::sleep(delay);
+        }
+
+        Ok(())
+    }
+
+    /// Sends a list of pieces coalescing them into as few writes as possible.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mut proc = expectrl::spawn("cat").unwrap();
+    /// proc.send_all(&["ls", " -l", "\n"]).unwrap();
+    /// ```
+    pub fn send_all<B: AsRef<[u8]>>(&mut self, bufs: &[B]) -> io::Result<()> {
+        if let Some(delay) = self.send_delay {
+            for buf in bufs {
+                self.send_slow(buf, delay)?;
+            }
+
+            return Ok(());
+        }
+
+        let mut slices = bufs
+            .iter()
+            .map(|buf| io::IoSlice::new(buf.as_ref()))
+            .collect::<Vec<_>>();
+        let mut slices = &mut slices[..];
+        // drops leading empty pieces, as writing them would return 0
+        io::IoSlice::advance_slices(&mut slices, 0);
+        while !slices.is_empty() {
+            match self.stream.write_vectored(slices) {
+                Ok(0) => return Err(io::ErrorKind::WriteZero.into()),
+                Ok(n) => io::IoSlice::advance_slices(&mut slices, n),
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                Err(err) => return Err(err),
+            }
+        }
+
+        Ok(())
     }
 
     /// Send a line to child’s STDIN.
@@ -368,15 +1342,154 @@ impl<Proc, Stream: Write> Session<Proc, Stream> {
     /// proc.send_line(ControlCode::try_from("^C").unwrap());
     /// ```
     pub fn send_line<B: AsRef<[u8]>>(&mut self, buf: B) -> io::Result<()> {
+        self.send(buf.as_ref())?;
+        self.send(LINE_ENDING)?;
+
+        if self.skip_echo {
+            self.echo.push_line(buf.as_ref());
+        }
+
+        Ok(())
+    }
+
+    /// Sends a secret, e.g. a password, followed by a line ending.
+    ///
+    /// Unlike [Session::send_line] the secret is never passed to a logger set by [Session::set_log],
+    /// nor recorded by a [`LogStream`] or an [`AsciicastStream`] the session is built on,
+    /// and it's not kept in memory to skip its echo, as passwords are usually not echoed.
+    /// It's written right from a given reference, bypassing a write buffer,
+    /// so no copies of it are left behind.
+    ///
+    /// With the `secrecy` feature a `secrecy::SecretString` can be passed.
+    ///
+    /// [`LogStream`]: crate::stream::log::LogStream
+    /// [`AsciicastStream`]: crate::stream::asciicast::AsciicastStream
+    pub fn send_secret<PW: Password + ?Sized>(&mut self, secret: &PW) -> io::Result<()> {
+        // buffered bytes are not a secret, so they're written apart to be logged
+        self.stream.flush_write_buffer()?;
+
+        let secret = secret.expose().as_bytes();
+        crate::secret::write_secret(|| match self.send_delay {
+            Some(delay) => {
+                for c in super::split_chars(secret) {
+                    self.stream.write_all_unbuffered(c)?;
+                    self.stream.flush()?;
+                    thread::sleep(delay);
+                }
+
+                Ok(())
+            }
+            None => self.stream.write_all_unbuffered(secret),
+        })?;
+
+        self.send(LINE_ENDING)
+    }
+
+    /// Sends an interrupt character (`^C`) to the child,
+    /// which usually makes a terminal deliver `SIGINT` to a foreground process.
+    pub fn send_intr(&mut self) -> io::Result<()> {
+        self.send(ControlCode::EndOfText)
+    }
+
+    /// Sends an end of file character to the child.
+    ///
+    /// It's `^D` on unix and `^Z` on windows.
+    /// A child's `read` returns 0 bytes if it's sent at the beginning of a line.
+    pub fn send_eof(&mut self) -> io::Result<()> {
         #[cfg(windows)]
-        const LINE_ENDING: &[u8] = b"\r\n";
+        const EOF: ControlCode = ControlCode::Substitute;
         #[cfg(not(windows))]
-        const LINE_ENDING: &[u8] = b"\n";
+        const EOF: ControlCode = ControlCode::EndOfTransmission;
 
-        self.stream.write_all(buf.as_ref())?;
-        self.write_all(LINE_ENDING)?;
+        self.send(EOF)
+    }
+}
 
-        Ok(())
+impl<P, S: Read + NonBlocking + Write> Session<P, S> {
+    /// Expect waits until a pattern is matched,
+    /// answering prompts recognized by a [AnswerPolicy] in the meantime.
+    ///
+    /// A prompt is consumed once it's answered.
+    /// The pattern itself has a priority over prompts.
+    ///
+    /// It returns an error if timeout is reached.
+    /// You can specify a timeout value by [Session::set_expect_timeout] method.
+    pub fn expect_with_policy<N>(
+        &mut self,
+        needle: N,
+        policy: &mut AnswerPolicy,
+    ) -> Result<Captures, Error>
+    where
+        N: Needle,
+    {
+        let start = time::Instant::now();
+        loop {
+            // an answer is sent by send_line so its echo is skipped too
+            self.consume_echo(start)?;
+
+            let eof = self.read_available()?;
+            let data = self.stream.get_available();
+
+            let found = needle.check(data, eof)?;
+            if !found.is_empty() {
+                let end_index = Captures::right_most_index(&found);
+                let involved_bytes = data[..end_index].to_vec();
+                self.stream.consume_available(end_index);
+
+                return Ok(Captures::new(involved_bytes, found));
+            }
+
+            if let Some((end_index, answer)) = policy.check(data, eof)? {
+                self.stream.consume_available(end_index);
+                self.send_line(answer)?;
+                continue;
+            }
+
+            if eof {
+                return Err(Error::Eof);
+            }
+
+            if let Some(timeout) = self.expect_timeout {
+                if start.elapsed() > timeout {
+                    return Err(Error::ExpectTimeout);
+                }
+            }
+
+            self.stream
+                .wait_readable(tick(remaining(start, self.expect_timeout)))?;
+        }
+    }
+
+    /// Expect waits until a pattern is matched,
+    /// repeating the attempt according to a [RetryPolicy] if it's timed out.
+    ///
+    /// Before each retry the policy's backoff delay is made and its probe is sent if it's set.
+    ///
+    /// Each attempt has its own timeout which you can set by [Session::set_expect_timeout].
+    /// It returns an error of the last attempt if all of them failed.
+    pub fn expect_with_retry<N>(
+        &mut self,
+        needle: N,
+        policy: &RetryPolicy,
+    ) -> Result<Captures, Error>
+    where
+        N: Needle,
+    {
+        let mut retries = policy.get_retries();
+        loop {
+            match self.expect(&needle) {
+                Err(err) if matches!(err.root(), Error::ExpectTimeout) && retries > 0 => {
+                    retries -= 1
+                }
+                result => return result,
+            }
+
+            thread::sleep(policy.get_backoff());
+
+            if let Some(probe) = policy.get_probe() {
+                self.send(probe)?;
+            }
+        }
     }
 }
 
@@ -393,6 +1506,63 @@ impl<P, S: Read + NonBlocking> Session<P, S> {
     pub fn is_empty(&mut self) -> io::Result<bool> {
         self.stream.is_empty()
     }
+
+    /// Blocks until an output arrives but not longer than a given limit and a short tick,
+    /// so needles which depend on time are checked again in time.
+    ///
+    /// It's used by [crate::expect!] between rounds of checks.
+    #[doc(hidden)]
+    pub fn __wait_output(&mut self, limit: Option<Duration>) -> io::Result<()> {
+        self.stream.wait_readable(tick(limit))
+    }
+
+    /// Returns bytes which were read but not yet matched by any `expect` call.
+    ///
+    /// It doesn't make any IO calls.
+    pub fn buffer(&mut self) -> &[u8] {
+        self.stream.flush_in_buffer();
+        self.stream.get_available()
+    }
+
+    /// Returns bytes which were read but not yet matched, without consuming them.
+    ///
+    /// It's the same as [Session::buffer].
+    pub fn peek_buffer(&mut self) -> &[u8] {
+        self.buffer()
+    }
+
+    /// Returns lines which were read but not yet matched, without consuming them.
+    ///
+    /// Line endings are stripped and the last line may be incomplete.
+    pub fn peek_lines(&mut self) -> Vec<String> {
+        Line::split(self.buffer())
+    }
+
+    /// Removes and returns all bytes which were read but not yet matched.
+    ///
+    /// It's useful to get a partial data after a timeout, e.g. of a [ByteRange](crate::ByteRange) needle.
+    pub fn take_buffer(&mut self) -> Vec<u8> {
+        self.stream.flush_in_buffer();
+        let buf = self.stream.get_available().to_vec();
+        self.stream.consume_available(buf.len());
+        buf
+    }
+
+    /// Discards all bytes which were read but not yet matched.
+    pub fn clear_buffer(&mut self) {
+        self.stream.flush_in_buffer();
+        let n = self.stream.get_available().len();
+        self.stream.consume_available(n);
+    }
+
+    /// Reads everything which is available at the moment, without blocking,
+    /// and returns the whole buffer.
+    ///
+    /// The bytes are not consumed, so they will be available for a following `expect` call.
+    pub fn peek(&mut self) -> Result<&[u8], Error> {
+        let _ = self.read_available()?;
+        Ok(self.stream.get_available())
+    }
 }
 
 impl<P, S: Write> Write for Session<P, S> {
@@ -411,13 +1581,23 @@ impl<P, S: Write> Write for Session<P, S> {
 
 impl<P, S: Read> Read for Session<P, S> {
     fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
-        self.stream.read(buf)
+        let n = self.stream.read(buf)?;
+        if n == 0 && !buf.is_empty() {
+            self.events.report_eof();
+        }
+
+        Ok(n)
     }
 }
 
 impl<P, S: Read> BufRead for Session<P, S> {
     fn fill_buf(&mut self) -> std::io::Result<&[u8]> {
-        self.stream.fill_buf()
+        let buf = self.stream.fill_buf()?;
+        if buf.is_empty() {
+            self.events.report_eof();
+        }
+
+        Ok(buf)
     }
 
     fn consume(&mut self, amt: usize) {
@@ -425,22 +1605,73 @@ impl<P, S: Read> BufRead for Session<P, S> {
     }
 }
 
+/// Returns how much time is left till a timeout since a start.
+fn remaining(start: time::Instant, timeout: Option<Duration>) -> Option<Duration> {
+    timeout.map(|timeout| timeout.saturating_sub(start.elapsed()))
+}
+
+/// Limits a wait for an output by [super::CHECK_INTERVAL], so a needle is checked again in time.
+fn tick(limit: Option<Duration>) -> Option<Duration> {
+    let interval = super::CHECK_INTERVAL;
+    Some(limit.map_or(interval, |limit| std::cmp::min(limit, interval)))
+}
+
+type WriteFlush<S> = fn(&mut TryStream<S>) -> io::Result<()>;
+
 #[derive(Debug)]
 struct TryStream<S> {
     stream: ControlledReader<S>,
+    write_buffer: Vec<u8>,
+    write_buffering: bool,
+    /// Writes the write buffer, it's set once buffering is turned on.
+    ///
+    /// It lets the buffer be written on reads and on drop, which don't require the stream to be [Write].
+    write_flush: Option<WriteFlush<S>>,
+    read_buffer: Vec<u8>,
 }
 
 impl<S> TryStream<S> {
-    fn into_inner(self) -> S {
-        self.stream.inner.into_inner().inner
+    fn into_inner(mut self) -> S {
+        // the stream is handed over, so bytes which can't be written are dropped
+        let _ = self.flush_pending_writes();
+        self.write_buffer.clear();
+        self.stream.inner.get_mut().take_stream()
     }
 
     fn as_ref(&self) -> &S {
-        &self.stream.inner.get_ref().inner
+        self.stream.inner.get_ref().stream()
     }
 
     fn as_mut(&mut self) -> &mut S {
-        &mut self.stream.inner.get_mut().inner
+        self.stream.inner.get_mut().stream_mut()
+    }
+
+    /// Writes buffered bytes, so a process gets them before its output is awaited.
+    fn flush_pending_writes(&mut self) -> io::Result<()> {
+        match self.write_flush {
+            Some(flush) if !self.write_buffer.is_empty() => flush(self),
+            _ => Ok(()),
+        }
+    }
+
+    fn stats(&self) -> &Stats {
+        &self.stream.inner.get_ref().stats
+    }
+
+    fn stats_mut(&mut self) -> &mut Stats {
+        &mut self.stream.inner.get_mut().stats
+    }
+
+    fn logger_mut(&mut self) -> &mut Logger {
+        &mut self.stream.inner.get_mut().logger
+    }
+
+    fn tee_mut(&mut self) -> &mut Tee {
+        &mut self.stream.inner.get_mut().tee
+    }
+
+    fn buffer_mut(&mut self) -> &mut BufferedReader<S> {
+        self.stream.inner.get_mut()
     }
 }
 
@@ -449,6 +1680,10 @@ impl<S: Read> TryStream<S> {
     fn new(stream: S) -> io::Result<Self> {
         Ok(Self {
             stream: ControlledReader::new(stream),
+            write_buffer: Vec::new(),
+            write_buffering: false,
+            write_flush: None,
+            read_buffer: vec![0; super::DEFAULT_READ_CHUNK_SIZE],
         })
     }
 
@@ -462,6 +1697,14 @@ impl<S> TryStream<S> {
         self.stream.keep_in_buffer(v);
     }
 
+    fn set_normalize_newlines(&mut self, on: bool) {
+        self.stream.set_normalize_newlines(on);
+    }
+
+    fn set_read_chunk_size(&mut self, size: usize) {
+        self.read_buffer = vec![0; std::cmp::max(size, 1)];
+    }
+
     fn get_available(&mut self) -> &[u8] {
         self.stream.get_available()
     }
@@ -476,6 +1719,7 @@ impl<R: Read + NonBlocking> TryStream<R> {
     ///
     /// It raises io::ErrorKind::WouldBlock if there's nothing to read.
     fn try_read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.flush_pending_writes()?;
         self.stream.get_mut().set_non_blocking()?;
 
         let result = self.stream.inner.read(buf);
@@ -498,10 +1742,12 @@ impl<R: Read + NonBlocking> TryStream<R> {
     }
 
     fn read_available(&mut self) -> std::io::Result<bool> {
+        self.flush_pending_writes()?;
         self.stream.flush_in_buffer();
 
-        let mut buf = [0; 248];
-        loop {
+        // the buffer is taken out only to satisfy the borrow checker
+        let mut buf = std::mem::take(&mut self.read_buffer);
+        let result = loop {
             match self.try_read_inner(&mut buf) {
                 Ok(0) => break Ok(true),
                 Ok(n) => {
@@ -510,10 +1756,14 @@ impl<R: Read + NonBlocking> TryStream<R> {
                 Err(err) if err.kind() == io::ErrorKind::WouldBlock => break Ok(false),
                 Err(err) => break Err(err),
             }
-        }
+        };
+        self.read_buffer = buf;
+
+        result
     }
 
     fn read_available_once(&mut self, buf: &mut [u8]) -> std::io::Result<Option<usize>> {
+        self.flush_pending_writes()?;
         self.stream.flush_in_buffer();
 
         match self.try_read_inner(buf) {
@@ -527,11 +1777,27 @@ impl<R: Read + NonBlocking> TryStream<R> {
         }
     }
 
+    /// Blocks until an underlying stream has something to read or a timeout is reached.
+    ///
+    /// It returns right away if some bytes are already buffered.
+    fn wait_readable(&mut self, timeout: Option<Duration>) -> io::Result<()> {
+        self.flush_pending_writes()?;
+
+        if !self.stream.inner.buffer().is_empty() {
+            return Ok(());
+        }
+
+        self.stream.get_mut().wait_readable(timeout)
+    }
+
     // non-buffered && non-blocking read
     fn try_read_inner(&mut self, buf: &mut [u8]) -> io::Result<usize> {
         self.stream.get_mut().set_non_blocking()?;
 
         let result = self.stream.get_mut().read(buf);
+        if let Ok(n) = result {
+            self.stream.inner.get_mut().on_read(&buf[..n]);
+        }
 
         // As file is DUPed changes in one descriptor affects all ones
         // so we need to make blocking file after we finished.
@@ -541,28 +1807,128 @@ impl<R: Read + NonBlocking> TryStream<R> {
     }
 }
 
+impl<S: Write> TryStream<S> {
+    fn set_write_buffering(&mut self, on: bool) {
+        self.write_buffering = on;
+        self.write_flush = Some(Self::flush_write_buffer);
+    }
+
+    /// Writes directly to an underlying stream, bypassing a write buffer.
+    fn write_inner(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.stream.get_mut().write(buf)?;
+        self.stream.inner.get_mut().on_write(&buf[..n]);
+        Ok(n)
+    }
+
+    /// Writes all buffered bytes to an underlying stream.
+    ///
+    /// Bytes which were not written due to an error stay in the buffer.
+    fn flush_write_buffer(&mut self) -> io::Result<()> {
+        let mut buf = std::mem::take(&mut self.write_buffer);
+        let mut written = 0;
+        let mut result = Ok(());
+        while written < buf.len() {
+            match self.write_inner(&buf[written..]) {
+                Ok(0) => {
+                    result = Err(io::ErrorKind::WriteZero.into());
+                    break;
+                }
+                Ok(n) => written += n,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                Err(err) => {
+                    result = Err(err);
+                    break;
+                }
+            }
+        }
+
+        let _ = buf.drain(..written);
+        self.write_buffer = buf;
+
+        result
+    }
+
+    /// Writes all bytes directly to an underlying stream, so they're not kept in a write buffer.
+    ///
+    /// Already buffered bytes are written first to keep an order.
+    fn write_all_unbuffered(&mut self, mut buf: &[u8]) -> io::Result<()> {
+        self.flush_write_buffer()?;
+
+        while !buf.is_empty() {
+            match self.write_inner(buf) {
+                Ok(0) => return Err(io::ErrorKind::WriteZero.into()),
+                Ok(n) => buf = &buf[n..],
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                Err(err) => return Err(err),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<S> Drop for TryStream<S> {
+    fn drop(&mut self) {
+        // a session may be dropped without a flush, e.g. after the last command of a dialogue
+        let _ = self.flush_pending_writes();
+    }
+}
+
 impl<S: Write> Write for TryStream<S> {
     fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
-        self.stream.inner.get_mut().inner.write(buf)
+        if !self.write_buffering {
+            self.flush_write_buffer()?;
+            return self.write_inner(buf);
+        }
+
+        if self.write_buffer.len() + buf.len() > WRITE_BUFFER_CAPACITY {
+            self.flush_write_buffer()?;
+        }
+
+        if buf.len() >= WRITE_BUFFER_CAPACITY {
+            return self.write_inner(buf);
+        }
+
+        self.write_buffer.extend(buf);
+        Ok(buf.len())
     }
 
     fn flush(&mut self) -> io::Result<()> {
-        self.stream.inner.get_mut().inner.flush()
+        self.flush_write_buffer()?;
+        self.stream.get_mut().flush()
     }
 
     fn write_vectored(&mut self, bufs: &[io::IoSlice<'_>]) -> io::Result<usize> {
-        self.stream.inner.get_mut().inner.write_vectored(bufs)
+        let length = bufs.iter().map(|buf| buf.len()).sum::<usize>();
+        if self.write_buffering && self.write_buffer.len() + length <= WRITE_BUFFER_CAPACITY {
+            for buf in bufs {
+                self.write_buffer.extend(buf.iter());
+            }
+
+            return Ok(length);
+        }
+
+        self.flush_write_buffer()?;
+
+        let n = self.stream.get_mut().write_vectored(bufs)?;
+        self.stream
+            .inner
+            .get_mut()
+            .on_write(&written_bytes(bufs, n));
+        Ok(n)
     }
 }
 
 impl<R: Read> Read for TryStream<R> {
     fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.flush_pending_writes()?;
         self.stream.inner.read(buf)
     }
 }
 
 impl<R: Read> BufRead for TryStream<R> {
     fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        self.flush_pending_writes()?;
         self.stream.inner.fill_buf()
     }
 
@@ -588,6 +1954,10 @@ impl<R: Read> ControlledReader<R> {
         // in read operations and the data which was via `keep_in_buffer` function.
         //
         // To eliminate it we move BufReader buffer to our buffer.
+        if self.inner.buffer().is_empty() {
+            return;
+        }
+
         let b = self.inner.buffer().to_vec();
         self.inner.consume(b.len());
         self.keep_in_buffer(&b);
@@ -596,11 +1966,26 @@ impl<R: Read> ControlledReader<R> {
 
 impl<R> ControlledReader<R> {
     fn keep_in_buffer(&mut self, v: &[u8]) {
-        self.inner.get_mut().buffer.extend(v);
+        let reader = self.inner.get_mut();
+        let start = reader.buffer.len();
+        reader.buffer.extend_from_slice(v);
+
+        if reader.normalize_newlines {
+            super::normalize_newlines(&mut reader.buffer, start);
+        }
+    }
+
+    fn set_normalize_newlines(&mut self, on: bool) {
+        let reader = self.inner.get_mut();
+        reader.normalize_newlines = on;
+
+        if on {
+            super::normalize_newlines(&mut reader.buffer, 0);
+        }
     }
 
     fn get_mut(&mut self) -> &mut R {
-        &mut self.inner.get_mut().inner
+        self.inner.get_mut().stream_mut()
     }
 
     fn get_available(&mut self) -> &[u8] {
@@ -614,23 +1999,69 @@ impl<R> ControlledReader<R> {
 
 #[derive(Debug)]
 struct BufferedReader<R> {
-    inner: R,
+    /// An underlying stream, it's only taken out by [TryStream::into_inner].
+    inner: Option<R>,
     buffer: Vec<u8>,
+    normalize_newlines: bool,
+    stats: Stats,
+    logger: Logger,
+    tee: Tee,
 }
 
 impl<R> BufferedReader<R> {
     fn new(reader: R) -> Self {
         Self {
-            inner: reader,
+            inner: Some(reader),
             buffer: Vec::new(),
+            normalize_newlines: false,
+            stats: Stats::default(),
+            logger: Logger::default(),
+            tee: Tee::default(),
         }
     }
+
+    fn stream(&self) -> &R {
+        self.inner.as_ref().expect("the stream is already taken")
+    }
+
+    fn stream_mut(&mut self) -> &mut R {
+        self.inner.as_mut().expect("the stream is already taken")
+    }
+
+    fn take_stream(&mut self) -> R {
+        self.inner.take().expect("the stream is already taken")
+    }
+
+    /// Accounts bytes read from an underlying stream.
+    fn on_read(&mut self, buf: &[u8]) {
+        self.stats.add_read(buf.len());
+        self.logger.log_read(buf);
+        self.tee.write(buf);
+    }
+
+    /// Accounts bytes written to an underlying stream.
+    fn on_write(&mut self, buf: &[u8]) {
+        self.stats.add_written(buf.len());
+        self.logger.log_write(buf);
+    }
+}
+
+impl<R> crate::captures::Buffer for BufferedReader<R> {
+    fn bytes(&self) -> &[u8] {
+        &self.buffer
+    }
+
+    fn consume(&mut self, n: usize) {
+        let _ = self.buffer.drain(..n);
+    }
 }
 
 impl<R: Read> Read for BufferedReader<R> {
     fn read(&mut self, mut buf: &mut [u8]) -> std::io::Result<usize> {
         if self.buffer.is_empty() {
-            self.inner.read(buf)
+            let n = self.stream_mut().read(buf)?;
+            self.on_read(&buf[..n]);
+            Ok(n)
         } else {
             let n = buf.write(&self.buffer)?;
             let _ = self.buffer.drain(..n);
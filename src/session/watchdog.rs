@@ -0,0 +1,178 @@
+//! This module contains a [Watchdog] which warns about slow expect calls.
+
+use std::{
+    fmt,
+    time::{Duration, Instant},
+};
+
+use crate::{stream::log::Logger, Needle};
+
+/// Watchdog warns about an expect call which runs suspiciously long
+/// or collects a lot of output without a match.
+///
+/// It helps to spot a pathological regex or a runaway child long before a timeout fires.
+/// Each kind of warning is reported at most once per expect call.
+///
+/// By default warnings are written to a log set by `Session::set_log`,
+/// a callback set by [Watchdog::on_warning] is called instead if it's present.
+///
+/// # Example
+///
+/// ```no_run,ignore
+/// use std::time::Duration;
+/// use expectrl::{spawn, session::Watchdog};
+///
+/// let mut p = spawn("cat").unwrap();
+/// p.set_watchdog(
+///     Watchdog::new()
+///         .elapsed(Duration::from_secs(5))
+///         .buffer_size(1024 * 1024)
+///         .on_warning(|warning| eprintln!("{}", warning)),
+/// );
+/// ```
+#[derive(Default)]
+pub struct Watchdog {
+    elapsed: Option<Duration>,
+    buffer_size: Option<usize>,
+    callback: Option<Callback>,
+}
+
+type Callback = Box<dyn FnMut(&WatchdogWarning) + Send>;
+
+impl Watchdog {
+    /// Creates a watchdog which doesn't watch anything yet.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Warns when an expect call is running longer than a given time.
+    pub fn elapsed(mut self, elapsed: Duration) -> Self {
+        self.elapsed = Some(elapsed);
+        self
+    }
+
+    /// Warns when a buffer grows over a given number of bytes without a match.
+    pub fn buffer_size(mut self, size: usize) -> Self {
+        self.buffer_size = Some(size);
+        self
+    }
+
+    /// Sets a callback which is called on each warning instead of logging it.
+    pub fn on_warning<F>(mut self, callback: F) -> Self
+    where
+        F: FnMut(&WatchdogWarning) + Send + 'static,
+    {
+        self.callback = Some(Box::new(callback));
+        self
+    }
+
+    /// Checks an expect call and reports a warning if a threshold is exceeded.
+    pub(crate) fn check<N: Needle>(
+        &mut self,
+        watch: &mut Watch,
+        needle: &N,
+        buffered: usize,
+        logger: &mut Logger,
+    ) {
+        if let Some(limit) = self.elapsed {
+            let elapsed = watch.start.elapsed();
+            if !watch.is_elapsed_reported && elapsed >= limit {
+                watch.is_elapsed_reported = true;
+                let needle = needle.description();
+                self.report(WatchdogWarning::Elapsed { needle, elapsed }, logger);
+            }
+        }
+
+        if let Some(limit) = self.buffer_size {
+            if !watch.is_size_reported && buffered > limit {
+                watch.is_size_reported = true;
+                let needle = needle.description();
+                let size = buffered;
+                self.report(WatchdogWarning::BufferSize { needle, size }, logger);
+            }
+        }
+    }
+
+    /// Returns how long is left till the elapsed time warning.
+    ///
+    /// It's [None] if there's nothing to wait for.
+    pub(crate) fn time_left(&self, watch: &Watch) -> Option<Duration> {
+        match self.elapsed {
+            Some(limit) if !watch.is_elapsed_reported => {
+                Some(limit.saturating_sub(watch.start.elapsed()))
+            }
+            _ => None,
+        }
+    }
+
+    fn report(&mut self, warning: WatchdogWarning, logger: &mut Logger) {
+        match &mut self.callback {
+            Some(callback) => callback(&warning),
+            None => logger.log_event("watchdog", &warning.to_string()),
+        }
+    }
+}
+
+impl fmt::Debug for Watchdog {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Watchdog")
+            .field("elapsed", &self.elapsed)
+            .field("buffer_size", &self.buffer_size)
+            .field("callback", &self.callback.is_some())
+            .finish()
+    }
+}
+
+/// WatchdogWarning is reported by a [Watchdog].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WatchdogWarning {
+    /// An expect call is running longer than expected.
+    Elapsed {
+        /// A description of a needle, see [Needle::description].
+        needle: String,
+        /// How long the expect call is running.
+        elapsed: Duration,
+    },
+    /// A buffer grew too big without a match.
+    BufferSize {
+        /// A description of a needle, see [Needle::description].
+        needle: String,
+        /// A number of buffered bytes.
+        size: usize,
+    },
+}
+
+impl fmt::Display for WatchdogWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Elapsed { needle, elapsed } => {
+                write!(f, "waiting for {} for {:?}", needle, elapsed)
+            }
+            Self::BufferSize { needle, size } => {
+                write!(
+                    f,
+                    "{} bytes are buffered without a match of {}",
+                    size, needle
+                )
+            }
+        }
+    }
+}
+
+/// Watch keeps a state of a single expect call.
+#[derive(Debug)]
+pub(crate) struct Watch {
+    start: Instant,
+    is_elapsed_reported: bool,
+    is_size_reported: bool,
+}
+
+impl Watch {
+    pub(crate) fn new() -> Self {
+        Self {
+            start: Instant::now(),
+            is_elapsed_reported: false,
+            is_size_reported: false,
+        }
+    }
+}
@@ -0,0 +1,275 @@
+//! This module contains a [`login`] function which connects to a host via `ssh`,
+//! alike `pxssh` in `pexpect`.
+//!
+//! # Example
+//!
+#![cfg_attr(not(feature = "async"), doc = "```no_run")]
+#![cfg_attr(feature = "async", doc = "```ignore")]
+//! use expectrl::ssh::{login, Auth};
+//!
+//! let mut shell = login("example.com", "user", Auth::Password("secret".into())).unwrap();
+//! let output = shell.execute("uname -a").unwrap();
+//! println!("{}", String::from_utf8_lossy(&output));
+//! ```
+
+use std::process::Command;
+
+use crate::{
+    repl::{ReplSession, SHELL_PROMPT},
+    Any, Error, Password, Regex, Secret, Session,
+};
+
+/// A way to authenticate on a remote host.
+///
+/// A [`Secret`] can be made from a string,
+/// or from a `secrecy::SecretString` with the `secrecy` feature.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Auth {
+    /// A key which is not protected by a passphrase or an ssh agent is used.
+    Key,
+    /// A password of a user.
+    Password(Secret),
+    /// A passphrase of a user's key.
+    Passphrase(Secret),
+}
+
+/// A list of what may be shown by `ssh` while logging in.
+///
+/// An order must match the [`Step`] indexes.
+const LOGIN_NEEDLES: [Regex<&str>; 6] = [
+    Regex(r"continue connecting \(yes/no"),
+    Regex(r"(?i)passphrase for key[^:]*:"),
+    Regex(r"(?i)password:"),
+    Regex(r"(?i)permission denied"),
+    Regex(
+        r"(?i)(connection refused|could not resolve|no route to host|connection timed out|connection closed|host key verification failed)",
+    ),
+    Regex(r"[#$%>] ?$"),
+];
+
+/// A list of what may be shown after a shell setup is sent.
+///
+/// The [`LOGIN_NEEDLES`] prompt check is a guess, e.g. it can be matched by a banner,
+/// so a login prompt here means the login was not done.
+const SETUP_NEEDLES: [Regex<&str>; 4] = [
+    Regex("EXPECT_PROMPT"),
+    Regex(r"(?i)passphrase for key[^:]*:"),
+    Regex(r"(?i)password:"),
+    Regex(r"(?i)permission denied"),
+];
+
+/// A shell setup which sets a known prompt.
+///
+/// The prompt is split by quotes, so an echo of the command isn't mistaken for the prompt itself.
+const SHELL_SETUP: &str =
+    "unset PROMPT_COMMAND; PS1='EXPECT_PROMP''T'; PS2='EXPECT_CONTINUATIO''N'";
+
+/// Options of a login, see [`login_with`].
+#[derive(Debug, Clone, Default)]
+pub struct LoginOptions {
+    accept_unknown_host: bool,
+}
+
+impl LoginOptions {
+    /// Creates default options.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets whether a key of an unknown host is accepted and added to known hosts.
+    ///
+    /// By default the login fails on an unknown host,
+    /// as accepting a key without a check makes a connection open to a man-in-the-middle attack.
+    /// A changed key of a known host is always rejected.
+    pub fn accept_unknown_host(mut self, on: bool) -> Self {
+        self.accept_unknown_host = on;
+        self
+    }
+}
+
+/// Login is a state of an authentication.
+#[derive(Debug)]
+struct Login {
+    auth: Auth,
+    options: LoginOptions,
+    secret_sent: bool,
+}
+
+/// Step is an action which must be done after a [`LOGIN_NEEDLES`] was matched.
+#[derive(Debug, PartialEq, Eq)]
+enum Step<'a> {
+    Send(&'a str),
+    SendSecret(&'a str),
+    Done,
+}
+
+impl Login {
+    fn new(auth: Auth, options: LoginOptions) -> Self {
+        Self {
+            auth,
+            options,
+            secret_sent: false,
+        }
+    }
+
+    fn step(&mut self, index: usize) -> Result<Step<'_>, Error> {
+        match index {
+            0 if self.options.accept_unknown_host => Ok(Step::Send("yes")),
+            0 => Err(login_error("the host key is unknown")),
+            1 | 2 => {
+                let secret = match (&self.auth, index) {
+                    (Auth::Passphrase(secret), 1) | (Auth::Password(secret), 2) => secret,
+                    _ => return Err(login_error("an unexpected authentication prompt")),
+                };
+
+                if self.secret_sent {
+                    return Err(login_error("authentication failed"));
+                }
+
+                self.secret_sent = true;
+                Ok(Step::SendSecret(secret.expose()))
+            }
+            3 => Err(login_error("authentication failed")),
+            4 => Err(login_error("connection failed")),
+            _ => Ok(Step::Done),
+        }
+    }
+}
+
+fn login_error(err: &str) -> Error {
+    Error::Other {
+        message: "ssh login failed".to_string(),
+        err: err.to_string(),
+    }
+}
+
+fn ssh_command(host: &str, user: &str) -> Command {
+    let mut cmd = Command::new("ssh");
+    let _ = cmd.arg("-l").arg(user).arg(host);
+    cmd
+}
+
+fn new_repl(session: Session) -> ReplSession {
+    let mut shell = ReplSession::new(
+        session,
+        SHELL_PROMPT.to_string(),
+        Some("exit".to_string()),
+        false,
+    );
+    shell.set_continuation_prompt("EXPECT_CONTINUATION");
+    shell
+}
+
+/// Connects to a host via `ssh` and returns a shell with a known prompt.
+///
+/// It's [`login_with`] with default [`LoginOptions`], so an unknown host key is rejected.
+#[cfg(not(feature = "async"))]
+pub fn login(host: &str, user: &str, auth: Auth) -> Result<ReplSession, Error> {
+    login_with(host, user, auth, LoginOptions::default())
+}
+
+/// Connects to a host via `ssh` with given options and returns a shell with a known prompt.
+///
+/// A remote shell is expected to be POSIX compatible as its prompt is changed via `PS1`.
+/// A shell prompt is recognized by its last character, e.g. `$` or `#`,
+/// so it's verified after the prompt is changed.
+///
+/// An error is returned if authentication fails or a prompt which is not covered by `auth` is shown.
+#[cfg(not(feature = "async"))]
+pub fn login_with(
+    host: &str,
+    user: &str,
+    auth: Auth,
+    options: LoginOptions,
+) -> Result<ReplSession, Error> {
+    let mut session = Session::spawn(ssh_command(host, user))?;
+    let mut login = Login::new(auth, options);
+    loop {
+        let found = session.expect(Any(LOGIN_NEEDLES))?;
+        let index = found.matched_index().unwrap_or_default();
+        match login.step(index)? {
+            Step::Send(line) => session.send_line(line)?,
+            Step::SendSecret(secret) => session.send_secret(secret)?,
+            Step::Done => break,
+        }
+    }
+
+    session.send_line(SHELL_SETUP)?;
+    let found = session.expect(Any(SETUP_NEEDLES))?;
+    if found.matched_index() != Some(0) {
+        return Err(login_error("a shell prompt was expected"));
+    }
+
+    Ok(new_repl(session))
+}
+
+/// Connects to a host via `ssh` and returns a shell with a known prompt.
+///
+/// It's [`login_with`] with default [`LoginOptions`], so an unknown host key is rejected.
+#[cfg(feature = "async")]
+pub async fn login(host: &str, user: &str, auth: Auth) -> Result<ReplSession, Error> {
+    login_with(host, user, auth, LoginOptions::default()).await
+}
+
+/// Connects to a host via `ssh` with given options and returns a shell with a known prompt.
+///
+/// A remote shell is expected to be POSIX compatible as its prompt is changed via `PS1`.
+/// A shell prompt is recognized by its last character, e.g. `$` or `#`,
+/// so it's verified after the prompt is changed.
+///
+/// An error is returned if authentication fails or a prompt which is not covered by `auth` is shown.
+#[cfg(feature = "async")]
+pub async fn login_with(
+    host: &str,
+    user: &str,
+    auth: Auth,
+    options: LoginOptions,
+) -> Result<ReplSession, Error> {
+    let mut session = Session::spawn(ssh_command(host, user))?;
+    let mut login = Login::new(auth, options);
+    loop {
+        let found = session.expect(Any(LOGIN_NEEDLES)).await?;
+        let index = found.matched_index().unwrap_or_default();
+        match login.step(index)? {
+            Step::Send(line) => session.send_line(line).await?,
+            Step::SendSecret(secret) => session.send_secret(secret).await?,
+            Step::Done => break,
+        }
+    }
+
+    session.send_line(SHELL_SETUP).await?;
+    let found = session.expect(Any(SETUP_NEEDLES)).await?;
+    if found.matched_index() != Some(0) {
+        return Err(login_error("a shell prompt was expected"));
+    }
+
+    Ok(new_repl(session))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_login_steps() {
+        let options = LoginOptions::new().accept_unknown_host(true);
+        let mut login = Login::new(Auth::Password("secret".into()), options);
+        assert_eq!(login.step(0).unwrap(), Step::Send("yes"));
+        assert_eq!(login.step(2).unwrap(), Step::SendSecret("secret"));
+        assert!(login.step(2).is_err());
+        assert!(login.step(1).is_err());
+        assert_eq!(login.step(5).unwrap(), Step::Done);
+
+        let mut login = Login::new(Auth::Key, LoginOptions::default());
+        assert!(login.step(0).is_err());
+        assert!(login.step(2).is_err());
+        assert!(login.step(3).is_err());
+        assert!(login.step(4).is_err());
+    }
+
+    #[test]
+    fn test_auth_debug_hides_secret() {
+        let auth = Auth::Passphrase("secret".into());
+        assert_eq!(format!("{:?}", auth), "Passphrase(***)");
+    }
+}
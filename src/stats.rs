@@ -0,0 +1,86 @@
+//! This module contains a [`Stats`] which keeps counters of a session activity.
+
+use std::time::Duration;
+
+use crate::Error;
+
+/// Stats is a set of counters collected by a session.
+///
+/// It's useful to investigate where the time goes in a slow automation
+/// without wrapping each call by timing code.
+///
+/// # Example
+///
+/// ```no_run,ignore
+/// use expectrl::spawn;
+///
+/// let mut p = spawn("cat").unwrap();
+/// p.send_line("Hello World").unwrap();
+/// p.expect("Hello World").unwrap();
+///
+/// let stats = p.stats();
+/// println!("read {} bytes in {} expects", stats.bytes_read(), stats.expects());
+/// println!("the slowest match took {:?}", stats.longest_match());
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Stats {
+    bytes_read: u64,
+    bytes_written: u64,
+    expects: u64,
+    matches: u64,
+    timeouts: u64,
+    longest_match: Duration,
+}
+
+impl Stats {
+    /// Returns a number of bytes read from a process.
+    pub fn bytes_read(&self) -> u64 {
+        self.bytes_read
+    }
+
+    /// Returns a number of bytes written to a process.
+    pub fn bytes_written(&self) -> u64 {
+        self.bytes_written
+    }
+
+    /// Returns a number of expect calls.
+    pub fn expects(&self) -> u64 {
+        self.expects
+    }
+
+    /// Returns a number of expect calls which were matched.
+    pub fn matches(&self) -> u64 {
+        self.matches
+    }
+
+    /// Returns a number of expect calls which ended up with [`Error::ExpectTimeout`].
+    pub fn timeouts(&self) -> u64 {
+        self.timeouts
+    }
+
+    /// Returns the longest time a successful expect call took.
+    pub fn longest_match(&self) -> Duration {
+        self.longest_match
+    }
+
+    pub(crate) fn add_read(&mut self, n: usize) {
+        self.bytes_read += n as u64;
+    }
+
+    pub(crate) fn add_written(&mut self, n: usize) {
+        self.bytes_written += n as u64;
+    }
+
+    pub(crate) fn add_expect<T>(&mut self, result: &Result<T, Error>, elapsed: Duration) {
+        self.expects += 1;
+
+        match result {
+            Ok(_) => {
+                self.matches += 1;
+                self.longest_match = std::cmp::max(self.longest_match, elapsed);
+            }
+            Err(Error::ExpectTimeout) => self.timeouts += 1,
+            Err(_) => {}
+        }
+    }
+}
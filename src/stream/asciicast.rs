@@ -0,0 +1,308 @@
+//! This module contains an [AsciicastStream]
+//! which records a stream into an [asciicast v2] file.
+//!
+//! The file can be replayed by `asciinema play` or by a web player,
+//! which is handy to inspect a failure of a TUI application.
+//!
+//! [asciicast v2]: https://docs.asciinema.org/manual/asciicast/v2/
+
+use std::{
+    io::{Read, Result, Write},
+    ops::{Deref, DerefMut},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+#[cfg(feature = "async")]
+use futures_lite::{AsyncRead, AsyncWrite};
+#[cfg(feature = "async")]
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use crate::{needle::complete_utf8_len, process::NonBlocking};
+
+const DEFAULT_WIDTH: u16 = 80;
+const DEFAULT_HEIGHT: u16 = 24;
+
+/// AsciicastStream a IO stream wrapper,
+/// which records read bytes as output events and written bytes as input events.
+///
+/// A header is written on a first event,
+/// so a terminal size can be changed by [AsciicastStream::set_size] until then.
+#[derive(Debug)]
+pub struct AsciicastStream<S, W> {
+    stream: S,
+    recorder: Recorder<W>,
+}
+
+impl<S, W> AsciicastStream<S, W> {
+    /// Creates a new instance of the stream.
+    ///
+    /// A terminal size is set to 80x24 by default.
+    pub fn new(stream: S, writer: W) -> Self {
+        Self {
+            stream,
+            recorder: Recorder::new(writer),
+        }
+    }
+
+    /// Sets a terminal size which is written into a header.
+    ///
+    /// It has no effect after a first event was recorded.
+    pub fn set_size(&mut self, width: u16, height: u16) {
+        self.recorder.width = width;
+        self.recorder.height = height;
+    }
+
+    /// Returns a reference to an underlying writer.
+    pub fn get_writer(&self) -> &W {
+        &self.recorder.writer
+    }
+}
+
+impl<S: Write, W: Write> Write for AsciicastStream<S, W> {
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        let n = self.stream.write(buf)?;
+        self.recorder.record(EventKind::Input, &buf[..n]);
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        self.stream.flush()?;
+        let _ = self.recorder.writer.flush();
+        Ok(())
+    }
+}
+
+impl<S: Read, W: Write> Read for AsciicastStream<S, W> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        let n = self.stream.read(buf)?;
+        self.recorder.record(EventKind::Output, &buf[..n]);
+        Ok(n)
+    }
+}
+
+impl<S: NonBlocking, W> NonBlocking for AsciicastStream<S, W> {
+    fn set_non_blocking(&mut self) -> Result<()> {
+        self.stream.set_non_blocking()
+    }
+
+    fn set_blocking(&mut self) -> Result<()> {
+        self.stream.set_blocking()
+    }
+
+    fn wait_readable(&mut self, timeout: Option<Duration>) -> Result<()> {
+        self.stream.wait_readable(timeout)
+    }
+}
+
+impl<S, W> Deref for AsciicastStream<S, W> {
+    type Target = S;
+
+    fn deref(&self) -> &Self::Target {
+        &self.stream
+    }
+}
+
+impl<S, W> DerefMut for AsciicastStream<S, W> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.stream
+    }
+}
+
+#[cfg(feature = "async")]
+impl<S: AsyncWrite + Unpin, W: Write + Unpin> AsyncWrite for AsciicastStream<S, W> {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<Result<usize>> {
+        let result = Pin::new(&mut self.stream).poll_write(cx, buf);
+        if let Poll::Ready(Ok(n)) = &result {
+            self.recorder.record(EventKind::Input, &buf[..*n]);
+        }
+
+        result
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        let _ = self.recorder.writer.flush();
+        Pin::new(&mut self.stream).poll_flush(cx)
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        Pin::new(&mut self.stream).poll_close(cx)
+    }
+}
+
+#[cfg(feature = "async")]
+impl<S: AsyncRead + Unpin, W: Write + Unpin> AsyncRead for AsciicastStream<S, W> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize>> {
+        let result = Pin::new(&mut self.stream).poll_read(cx, buf);
+        if let Poll::Ready(Ok(n)) = &result {
+            self.recorder.record(EventKind::Output, &buf[..*n]);
+        }
+
+        result
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum EventKind {
+    Output,
+    Input,
+}
+
+#[derive(Debug)]
+struct Recorder<W> {
+    writer: W,
+    width: u16,
+    height: u16,
+    start: Option<Instant>,
+    // Not yet complete UTF-8 characters, which are kept until the next event of the same kind.
+    pending: Vec<u8>,
+    pending_kind: Option<EventKind>,
+}
+
+impl<W> Recorder<W> {
+    fn new(writer: W) -> Self {
+        Self {
+            writer,
+            width: DEFAULT_WIDTH,
+            height: DEFAULT_HEIGHT,
+            start: None,
+            pending: Vec::new(),
+            pending_kind: None,
+        }
+    }
+}
+
+impl<W: Write> Recorder<W> {
+    fn record(&mut self, kind: EventKind, data: &[u8]) {
+        if matches!(kind, EventKind::Input) && crate::secret::is_secret_write() {
+            return;
+        }
+
+        // A recording must not break the session so errors are ignored the same way logging does it.
+        let _ = self.try_record(kind, data);
+    }
+
+    fn try_record(&mut self, kind: EventKind, data: &[u8]) -> Result<()> {
+        if data.is_empty() {
+            return Ok(());
+        }
+
+        let start = match self.start {
+            Some(start) => start,
+            None => {
+                self.write_header()?;
+                *self.start.insert(Instant::now())
+            }
+        };
+
+        let mut bytes = Vec::new();
+        if self.pending_kind == Some(kind) {
+            bytes.append(&mut self.pending);
+        } else {
+            self.pending.clear();
+        }
+        bytes.extend_from_slice(data);
+
+        let complete = complete_utf8_len(&bytes);
+        self.pending = bytes.split_off(complete);
+        self.pending_kind = Some(kind);
+
+        if bytes.is_empty() {
+            return Ok(());
+        }
+
+        let code = match kind {
+            EventKind::Output => "o",
+            EventKind::Input => "i",
+        };
+
+        let time = start.elapsed().as_secs_f64();
+        let data = String::from_utf8_lossy(&bytes);
+
+        write!(self.writer, "[{:.6}, \"{}\", ", time, code)?;
+        write_json_string(&mut self.writer, &data)?;
+        writeln!(self.writer, "]")
+    }
+
+    fn write_header(&mut self) -> Result<()> {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+
+        writeln!(
+            self.writer,
+            "{{\"version\": 2, \"width\": {}, \"height\": {}, \"timestamp\": {}}}",
+            self.width, self.height, timestamp
+        )
+    }
+}
+
+fn write_json_string(mut writer: impl Write, s: &str) -> Result<()> {
+    writer.write_all(b"\"")?;
+    for c in s.chars() {
+        match c {
+            '"' => writer.write_all(b"\\\"")?,
+            '\\' => writer.write_all(b"\\\\")?,
+            '\n' => writer.write_all(b"\\n")?,
+            '\r' => writer.write_all(b"\\r")?,
+            '\t' => writer.write_all(b"\\t")?,
+            c if (c as u32) < 0x20 || c == '\u{7f}' => write!(writer, "\\u{:04x}", c as u32)?,
+            c => write!(writer, "{}", c)?,
+        }
+    }
+    writer.write_all(b"\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record() {
+        let mut stream = AsciicastStream::new(
+            std::io::Cursor::new(b"\x1b[1mhi\"\n\xd0".to_vec()),
+            Vec::new(),
+        );
+        stream.set_size(120, 40);
+
+        let mut buf = [0; 64];
+        let n = stream.read(&mut buf).unwrap();
+        assert_eq!(n, 9);
+
+        stream.recorder.record(EventKind::Output, b"\x96");
+
+        let content = String::from_utf8(stream.get_writer().clone()).unwrap();
+        let lines = content.lines().collect::<Vec<_>>();
+
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with(r#"{"version": 2, "width": 120, "height": 40, "timestamp": "#));
+        assert!(lines[1].starts_with('['));
+        assert!(lines[1].ends_with(r#", "o", "\u001b[1mhi\"\n"]"#));
+        assert!(lines[2].ends_with(", \"o\", \"Ж\"]"));
+    }
+
+    #[test]
+    fn test_secret_is_not_recorded() {
+        let mut stream = AsciicastStream::new(std::io::Cursor::new(Vec::new()), Vec::new());
+
+        let _ = crate::secret::write_secret(|| stream.write(b"secret")).unwrap();
+        let _ = stream.write(b"\n").unwrap();
+
+        let content = String::from_utf8(stream.get_writer().clone()).unwrap();
+        let lines = content.lines().collect::<Vec<_>>();
+
+        assert_eq!(lines.len(), 2);
+        assert!(lines[1].ends_with(r#", "i", "\n"]"#));
+    }
+}
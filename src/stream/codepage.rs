@@ -0,0 +1,352 @@
+//! This module contains a [CodepageStream]
+//! which translates an output written in a Windows codepage into UTF-8.
+//!
+//! ConPTY itself produces UTF-8,
+//! but programs which write raw bytes in the OEM or ANSI codepage
+//! (which is what `cmd.exe` builtins and many legacy tools do)
+//! produce an output which doesn't match UTF-8 needles.
+//! The stream decodes such an output, and encodes input back to the codepage.
+
+use std::{
+    collections::VecDeque,
+    io::{Read, Result, Write},
+    ops::{Deref, DerefMut},
+    ptr::{null, null_mut},
+    time::Duration,
+};
+
+#[cfg(feature = "async")]
+use futures_lite::{ready, AsyncRead, AsyncWrite};
+#[cfg(feature = "async")]
+use std::{
+    io,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use crate::{needle::complete_utf8_len, process::NonBlocking};
+
+/// The system default ANSI codepage.
+pub const ANSI: u32 = 0;
+/// The system default OEM codepage, which is used by a console by default.
+pub const OEM: u32 = 1;
+/// UTF-8 codepage, the same as set by `chcp 65001`.
+pub const UTF8: u32 = 65001;
+
+#[link(name = "kernel32")]
+extern "system" {
+    fn MultiByteToWideChar(
+        code_page: u32,
+        flags: u32,
+        multi_byte_str: *const u8,
+        multi_byte_len: i32,
+        wide_char_str: *mut u16,
+        wide_char_len: i32,
+    ) -> i32;
+
+    fn WideCharToMultiByte(
+        code_page: u32,
+        flags: u32,
+        wide_char_str: *const u16,
+        wide_char_len: i32,
+        multi_byte_str: *mut u8,
+        multi_byte_len: i32,
+        default_char: *const u8,
+        used_default_char: *mut i32,
+    ) -> i32;
+
+    fn IsDBCSLeadByteEx(code_page: u32, test_char: u8) -> i32;
+}
+
+/// CodepageStream a IO stream wrapper,
+/// which decodes read bytes from a given codepage into UTF-8
+/// and encodes written UTF-8 bytes into the codepage.
+///
+/// With [UTF8] codepage bytes are passed as they are.
+#[derive(Debug)]
+pub struct CodepageStream<S> {
+    stream: S,
+    codepage: u32,
+    // Bytes of the codepage which don't make a complete character yet.
+    undecoded: Vec<u8>,
+    decoded: VecDeque<u8>,
+    // UTF-8 bytes which don't make a complete character yet.
+    unencoded: Vec<u8>,
+}
+
+impl<S> CodepageStream<S> {
+    /// Creates a new instance of the stream.
+    pub fn new(stream: S, codepage: u32) -> Self {
+        Self {
+            stream,
+            codepage,
+            undecoded: Vec::new(),
+            decoded: VecDeque::new(),
+            unencoded: Vec::new(),
+        }
+    }
+
+    /// Returns a codepage of the stream.
+    pub fn get_codepage(&self) -> u32 {
+        self.codepage
+    }
+
+    fn decode(&mut self, buf: &[u8]) {
+        if self.codepage == UTF8 {
+            self.decoded.extend(buf);
+            return;
+        }
+
+        self.undecoded.extend_from_slice(buf);
+
+        let len = complete_dbcs_len(self.codepage, &self.undecoded);
+        let text = decode(self.codepage, &self.undecoded[..len]);
+        let _ = self.undecoded.drain(..len);
+
+        self.decoded.extend(text.as_bytes());
+    }
+
+    fn encode(&mut self, buf: &[u8]) -> Vec<u8> {
+        if self.codepage == UTF8 {
+            return buf.to_vec();
+        }
+
+        self.unencoded.extend_from_slice(buf);
+
+        let len = complete_utf8_len(&self.unencoded);
+        let text = String::from_utf8_lossy(&self.unencoded[..len]).into_owned();
+        let _ = self.unencoded.drain(..len);
+
+        encode(self.codepage, &text)
+    }
+
+    fn read_decoded(&mut self, buf: &mut [u8]) -> usize {
+        let n = std::cmp::min(buf.len(), self.decoded.len());
+        for (b, byte) in buf.iter_mut().zip(self.decoded.drain(..n)) {
+            *b = byte;
+        }
+
+        n
+    }
+}
+
+impl<S: Write> Write for CodepageStream<S> {
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        let encoded = self.encode(buf);
+        self.stream.write_all(&encoded)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        self.stream.flush()
+    }
+}
+
+impl<S: Read> Read for CodepageStream<S> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        while self.decoded.is_empty() {
+            let mut raw = [0; 512];
+            let n = self.stream.read(&mut raw)?;
+            if n == 0 {
+                // Flush an incomplete character on EOF.
+                let rest = std::mem::take(&mut self.undecoded);
+                self.decoded.extend(decode(self.codepage, &rest).as_bytes());
+                break;
+            }
+
+            self.decode(&raw[..n]);
+        }
+
+        Ok(self.read_decoded(buf))
+    }
+}
+
+impl<S: NonBlocking> NonBlocking for CodepageStream<S> {
+    fn set_non_blocking(&mut self) -> Result<()> {
+        self.stream.set_non_blocking()
+    }
+
+    fn set_blocking(&mut self) -> Result<()> {
+        self.stream.set_blocking()
+    }
+
+    fn wait_readable(&mut self, timeout: Option<Duration>) -> Result<()> {
+        if !self.decoded.is_empty() {
+            return Ok(());
+        }
+
+        self.stream.wait_readable(timeout)
+    }
+}
+
+impl<S> Deref for CodepageStream<S> {
+    type Target = S;
+
+    fn deref(&self) -> &Self::Target {
+        &self.stream
+    }
+}
+
+impl<S> DerefMut for CodepageStream<S> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.stream
+    }
+}
+
+#[cfg(feature = "async")]
+impl<S: AsyncWrite + Unpin> AsyncWrite for CodepageStream<S> {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<Result<usize>> {
+        // A partially written buffer can't be reported back after encoding,
+        // so the encoded bytes are required to be written at once.
+        let encoded = self.encode(buf);
+        let n = ready!(Pin::new(&mut self.stream).poll_write(cx, &encoded))?;
+        if n < encoded.len() {
+            return Poll::Ready(Err(io::Error::new(
+                io::ErrorKind::WriteZero,
+                "failed to write an encoded buffer",
+            )));
+        }
+
+        Poll::Ready(Ok(buf.len()))
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        Pin::new(&mut self.stream).poll_flush(cx)
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        Pin::new(&mut self.stream).poll_close(cx)
+    }
+}
+
+#[cfg(feature = "async")]
+impl<S: AsyncRead + Unpin> AsyncRead for CodepageStream<S> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize>> {
+        if buf.is_empty() {
+            return Poll::Ready(Ok(0));
+        }
+
+        while self.decoded.is_empty() {
+            let mut raw = [0; 512];
+            let n = ready!(Pin::new(&mut self.stream).poll_read(cx, &mut raw))?;
+            if n == 0 {
+                let rest = std::mem::take(&mut self.undecoded);
+                let text = decode(self.codepage, &rest);
+                self.decoded.extend(text.as_bytes());
+                break;
+            }
+
+            self.decode(&raw[..n]);
+        }
+
+        Poll::Ready(Ok(self.read_decoded(buf)))
+    }
+}
+
+/// Returns a length of the buffer without a trailing lead byte of a double byte character.
+fn complete_dbcs_len(codepage: u32, buf: &[u8]) -> usize {
+    let mut i = 0;
+    while i < buf.len() {
+        // SAFETY: the function only looks up a table of the codepage.
+        let is_lead = unsafe { IsDBCSLeadByteEx(codepage, buf[i]) } != 0;
+        if !is_lead {
+            i += 1;
+            continue;
+        }
+
+        if i + 1 == buf.len() {
+            return i;
+        }
+
+        i += 2;
+    }
+
+    buf.len()
+}
+
+fn decode(codepage: u32, buf: &[u8]) -> String {
+    if buf.is_empty() {
+        return String::new();
+    }
+
+    let len = i32::try_from(buf.len()).unwrap_or(i32::MAX);
+
+    // SAFETY: the pointer and the length are taken from a valid slice,
+    // and the first call only calculates a length of an output.
+    let wide_len = unsafe { MultiByteToWideChar(codepage, 0, buf.as_ptr(), len, null_mut(), 0) };
+    if wide_len <= 0 {
+        return String::from_utf8_lossy(buf).into_owned();
+    }
+
+    let mut wide = vec![0u16; wide_len as usize];
+
+    // SAFETY: the output buffer has a length returned by the previous call.
+    let wide_len =
+        unsafe { MultiByteToWideChar(codepage, 0, buf.as_ptr(), len, wide.as_mut_ptr(), wide_len) };
+    if wide_len <= 0 {
+        return String::from_utf8_lossy(buf).into_owned();
+    }
+
+    String::from_utf16_lossy(&wide[..wide_len as usize])
+}
+
+fn encode(codepage: u32, text: &str) -> Vec<u8> {
+    if text.is_empty() {
+        return Vec::new();
+    }
+
+    let wide = text.encode_utf16().collect::<Vec<_>>();
+    let len = i32::try_from(wide.len()).unwrap_or(i32::MAX);
+
+    // SAFETY: the pointer and the length are taken from a valid slice,
+    // and the first call only calculates a length of an output.
+    let out_len = unsafe {
+        WideCharToMultiByte(
+            codepage,
+            0,
+            wide.as_ptr(),
+            len,
+            null_mut(),
+            0,
+            null(),
+            null_mut(),
+        )
+    };
+    if out_len <= 0 {
+        return text.as_bytes().to_vec();
+    }
+
+    let mut out = vec![0u8; out_len as usize];
+
+    // SAFETY: the output buffer has a length returned by the previous call.
+    let out_len = unsafe {
+        WideCharToMultiByte(
+            codepage,
+            0,
+            wide.as_ptr(),
+            len,
+            out.as_mut_ptr(),
+            out_len,
+            null(),
+            null_mut(),
+        )
+    };
+    if out_len <= 0 {
+        return text.as_bytes().to_vec();
+    }
+
+    out.truncate(out_len as usize);
+    out
+}
@@ -0,0 +1,379 @@
+//! The module contains a [`CrosstermInput`] which reads a terminal input via `crossterm`.
+//!
+//! It's available with the `crossterm` feature.
+//!
+//! Unlike [`Stdin`] it reads key events instead of raw bytes,
+//! so special keys are recognized on Windows as well,
+//! and converts them to the escape sequences a process in a PTY expects.
+//!
+//! It's not backed by a file descriptor as `crossterm` buffers events internally,
+//! so it's used by the default interact loop and not by the `polling` one.
+//!
+//! # Example
+//!
+//! ```no_run,ignore
+//! use expectrl::{spawn, interact::InteractOptions};
+//! use std::io::stdout;
+//!
+//! let mut sh = spawn("sh").unwrap();
+//!
+//! sh.interact_crossterm(stdout())
+//!     .unwrap()
+//!     .spawn(InteractOptions::default())
+//!     .unwrap();
+//! ```
+//!
+//! [`Stdin`]: crate::stream::stdin::Stdin
+
+use std::{
+    io::{self, Read},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use ::crossterm::{
+    event::{
+        self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers, MouseButton, MouseEvent,
+        MouseEventKind,
+    },
+    terminal,
+};
+
+use crate::Error;
+
+const PASTE_START: &[u8] = b"\x1b[200~";
+const PASTE_END: &[u8] = b"\x1b[201~";
+
+/// A non blocking terminal input which is read via `crossterm`.
+///
+/// It puts a terminal into a raw mode,
+/// which is turned off by [`CrosstermInput::close`] or when it's dropped.
+#[derive(Debug)]
+pub struct CrosstermInput {
+    pending: Vec<u8>,
+    resize: Resize,
+    is_raw: bool,
+}
+
+impl CrosstermInput {
+    /// Creates a new instance and puts a terminal into a raw mode.
+    pub fn open() -> Result<Self, Error> {
+        let (cols, rows) = terminal::size()?;
+        terminal::enable_raw_mode()?;
+
+        let resize = Resize::default();
+        resize.set(cols, rows);
+
+        Ok(Self {
+            pending: Vec::new(),
+            resize,
+            is_raw: true,
+        })
+    }
+
+    /// Turns off a raw mode of a terminal.
+    pub fn close(mut self) -> Result<(), Error> {
+        self.disable_raw_mode()?;
+        Ok(())
+    }
+
+    /// Returns the last terminal size as columns and rows if it was changed since the last call.
+    ///
+    /// The first call returns a size the terminal had when the input was opened.
+    /// The size is supposed to be passed to a process so it redraws its output,
+    /// [`Session::interact_crossterm`] does it on its own.
+    ///
+    /// [`Session::interact_crossterm`]: crate::session::Session::interact_crossterm
+    pub fn take_resize(&mut self) -> Option<(u16, u16)> {
+        self.resize.take()
+    }
+
+    /// Returns a handle to terminal size changes which is shared with the input.
+    #[cfg(not(feature = "polling"))]
+    pub(crate) fn resize_handle(&self) -> Resize {
+        self.resize.clone()
+    }
+
+    fn disable_raw_mode(&mut self) -> io::Result<()> {
+        if self.is_raw {
+            terminal::disable_raw_mode()?;
+            self.is_raw = false;
+        }
+
+        Ok(())
+    }
+
+    fn handle(&mut self, event: Event) {
+        match event {
+            Event::Key(key) => key_to_bytes(&key, &mut self.pending),
+            Event::Mouse(mouse) => mouse_to_bytes(&mouse, &mut self.pending),
+            Event::Paste(text) => {
+                self.pending.extend_from_slice(PASTE_START);
+                self.pending.extend_from_slice(text.as_bytes());
+                self.pending.extend_from_slice(PASTE_END);
+            }
+            Event::Resize(cols, rows) => self.resize.set(cols, rows),
+            _ => {}
+        }
+    }
+}
+
+impl Read for CrosstermInput {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.pending.is_empty() && event::poll(Duration::ZERO)? {
+            let event = event::read()?;
+            self.handle(event);
+        }
+
+        if self.pending.is_empty() {
+            return Err(io::Error::new(io::ErrorKind::WouldBlock, ""));
+        }
+
+        let n = std::cmp::min(buf.len(), self.pending.len());
+        buf[..n].copy_from_slice(&self.pending[..n]);
+        let _ = self.pending.drain(..n);
+
+        Ok(n)
+    }
+}
+
+impl Drop for CrosstermInput {
+    fn drop(&mut self) {
+        let _ = self.disable_raw_mode();
+    }
+}
+
+/// Resize keeps the last terminal size which is not yet passed to a process.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Resize(Arc<Mutex<Option<(u16, u16)>>>);
+
+impl Resize {
+    pub(crate) fn set(&self, cols: u16, rows: u16) {
+        *self.0.lock().unwrap() = Some((cols, rows));
+    }
+
+    pub(crate) fn take(&self) -> Option<(u16, u16)> {
+        self.0.lock().unwrap().take()
+    }
+}
+
+/// Converts a key event to bytes an xterm compatible terminal would send.
+fn key_to_bytes(key: &KeyEvent, buf: &mut Vec<u8>) {
+    if key.kind == KeyEventKind::Release {
+        return;
+    }
+
+    // keys which are sent as CSI sequences carry modifiers in a parameter
+    let modifier = modifier_param(key.modifiers);
+    let csi = |buf: &mut Vec<u8>, code: u8, end: char| match (modifier, code) {
+        (1, 1) => buf.extend_from_slice(format!("\x1b[{end}").as_bytes()),
+        (1, _) => buf.extend_from_slice(format!("\x1b[{code}{end}").as_bytes()),
+        _ => buf.extend_from_slice(format!("\x1b[{code};{modifier}{end}").as_bytes()),
+    };
+
+    match key.code {
+        KeyCode::Up => return csi(buf, 1, 'A'),
+        KeyCode::Down => return csi(buf, 1, 'B'),
+        KeyCode::Right => return csi(buf, 1, 'C'),
+        KeyCode::Left => return csi(buf, 1, 'D'),
+        KeyCode::Home => return csi(buf, 1, 'H'),
+        KeyCode::End => return csi(buf, 1, 'F'),
+        KeyCode::Insert => return csi(buf, 2, '~'),
+        KeyCode::Delete => return csi(buf, 3, '~'),
+        KeyCode::PageUp => return csi(buf, 5, '~'),
+        KeyCode::PageDown => return csi(buf, 6, '~'),
+        KeyCode::F(n @ 1..=4) if modifier == 1 => {
+            return buf.extend_from_slice(&[0x1b, b'O', b'P' + n - 1])
+        }
+        KeyCode::F(n @ 1..=4) => return csi(buf, 1, char::from(b'P' + n - 1)),
+        KeyCode::F(n) => {
+            let code = match n {
+                5 => 15,
+                6..=10 => n + 11,
+                11..=12 => n + 12,
+                _ => return,
+            };
+            return csi(buf, code, '~');
+        }
+        _ => {}
+    }
+
+    if key.modifiers.contains(KeyModifiers::ALT) {
+        buf.push(0x1b);
+    }
+
+    match key.code {
+        KeyCode::Char(c) if key.modifiers.contains(KeyModifiers::CONTROL) => match c {
+            'a'..='z' | 'A'..='Z' => buf.push(c.to_ascii_lowercase() as u8 - b'a' + 1),
+            ' ' | '@' => buf.push(0),
+            '[' => buf.push(27),
+            '\\' => buf.push(28),
+            ']' => buf.push(29),
+            '^' => buf.push(30),
+            '_' => buf.push(31),
+            c => buf.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes()),
+        },
+        KeyCode::Char(c) => buf.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes()),
+        KeyCode::Enter => buf.push(b'\r'),
+        KeyCode::Tab => buf.push(b'\t'),
+        KeyCode::BackTab => buf.extend_from_slice(b"\x1b[Z"),
+        KeyCode::Backspace => buf.push(0x7f),
+        KeyCode::Esc => buf.push(0x1b),
+        KeyCode::Null => buf.push(0),
+        _ => {}
+    }
+}
+
+/// Returns an xterm modifier parameter, 1 means no modifiers.
+fn modifier_param(modifiers: KeyModifiers) -> u8 {
+    let mut param = 1;
+    if modifiers.contains(KeyModifiers::SHIFT) {
+        param += 1;
+    }
+
+    if modifiers.contains(KeyModifiers::ALT) {
+        param += 2;
+    }
+
+    if modifiers.contains(KeyModifiers::CONTROL) {
+        param += 4;
+    }
+
+    param
+}
+
+/// Converts a mouse event to an xterm report in the SGR format.
+fn mouse_to_bytes(mouse: &MouseEvent, buf: &mut Vec<u8>) {
+    let button = |button: MouseButton| match button {
+        MouseButton::Left => 0,
+        MouseButton::Middle => 1,
+        MouseButton::Right => 2,
+    };
+
+    let (code, end) = match mouse.kind {
+        MouseEventKind::Down(b) => (button(b), 'M'),
+        MouseEventKind::Up(b) => (button(b), 'm'),
+        MouseEventKind::Drag(b) => (button(b) + 32, 'M'),
+        MouseEventKind::Moved => (35, 'M'),
+        MouseEventKind::ScrollUp => (64, 'M'),
+        MouseEventKind::ScrollDown => (65, 'M'),
+        MouseEventKind::ScrollLeft => (66, 'M'),
+        MouseEventKind::ScrollRight => (67, 'M'),
+    };
+
+    let mut code = code;
+    if mouse.modifiers.contains(KeyModifiers::SHIFT) {
+        code += 4;
+    }
+
+    if mouse.modifiers.contains(KeyModifiers::ALT) {
+        code += 8;
+    }
+
+    if mouse.modifiers.contains(KeyModifiers::CONTROL) {
+        code += 16;
+    }
+
+    // the SGR format counts cells from 1
+    let report = format!(
+        "\x1b[<{};{};{}{}",
+        code,
+        u32::from(mouse.column) + 1,
+        u32::from(mouse.row) + 1,
+        end
+    );
+    buf.extend_from_slice(report.as_bytes());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bytes(code: KeyCode, modifiers: KeyModifiers) -> Vec<u8> {
+        let mut buf = Vec::new();
+        key_to_bytes(&KeyEvent::new(code, modifiers), &mut buf);
+        buf
+    }
+
+    #[test]
+    fn test_key_to_bytes() {
+        assert_eq!(bytes(KeyCode::Char('a'), KeyModifiers::NONE), b"a");
+        assert_eq!(bytes(KeyCode::Char('c'), KeyModifiers::CONTROL), [3]);
+        assert_eq!(bytes(KeyCode::Char(']'), KeyModifiers::CONTROL), [29]);
+        assert_eq!(bytes(KeyCode::Char('x'), KeyModifiers::ALT), b"\x1bx");
+        assert_eq!(
+            bytes(KeyCode::Char('ж'), KeyModifiers::NONE),
+            "ж".as_bytes()
+        );
+        assert_eq!(bytes(KeyCode::Enter, KeyModifiers::NONE), b"\r");
+        assert_eq!(bytes(KeyCode::Up, KeyModifiers::NONE), b"\x1b[A");
+        assert_eq!(bytes(KeyCode::F(1), KeyModifiers::NONE), b"\x1bOP");
+        assert_eq!(bytes(KeyCode::F(5), KeyModifiers::NONE), b"\x1b[15~");
+        assert_eq!(bytes(KeyCode::F(10), KeyModifiers::NONE), b"\x1b[21~");
+        assert_eq!(bytes(KeyCode::F(12), KeyModifiers::NONE), b"\x1b[24~");
+    }
+
+    #[test]
+    fn test_key_to_bytes_with_modifiers() {
+        assert_eq!(bytes(KeyCode::Up, KeyModifiers::SHIFT), b"\x1b[1;2A");
+        assert_eq!(bytes(KeyCode::Left, KeyModifiers::CONTROL), b"\x1b[1;5D");
+        assert_eq!(
+            bytes(KeyCode::Right, KeyModifiers::CONTROL | KeyModifiers::ALT),
+            b"\x1b[1;7C"
+        );
+        assert_eq!(bytes(KeyCode::Delete, KeyModifiers::ALT), b"\x1b[3;3~");
+        assert_eq!(bytes(KeyCode::F(1), KeyModifiers::SHIFT), b"\x1b[1;2P");
+        assert_eq!(bytes(KeyCode::F(5), KeyModifiers::CONTROL), b"\x1b[15;5~");
+    }
+
+    #[test]
+    fn test_mouse_to_bytes() {
+        let mouse = |kind, modifiers| {
+            let mut buf = Vec::new();
+            let event = MouseEvent {
+                kind,
+                column: 9,
+                row: 4,
+                modifiers,
+            };
+            mouse_to_bytes(&event, &mut buf);
+            buf
+        };
+
+        assert_eq!(
+            mouse(MouseEventKind::Down(MouseButton::Left), KeyModifiers::NONE),
+            b"\x1b[<0;10;5M"
+        );
+        assert_eq!(
+            mouse(MouseEventKind::Up(MouseButton::Right), KeyModifiers::NONE),
+            b"\x1b[<2;10;5m"
+        );
+        assert_eq!(
+            mouse(
+                MouseEventKind::Drag(MouseButton::Left),
+                KeyModifiers::CONTROL
+            ),
+            b"\x1b[<48;10;5M"
+        );
+        assert_eq!(
+            mouse(MouseEventKind::ScrollDown, KeyModifiers::NONE),
+            b"\x1b[<65;10;5M"
+        );
+    }
+
+    #[test]
+    fn test_paste_keeps_markers() {
+        let mut input = CrosstermInput {
+            pending: Vec::new(),
+            resize: Resize::default(),
+            is_raw: false,
+        };
+
+        input.handle(Event::Paste(String::from("ls\n")));
+        input.handle(Event::Resize(100, 40));
+
+        assert_eq!(input.pending, b"\x1b[200~ls\n\x1b[201~");
+        assert_eq!(input.take_resize(), Some((100, 40)));
+        assert_eq!(input.take_resize(), None);
+    }
+}
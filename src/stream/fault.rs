@@ -0,0 +1,342 @@
+//! This module contains a [FaultStream]
+//! which wraps other streams in order to emulate a flaky connection.
+//!
+//! It's supposed to be used in tests to verify that matching logic
+//! handles partial reads, e.g. a prompt split across two reads.
+
+use std::{
+    io::{self, Read, Result, Write},
+    ops::{Deref, DerefMut},
+    time::{Duration, Instant},
+};
+
+#[cfg(feature = "async")]
+use futures_lite::{AsyncRead, AsyncWrite};
+#[cfg(feature = "async")]
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use crate::process::NonBlocking;
+
+/// FaultStream a IO stream wrapper,
+/// which chunks read bytes, delays reads and can drop a connection.
+///
+/// # Example
+///
+/// ```
+/// use std::time::Duration;
+/// use expectrl::{stream::fault::FaultStream, test::MockStream, Session};
+///
+/// let stream = MockStream::new().output("Password: ");
+/// let stream = FaultStream::new(stream)
+///     .max_chunk(1)
+///     .delay(Duration::from_millis(1));
+///
+/// let mut session = Session::new((), stream).unwrap();
+/// # #[cfg(not(feature = "async"))]
+/// session.expect("Password:").unwrap();
+/// ```
+#[derive(Debug)]
+pub struct FaultStream<S> {
+    stream: S,
+    max_chunk: Option<usize>,
+    splits: Vec<usize>,
+    delay: Option<Duration>,
+    disconnect_after: Option<usize>,
+    read: usize,
+    non_blocking: bool,
+    last_read: Option<Instant>,
+    #[cfg(feature = "async")]
+    timer: Option<futures_timer::Delay>,
+}
+
+impl<S> FaultStream<S> {
+    /// Creates a new instance of the stream which doesn't inject any faults.
+    pub fn new(stream: S) -> Self {
+        Self {
+            stream,
+            max_chunk: None,
+            splits: Vec::new(),
+            delay: None,
+            disconnect_after: None,
+            read: 0,
+            non_blocking: false,
+            last_read: None,
+            #[cfg(feature = "async")]
+            timer: None,
+        }
+    }
+
+    /// Sets a maximum number of bytes returned by a single read.
+    pub fn max_chunk(mut self, size: usize) -> Self {
+        self.max_chunk = Some(std::cmp::max(size, 1));
+        self
+    }
+
+    /// Makes a read end at a given offset of the stream.
+    ///
+    /// It can be used to split a particular prompt in two reads.
+    pub fn split_at(mut self, offset: usize) -> Self {
+        self.splits.push(offset);
+        self
+    }
+
+    /// Sets a delay between reads.
+    ///
+    /// In a non-blocking mode a read fails with [`io::ErrorKind::WouldBlock`] until the delay passes.
+    pub fn delay(mut self, delay: Duration) -> Self {
+        self.delay = Some(delay);
+        self
+    }
+
+    /// Drops a connection after a given number of bytes was read.
+    ///
+    /// All following reads return `0` as a closed terminal does.
+    pub fn disconnect_after(mut self, n: usize) -> Self {
+        self.disconnect_after = Some(n);
+        self
+    }
+
+    /// Returns a number of bytes read so far.
+    pub fn get_read_count(&self) -> usize {
+        self.read
+    }
+
+    /// Returns a time left until a next read is allowed.
+    fn time_left(&self) -> Option<Duration> {
+        let delay = self.delay?;
+        let last = self.last_read?;
+        delay.checked_sub(last.elapsed()).filter(|d| !d.is_zero())
+    }
+
+    /// Returns a maximum number of bytes the next read can return.
+    fn limit(&self, len: usize) -> usize {
+        let mut limit = len;
+        if let Some(max) = self.max_chunk {
+            limit = std::cmp::min(limit, max);
+        }
+
+        if let Some(n) = self.disconnect_after {
+            limit = std::cmp::min(limit, n.saturating_sub(self.read));
+        }
+
+        let next_split = self
+            .splits
+            .iter()
+            .filter(|&&offset| offset > self.read)
+            .min();
+        if let Some(offset) = next_split {
+            limit = std::cmp::min(limit, offset - self.read);
+        }
+
+        limit
+    }
+
+    fn is_disconnected(&self) -> bool {
+        matches!(self.disconnect_after, Some(n) if self.read >= n)
+    }
+
+    fn consume(&mut self, n: usize) {
+        self.read += n;
+        if n > 0 {
+            self.last_read = Some(Instant::now());
+        }
+    }
+}
+
+impl<S: Write> Write for FaultStream<S> {
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        self.stream.write(buf)
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        self.stream.flush()
+    }
+}
+
+impl<S: Read> Read for FaultStream<S> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        if self.is_disconnected() || buf.is_empty() {
+            return Ok(0);
+        }
+
+        if let Some(left) = self.time_left() {
+            if self.non_blocking {
+                return Err(io::Error::new(io::ErrorKind::WouldBlock, ""));
+            }
+
+            std::thread::sleep(left);
+        }
+
+        let limit = self.limit(buf.len());
+        let n = self.stream.read(&mut buf[..limit])?;
+        self.consume(n);
+
+        Ok(n)
+    }
+}
+
+impl<S: NonBlocking> NonBlocking for FaultStream<S> {
+    fn set_non_blocking(&mut self) -> Result<()> {
+        self.non_blocking = true;
+        self.stream.set_non_blocking()
+    }
+
+    fn set_blocking(&mut self) -> Result<()> {
+        self.non_blocking = false;
+        self.stream.set_blocking()
+    }
+    fn wait_readable(&mut self, timeout: Option<Duration>) -> Result<()> {
+        if self.is_disconnected() {
+            return Ok(());
+        }
+
+        // a delayed read can't be done earlier no matter what the stream has
+        if let Some(left) = self.time_left() {
+            let left = timeout.map_or(left, |timeout| std::cmp::min(left, timeout));
+            std::thread::sleep(left);
+            return Ok(());
+        }
+
+        self.stream.wait_readable(timeout)
+    }
+}
+
+impl<S> Deref for FaultStream<S> {
+    type Target = S;
+
+    fn deref(&self) -> &Self::Target {
+        &self.stream
+    }
+}
+
+impl<S> DerefMut for FaultStream<S> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.stream
+    }
+}
+
+#[cfg(feature = "async")]
+impl<S: AsyncWrite + Unpin> AsyncWrite for FaultStream<S> {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<Result<usize>> {
+        Pin::new(&mut self.stream).poll_write(cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        Pin::new(&mut self.stream).poll_flush(cx)
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        Pin::new(&mut self.stream).poll_close(cx)
+    }
+}
+
+#[cfg(feature = "async")]
+impl<S: AsyncRead + Unpin> AsyncRead for FaultStream<S> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize>> {
+        if self.is_disconnected() || buf.is_empty() {
+            return Poll::Ready(Ok(0));
+        }
+
+        if let Some(left) = self.time_left() {
+            let timer = self
+                .timer
+                .get_or_insert_with(|| futures_timer::Delay::new(left));
+            if Pin::new(timer).poll(cx).is_pending() {
+                return Poll::Pending;
+            }
+        }
+
+        self.timer = None;
+
+        let limit = self.limit(buf.len());
+        let result = Pin::new(&mut self.stream).poll_read(cx, &mut buf[..limit]);
+        if let Poll::Ready(Ok(n)) = &result {
+            self.consume(*n);
+        }
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test::MockStream;
+
+    #[test]
+    fn test_chunks() {
+        let stream = MockStream::new().output("Hello World").eof();
+        let mut stream = FaultStream::new(stream).max_chunk(4).split_at(5);
+
+        let mut buf = [0; 16];
+        let mut reads = Vec::new();
+        loop {
+            let n = stream.read(&mut buf).unwrap();
+            if n == 0 {
+                break;
+            }
+
+            reads.push(String::from_utf8_lossy(&buf[..n]).to_string());
+        }
+
+        assert_eq!(reads, ["Hell", "o", " Wor", "ld"]);
+        assert_eq!(stream.get_read_count(), 11);
+    }
+
+    #[test]
+    fn test_disconnect() {
+        let stream = MockStream::new().output("Hello World");
+        let mut stream = FaultStream::new(stream).disconnect_after(3);
+
+        let mut buf = [0; 16];
+        assert_eq!(stream.read(&mut buf).unwrap(), 3);
+        assert_eq!(stream.read(&mut buf).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_delay() {
+        let stream = MockStream::new().output("Hello");
+        let mut stream = FaultStream::new(stream)
+            .max_chunk(1)
+            .delay(Duration::from_millis(50));
+
+        let mut buf = [0; 16];
+        assert_eq!(stream.read(&mut buf).unwrap(), 1);
+
+        stream.set_non_blocking().unwrap();
+        let err = stream.read(&mut buf).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::WouldBlock);
+
+        stream.set_blocking().unwrap();
+        let now = Instant::now();
+        assert_eq!(stream.read(&mut buf).unwrap(), 1);
+        assert!(now.elapsed() >= Duration::from_millis(25));
+    }
+
+    #[test]
+    fn test_split_prompt() {
+        let stream = MockStream::new().output("$ ls\r\nPassword: ");
+        let stream = FaultStream::new(stream).split_at(12);
+        let mut session = crate::Session::new((), stream).unwrap();
+
+        #[cfg(not(feature = "async"))]
+        let captures = session.expect("Password:").unwrap();
+        #[cfg(feature = "async")]
+        let captures = futures_lite::future::block_on(session.expect("Password:")).unwrap();
+
+        assert_eq!(captures.before(), b"$ ls\r\n");
+        assert_eq!(session.get_stream().get_read_count(), 16);
+    }
+}
@@ -2,8 +2,13 @@
 //! which can wrap other streams in order to log a read/write operations.
 
 use std::{
+    ffi::OsString,
+    fmt,
+    fs::{self, File, OpenOptions},
     io::{self, Read, Result, Write},
     ops::{Deref, DerefMut},
+    path::{Path, PathBuf},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
 
 #[cfg(feature = "async")]
@@ -16,28 +21,112 @@ use std::{
 
 use crate::process::NonBlocking;
 
+/// LogFormat defines how logged bytes are rendered.
+///
+/// Each read/write operation is logged as a single line tagged by its direction.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum LogFormat {
+    /// A valid UTF-8 text is written as a debug string, e.g. `"Hi\r\n"`,
+    /// other bytes are written as a list of numbers, e.g. `(bytes): [72, 105, 255]`.
+    ///
+    /// It's the format the log had before the other ones were added.
+    #[default]
+    Debug,
+    /// Bytes are written as they are.
+    ///
+    /// Control characters are not escaped, so they may affect a terminal the log is printed to.
+    Raw,
+    /// Printable ASCII characters are written as they are,
+    /// other bytes are escaped, e.g. `\r` or `\x1b`.
+    EscapedAscii,
+    /// Bytes are written as hex numbers followed by their ASCII representation,
+    /// e.g. `48 69 0d 0a |Hi..|`.
+    HexDump,
+}
+
+/// LogClock defines a timestamp each logged line is prefixed with.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum LogClock {
+    /// Lines are not timestamped.
+    #[default]
+    Off,
+    /// Time passed since the clock was set, e.g. `[+1.250000s]`.
+    Monotonic,
+    /// UTC time of a system clock, e.g. `[2024-01-31T12:00:00.250Z]`.
+    WallClock,
+}
+
 /// LogStream a IO stream wrapper,
 /// which logs each write/read operation.
 #[derive(Debug)]
 pub struct LogStream<S, W> {
     stream: S,
     logger: W,
+    layout: Layout,
 }
 
 impl<S, W> LogStream<S, W> {
     /// Creates a new instance of the stream.
     pub fn new(stream: S, logger: W) -> Self {
-        Self { stream, logger }
+        Self::with_format(stream, logger, LogFormat::default())
+    }
+
+    /// Creates a new instance of the stream with a given log format.
+    pub fn with_format(stream: S, logger: W, format: LogFormat) -> Self {
+        Self {
+            stream,
+            logger,
+            layout: Layout {
+                format,
+                ..Layout::default()
+            },
+        }
+    }
+
+    /// Sets a format of the log.
+    pub fn set_format(&mut self, format: LogFormat) {
+        self.layout.format = format;
+    }
+
+    /// Returns a format of the log.
+    pub fn get_format(&self) -> LogFormat {
+        self.layout.format
+    }
+
+    /// Sets a clock each line of the log is timestamped by.
+    ///
+    /// [LogClock::Monotonic] time is counted from this call.
+    pub fn set_clock(&mut self, clock: LogClock) {
+        self.layout.set_clock(clock);
+    }
+
+    /// Returns a clock each line of the log is timestamped by.
+    pub fn get_clock(&self) -> LogClock {
+        self.layout.clock
+    }
+
+    /// Sets a name each line of the log is tagged by, e.g. `[router-1] read: "Hello"`.
+    pub fn set_name<N: Into<String>>(&mut self, name: N) {
+        self.layout.name = Some(name.into());
+    }
+
+    /// Returns a name each line of the log is tagged by.
+    pub fn get_name(&self) -> Option<&str> {
+        self.layout.name.as_deref()
     }
 }
 
 impl<S, W: Write> LogStream<S, W> {
     fn log_write(&mut self, buf: &[u8]) {
-        log(&mut self.logger, "write", buf);
+        if crate::secret::is_secret_write() {
+            return;
+        }
+
+        self.layout.log(&mut self.logger, "write", buf);
     }
 
     fn log_read(&mut self, buf: &[u8]) {
-        log(&mut self.logger, "read", buf);
+        self.layout.log(&mut self.logger, "read", buf);
     }
 }
 
@@ -54,22 +143,7 @@ impl<S: Write, W: Write> Write for LogStream<S, W> {
 
     fn write_vectored(&mut self, bufs: &[io::IoSlice<'_>]) -> Result<usize> {
         let n = self.stream.write_vectored(bufs)?;
-
-        let mut rest = n;
-        let mut bytes = Vec::new();
-        for buf in bufs {
-            let written = std::cmp::min(buf.len(), rest);
-            rest -= written;
-
-            bytes.extend(&buf.as_ref()[..written]);
-
-            if rest == 0 {
-                break;
-            }
-        }
-
-        self.log_write(&bytes);
-
+        self.log_write(&written_bytes(bufs, n));
         Ok(n)
     }
 }
@@ -90,6 +164,10 @@ impl<S: NonBlocking, W> NonBlocking for LogStream<S, W> {
     fn set_blocking(&mut self) -> Result<()> {
         self.stream.set_blocking()
     }
+
+    fn wait_readable(&mut self, timeout: Option<Duration>) -> Result<()> {
+        self.stream.wait_readable(timeout)
+    }
 }
 
 impl<S, W> Deref for LogStream<S, W> {
@@ -150,9 +228,459 @@ impl<S: AsyncRead + Unpin, W: Write + Unpin> AsyncRead for LogStream<S, W> {
     }
 }
 
-fn log(mut writer: impl Write, target: &str, data: &[u8]) {
-    let _ = match std::str::from_utf8(data) {
-        Ok(data) => writeln!(writer, "{}: {:?}", target, data),
-        Err(..) => writeln!(writer, "{}:(bytes): {:?}", target, data),
-    };
+/// Logger writes each read/write operation of a session into a sink.
+///
+/// Unlike [LogStream] it's a part of a session,
+/// so it can be set, removed or paused without changing a type of the session.
+#[derive(Default)]
+pub(crate) struct Logger {
+    sink: Option<Box<dyn Write + Send>>,
+    layout: Layout,
+    paused: bool,
+}
+
+impl Logger {
+    pub(crate) fn set_sink(&mut self, sink: Box<dyn Write + Send>) {
+        self.sink = Some(sink);
+    }
+
+    pub(crate) fn unset_sink(&mut self) {
+        self.sink = None;
+    }
+
+    pub(crate) fn set_format(&mut self, format: LogFormat) {
+        self.layout.format = format;
+    }
+
+    pub(crate) fn set_clock(&mut self, clock: LogClock) {
+        self.layout.set_clock(clock);
+    }
+
+    pub(crate) fn set_paused(&mut self, paused: bool) {
+        self.paused = paused;
+    }
+
+    pub(crate) fn set_name(&mut self, name: Option<String>) {
+        self.layout.name = name;
+    }
+
+    pub(crate) fn log_read(&mut self, buf: &[u8]) {
+        self.log("read", buf);
+    }
+
+    pub(crate) fn log_write(&mut self, buf: &[u8]) {
+        if crate::secret::is_secret_write() {
+            return;
+        }
+
+        self.log("write", buf);
+    }
+
+    /// Logs a notable event of a session, e.g. a timeout.
+    pub(crate) fn log_event(&mut self, target: &str, text: &str) {
+        if self.paused {
+            return;
+        }
+
+        if let Some(sink) = &mut self.sink {
+            self.layout.event(sink, target, text);
+        }
+    }
+
+    fn log(&mut self, target: &str, buf: &[u8]) {
+        if self.paused || buf.is_empty() {
+            return;
+        }
+
+        if let Some(sink) = &mut self.sink {
+            self.layout.log(sink, target, buf);
+        }
+    }
+}
+
+impl fmt::Debug for Logger {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Logger")
+            .field("sink", &self.sink.is_some())
+            .field("layout", &self.layout)
+            .field("paused", &self.paused)
+            .finish()
+    }
+}
+
+/// RotatingFile is a log sink which caps a size of a log file.
+///
+/// Once a file would exceed a given size it's renamed with a `.1` suffix,
+/// a previous `.1` file becomes `.2` and so on.
+/// Only a given number of rotated files is kept, older ones are removed.
+///
+/// It can be used with [`LogStream`] or `Session::set_log`.
+///
+/// # Example
+///
+/// ```no_run,ignore
+/// use expectrl::{spawn, stream::log::RotatingFile};
+///
+/// let mut p = spawn("cat").unwrap();
+/// // at most 10MB in session.log and 3 more files session.log.1..session.log.3
+/// p.set_log(RotatingFile::new("session.log", 10 * 1024 * 1024, 3).unwrap());
+/// ```
+#[derive(Debug)]
+pub struct RotatingFile {
+    path: PathBuf,
+    max_size: u64,
+    max_files: usize,
+    file: Option<File>,
+    size: u64,
+}
+
+impl RotatingFile {
+    /// Opens a file for appending, creating it if it doesn't exist.
+    pub fn new<P: Into<PathBuf>>(path: P, max_size: u64, max_files: usize) -> Result<Self> {
+        let path = path.into();
+        let file = open_append(&path)?;
+        let size = file.metadata()?.len();
+
+        Ok(Self {
+            path,
+            max_size,
+            max_files,
+            file: Some(file),
+            size,
+        })
+    }
+
+    /// Returns a path of a current file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn rotated_path(&self, index: usize) -> PathBuf {
+        let mut path = OsString::from(self.path.as_os_str());
+        path.push(format!(".{}", index));
+        PathBuf::from(path)
+    }
+
+    fn rotate(&mut self) -> Result<()> {
+        // A file is closed before it's renamed, as an opened file can't be renamed on Windows.
+        if let Some(mut file) = self.file.take() {
+            file.flush()?;
+        }
+
+        if self.max_files == 0 {
+            self.file = Some(File::create(&self.path)?);
+            self.size = 0;
+            return Ok(());
+        }
+
+        let _ = fs::remove_file(self.rotated_path(self.max_files));
+        for index in (1..self.max_files).rev() {
+            let _ = fs::rename(self.rotated_path(index), self.rotated_path(index + 1));
+        }
+
+        fs::rename(&self.path, self.rotated_path(1))?;
+
+        self.file = Some(open_append(&self.path)?);
+        self.size = 0;
+
+        Ok(())
+    }
+
+    fn file(&mut self) -> Result<&mut File> {
+        match &mut self.file {
+            Some(file) => Ok(file),
+            file => Ok(file.insert(open_append(&self.path)?)),
+        }
+    }
+}
+
+fn open_append(path: &Path) -> Result<File> {
+    OpenOptions::new().create(true).append(true).open(path)
+}
+
+impl Write for RotatingFile {
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        if self.size > 0 && self.size + buf.len() as u64 > self.max_size {
+            self.rotate()?;
+        }
+
+        let n = self.file()?.write(buf)?;
+        self.size += n as u64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        match &mut self.file {
+            Some(file) => file.flush(),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Returns bytes which were written by a vectored write of `n` bytes.
+pub(crate) fn written_bytes(bufs: &[io::IoSlice<'_>], n: usize) -> Vec<u8> {
+    let mut rest = n;
+    let mut bytes = Vec::new();
+    for buf in bufs {
+        let written = std::cmp::min(buf.len(), rest);
+        rest -= written;
+
+        bytes.extend(&buf.as_ref()[..written]);
+
+        if rest == 0 {
+            break;
+        }
+    }
+
+    bytes
+}
+
+/// Layout defines how a logged line looks like.
+#[derive(Debug)]
+struct Layout {
+    format: LogFormat,
+    clock: LogClock,
+    start: Instant,
+    name: Option<String>,
+}
+
+impl Default for Layout {
+    fn default() -> Self {
+        Self {
+            format: LogFormat::default(),
+            clock: LogClock::default(),
+            start: Instant::now(),
+            name: None,
+        }
+    }
+}
+
+impl Layout {
+    fn set_clock(&mut self, clock: LogClock) {
+        self.clock = clock;
+        self.start = Instant::now();
+    }
+
+    fn log(&self, writer: impl Write, target: &str, data: &[u8]) {
+        log(writer, self.format, &self.prefix(), target, data);
+    }
+
+    /// Logs a text as it is regardless of a format.
+    fn event(&self, mut writer: impl Write, target: &str, text: &str) {
+        let _ = writeln!(writer, "{}{}: {}", self.prefix(), target, text);
+    }
+
+    fn prefix(&self) -> String {
+        let mut prefix = match self.clock {
+            LogClock::Off => String::new(),
+            LogClock::Monotonic => {
+                format!("[+{:.6}s] ", self.start.elapsed().as_secs_f64())
+            }
+            LogClock::WallClock => format!("[{}] ", utc_time(SystemTime::now())),
+        };
+
+        if let Some(name) = &self.name {
+            prefix.push_str(&format!("[{}] ", name));
+        }
+
+        prefix
+    }
+}
+
+/// Formats a time as RFC 3339 UTC time with milliseconds.
+fn utc_time(time: SystemTime) -> String {
+    let since_epoch = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+    let secs = since_epoch.as_secs();
+    let (days, secs_of_day) = (secs / 86400, secs % 86400);
+
+    // Converts days since 1970-01-01 to a civil date.
+    // See http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let z = days as i64 + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+        year,
+        month,
+        day,
+        secs_of_day / 3600,
+        secs_of_day % 3600 / 60,
+        secs_of_day % 60,
+        since_epoch.subsec_millis(),
+    )
+}
+
+fn log(mut writer: impl Write, format: LogFormat, prefix: &str, target: &str, data: &[u8]) {
+    let mut line = format!("{}{}", prefix, target).into_bytes();
+    match format {
+        LogFormat::Debug => {
+            let text = match std::str::from_utf8(data) {
+                Ok(text) => format!(": {:?}", text),
+                Err(..) => format!(":(bytes): {:?}", data),
+            };
+            line.extend(text.bytes());
+        }
+        LogFormat::Raw => {
+            line.extend(b": ");
+            line.extend(data);
+        }
+        LogFormat::EscapedAscii => {
+            line.extend(b": \"");
+            line.extend(data.escape_ascii());
+            line.push(b'"');
+        }
+        LogFormat::HexDump => {
+            line.extend(b": ");
+            for b in data {
+                line.extend(format!("{:02x} ", b).bytes());
+            }
+
+            line.push(b'|');
+            line.extend(
+                data.iter()
+                    .map(|&b| match b.is_ascii_graphic() || b == b' ' {
+                        true => b,
+                        false => b'.',
+                    }),
+            );
+            line.push(b'|');
+        }
+    }
+
+    line.push(b'\n');
+
+    // A line is written at once, so lines of streams sharing a logger are not mixed.
+    let _ = writer.write_all(&line);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn logged(format: LogFormat, data: &[u8]) -> Vec<u8> {
+        let mut buf = Vec::new();
+        log(&mut buf, format, "", "read", data);
+        buf
+    }
+
+    #[test]
+    fn test_log_format() {
+        assert_eq!(
+            logged(LogFormat::default(), b"Hi\x1b\r\n"),
+            b"read: \"Hi\\u{1b}\\r\\n\"\n"
+        );
+        assert_eq!(
+            logged(LogFormat::default(), b"Hi\xFF"),
+            b"read:(bytes): [72, 105, 255]\n"
+        );
+
+        let data = b"Hi \"\x1b[0m\r\n\xFF";
+        assert_eq!(
+            logged(LogFormat::Raw, data),
+            b"read: Hi \"\x1b[0m\r\n\xFF\n"
+        );
+        assert_eq!(
+            logged(LogFormat::EscapedAscii, data),
+            br#"read: "Hi \"\x1b[0m\r\n\xff""#.iter().chain(b"\n").copied().collect::<Vec<_>>()
+        );
+        assert_eq!(
+            logged(LogFormat::HexDump, data),
+            b"read: 48 69 20 22 1b 5b 30 6d 0d 0a ff |Hi \".[0m...|\n"
+        );
+    }
+
+    #[test]
+    fn test_utc_time() {
+        assert_eq!(utc_time(UNIX_EPOCH), "1970-01-01T00:00:00.000Z");
+        assert_eq!(
+            utc_time(UNIX_EPOCH + Duration::from_millis(951_782_400_250)),
+            "2000-02-29T00:00:00.250Z"
+        );
+        assert_eq!(
+            utc_time(UNIX_EPOCH + Duration::from_secs(1_706_702_399)),
+            "2024-01-31T11:59:59.000Z"
+        );
+    }
+
+    #[test]
+    fn test_log_clock() {
+        let mut stream = LogStream::new(io::Cursor::new(Vec::new()), Vec::new());
+        stream.set_clock(LogClock::Monotonic);
+        stream.write_all(b"Hello").unwrap();
+        stream.set_clock(LogClock::WallClock);
+        stream.write_all(b"World").unwrap();
+
+        let log = String::from_utf8(stream.logger).unwrap();
+        let lines = log.lines().collect::<Vec<_>>();
+        assert!(lines[0].starts_with("[+0.0"), "{:?}", lines[0]);
+        assert!(lines[0].ends_with("s] write: \"Hello\""), "{:?}", lines[0]);
+        assert!(lines[1].starts_with("[20"), "{:?}", lines[1]);
+        assert!(lines[1].ends_with("Z] write: \"World\""), "{:?}", lines[1]);
+    }
+
+    #[test]
+    fn test_rotating_file() {
+        let dir = std::env::temp_dir().join(format!("expectrl-log-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("session.log");
+
+        let mut file = RotatingFile::new(&path, 10, 2).unwrap();
+        for line in ["first\n", "second\n", "third\n", "fourth\n"] {
+            file.write_all(line.as_bytes()).unwrap();
+        }
+        file.flush().unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "fourth\n");
+        assert_eq!(
+            fs::read_to_string(dir.join("session.log.1")).unwrap(),
+            "third\n"
+        );
+        assert_eq!(
+            fs::read_to_string(dir.join("session.log.2")).unwrap(),
+            "second\n"
+        );
+        assert!(!dir.join("session.log.3").exists());
+
+        let mut file = RotatingFile::new(&path, 10, 0).unwrap();
+        file.write_all(b"fifth\n").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "fifth\n");
+        assert_eq!(
+            fs::read_to_string(dir.join("session.log.1")).unwrap(),
+            "third\n"
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_log_stream() {
+        let mut stream = LogStream::with_format(
+            io::Cursor::new(b"Hello".to_vec()),
+            Vec::new(),
+            LogFormat::HexDump,
+        );
+        let mut buf = [0; 5];
+        stream.read_exact(&mut buf).unwrap();
+
+        stream.set_format(LogFormat::Raw);
+        stream.write_all(b"World").unwrap();
+
+        assert_eq!(
+            stream.logger,
+            b"read: 48 65 6c 6c 6f |Hello|\nwrite: World\n"
+        );
+
+        stream.logger.clear();
+        stream.set_name("router-1");
+        stream.write_all(b"!").unwrap();
+        assert_eq!(stream.get_name(), Some("router-1"));
+        assert_eq!(stream.logger, b"[router-1] write: !\n");
+    }
 }
@@ -1,4 +1,12 @@
 //! Stream module contains a set of IO (write/read) wrappers.
 
+pub mod asciicast;
+#[cfg(windows)]
+pub mod codepage;
+#[cfg(all(feature = "crossterm", not(feature = "async")))]
+pub mod crossterm;
+pub mod fault;
 pub mod log;
+#[cfg(unix)]
+pub mod pipe;
 pub mod stdin;
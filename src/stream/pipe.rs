@@ -0,0 +1,271 @@
+//! This module contains a [PipeStream]
+//! which is a IO stream over plain pipes of a process rather than a pseudo terminal.
+
+use std::{
+    fs::File,
+    io::{self, Read, Result, Write},
+    os::unix::io::{AsFd, AsRawFd, BorrowedFd, RawFd},
+    time::Duration,
+};
+
+#[cfg(feature = "async")]
+use futures_lite::{AsyncRead, AsyncWrite};
+#[cfg(feature = "async")]
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+#[cfg(feature = "async")]
+use crate::process::IntoAsyncStream;
+use crate::process::{
+    unix::{make_non_blocking, wait_readable},
+    NonBlocking,
+};
+
+/// PipeStream a IO stream which reads from one pipe and writes to another.
+///
+/// A stream can be read only, e.g. when it represents a stderr of a process.
+#[derive(Debug)]
+pub struct PipeStream {
+    reader: File,
+    writer: Option<File>,
+}
+
+impl PipeStream {
+    /// Creates a new stream.
+    pub fn new(reader: File, writer: File) -> Self {
+        Self {
+            reader,
+            writer: Some(writer),
+        }
+    }
+
+    /// Creates a new read only stream.
+    ///
+    /// A write to the stream fails.
+    pub fn read_only(reader: File) -> Self {
+        Self {
+            reader,
+            writer: None,
+        }
+    }
+
+    /// Closes a writing end of the stream,
+    /// so a process gets EOF on its input.
+    pub fn close_writer(&mut self) {
+        self.writer = None;
+    }
+
+    fn writer(&mut self) -> Result<&mut File> {
+        self.writer
+            .as_mut()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::BrokenPipe, "the stream is read only"))
+    }
+}
+
+impl Write for PipeStream {
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        self.writer()?.write(buf)
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        match &mut self.writer {
+            Some(writer) => writer.flush(),
+            None => Ok(()),
+        }
+    }
+
+    fn write_vectored(&mut self, bufs: &[io::IoSlice<'_>]) -> Result<usize> {
+        self.writer()?.write_vectored(bufs)
+    }
+}
+
+impl Read for PipeStream {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        self.reader.read(buf)
+    }
+}
+
+impl NonBlocking for PipeStream {
+    fn set_non_blocking(&mut self) -> Result<()> {
+        make_non_blocking(self.reader.as_raw_fd(), true)
+    }
+
+    fn set_blocking(&mut self) -> Result<()> {
+        make_non_blocking(self.reader.as_raw_fd(), false)
+    }
+
+    fn wait_readable(&mut self, timeout: Option<Duration>) -> Result<()> {
+        wait_readable(self.reader.as_raw_fd(), timeout)
+    }
+}
+
+impl AsRawFd for PipeStream {
+    fn as_raw_fd(&self) -> RawFd {
+        self.reader.as_raw_fd()
+    }
+}
+
+impl AsFd for PipeStream {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.reader.as_fd()
+    }
+}
+
+#[cfg(feature = "async")]
+impl IntoAsyncStream for PipeStream {
+    type AsyncStream = AsyncPipeStream;
+
+    fn into_async_stream(self) -> Result<Self::AsyncStream> {
+        AsyncPipeStream::new(self)
+    }
+}
+
+/// An async version of [PipeStream].
+#[cfg(feature = "async")]
+#[derive(Debug)]
+pub struct AsyncPipeStream {
+    reader: async_io::Async<File>,
+    writer: Option<async_io::Async<File>>,
+}
+
+#[cfg(feature = "async")]
+impl AsyncPipeStream {
+    fn new(stream: PipeStream) -> Result<Self> {
+        let reader = async_io::Async::new(stream.reader)?;
+        let writer = stream.writer.map(async_io::Async::new).transpose()?;
+        Ok(Self { reader, writer })
+    }
+
+    /// Closes a writing end of the stream,
+    /// so a process gets EOF on its input.
+    pub fn close_writer(&mut self) {
+        self.writer = None;
+    }
+}
+
+#[cfg(feature = "async")]
+impl AsRawFd for AsyncPipeStream {
+    fn as_raw_fd(&self) -> RawFd {
+        self.reader.as_raw_fd()
+    }
+}
+
+#[cfg(feature = "async")]
+impl AsFd for AsyncPipeStream {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.reader.get_ref().as_fd()
+    }
+}
+
+#[cfg(feature = "async")]
+impl AsyncWrite for AsyncPipeStream {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<Result<usize>> {
+        match &mut self.writer {
+            Some(writer) => Pin::new(writer).poll_write(cx, buf),
+            None => Poll::Ready(Err(io::Error::new(
+                io::ErrorKind::BrokenPipe,
+                "the stream is read only",
+            ))),
+        }
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        match &mut self.writer {
+            Some(writer) => Pin::new(writer).poll_flush(cx),
+            None => Poll::Ready(Ok(())),
+        }
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        match &mut self.writer {
+            Some(writer) => Pin::new(writer).poll_close(cx),
+            None => Poll::Ready(Ok(())),
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+impl AsyncRead for AsyncPipeStream {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize>> {
+        Pin::new(&mut self.reader).poll_read(cx, buf)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pipe() -> (File, File) {
+        use std::os::unix::io::FromRawFd;
+
+        let (reader, writer) = nix::unistd::pipe().unwrap();
+        // SAFETY: the descriptors are just created and owned by nobody else.
+        unsafe { (File::from_raw_fd(reader), File::from_raw_fd(writer)) }
+    }
+
+    #[test]
+    fn test_pipe_stream() {
+        let (reader, mut input) = pipe();
+        let (mut output, writer) = pipe();
+        let mut stream = PipeStream::new(reader, writer);
+
+        input.write_all(b"Hello").unwrap();
+        let mut buf = [0; 5];
+        stream.read_exact(&mut buf).unwrap();
+        assert_eq!(&buf, b"Hello");
+
+        stream.write_all(b"World").unwrap();
+        output.read_exact(&mut buf).unwrap();
+        assert_eq!(&buf, b"World");
+
+        stream.set_non_blocking().unwrap();
+        let err = stream.read(&mut buf).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::WouldBlock);
+    }
+
+    #[test]
+    fn test_wait_readable() {
+        use std::time::Instant;
+
+        let (reader, mut input) = pipe();
+        let mut stream = PipeStream::read_only(reader);
+
+        let now = Instant::now();
+        stream
+            .wait_readable(Some(Duration::from_millis(50)))
+            .unwrap();
+        assert!(now.elapsed() >= Duration::from_millis(50));
+
+        let writer = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(50));
+            input.write_all(b"Hello").unwrap();
+        });
+
+        stream.wait_readable(None).unwrap();
+        stream.set_non_blocking().unwrap();
+        let mut buf = [0; 5];
+        assert_eq!(stream.read(&mut buf).unwrap(), 5);
+
+        writer.join().unwrap();
+    }
+
+    #[test]
+    fn test_read_only_pipe_stream() {
+        let (reader, _writer) = pipe();
+        let mut stream = PipeStream::read_only(reader);
+
+        let err = stream.write(b"Hello").unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::BrokenPipe);
+        stream.flush().unwrap();
+    }
+}
@@ -32,6 +32,9 @@ impl Stdin {
     ///
     /// It may change terminal's STDIN state therefore, after
     /// it's used you must call [Stdin::close].
+    ///
+    /// The state is also restored when [Stdin] is dropped,
+    /// e.g. in case a callback of an interact session panics.
     pub fn open() -> Result<Self, Error> {
         #[cfg(not(feature = "async"))]
         {
@@ -64,6 +67,21 @@ impl Stdin {
     }
 }
 
+/// Restores a terminal state changed by [`Stdin::open`].
+///
+/// [`Stdin`] restores the state on [`Stdin::close`] or when it's dropped.
+/// But it doesn't happen when a process is interrupted by a signal,
+/// or when a panic message is printed before a [`Stdin`] is dropped.
+/// So the function can be called from a signal handler,
+/// e.g. the ones set by `ctrlc` or `signal-hook` crates which run handlers on a regular thread,
+/// or from a panic hook set by an application.
+///
+/// It never blocks and does nothing if the terminal wasn't changed.
+#[cfg(unix)]
+pub fn restore_terminal() -> Result<(), Error> {
+    inner::restore_terminal()
+}
+
 #[cfg(not(feature = "async"))]
 impl Read for Stdin {
     fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
@@ -116,6 +134,11 @@ mod inner {
     };
     use ptyprocess::set_raw;
 
+    use std::sync::Mutex;
+
+    /// A terminal state before the first [`Stdin`] was opened.
+    static ORIGINAL_STATE: Mutex<Option<Termios>> = Mutex::new(None);
+
     #[derive(Debug)]
     pub(super) struct StdinInner {
         orig_flags: Option<Termios>,
@@ -157,6 +180,10 @@ mod inner {
                     .map(Some)
                     .map_err(|e| Error::unknown("failed to call tcgetattr", e.to_string()))?;
 
+                if let Some(flags) = &o_pty_flags {
+                    save_state(flags);
+                }
+
                 set_raw(STDIN_FILENO)
                     .map_err(|e| Error::unknown("failed to set a raw tty", e.to_string()))?;
             }
@@ -165,9 +192,9 @@ mod inner {
         }
 
         pub(super) fn close(&mut self) -> Result<(), Error> {
-            if let Some(origin_stdin_flags) = &self.orig_flags {
-                termios::tcsetattr(STDIN_FILENO, termios::SetArg::TCSAFLUSH, origin_stdin_flags)
-                    .map_err(|e| Error::unknown("failed to call tcsetattr", e.to_string()))?;
+            if let Some(origin_stdin_flags) = self.orig_flags.take() {
+                set_state(&origin_stdin_flags)?;
+                forget_state(&origin_stdin_flags);
             }
 
             Ok(())
@@ -179,6 +206,45 @@ mod inner {
         }
     }
 
+    impl Drop for StdinInner {
+        fn drop(&mut self) {
+            #[cfg(not(feature = "async"))]
+            let _ = self.blocking(false);
+            let _ = self.close();
+        }
+    }
+
+    pub(super) fn restore_terminal() -> Result<(), Error> {
+        let state = match ORIGINAL_STATE.try_lock() {
+            Ok(mut state) => state.take(),
+            Err(_) => None,
+        };
+
+        match state {
+            Some(state) => set_state(&state),
+            None => Ok(()),
+        }
+    }
+
+    fn save_state(flags: &Termios) {
+        let mut state = ORIGINAL_STATE.lock().unwrap_or_else(|err| err.into_inner());
+        if state.is_none() {
+            *state = Some(flags.clone());
+        }
+    }
+
+    fn forget_state(flags: &Termios) {
+        let mut state = ORIGINAL_STATE.lock().unwrap_or_else(|err| err.into_inner());
+        if state.as_ref() == Some(flags) {
+            *state = None;
+        }
+    }
+
+    fn set_state(flags: &Termios) -> Result<(), Error> {
+        termios::tcsetattr(STDIN_FILENO, termios::SetArg::TCSAFLUSH, flags)
+            .map_err(|e| Error::unknown("failed to call tcsetattr", e.to_string()))
+    }
+
     impl AsRawFd for StdinInner {
         fn as_raw_fd(&self) -> std::os::unix::prelude::RawFd {
             self.stdin.as_raw_fd()
@@ -276,6 +342,12 @@ mod inner {
         }
     }
 
+    impl Drop for StdinInner {
+        fn drop(&mut self) {
+            let _ = self.close();
+        }
+    }
+
     #[cfg(not(feature = "async"))]
     impl Read for StdinInner {
         fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
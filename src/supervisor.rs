@@ -0,0 +1,535 @@
+//! This module contains a [`Supervisor`] which respawns a process once it exits unexpectedly.
+//!
+//! # Example
+//!
+#![cfg_attr(all(unix, not(feature = "async")), doc = "```no_run")]
+#![cfg_attr(not(all(unix, not(feature = "async"))), doc = "```ignore")]
+//! use std::time::Duration;
+//! use expectrl::{dialogue::Dialogue, spawn, supervisor::Supervisor};
+//!
+//! let login = Dialogue::new()
+//!     .expect("login:")
+//!     .send_line("admin")
+//!     .expect("$ ");
+//!
+//! let mut supervisor = Supervisor::new(|| spawn("telnet 192.168.0.1"))
+//!     .max_restarts(5)
+//!     .backoff(Duration::from_secs(1))
+//!     .login(login);
+//!
+//! loop {
+//!     let alarm = supervisor.expect("ALARM").unwrap();
+//!     println!("{:?}", alarm.before());
+//! }
+//! ```
+
+use std::{
+    fmt,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    dialogue::Dialogue,
+    process::Healthcheck,
+    session::{OsProcess, OsProcessStream},
+    Captures, Error, Needle, Session,
+};
+
+#[cfg(not(feature = "async"))]
+use crate::process::NonBlocking;
+#[cfg(not(feature = "async"))]
+use std::io::{Read, Write};
+
+#[cfg(feature = "async")]
+use futures_lite::{AsyncRead, AsyncWrite};
+
+type Spawn<P, S> = Box<dyn FnMut() -> Result<Session<P, S>, Error>>;
+type RestartHook<P, S> = Box<dyn FnMut(&mut Session<P, S>, usize) -> Result<(), Error>>;
+
+/// Supervisor owns a session and spawns it again once the process exits,
+/// e.g. when a connection to a device was dropped.
+///
+/// A process is spawned by a given function on a first use,
+/// after that the [login](Supervisor::login) dialogue is run.
+///
+/// Delays between restarts grow twice each time starting from [`Supervisor::backoff`]
+/// up to [`Supervisor::max_backoff`].
+/// Once a process runs longer than [`Supervisor::stable_after`]
+/// the delay and the count of [`Supervisor::max_restarts`] start over.
+pub struct Supervisor<P = OsProcess, S = OsProcessStream> {
+    spawn: Spawn<P, S>,
+    session: Option<Session<P, S>>,
+    login: Option<Dialogue>,
+    on_restart: Option<RestartHook<P, S>>,
+    max_restarts: Option<usize>,
+    backoff: Duration,
+    max_backoff: Duration,
+    stable_after: Duration,
+    restarts: usize,
+    failures: usize,
+    is_spawned: bool,
+    started_at: Option<Instant>,
+}
+
+impl<P, S> Supervisor<P, S> {
+    /// Creates a new supervisor which spawns a session by a given function.
+    ///
+    /// The function is called each time the process is restarted,
+    /// so it must spawn the same command.
+    pub fn new<F>(spawn: F) -> Self
+    where
+        F: FnMut() -> Result<Session<P, S>, Error> + 'static,
+    {
+        Self {
+            spawn: Box::new(spawn),
+            session: None,
+            login: None,
+            on_restart: None,
+            max_restarts: None,
+            backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(30),
+            stable_after: Duration::from_secs(60),
+            restarts: 0,
+            failures: 0,
+            is_spawned: false,
+            started_at: None,
+        }
+    }
+
+    /// Sets a maximum number of restarts in a row,
+    /// after which an error is returned instead of a restart.
+    ///
+    /// Restarts are counted again once a process runs stable, see [`Supervisor::stable_after`].
+    /// By default the number is not limited.
+    pub fn max_restarts(mut self, max: usize) -> Self {
+        self.max_restarts = Some(max);
+        self
+    }
+
+    /// Sets a delay which is made before a first restart.
+    ///
+    /// By default it's 100ms.
+    pub fn backoff(mut self, backoff: Duration) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    /// Sets a maximum delay between restarts.
+    ///
+    /// By default it's 30s.
+    pub fn max_backoff(mut self, max: Duration) -> Self {
+        self.max_backoff = max;
+        self
+    }
+
+    /// Sets how long a process must run to be considered stable.
+    ///
+    /// If a stable process exits, the next restart is made after [`Supervisor::backoff`]
+    /// and it's not counted against [`Supervisor::max_restarts`].
+    ///
+    /// By default it's 60s.
+    pub fn stable_after(mut self, period: Duration) -> Self {
+        self.stable_after = period;
+        self
+    }
+
+    /// Sets a dialogue which is run each time the process is spawned,
+    /// including the first time.
+    pub fn login(mut self, dialogue: Dialogue) -> Self {
+        self.login = Some(dialogue);
+        self
+    }
+
+    /// Sets a function which is called after each restart,
+    /// once the [login](Supervisor::login) dialogue is passed.
+    ///
+    /// It's given a restarted session and a number of the restart.
+    pub fn on_restart<F>(mut self, hook: F) -> Self
+    where
+        F: FnMut(&mut Session<P, S>, usize) -> Result<(), Error> + 'static,
+    {
+        self.on_restart = Some(Box::new(hook));
+        self
+    }
+
+    /// Returns a total number of restarts made so far.
+    pub fn restarts(&self) -> usize {
+        self.restarts
+    }
+
+    /// Returns a current session if it was spawned.
+    pub fn get_session(&self) -> Option<&Session<P, S>> {
+        self.session.as_ref()
+    }
+
+    /// Returns a current session if it was spawned.
+    pub fn get_session_mut(&mut self) -> Option<&mut Session<P, S>> {
+        self.session.as_mut()
+    }
+
+    fn next_delay(&self) -> Duration {
+        let factor = 2u32.saturating_pow(self.failures.try_into().unwrap_or(u32::MAX));
+        self.backoff
+            .checked_mul(factor)
+            .map_or(self.max_backoff, |delay| delay.min(self.max_backoff))
+    }
+
+    fn check_restarts(&mut self) -> Result<(), Error> {
+        if let Some(started_at) = self.started_at {
+            if started_at.elapsed() >= self.stable_after {
+                self.failures = 0;
+            }
+        }
+
+        match self.max_restarts {
+            Some(max) if self.failures >= max => Err(Error::Other {
+                message: "Failed to restart a process".to_string(),
+                err: format!("the process was already restarted {} times in a row", max),
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Accounts a restart which is about to be made.
+    fn begin_restart(&mut self) {
+        // The old process is dropped first so it doesn't hold a device.
+        self.session = None;
+        self.started_at = None;
+        self.restarts += 1;
+        self.failures += 1;
+    }
+
+    fn set_session(&mut self, session: Session<P, S>) {
+        self.session = Some(session);
+        self.started_at = Some(Instant::now());
+    }
+}
+
+#[cfg(not(feature = "async"))]
+impl<P, S> Supervisor<P, S>
+where
+    P: Healthcheck,
+    S: Read + Write + NonBlocking,
+{
+    /// Spawns a session unless it was already spawned.
+    ///
+    /// If a previous spawn or restart failed, e.g. because of the login dialogue,
+    /// a restart is made, so the backoff and the restarts limit are respected.
+    pub fn start(&mut self) -> Result<&mut Session<P, S>, Error> {
+        if self.session.is_none() {
+            if self.is_spawned {
+                self.restart()?;
+            } else {
+                self.is_spawned = true;
+                let session = self.spawn_session()?;
+                self.set_session(session);
+            }
+        }
+
+        Ok(self.session.as_mut().expect("a session is set"))
+    }
+
+    /// Restarts the process if it's not alive.
+    ///
+    /// It returns true if a restart was made.
+    pub fn ensure_alive(&mut self) -> Result<bool, Error> {
+        if self.start()?.is_alive()? {
+            return Ok(false);
+        }
+
+        self.restart()?;
+
+        Ok(true)
+    }
+
+    /// Restarts the process regardless of whether it's alive.
+    pub fn restart(&mut self) -> Result<(), Error> {
+        self.check_restarts()?;
+
+        std::thread::sleep(self.next_delay());
+
+        self.begin_restart();
+
+        let mut session = self.spawn_session()?;
+        if let Some(hook) = &mut self.on_restart {
+            hook(&mut session, self.restarts)?;
+        }
+
+        self.set_session(session);
+
+        Ok(())
+    }
+
+    /// Expects a needle in the output of the process,
+    /// restarting the process if it exits in the meantime.
+    ///
+    /// After a restart the needle is expected in the output of the new process.
+    pub fn expect<N: Needle>(&mut self, needle: N) -> Result<Captures, Error> {
+        loop {
+            let session = self.start()?;
+            match session.expect(&needle) {
+                Err(err) if matches!(err.root(), Error::Eof) => self.restart()?,
+                Err(err) if matches!(err.root(), Error::IO(_)) => match session.is_alive()? {
+                    true => return Err(err),
+                    false => self.restart()?,
+                },
+                result => return result,
+            }
+        }
+    }
+
+    fn spawn_session(&mut self) -> Result<Session<P, S>, Error> {
+        let mut session = (self.spawn)()?;
+        if let Some(login) = &self.login {
+            let _ = login.run(&mut session)?;
+        }
+
+        Ok(session)
+    }
+}
+
+#[cfg(feature = "async")]
+impl<P, S> Supervisor<P, S>
+where
+    P: Healthcheck,
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    /// Spawns a session unless it was already spawned.
+    ///
+    /// If a previous spawn or restart failed, e.g. because of the login dialogue,
+    /// a restart is made, so the backoff and the restarts limit are respected.
+    pub async fn start(&mut self) -> Result<&mut Session<P, S>, Error> {
+        if self.session.is_none() {
+            if self.is_spawned {
+                self.restart().await?;
+            } else {
+                self.is_spawned = true;
+                let session = self.spawn_session().await?;
+                self.set_session(session);
+            }
+        }
+
+        Ok(self.session.as_mut().expect("a session is set"))
+    }
+
+    /// Restarts the process if it's not alive.
+    ///
+    /// It returns true if a restart was made.
+    pub async fn ensure_alive(&mut self) -> Result<bool, Error> {
+        if self.start().await?.is_alive()? {
+            return Ok(false);
+        }
+
+        self.restart().await?;
+
+        Ok(true)
+    }
+
+    /// Restarts the process regardless of whether it's alive.
+    pub async fn restart(&mut self) -> Result<(), Error> {
+        self.check_restarts()?;
+
+        futures_timer::Delay::new(self.next_delay()).await;
+
+        self.begin_restart();
+
+        let mut session = self.spawn_session().await?;
+        if let Some(hook) = &mut self.on_restart {
+            hook(&mut session, self.restarts)?;
+        }
+
+        self.set_session(session);
+
+        Ok(())
+    }
+
+    /// Expects a needle in the output of the process,
+    /// restarting the process if it exits in the meantime.
+    ///
+    /// After a restart the needle is expected in the output of the new process.
+    pub async fn expect<N: Needle>(&mut self, needle: N) -> Result<Captures, Error> {
+        loop {
+            let session = self.start().await?;
+            match session.expect(&needle).await {
+                Err(err) if matches!(err.root(), Error::Eof) => self.restart().await?,
+                Err(err) if matches!(err.root(), Error::IO(_)) => match session.is_alive()? {
+                    true => return Err(err),
+                    false => self.restart().await?,
+                },
+                result => return result,
+            }
+        }
+    }
+
+    async fn spawn_session(&mut self) -> Result<Session<P, S>, Error> {
+        let mut session = (self.spawn)()?;
+        if let Some(login) = &self.login {
+            let _ = login.run(&mut session).await?;
+        }
+
+        Ok(session)
+    }
+}
+
+impl<P, S> fmt::Debug for Supervisor<P, S>
+where
+    P: fmt::Debug,
+    S: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Supervisor")
+            .field("session", &self.session)
+            .field("login", &self.login)
+            .field("max_restarts", &self.max_restarts)
+            .field("backoff", &self.backoff)
+            .field("max_backoff", &self.max_backoff)
+            .field("stable_after", &self.stable_after)
+            .field("restarts", &self.restarts)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test::MockStream;
+
+    #[derive(Debug)]
+    struct Exited;
+
+    impl Healthcheck for Exited {
+        fn is_alive(&mut self) -> std::io::Result<bool> {
+            Ok(false)
+        }
+    }
+
+    fn supervisor<F>(mut streams: F) -> Supervisor<Exited, MockStream>
+    where
+        F: FnMut(usize) -> MockStream + 'static,
+    {
+        let mut spawned = 0;
+        Supervisor::new(move || {
+            spawned += 1;
+            Ok(Session::new(Exited, streams(spawned))?)
+        })
+        .backoff(Duration::ZERO)
+    }
+
+    #[test]
+    fn test_delay() {
+        let mut supervisor = supervisor(|_| MockStream::new())
+            .backoff(Duration::from_secs(1))
+            .max_backoff(Duration::from_secs(3));
+        assert_eq!(supervisor.next_delay(), Duration::from_secs(1));
+        supervisor.failures = 1;
+        assert_eq!(supervisor.next_delay(), Duration::from_secs(2));
+        supervisor.failures = 2;
+        assert_eq!(supervisor.next_delay(), Duration::from_secs(3));
+        supervisor.failures = 100;
+        assert_eq!(supervisor.next_delay(), Duration::from_secs(3));
+    }
+
+    #[test]
+    fn test_expect_restarts() {
+        let mut supervisor = supervisor(|n| match n {
+            3 => MockStream::new().output("ready"),
+            _ => MockStream::new().output("booting").eof(),
+        });
+
+        #[cfg(not(feature = "async"))]
+        let captures = supervisor.expect("ready").unwrap();
+        #[cfg(feature = "async")]
+        let captures = futures_lite::future::block_on(supervisor.expect("ready")).unwrap();
+
+        assert_eq!(captures.as_bytes(), b"ready");
+        assert_eq!(supervisor.restarts(), 2);
+    }
+
+    #[test]
+    fn test_max_restarts() {
+        let mut supervisor =
+            supervisor(|_| MockStream::new().output("booting").eof()).max_restarts(1);
+
+        #[cfg(not(feature = "async"))]
+        let result = supervisor.expect("ready");
+        #[cfg(feature = "async")]
+        let result = futures_lite::future::block_on(supervisor.expect("ready"));
+
+        assert!(matches!(result, Err(Error::Other { .. })));
+        assert_eq!(supervisor.restarts(), 1);
+    }
+
+    #[test]
+    fn test_login_and_hook_after_restart() {
+        let mut supervisor = supervisor(|n| {
+            let stream = MockStream::new().output("login:");
+            match n {
+                1 => stream.on_receive("admin\n").ignore().eof(),
+                _ => stream.on_receive("admin\n").respond("$ ready"),
+            }
+        })
+        .login(Dialogue::new().expect("login:").send_line("admin"))
+        .on_restart(|session, n| {
+            assert_eq!(n, 1);
+            session.set_expect_lazy(true);
+            Ok(())
+        });
+
+        #[cfg(not(feature = "async"))]
+        let captures = supervisor.expect("ready").unwrap();
+        #[cfg(feature = "async")]
+        let captures = futures_lite::future::block_on(supervisor.expect("ready")).unwrap();
+
+        assert_eq!(captures.before(), b"$ ");
+        assert_eq!(supervisor.restarts(), 1);
+
+        let session = supervisor.get_session().unwrap();
+        assert_eq!(session.get_stream().get_written(), b"admin\n");
+    }
+
+    #[test]
+    fn test_restarts_start_over_after_stable_run() {
+        let mut supervisor = supervisor(|n| match n {
+            3 => MockStream::new().output("ready"),
+            _ => MockStream::new().output("booting").eof(),
+        })
+        .max_restarts(1)
+        .stable_after(Duration::ZERO);
+
+        #[cfg(not(feature = "async"))]
+        let captures = supervisor.expect("ready").unwrap();
+        #[cfg(feature = "async")]
+        let captures = futures_lite::future::block_on(supervisor.expect("ready")).unwrap();
+
+        assert_eq!(captures.as_bytes(), b"ready");
+        assert_eq!(supervisor.restarts(), 2);
+    }
+
+    #[test]
+    fn test_failed_login_is_restarted() {
+        let mut supervisor = supervisor(|n| match n {
+            1 => MockStream::new().output("login:").eof(),
+            _ => MockStream::new().eof(),
+        })
+        .login(Dialogue::new().expect("login:"))
+        .max_restarts(2);
+
+        for _ in 0..2 {
+            #[cfg(not(feature = "async"))]
+            let err = supervisor.expect("ready").unwrap_err();
+            #[cfg(feature = "async")]
+            let err = futures_lite::future::block_on(supervisor.expect("ready")).unwrap_err();
+
+            assert!(matches!(err.root(), Error::Eof));
+            assert!(supervisor.get_session().is_none());
+        }
+
+        #[cfg(not(feature = "async"))]
+        let err = supervisor.expect("ready").unwrap_err();
+        #[cfg(feature = "async")]
+        let err = futures_lite::future::block_on(supervisor.expect("ready")).unwrap_err();
+
+        assert!(matches!(err, Error::Other { .. }));
+        assert_eq!(supervisor.restarts(), 2);
+    }
+}
@@ -0,0 +1,397 @@
+//! This module contains a [`MockSession`] which helps to test code built on top of the crate
+//! without spawning real processes.
+//!
+//! A [`MockStream`] is given a list of writes it expects and responses to them.
+//!
+//! # Example
+//!
+#![cfg_attr(not(feature = "async"), doc = "```")]
+#![cfg_attr(feature = "async", doc = "```ignore")]
+//! use expectrl::test::{MockSession, MockStream};
+//!
+//! let stream = MockStream::new()
+//!     .output("ready\n")
+//!     .on_receive("PING\n")
+//!     .respond("PONG\n")
+//!     .eof();
+//!
+//! let mut session = MockSession::mock(stream).unwrap();
+//! session.expect("ready").unwrap();
+//! session.send_line("PING").unwrap();
+//! session.expect("PONG").unwrap();
+//!
+//! session.get_stream().verify().unwrap();
+//! ```
+
+use std::{
+    collections::VecDeque,
+    io::{self, Read, Write},
+};
+
+#[cfg(feature = "async")]
+use futures_lite::{AsyncRead, AsyncWrite};
+#[cfg(feature = "async")]
+use std::{
+    pin::Pin,
+    task::{Context, Poll, Waker},
+};
+
+use crate::{process::NonBlocking, Error, Session};
+
+/// A session which talks to a [`MockStream`].
+///
+/// There's no process behind it so the process type is `()`.
+pub type MockSession = Session<(), MockStream>;
+
+impl Session<(), MockStream> {
+    /// Creates a session on top of a mock stream.
+    pub fn mock(stream: MockStream) -> Result<Self, Error> {
+        let session = Self::new((), stream)?;
+        Ok(session)
+    }
+}
+
+/// MockStream verifies that writes come in an expected order
+/// and makes scripted responses available for reading.
+///
+/// An unexpected write fails with [`io::ErrorKind::InvalidInput`].
+/// If there's nothing to read a read fails with [`io::ErrorKind::WouldBlock`],
+/// so an expect call reaches its timeout instead of blocking forever.
+#[derive(Debug, Default)]
+pub struct MockStream {
+    steps: VecDeque<Step>,
+    output: VecDeque<u8>,
+    input: Vec<u8>,
+    written: Vec<u8>,
+    #[cfg(feature = "async")]
+    waker: Option<Waker>,
+}
+
+#[derive(Debug)]
+enum Step {
+    Receive(Vec<u8>, Vec<u8>),
+    Eof,
+}
+
+impl MockStream {
+    /// Creates an empty stream.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds an output which is available right away, e.g. a banner.
+    ///
+    /// If there are registered receive steps the output is available after the last of them is matched.
+    ///
+    /// # Panics
+    ///
+    /// It panics if it's called after [`MockStream::eof`], as nothing can be read after EOF.
+    pub fn output<B: AsRef<[u8]>>(mut self, buf: B) -> Self {
+        match self.steps.back_mut() {
+            Some(Step::Receive(_, response)) => response.extend_from_slice(buf.as_ref()),
+            Some(Step::Eof) => panic!("an output can't be added after EOF"),
+            None => {
+                self.output.extend(buf.as_ref());
+                self.wake();
+            }
+        }
+
+        self
+    }
+
+    /// Registers an expected write.
+    ///
+    /// A response is set by [`OnReceive::respond`].
+    pub fn on_receive<B: AsRef<[u8]>>(self, buf: B) -> OnReceive {
+        OnReceive {
+            stream: self,
+            expected: buf.as_ref().to_vec(),
+        }
+    }
+
+    /// Registers an end of the stream.
+    ///
+    /// After all previous steps are matched and their output is read, a read returns `0`.
+    pub fn eof(mut self) -> Self {
+        self.steps.push_back(Step::Eof);
+        self
+    }
+
+    /// Returns all bytes which were written to the stream.
+    pub fn get_written(&self) -> &[u8] {
+        &self.written
+    }
+
+    /// Verifies that all expected writes were made.
+    pub fn verify(&self) -> Result<(), Error> {
+        match self.steps.front() {
+            Some(Step::Receive(expected, _)) => Err(Error::Other {
+                message: String::from("Not all expected writes were made"),
+                err: format!(
+                    "expected {:?} but got {:?}",
+                    String::from_utf8_lossy(expected),
+                    String::from_utf8_lossy(&self.input)
+                ),
+            }),
+            Some(Step::Eof) | None => Ok(()),
+        }
+    }
+
+    fn receive(&mut self, buf: &[u8]) -> io::Result<()> {
+        self.written.extend_from_slice(buf);
+        self.input.extend_from_slice(buf);
+
+        loop {
+            let expected = match self.steps.front() {
+                Some(Step::Receive(expected, _)) => expected,
+                Some(Step::Eof) | None if self.input.is_empty() => return Ok(()),
+                Some(Step::Eof) | None => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        format!(
+                            "unexpected write {:?}",
+                            String::from_utf8_lossy(&self.input)
+                        ),
+                    ))
+                }
+            };
+
+            if self.input.starts_with(expected) {
+                let _ = self.input.drain(..expected.len());
+                if let Some(Step::Receive(_, response)) = self.steps.pop_front() {
+                    self.output.extend(response);
+                    self.wake();
+                }
+
+                continue;
+            }
+
+            if expected.starts_with(&self.input) {
+                return Ok(());
+            }
+
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "unexpected write {:?}, expected {:?}",
+                    String::from_utf8_lossy(&self.input),
+                    String::from_utf8_lossy(expected)
+                ),
+            ));
+        }
+    }
+
+    /// Wakes a reader waiting for an output.
+    fn wake(&mut self) {
+        #[cfg(feature = "async")]
+        if let Some(waker) = self.waker.take() {
+            waker.wake();
+        }
+    }
+
+    fn read_output(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        if self.output.is_empty() {
+            if let Some(Step::Eof) = self.steps.front() {
+                return Ok(0);
+            }
+
+            return Err(io::Error::new(io::ErrorKind::WouldBlock, ""));
+        }
+
+        let n = std::cmp::min(buf.len(), self.output.len());
+        for (b, byte) in buf.iter_mut().zip(self.output.drain(..n)) {
+            *b = byte;
+        }
+
+        Ok(n)
+    }
+}
+
+/// A builder of a receive step of a [`MockStream`].
+#[derive(Debug)]
+pub struct OnReceive {
+    stream: MockStream,
+    expected: Vec<u8>,
+}
+
+impl OnReceive {
+    /// Sets a response to an expected write.
+    pub fn respond<B: AsRef<[u8]>>(mut self, buf: B) -> MockStream {
+        let step = Step::Receive(self.expected, buf.as_ref().to_vec());
+        self.stream.steps.push_back(step);
+        self.stream
+    }
+
+    /// Registers an expected write without a response.
+    pub fn ignore(self) -> MockStream {
+        self.respond([])
+    }
+}
+
+impl Read for MockStream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.read_output(buf)
+    }
+}
+
+impl Write for MockStream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.receive(buf)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl NonBlocking for MockStream {
+    fn set_non_blocking(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn set_blocking(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(feature = "async")]
+impl AsyncRead for MockStream {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        match self.read_output(buf) {
+            Err(err) if err.kind() == io::ErrorKind::WouldBlock => {
+                self.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+            result => Poll::Ready(result),
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+impl AsyncWrite for MockStream {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        _: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Poll::Ready(self.write(buf))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stream() {
+        let mut stream = MockStream::new()
+            .output("banner\n")
+            .on_receive("PING\n")
+            .respond("PONG\n")
+            .on_receive("QUIT\n")
+            .ignore()
+            .eof();
+
+        let mut buf = [0; 16];
+        assert_eq!(stream.read(&mut buf).unwrap(), 7);
+        assert_eq!(
+            stream.read(&mut buf).unwrap_err().kind(),
+            io::ErrorKind::WouldBlock
+        );
+
+        stream.write_all(b"PI").unwrap();
+        assert!(stream.verify().is_err());
+        stream.write_all(b"NG\nQUIT\n").unwrap();
+
+        assert_eq!(stream.read(&mut buf).unwrap(), 5);
+        assert_eq!(&buf[..5], b"PONG\n");
+        assert_eq!(stream.read(&mut buf).unwrap(), 0);
+
+        stream.verify().unwrap();
+        assert_eq!(stream.get_written(), b"PING\nQUIT\n");
+    }
+
+    #[test]
+    fn test_unexpected_write() {
+        let mut stream = MockStream::new().on_receive("PING\n").respond("PONG\n");
+
+        let err = stream.write_all(b"PONG\n").unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    #[should_panic(expected = "after EOF")]
+    fn test_output_after_eof() {
+        let _ = MockStream::new().eof().output("lost");
+    }
+
+    #[test]
+    #[cfg(feature = "async")]
+    fn test_read_wakes_on_response() {
+        use std::sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc,
+        };
+        use std::task::Wake;
+
+        struct Flag(AtomicBool);
+
+        impl Wake for Flag {
+            fn wake(self: Arc<Self>) {
+                self.0.store(true, Ordering::SeqCst);
+            }
+        }
+
+        let flag = Arc::new(Flag(AtomicBool::new(false)));
+        let waker = Waker::from(flag.clone());
+        let mut cx = Context::from_waker(&waker);
+
+        let mut stream = MockStream::new().on_receive("PING\n").respond("PONG\n");
+
+        let mut buf = [0; 16];
+        assert!(Pin::new(&mut stream)
+            .poll_read(&mut cx, &mut buf)
+            .is_pending());
+        assert!(!flag.0.load(Ordering::SeqCst));
+
+        stream.write_all(b"PING\n").unwrap();
+        assert!(flag.0.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn test_session() {
+        let stream = MockStream::new().on_receive("PING\n").respond("PONG\n");
+        let mut session = MockSession::mock(stream).unwrap();
+
+        #[cfg(not(feature = "async"))]
+        {
+            session.send_line("PING").unwrap();
+            let _ = session.expect("PONG").unwrap();
+        }
+
+        #[cfg(feature = "async")]
+        futures_lite::future::block_on(async {
+            session.send_line("PING").await.unwrap();
+            let _ = session.expect("PONG").await.unwrap();
+        });
+
+        session.get_stream().verify().unwrap();
+    }
+}
@@ -0,0 +1,212 @@
+//! This module contains a [`Timeline`] which records when a set of needles were matched.
+
+use std::{
+    fmt,
+    time::{Duration, Instant},
+};
+
+use crate::{needle::Match, Error, Needle};
+
+/// Timeline records a time at which each of registered needles was matched.
+///
+/// It's supposed to be used with [`Session::expect_timeline`],
+/// which waits until all needles are matched regardless of the order they appear in.
+/// After that you can verify an ordering and a latency of events.
+///
+/// # Example
+///
+/// ```no_run,ignore
+/// use expectrl::{spawn, Timeline};
+/// use std::time::Duration;
+///
+/// let mut p = spawn("my-daemon").unwrap();
+///
+/// let mut timeline = Timeline::new()
+///     .on("listening", "listening on")
+///     .on("accepted", "accepted connection");
+///
+/// p.expect_timeline(&mut timeline).unwrap();
+///
+/// assert!(timeline.is_before("listening", "accepted"));
+/// assert!(timeline.between("listening", "accepted").unwrap() < Duration::from_secs(2));
+/// ```
+///
+/// [`Session::expect_timeline`]: crate::session::Session::expect_timeline
+pub struct Timeline {
+    start: Instant,
+    events: Vec<Event>,
+}
+
+struct Event {
+    label: String,
+    needle: Box<dyn Needle>,
+    matched: Option<(Instant, Vec<Match>)>,
+}
+
+impl Timeline {
+    /// Creates an empty timeline.
+    ///
+    /// All times are measured relatively to the moment of creation.
+    pub fn new() -> Self {
+        Self {
+            start: Instant::now(),
+            events: Vec::new(),
+        }
+    }
+
+    /// Registers a needle under a given label.
+    pub fn on<L, N>(mut self, label: L, needle: N) -> Self
+    where
+        L: Into<String>,
+        N: Needle + 'static,
+    {
+        self.events.push(Event {
+            label: label.into(),
+            needle: Box::new(needle),
+            matched: None,
+        });
+        self
+    }
+
+    /// Returns a time passed since the timeline creation till the labeled needle was matched.
+    ///
+    /// Returns [`None`] if there's no such label or it wasn't matched yet.
+    pub fn get(&self, label: &str) -> Option<Duration> {
+        self.find(label).map(|time| time.duration_since(self.start))
+    }
+
+    /// Verifies that the first label was matched before the second one.
+    ///
+    /// Returns false if any of them wasn't matched.
+    pub fn is_before(&self, first: &str, second: &str) -> bool {
+        match (self.find(first), self.find(second)) {
+            (Some(first), Some(second)) => first <= second,
+            _ => false,
+        }
+    }
+
+    /// Returns a time passed between the first and the second label were matched.
+    ///
+    /// Returns [`None`] if any of them wasn't matched or the second one was matched earlier.
+    pub fn between(&self, first: &str, second: &str) -> Option<Duration> {
+        let first = self.find(first)?;
+        let second = self.find(second)?;
+        second.checked_duration_since(first)
+    }
+
+    /// Returns a list of matched labels in order they were matched.
+    pub fn order(&self) -> Vec<&str> {
+        let mut events = self
+            .events
+            .iter()
+            .filter_map(|e| e.matched.as_ref().map(|(time, _)| (time, e.label.as_str())))
+            .collect::<Vec<_>>();
+        events.sort_by_key(|(time, _)| *time);
+        events.into_iter().map(|(_, label)| label).collect()
+    }
+
+    /// Verifies whether all needles were matched.
+    pub fn is_complete(&self) -> bool {
+        self.events.iter().all(|e| e.matched.is_some())
+    }
+
+    /// Forgets all recorded matches and resets the start time.
+    pub fn reset(&mut self) {
+        self.start = Instant::now();
+        for event in &mut self.events {
+            event.matched = None;
+        }
+    }
+
+    /// Runs all not yet matched needles against a buffer, recording the ones which were found.
+    pub(crate) fn check(&mut self, buf: &[u8], eof: bool) -> Result<(), Error> {
+        for event in self.events.iter_mut().filter(|e| e.matched.is_none()) {
+            let found = event.needle.check(buf, eof)?;
+            if !found.is_empty() {
+                event.matched = Some((Instant::now(), found));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns all matches in order needles were registered.
+    pub(crate) fn matches(&self) -> Vec<Match> {
+        self.events
+            .iter()
+            .filter_map(|e| e.matched.as_ref())
+            .flat_map(|(_, found)| found.iter().cloned())
+            .collect()
+    }
+
+    fn find(&self, label: &str) -> Option<Instant> {
+        self.events
+            .iter()
+            .find(|e| e.label == label)
+            .and_then(|e| e.matched.as_ref())
+            .map(|(time, _)| *time)
+    }
+}
+
+impl Default for Timeline {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for Timeline {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Timeline")
+            .field("start", &self.start)
+            .field("events", &self.events)
+            .finish()
+    }
+}
+
+impl fmt::Debug for Event {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Event")
+            .field("label", &self.label)
+            .field("matched", &self.matched)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_timeline_check() {
+        let mut timeline = Timeline::new().on("first", "World").on("second", "Hello");
+
+        timeline.check(b"Hello", false).unwrap();
+        assert!(!timeline.is_complete());
+        assert!(timeline.get("second").is_some());
+        assert!(timeline.get("first").is_none());
+
+        timeline.check(b"Hello World", false).unwrap();
+        assert!(timeline.is_complete());
+        assert!(timeline.is_before("second", "first"));
+        assert!(timeline.between("second", "first").is_some());
+        assert_eq!(timeline.order(), vec!["second", "first"]);
+        assert_eq!(
+            timeline.matches(),
+            vec![Match::new(6, 11), Match::new(0, 5)]
+        );
+
+        timeline.reset();
+        assert!(!timeline.is_complete());
+        assert!(timeline.order().is_empty());
+    }
+
+    #[test]
+    fn test_timeline_unknown_label() {
+        let mut timeline = Timeline::new().on("a", "a");
+        timeline.check(b"a", false).unwrap();
+
+        assert!(timeline.get("b").is_none());
+        assert!(!timeline.is_before("a", "b"));
+        assert!(timeline.between("a", "b").is_none());
+    }
+}
@@ -0,0 +1,365 @@
+//! This module contains a [`Transcript`] which keeps a record of commands executed in a repl.
+
+use std::{fmt::Write as _, fs, path::Path, time::Duration};
+
+use crate::Error;
+
+/// Transcript is an ordered list of commands executed in a [`ReplSession`] together with their output.
+///
+/// It's useful for auditable automation runs, where a structured record is needed rather than a raw log.
+///
+/// # Example
+///
+/// ```no_run,ignore
+/// use expectrl::{repl::spawn_bash, TranscriptFormat};
+///
+/// let mut bash = spawn_bash().unwrap();
+/// bash.set_transcript_limit(100);
+/// bash.execute("uname -a").unwrap();
+///
+/// for entry in bash.transcript().entries() {
+///     println!("{} took {:?}", entry.command(), entry.duration());
+/// }
+///
+/// bash.save_transcript("run.md", TranscriptFormat::Markdown).unwrap();
+/// ```
+///
+/// [`ReplSession`]: crate::repl::ReplSession
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Transcript {
+    entries: Vec<TranscriptEntry>,
+    limit: usize,
+}
+
+/// TranscriptEntry is a single command executed in a repl.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TranscriptEntry {
+    command: String,
+    output: Vec<u8>,
+    error: Option<String>,
+    duration: Duration,
+}
+
+/// A format in which a [`Transcript`] can be saved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TranscriptFormat {
+    /// A markdown document with a section per command.
+    Markdown,
+    /// A JSON array of objects with `command`, `output`, `error` and `duration_ms` fields.
+    Json,
+}
+
+impl Transcript {
+    /// Returns all entries in the order commands were executed.
+    pub fn entries(&self) -> &[TranscriptEntry] {
+        &self.entries
+    }
+
+    /// Returns a number of recorded commands.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Verifies whether no commands were recorded.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Renders the transcript as a markdown document.
+    ///
+    /// An output is shown as a code block, invalid UTF-8 is replaced.
+    /// A command of a few lines is shown as a code block too,
+    /// as a heading can't span lines.
+    pub fn to_markdown(&self) -> String {
+        let mut doc = String::from("# Transcript\n");
+        for entry in &self.entries {
+            let mut lines = entry.command.lines();
+            let first_line = lines.next().unwrap_or_default();
+            let is_multiline = lines.next().is_some();
+
+            doc.push_str("\n## ");
+            doc.push_str(&inline_code(first_line));
+            if is_multiline {
+                doc.push_str(" ...");
+            }
+            doc.push_str("\n\n");
+
+            if is_multiline {
+                push_code_block(&mut doc, "sh", &entry.command);
+                doc.push('\n');
+            }
+
+            let _ = writeln!(doc, "Took {} ms.", entry.duration.as_millis());
+            if let Some(err) = &entry.error {
+                let _ = writeln!(doc, "\nFailed: {}", err);
+            }
+            doc.push('\n');
+
+            push_code_block(&mut doc, "text", &String::from_utf8_lossy(&entry.output));
+        }
+
+        doc
+    }
+
+    /// Renders the transcript as a JSON array.
+    ///
+    /// An output is stored as a string, invalid UTF-8 is replaced.
+    /// An error is `null` for commands which succeeded.
+    pub fn to_json(&self) -> String {
+        let mut json = String::from("[");
+        for (i, entry) in self.entries.iter().enumerate() {
+            if i > 0 {
+                json.push(',');
+            }
+
+            json.push_str("{\"command\":");
+            push_json_string(&mut json, &entry.command);
+            json.push_str(",\"output\":");
+            push_json_string(&mut json, &String::from_utf8_lossy(&entry.output));
+            json.push_str(",\"error\":");
+            match &entry.error {
+                Some(err) => push_json_string(&mut json, err),
+                None => json.push_str("null"),
+            }
+            let _ = write!(json, ",\"duration_ms\":{}}}", entry.duration.as_millis());
+        }
+        json.push(']');
+
+        json
+    }
+
+    /// Writes the transcript to a file in a given format.
+    pub fn save<P: AsRef<Path>>(&self, path: P, format: TranscriptFormat) -> Result<(), Error> {
+        let content = match format {
+            TranscriptFormat::Markdown => self.to_markdown(),
+            TranscriptFormat::Json => self.to_json(),
+        };
+
+        fs::write(path, content)?;
+
+        Ok(())
+    }
+
+    /// Sets a number of last commands which are kept, 0 turns the recording off.
+    pub(crate) fn set_limit(&mut self, limit: usize) {
+        self.limit = limit;
+        self.truncate();
+    }
+
+    /// Records a command with its output, or with an error if it failed.
+    pub(crate) fn push(
+        &mut self,
+        command: &str,
+        result: Result<&[u8], &Error>,
+        duration: Duration,
+    ) {
+        if self.limit == 0 {
+            return;
+        }
+
+        let (output, error) = match result {
+            Ok(output) => (output.to_vec(), None),
+            Err(err) => (Vec::new(), Some(err.to_string())),
+        };
+
+        self.entries.push(TranscriptEntry {
+            command: command.to_owned(),
+            output,
+            error,
+            duration,
+        });
+        self.truncate();
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    fn truncate(&mut self) {
+        if self.entries.len() > self.limit {
+            let _ = self.entries.drain(..self.entries.len() - self.limit);
+        }
+    }
+}
+
+impl TranscriptEntry {
+    /// Returns an executed command.
+    pub fn command(&self) -> &str {
+        &self.command
+    }
+
+    /// Returns an output of the command.
+    pub fn output(&self) -> &[u8] {
+        &self.output
+    }
+
+    /// Returns an error message if the command failed.
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    /// Returns a time the command took.
+    pub fn duration(&self) -> Duration {
+        self.duration
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Transcript {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(&self.entries)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for TranscriptEntry {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+
+        let mut s = serializer.serialize_struct("TranscriptEntry", 4)?;
+        s.serialize_field("command", &self.command)?;
+        s.serialize_field("output", &String::from_utf8_lossy(&self.output))?;
+        s.serialize_field("error", &self.error)?;
+        s.serialize_field("duration_ms", &self.duration.as_millis())?;
+        s.end()
+    }
+}
+
+/// Returns a text as a markdown inline code.
+fn inline_code(text: &str) -> String {
+    if !text.contains('`') {
+        return format!("`{}`", text);
+    }
+
+    // spaces keep backticks of the text apart from the delimiters
+    let fence = "`".repeat(longest_backtick_run(text) + 1);
+    format!("{} {} {}", fence, text, fence)
+}
+
+fn push_code_block(doc: &mut String, lang: &str, text: &str) {
+    let fence = "`".repeat(std::cmp::max(3, longest_backtick_run(text) + 1));
+    let _ = write!(doc, "{}{}\n{}", fence, lang, text);
+    if !text.is_empty() && !text.ends_with('\n') {
+        doc.push('\n');
+    }
+    doc.push_str(&fence);
+    doc.push('\n');
+}
+
+fn longest_backtick_run(text: &str) -> usize {
+    text.split(|c| c != '`').map(str::len).max().unwrap_or(0)
+}
+
+fn push_json_string(json: &mut String, text: &str) {
+    json.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if c.is_control() => {
+                let _ = write!(json, "\\u{:04x}", c as u32);
+            }
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transcript() -> Transcript {
+        let mut transcript = Transcript::default();
+        transcript.set_limit(10);
+        transcript.push(
+            "echo \"Hello\"",
+            Ok(b"Hello\r\n"),
+            Duration::from_millis(12),
+        );
+        transcript.push(
+            "printf '```\\x1b'",
+            Ok(b"```\x1b"),
+            Duration::from_millis(3),
+        );
+        transcript
+    }
+
+    #[test]
+    fn test_transcript_markdown() {
+        assert_eq!(
+            transcript().to_markdown(),
+            "# Transcript\n\
+             \n## `echo \"Hello\"`\n\nTook 12 ms.\n\n```text\nHello\r\n```\n\
+             \n## ```` printf '```\\x1b' ````\n\nTook 3 ms.\n\n````text\n```\x1b\n````\n"
+        );
+    }
+
+    #[test]
+    fn test_transcript_markdown_multiline_and_failed() {
+        let mut transcript = Transcript::default();
+        transcript.set_limit(10);
+        let err = Error::ExpectTimeout;
+        transcript.push("for i in 1 2\ndo echo $i; done", Err(&err), Duration::ZERO);
+
+        assert_eq!(
+            transcript.to_markdown(),
+            format!(
+                "# Transcript\n\
+                 \n## `for i in 1 2` ...\n\n```sh\nfor i in 1 2\ndo echo $i; done\n```\n\
+                 \nTook 0 ms.\n\nFailed: {}\n\n```text\n```\n",
+                err
+            )
+        );
+    }
+
+    #[test]
+    fn test_transcript_json() {
+        assert_eq!(
+            transcript().to_json(),
+            r#"[{"command":"echo \"Hello\"","output":"Hello\r\n","error":null,"duration_ms":12},{"command":"printf '```\\x1b'","output":"```\u001b","error":null,"duration_ms":3}]"#
+        );
+
+        let mut transcript = Transcript::default();
+        transcript.set_limit(10);
+        transcript.push("sleep 10", Err(&Error::ExpectTimeout), Duration::ZERO);
+        assert_eq!(
+            transcript.to_json(),
+            format!(
+                r#"[{{"command":"sleep 10","output":"","error":"{}","duration_ms":0}}]"#,
+                Error::ExpectTimeout
+            )
+        );
+    }
+
+    #[test]
+    fn test_transcript_limit() {
+        let mut transcript = Transcript::default();
+        transcript.push("ls", Ok(b""), Duration::ZERO);
+        assert!(transcript.is_empty());
+
+        transcript.set_limit(2);
+        for cmd in ["1", "2", "3"] {
+            transcript.push(cmd, Ok(b""), Duration::ZERO);
+        }
+
+        let commands: Vec<_> = transcript.entries().iter().map(|e| e.command()).collect();
+        assert_eq!(commands, ["2", "3"]);
+
+        transcript.set_limit(1);
+        assert_eq!(transcript.entries()[0].command(), "3");
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_transcript_serialize() {
+        let transcript = transcript();
+        assert_eq!(
+            serde_json::to_string(&transcript).unwrap(),
+            transcript.to_json()
+        );
+    }
+}
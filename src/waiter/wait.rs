@@ -41,6 +41,10 @@ impl<R1, R2> Wait2<R1, R2> {
         }
     }
 
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = timeout;
+    }
+
     pub fn join(self) -> std::thread::Result<()> {
         self.b1.join()?;
         self.b2.join()?;
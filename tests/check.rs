@@ -249,6 +249,138 @@ fn check_macro() {
     });
 }
 
+#[cfg(unix)]
+#[cfg(not(feature = "async"))]
+#[test]
+fn check_macro_eof() {
+    let mut session = spawn("echo Hello").unwrap();
+
+    thread::sleep(Duration::from_millis(600));
+
+    let mut eof = false;
+    expectrl::check!(
+        &mut session,
+        _ = "World" => {
+            panic!("Unexpected result");
+        },
+        eof => {
+            eof = true;
+        },
+        timeout => {
+            panic!("Unexpected result");
+        },
+    )
+    .unwrap();
+
+    assert!(eof);
+}
+
+#[cfg(unix)]
+#[cfg(feature = "async")]
+#[test]
+fn check_macro_eof() {
+    futures_lite::future::block_on(async {
+        let mut session = spawn("echo Hello").unwrap();
+
+        thread::sleep(Duration::from_millis(600));
+
+        let mut eof = false;
+        expectrl::check!(
+            session,
+            _ = "World" => {
+                panic!("Unexpected result");
+            },
+            eof => {
+                eof = true;
+            },
+            timeout => {
+                panic!("Unexpected result");
+            },
+        )
+        .await
+        .unwrap();
+
+        assert!(eof);
+    });
+}
+
+#[cfg(unix)]
+#[cfg(not(feature = "async"))]
+#[test]
+fn check_macro_multiple_sessions() {
+    let mut server = spawn("cat").unwrap();
+    let mut client = spawn("cat").unwrap();
+    client.send_line("ready").unwrap();
+
+    thread::sleep(Duration::from_millis(600));
+
+    let mut ready = None;
+    expectrl::check!(
+        in [&mut server, &mut client],
+        (index, m) = "ready" => {
+            assert_eq!(m.get(0).unwrap(), b"ready");
+            ready = Some(index);
+        },
+        default => {
+            panic!("Unexpected result");
+        },
+    )
+    .unwrap();
+
+    assert_eq!(ready, Some(1));
+}
+
+#[cfg(unix)]
+#[cfg(feature = "async")]
+#[test]
+fn check_macro_multiple_sessions() {
+    futures_lite::future::block_on(async {
+        let mut server = spawn("cat").unwrap();
+        let mut client = spawn("cat").unwrap();
+        client.send_line("ready").await.unwrap();
+
+        thread::sleep(Duration::from_millis(600));
+
+        let mut ready = None;
+        expectrl::check!(
+            in [server, client],
+            (index, m) = "ready" => {
+                assert_eq!(m.get(0).unwrap(), b"ready");
+                ready = Some(index);
+            },
+            default => {
+                panic!("Unexpected result");
+            },
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(ready, Some(1));
+    });
+}
+
+#[cfg(unix)]
+#[cfg(not(feature = "async"))]
+#[test]
+fn check_macro_regex_groups() {
+    let mut session = spawn("cat").unwrap();
+    session.send_line("listening on port 8080").unwrap();
+
+    thread::sleep(Duration::from_millis(600));
+
+    expectrl::check!(
+        &mut session,
+        [_, port, host] = Regex("port (\\d+)(?: on (\\w+))?") => {
+            assert_eq!(port, Some(b"8080".as_ref()));
+            assert_eq!(host, None);
+        },
+        default => {
+            panic!("Unexpected result");
+        },
+    )
+    .unwrap();
+}
+
 #[cfg(unix)]
 #[cfg(not(feature = "async"))]
 #[test]
@@ -58,6 +58,19 @@ fn expect_regex() {
     assert_eq!(m.get(0).unwrap(), b"lo World\r");
 }
 
+#[cfg(unix)]
+#[cfg(not(feature = "async"))]
+#[test]
+fn expect_precompiled_regex() {
+    let regex = regex::bytes::Regex::new("lo.*").unwrap();
+
+    let mut session = spawn("cat").unwrap();
+    session.send_line("Hello World").unwrap();
+    let m = session.expect(&regex).unwrap();
+    assert_eq!(m.before(), b"Hel");
+    assert_eq!(m.get(0).unwrap(), b"lo World\r");
+}
+
 #[cfg(unix)]
 #[cfg(not(feature = "async"))]
 #[test]
@@ -333,3 +346,409 @@ fn expect_eof_timeout() {
         })
     }
 }
+
+#[cfg(unix)]
+#[cfg(not(feature = "async"))]
+#[test]
+fn expect_timeline() {
+    let mut session = spawn("cat").unwrap();
+    session.send_line("Hello").unwrap();
+
+    let mut timeline = expectrl::Timeline::new()
+        .on("world", "World")
+        .on("hello", "Hello");
+
+    session.send_line("World").unwrap();
+
+    let m = session.expect_timeline(&mut timeline).unwrap();
+    assert_eq!(m.get(0).unwrap(), b"World");
+    assert_eq!(m.get(1).unwrap(), b"Hello");
+    assert!(timeline.is_complete());
+}
+
+#[cfg(unix)]
+#[cfg(feature = "async")]
+#[test]
+fn expect_timeline() {
+    futures_lite::future::block_on(async {
+        let mut session = spawn("cat").unwrap();
+        session.send_line("Hello").await.unwrap();
+
+        let mut timeline = expectrl::Timeline::new()
+            .on("world", "World")
+            .on("hello", "Hello");
+
+        session.send_line("World").await.unwrap();
+
+        let m = session.expect_timeline(&mut timeline).await.unwrap();
+        assert_eq!(m.get(0).unwrap(), b"World");
+        assert_eq!(m.get(1).unwrap(), b"Hello");
+        assert!(timeline.is_complete());
+    })
+}
+
+#[cfg(unix)]
+#[cfg(not(feature = "async"))]
+#[test]
+fn expect_line() {
+    let mut session = spawn("printf 'Hello\\nWorld\\n!'").unwrap();
+    assert_eq!(session.expect_line().unwrap(), "Hello");
+    assert_eq!(session.expect_line().unwrap(), "World");
+    assert_eq!(session.expect_line().unwrap(), "!");
+    assert!(matches!(session.expect_line(), Err(expectrl::Error::Eof)));
+}
+
+#[cfg(unix)]
+#[cfg(feature = "async")]
+#[test]
+fn expect_line() {
+    futures_lite::future::block_on(async {
+        let mut session = spawn("printf 'Hello\\nWorld\\n!'").unwrap();
+        assert_eq!(session.expect_line().await.unwrap(), "Hello");
+        assert_eq!(session.expect_line().await.unwrap(), "World");
+        assert_eq!(session.expect_line().await.unwrap(), "!");
+        assert!(matches!(
+            session.expect_line().await,
+            Err(expectrl::Error::Eof)
+        ));
+    })
+}
+
+#[cfg(unix)]
+#[cfg(not(feature = "async"))]
+#[test]
+fn expect_lines() {
+    let mut session = spawn("printf 'Hello\\nWorld\\n'").unwrap();
+    let lines = session.lines().collect::<Result<Vec<_>, _>>().unwrap();
+    assert_eq!(lines, ["Hello", "World"]);
+}
+
+#[cfg(unix)]
+#[cfg(feature = "async")]
+#[test]
+fn expect_lines() {
+    use futures_lite::StreamExt;
+
+    futures_lite::future::block_on(async {
+        let mut session = spawn("printf 'Hello\\nWorld\\n'").unwrap();
+        let lines = session.lines().try_collect::<_, _, Vec<_>>().await.unwrap();
+        assert_eq!(lines, ["Hello", "World"]);
+    })
+}
+
+#[cfg(unix)]
+#[cfg(not(feature = "async"))]
+#[test]
+fn expect_with_policy() {
+    use expectrl::policy::AnswerPolicy;
+
+    let mut session =
+        spawn(r#"sh -c "printf 'Continue? [y/N] '; read a; echo answer=$a""#).unwrap();
+
+    let mut policy = AnswerPolicy::new().confirm(true);
+    session.expect_with_policy("answer=y", &mut policy).unwrap();
+    assert_eq!(policy.answered(), 1);
+}
+
+#[cfg(unix)]
+#[cfg(feature = "async")]
+#[test]
+fn expect_with_policy() {
+    use expectrl::policy::AnswerPolicy;
+
+    futures_lite::future::block_on(async {
+        let mut session =
+            spawn(r#"sh -c "printf 'Continue? [y/N] '; read a; echo answer=$a""#).unwrap();
+
+        let mut policy = AnswerPolicy::new().confirm(true);
+        session
+            .expect_with_policy("answer=y", &mut policy)
+            .await
+            .unwrap();
+        assert_eq!(policy.answered(), 1);
+    })
+}
+
+#[cfg(unix)]
+#[cfg(not(feature = "async"))]
+#[test]
+fn expect_any_matched_index() {
+    use expectrl::Any;
+
+    let mut session = spawn("echo World").unwrap();
+    let m = session.expect(Any(["Hello", "World"])).unwrap();
+    assert_eq!(m.matched_index(), Some(1));
+    assert_eq!(m.get(0).unwrap(), b"World");
+}
+
+#[cfg(unix)]
+#[cfg(feature = "async")]
+#[test]
+fn expect_any_matched_index() {
+    use expectrl::Any;
+
+    futures_lite::future::block_on(async {
+        let mut session = spawn("echo World").unwrap();
+        let m = session.expect(Any(["Hello", "World"])).await.unwrap();
+        assert_eq!(m.matched_index(), Some(1));
+        assert_eq!(m.get(0).unwrap(), b"World");
+    })
+}
+
+#[cfg(unix)]
+#[cfg(not(feature = "async"))]
+#[test]
+fn expect_macro() {
+    let mut session = spawn("sh -c 'sleep 0.3; echo World'").unwrap();
+
+    let mut lines = Vec::new();
+    expectrl::expect!(
+        &mut session,
+        _ = "Hello" => {
+            panic!("Unexpected result");
+        },
+        m = "World" => {
+            lines.push(m.before().to_vec());
+        },
+        timeout => {
+            panic!("Unexpected result");
+        },
+    )
+    .unwrap();
+    assert_eq!(lines, [b""]);
+
+    let mut eof = false;
+    expectrl::expect!(
+        &mut session,
+        _ = "Hello" => {
+            panic!("Unexpected result");
+        },
+        eof => {
+            eof = true;
+        },
+    )
+    .unwrap();
+    assert!(eof);
+}
+
+#[cfg(unix)]
+#[cfg(not(feature = "async"))]
+#[test]
+fn expect_macro_timeout() {
+    let mut session = spawn("sleep 3").unwrap();
+    session.set_expect_timeout(Some(Duration::from_millis(300)));
+
+    let mut timeout = false;
+    expectrl::expect!(
+        &mut session,
+        _ = "Hello" => {
+            panic!("Unexpected result");
+        },
+        timeout => {
+            timeout = true;
+        },
+    )
+    .unwrap();
+    assert!(timeout);
+}
+
+#[cfg(unix)]
+#[cfg(feature = "async")]
+#[test]
+fn expect_macro() {
+    futures_lite::future::block_on(async {
+        let mut session = spawn("sh -c 'sleep 0.3; echo World'").unwrap();
+
+        let mut lines = Vec::new();
+        expectrl::expect!(
+            session,
+            _ = "Hello" => {
+                panic!("Unexpected result");
+            },
+            m = "World" => {
+                lines.push(m.before().to_vec());
+            },
+            timeout => {
+                panic!("Unexpected result");
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(lines, [b""]);
+
+        let mut eof = false;
+        expectrl::expect!(
+            session,
+            _ = "Hello" => {
+                panic!("Unexpected result");
+            },
+            eof => {
+                eof = true;
+            },
+        )
+        .await
+        .unwrap();
+        assert!(eof);
+    })
+}
+
+#[cfg(unix)]
+#[cfg(not(feature = "async"))]
+#[test]
+fn expect_macro_multiple_sessions() {
+    let mut server = spawn("sleep 3").unwrap();
+    let mut client = spawn("sh -c 'sleep 0.3; echo ready'").unwrap();
+
+    let mut ready = None;
+    expectrl::expect!(
+        in [&mut server, &mut client],
+        (index, _) = "ready" => {
+            ready = Some(index);
+        },
+    )
+    .unwrap();
+
+    assert_eq!(ready, Some(1));
+}
+
+#[cfg(unix)]
+#[test]
+fn expect_not_without_output() {
+    use expectrl::Not;
+    use std::time::Instant;
+
+    let mut session = spawn("cat").unwrap();
+    session.set_expect_timeout(Some(Duration::from_secs(5)));
+
+    let now = Instant::now();
+
+    #[cfg(not(feature = "async"))]
+    session
+        .expect(Not::new("x", Duration::from_millis(50)))
+        .unwrap();
+
+    #[cfg(feature = "async")]
+    futures_lite::future::block_on(session.expect(Not::new("x", Duration::from_millis(50))))
+        .unwrap();
+
+    assert!(now.elapsed() < Duration::from_secs(1));
+}
+
+#[cfg(unix)]
+#[test]
+fn expect_idle() {
+    use std::time::{Duration, Instant};
+
+    let mut session =
+        spawn(r#"sh -c "echo 1; sleep 0.3; echo 2; sleep 0.3; echo 3; sleep 5""#).unwrap();
+    let mut busy = spawn(r#"sh -c "while true; do echo tick; sleep 0.05; done""#).unwrap();
+    busy.set_expect_timeout(Some(Duration::from_millis(500)));
+
+    #[cfg(not(feature = "async"))]
+    {
+        let now = Instant::now();
+        session.expect_idle(Duration::from_secs(1)).unwrap();
+        assert!(now.elapsed() >= Duration::from_millis(1600));
+        assert_eq!(session.buffer(), b"1\r\n2\r\n3\r\n");
+
+        assert!(matches!(
+            busy.expect_idle(Duration::from_millis(200)),
+            Err(expectrl::Error::ExpectTimeout)
+        ));
+    }
+
+    #[cfg(feature = "async")]
+    futures_lite::future::block_on(async {
+        let now = Instant::now();
+        session.expect_idle(Duration::from_secs(1)).await.unwrap();
+        assert!(now.elapsed() >= Duration::from_millis(1600));
+        assert_eq!(session.buffer(), b"1\r\n2\r\n3\r\n");
+
+        assert!(matches!(
+            busy.expect_idle(Duration::from_millis(200)).await,
+            Err(expectrl::Error::ExpectTimeout)
+        ));
+    });
+}
+
+#[cfg(unix)]
+#[test]
+fn expect_with_retry() {
+    use expectrl::policy::RetryPolicy;
+
+    let mut session = spawn(r#"sh -c "read a; read b; echo login:""#).unwrap();
+    session.set_expect_timeout(Some(Duration::from_millis(200)));
+
+    let policy = RetryPolicy::new(3)
+        .backoff(Duration::from_millis(50))
+        .probe("\n");
+
+    let mut failing = spawn("cat").unwrap();
+    failing.set_expect_timeout(Some(Duration::from_millis(100)));
+
+    #[cfg(not(feature = "async"))]
+    {
+        session.expect_with_retry("login:", &policy).unwrap();
+
+        assert!(matches!(
+            failing.expect_with_retry("login:", &RetryPolicy::new(2)),
+            Err(expectrl::Error::ExpectTimeout)
+        ));
+    }
+
+    #[cfg(feature = "async")]
+    futures_lite::future::block_on(async {
+        session.expect_with_retry("login:", &policy).await.unwrap();
+
+        assert!(matches!(
+            failing
+                .expect_with_retry("login:", &RetryPolicy::new(2))
+                .await,
+            Err(expectrl::Error::ExpectTimeout)
+        ));
+    });
+}
+
+#[cfg(unix)]
+#[test]
+fn dialogue() {
+    use expectrl::dialogue::Dialogue;
+
+    let mut session =
+        spawn(r#"sh -c "echo login:; read a; echo Password:; read b; echo hello $a""#).unwrap();
+    session.set_expect_timeout(Some(Duration::from_millis(500)));
+
+    let dialogue = Dialogue::new()
+        .expect("login:")
+        .send_line("root")
+        .expect("Password:")
+        .send_secret("qwerty")
+        .expect(Regex("hello (.*)\r\n"));
+
+    let failing = Dialogue::new().expect("login:").expect("Password:");
+    let mut failing_session = spawn("echo login:").unwrap();
+
+    #[cfg(not(feature = "async"))]
+    let (report, err) = (
+        dialogue.run(&mut session).unwrap(),
+        failing.run(&mut failing_session).unwrap_err(),
+    );
+
+    #[cfg(feature = "async")]
+    let (report, err) = futures_lite::future::block_on(async {
+        (
+            dialogue.run(&mut session).await.unwrap(),
+            failing.run(&mut failing_session).await.unwrap_err(),
+        )
+    });
+
+    assert_eq!(report.steps().len(), 5);
+    assert_eq!(report.steps()[3].description, "send <secret>");
+    assert_eq!(report.captures(4).unwrap().get(1).unwrap(), b"root");
+    assert!(report.captures(1).is_none());
+
+    assert!(err
+        .to_string()
+        .contains("step 2 of 2 (expect \"Password:\") failed"));
+    assert!(matches!(err.root(), expectrl::Error::Eof));
+}
@@ -151,6 +151,46 @@ fn interact_callbacks_with_stream_redirection() {
     assert!(buffer.contains("Hello World"), "{buffer:?}");
 }
 
+#[cfg(unix)]
+#[cfg(not(any(feature = "async", feature = "polling")))]
+#[test]
+fn interact_pause_and_resume() {
+    use expectrl::interact::{InteractOptions, InteractSession, StopReason};
+
+    let reader = ReaderWithDelayEof::new("Hello\n", Duration::from_secs(2));
+    let mut writer = io::Cursor::new(vec![0; 2048]);
+
+    let mut session = spawn("cat").unwrap();
+    let mut interact = InteractSession::new(&mut session, reader, &mut writer);
+
+    let pause = interact.pause_handle();
+    let mut lookup = Lookup::new();
+    let mut opts = InteractOptions::default().on_output(|ctx| {
+        if lookup.on(ctx.buf, ctx.eof, "Hello")?.is_some() {
+            pause.pause();
+        }
+
+        Ok(false)
+    });
+
+    assert_eq!(
+        interact.spawn(&mut opts).unwrap().reason(),
+        StopReason::Paused
+    );
+    assert!(interact.is_paused());
+
+    interact.get_session_mut().send_line("World").unwrap();
+    interact.get_session_mut().expect("World").unwrap();
+
+    assert!(interact.spawn(&mut opts).unwrap().is_alive());
+    assert!(!interact.is_paused());
+
+    drop(interact);
+
+    let buffer = String::from_utf8_lossy(writer.get_ref());
+    assert!(buffer.contains("Hello"), "{buffer:?}");
+}
+
 #[cfg(unix)]
 #[cfg(not(any(feature = "async", feature = "polling")))]
 #[test]
@@ -192,6 +232,209 @@ fn interact_filters() {
     assert_eq!(buffer, "19\r\nYES\r\n19\r\nYES\r\n");
 }
 
+#[cfg(unix)]
+#[cfg(not(any(feature = "async", feature = "polling")))]
+#[test]
+fn interact_output_filter_keeps_recording() {
+    use expectrl::interact::{InteractOptions, InteractSession, StopReason};
+
+    let reader = ReaderWithDelayEof::new("code 1234\n", Duration::from_secs(2));
+    let mut writer = io::Cursor::new(vec![0; 2048]);
+    let recording = SharedBuffer::default();
+
+    let mut session = spawn("cat").unwrap();
+    let status = InteractSession::new(&mut session, reader, &mut writer)
+        .set_recording(recording.clone())
+        .spawn(InteractOptions::default().output_filter(|buf| {
+            let v = buf
+                .iter()
+                .map(|&b| if b.is_ascii_digit() { b'*' } else { b })
+                .collect();
+            Ok(v)
+        }))
+        .unwrap();
+
+    assert_eq!(status.reason(), StopReason::InputEof);
+    assert_eq!(status.bytes_sent(), 10);
+    assert!(status.bytes_received() > 0);
+
+    let buffer = String::from_utf8_lossy(writer.get_ref());
+    assert!(buffer.contains("code ****"), "{buffer:?}");
+    assert!(!buffer.contains("1234"), "{buffer:?}");
+
+    let recording = recording.0.lock().unwrap();
+    let recording = String::from_utf8_lossy(&recording);
+    assert!(recording.contains("code 1234"), "{recording:?}");
+}
+
+#[cfg(unix)]
+#[cfg(not(any(feature = "async", feature = "polling")))]
+#[test]
+fn interact_break_on() {
+    use expectrl::interact::{InteractOptions, InteractSession, StopReason};
+
+    let reader = ReaderWithDelayEof::new("Login successful\n", Duration::from_secs(10));
+    let mut writer = io::Cursor::new(vec![0; 2048]);
+
+    let mut session = spawn("cat").unwrap();
+    let mut interact =
+        InteractSession::new(&mut session, reader, &mut writer).break_on("successful");
+
+    let now = Instant::now();
+    assert_eq!(
+        interact.spawn(InteractOptions::default()).unwrap().reason(),
+        StopReason::BreakOn
+    );
+    assert!(now.elapsed() < Duration::from_secs(10));
+
+    let captures = interact.get_captures().unwrap();
+    assert_eq!(captures.get(0).unwrap(), b"successful");
+
+    drop(interact);
+
+    let buffer = String::from_utf8_lossy(writer.get_ref());
+    assert!(buffer.contains("Login successful"), "{buffer:?}");
+}
+
+#[cfg(unix)]
+#[cfg(not(any(feature = "async", feature = "polling")))]
+#[test]
+fn interact_inject_input() {
+    use expectrl::interact::{InteractOptions, InteractSession, StopReason};
+
+    let reader = ReaderWithDelayEof::new("", Duration::from_secs(10));
+    let mut writer = io::Cursor::new(vec![0; 2048]);
+
+    let mut session = spawn("cat").unwrap();
+    let mut interact = InteractSession::new(&mut session, reader, &mut writer).break_on("Injected");
+
+    let handle = interact.handle();
+    let thread = std::thread::spawn(move || {
+        std::thread::sleep(Duration::from_millis(300));
+        handle.inject_input("Injected\n");
+    });
+
+    assert_eq!(
+        interact.spawn(InteractOptions::default()).unwrap().reason(),
+        StopReason::BreakOn
+    );
+    assert!(interact.get_captures().is_some());
+    assert!(!interact.handle().has_pending_input());
+
+    thread.join().unwrap();
+}
+
+#[cfg(unix)]
+#[cfg(not(any(feature = "async", feature = "polling")))]
+#[test]
+fn interact_with_stream() {
+    use expectrl::interact::InteractOptions;
+
+    let output = SharedBuffer::default();
+    let stream = Loopback {
+        input: ReaderWithDelayEof::new("Hello World\n", Duration::from_secs(2)),
+        output: output.clone(),
+    };
+
+    let mut session = spawn("cat").unwrap();
+    session
+        .interact_with(stream)
+        .spawn(InteractOptions::default())
+        .unwrap();
+
+    let buffer = output.0.lock().unwrap();
+    let buffer = String::from_utf8_lossy(&buffer);
+    assert!(buffer.contains("Hello World\r\n"), "{buffer:?}");
+}
+
+#[cfg(unix)]
+#[cfg(feature = "async")]
+#[test]
+fn interact_with_stream() {
+    use expectrl::interact::InteractOptions;
+
+    let output = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let stream = Loopback {
+        input: ReaderWithDelayEof::new("Hello World\n", Duration::from_secs(2)),
+        output: output.clone(),
+    };
+
+    let mut session = expectrl::spawn("cat").unwrap();
+    futures_lite::future::block_on(
+        session
+            .interact_with(stream)
+            .spawn(InteractOptions::default()),
+    )
+    .unwrap();
+
+    let buffer = output.lock().unwrap();
+    let buffer = String::from_utf8_lossy(&buffer);
+    assert!(buffer.contains("Hello World\r\n"), "{buffer:?}");
+}
+
+#[cfg(unix)]
+#[cfg(not(any(feature = "async", feature = "polling")))]
+#[test]
+fn interact_keepalive() {
+    use expectrl::interact::{InteractOptions, InteractSession, StopReason};
+
+    let reader = ReaderWithDelayEof::new("", Duration::from_secs(10));
+    let mut writer = io::Cursor::new(vec![0; 2048]);
+
+    let mut session = spawn("cat").unwrap();
+    let mut interact = InteractSession::new(&mut session, reader, &mut writer)
+        .set_keepalive(Duration::from_millis(300), "ping\n")
+        .set_idle_timeout(Duration::from_millis(100))
+        .break_on("ping");
+
+    let mut opts = InteractOptions::new(0).on_idle(|ctx| {
+        *ctx.state += 1;
+        Ok(false)
+    });
+
+    assert_eq!(
+        interact.spawn(&mut opts).unwrap().reason(),
+        StopReason::BreakOn
+    );
+    assert!(interact.get_captures().is_some());
+
+    let idle_calls = opts.into_inner();
+    assert!((1..10).contains(&idle_calls), "{idle_calls}");
+}
+
+#[cfg(unix)]
+#[cfg(not(any(feature = "async", feature = "polling")))]
+#[test]
+fn interact_terminal_modes() {
+    use expectrl::interact::{InteractOptions, InteractSession};
+
+    let reader = ReaderWithDelayEof::new("\x1b[<0;10;5M", Duration::from_secs(5));
+    let mut writer = io::Cursor::new(Vec::new());
+
+    // the process turns on a mouse reporting and prints a click it gets
+    let mut session = spawn(
+        r#"sh -c "stty raw -echo; printf '\033[?1000h\033[?1006h'; dd bs=1 count=10 2>/dev/null | od -An -c; sleep 1""#,
+    )
+    .unwrap();
+    InteractSession::new(&mut session, reader, &mut writer)
+        .set_mouse_reporting(true)
+        .set_bracketed_paste(true)
+        .spawn(InteractOptions::default())
+        .unwrap();
+
+    let buffer = String::from_utf8_lossy(writer.get_ref());
+    assert!(buffer.starts_with("\x1b[?2004h"), "{buffer:?}");
+    assert!(buffer.contains("\x1b[?1000h\x1b[?1006h"), "{buffer:?}");
+    assert!(
+        buffer.contains("033   [   <   0   ;   1   0   ;   5   M"),
+        "{buffer:?}"
+    );
+    assert!(
+        buffer.ends_with("\x1b[?2004l\x1b[?1006l\x1b[?1000l"),
+        "{buffer:?}"
+    );
+}
+
 #[cfg(all(unix, not(any(feature = "async", feature = "polling"))))]
 #[test]
 fn interact_context() {
@@ -231,14 +474,14 @@ fn interact_context() {
             Ok(false)
         });
 
-    let is_alive = session
+    let status = session
         .interact(reader, &mut writer)
         .spawn(&mut opts)
         .unwrap();
 
     let state = opts.into_inner();
 
-    assert!(is_alive);
+    assert!(status.is_alive());
 
     assert_eq!(state.0, 4);
     assert!(state.1 > 0, "{:?}", state.1);
@@ -288,14 +531,14 @@ fn interact_on_output_not_matched() {
             Ok(false)
         });
 
-    let is_alive = session
+    let status = session
         .interact(reader, &mut writer)
         .spawn(&mut opts)
         .unwrap();
 
     let state = opts.into_inner();
 
-    assert!(is_alive);
+    assert!(status.is_alive());
 
     assert_eq!(state.0, 2);
     assert_eq!(state.1, 0);
@@ -398,6 +641,21 @@ fn interact_output_callback() {
     assert!(matches!(state, 1 | 0), "{state:?}");
 }
 
+#[cfg(not(any(feature = "async", feature = "polling")))]
+#[derive(Debug, Default, Clone)]
+struct SharedBuffer(std::sync::Arc<std::sync::Mutex<Vec<u8>>>);
+
+#[cfg(not(any(feature = "async", feature = "polling")))]
+impl Write for SharedBuffer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
 struct ListReaderWithDelayedEof {
     lines: Vec<String>,
     eof_timeout: Duration,
@@ -487,3 +745,64 @@ where
         std::task::Poll::Ready(result)
     }
 }
+
+/// Loopback is a single stream which is used both as an input and an output of an interact session.
+#[cfg(any(feature = "async", not(feature = "polling")))]
+struct Loopback<W> {
+    input: ReaderWithDelayEof<&'static str>,
+    output: W,
+}
+
+#[cfg(not(any(feature = "async", feature = "polling")))]
+impl<W> Read for Loopback<W> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.input.read(buf)
+    }
+}
+
+#[cfg(not(any(feature = "async", feature = "polling")))]
+impl<W: Write> Write for Loopback<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.output.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.output.flush()
+    }
+}
+
+#[cfg(feature = "async")]
+impl<W: Unpin> futures_lite::AsyncRead for Loopback<W> {
+    fn poll_read(
+        self: std::pin::Pin<&mut Self>,
+        _cx: &mut std::task::Context<'_>,
+        buf: &mut [u8],
+    ) -> std::task::Poll<io::Result<usize>> {
+        std::task::Poll::Ready(self.get_mut().input.read(buf))
+    }
+}
+
+#[cfg(feature = "async")]
+impl futures_lite::AsyncWrite for Loopback<std::sync::Arc<std::sync::Mutex<Vec<u8>>>> {
+    fn poll_write(
+        self: std::pin::Pin<&mut Self>,
+        _cx: &mut std::task::Context<'_>,
+        buf: &[u8],
+    ) -> std::task::Poll<io::Result<usize>> {
+        std::task::Poll::Ready(self.output.lock().unwrap().write(buf))
+    }
+
+    fn poll_flush(
+        self: std::pin::Pin<&mut Self>,
+        _cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<io::Result<()>> {
+        std::task::Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(
+        self: std::pin::Pin<&mut Self>,
+        _cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<io::Result<()>> {
+        std::task::Poll::Ready(Ok(()))
+    }
+}
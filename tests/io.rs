@@ -696,3 +696,216 @@ fn do_until(mut foo: impl FnMut() -> bool, timeout: Duration) -> bool {
 
     return false;
 }
+
+#[test]
+#[cfg(unix)]
+fn read_until_timeout() {
+    let mut command = Command::new("printf");
+    let _ = command.arg("3:abc;rest");
+    let mut proc = Session::spawn(command).unwrap();
+
+    #[cfg(not(feature = "async"))]
+    {
+        let len = proc.read_until_timeout(b':', None).unwrap();
+        assert_eq!(len, b"3:");
+
+        let mut buf = [0; 3];
+        proc.read_exact_timeout(&mut buf, None).unwrap();
+        assert_eq!(&buf, b"abc");
+
+        assert_eq!(proc.read_until_timeout(b';', None).unwrap(), b";");
+        assert_eq!(proc.read_until_timeout(b';', None).unwrap(), b"rest");
+    }
+
+    #[cfg(feature = "async")]
+    block_on(async {
+        let len = proc.read_until_timeout(b':', None).await.unwrap();
+        assert_eq!(len, b"3:");
+
+        let mut buf = [0; 3];
+        proc.read_exact_timeout(&mut buf, None).await.unwrap();
+        assert_eq!(&buf, b"abc");
+
+        assert_eq!(proc.read_until_timeout(b';', None).await.unwrap(), b";");
+        assert_eq!(proc.read_until_timeout(b';', None).await.unwrap(), b"rest");
+    });
+}
+
+#[test]
+#[cfg(unix)]
+fn read_exact_timeout() {
+    let mut proc = Session::spawn(Command::new("cat")).unwrap();
+
+    #[cfg(not(feature = "async"))]
+    {
+        proc.send("ab").unwrap();
+
+        let mut buf = [0; 10];
+        let err = proc
+            .read_exact_timeout(&mut buf, Some(Duration::from_millis(300)))
+            .unwrap_err();
+        assert!(matches!(err, expectrl::Error::ExpectTimeout));
+
+        let mut buf = [0; 2];
+        proc.read_exact_timeout(&mut buf, None).unwrap();
+        assert_eq!(&buf, b"ab");
+    }
+
+    #[cfg(feature = "async")]
+    block_on(async {
+        proc.send("ab").await.unwrap();
+
+        let mut buf = [0; 10];
+        let err = proc
+            .read_exact_timeout(&mut buf, Some(Duration::from_millis(300)))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, expectrl::Error::ExpectTimeout));
+
+        let mut buf = [0; 2];
+        proc.read_exact_timeout(&mut buf, None).await.unwrap();
+        assert_eq!(&buf, b"ab");
+    });
+}
+
+#[test]
+#[cfg(unix)]
+fn peek_and_clear_buffer() {
+    let mut proc = Session::spawn(Command::new("cat")).unwrap();
+
+    #[cfg(not(feature = "async"))]
+    {
+        assert!(proc.buffer().is_empty());
+
+        proc.send_line("Hello World").unwrap();
+        thread::sleep(Duration::from_millis(300));
+
+        assert!(proc.peek().unwrap().starts_with(b"Hello World"));
+        assert!(proc.buffer().starts_with(b"Hello World"));
+
+        proc.expect("Hello").unwrap();
+        assert!(proc.buffer().starts_with(b" World"));
+
+        proc.clear_buffer();
+        assert!(proc.buffer().is_empty());
+    }
+
+    #[cfg(feature = "async")]
+    block_on(async {
+        assert!(proc.buffer().is_empty());
+
+        proc.send_line("Hello World").await.unwrap();
+        thread::sleep(Duration::from_millis(300));
+
+        assert!(proc.peek().await.unwrap().starts_with(b"Hello World"));
+        assert!(proc.buffer().starts_with(b"Hello World"));
+
+        proc.expect("Hello").await.unwrap();
+        assert!(proc.buffer().starts_with(b" World"));
+
+        proc.clear_buffer();
+        assert!(proc.buffer().is_empty());
+    });
+}
+
+#[test]
+#[cfg(unix)]
+fn peek_lines() {
+    let mut command = Command::new("printf");
+    let _ = command.arg("1\\n2\\n3");
+    let mut proc = Session::spawn(command).unwrap();
+    thread::sleep(Duration::from_millis(300));
+
+    #[cfg(not(feature = "async"))]
+    {
+        proc.peek().unwrap();
+        assert_eq!(proc.peek_lines(), ["1", "2", "3"]);
+        assert_eq!(proc.peek_buffer(), b"1\r\n2\r\n3");
+
+        assert_eq!(proc.expect_line().unwrap(), "1");
+        assert_eq!(proc.peek_lines(), ["2", "3"]);
+    }
+
+    #[cfg(feature = "async")]
+    block_on(async {
+        proc.peek().await.unwrap();
+        assert_eq!(proc.peek_lines(), ["1", "2", "3"]);
+        assert_eq!(proc.peek_buffer(), b"1\r\n2\r\n3");
+
+        assert_eq!(proc.expect_line().await.unwrap(), "1");
+        assert_eq!(proc.peek_lines(), ["2", "3"]);
+    });
+}
+
+#[cfg(unix)]
+#[test]
+fn send_line_longer_than_canonical_buffer() {
+    let line = "a".repeat(10000);
+
+    let mut session = expectrl::spawn(r#"sh -c "read line; echo length=${#line}""#).unwrap();
+
+    #[cfg(not(feature = "async"))]
+    {
+        session.send_line(&line).unwrap();
+        session.expect("length=10000").unwrap();
+    }
+
+    #[cfg(feature = "async")]
+    futures_lite::future::block_on(async {
+        session.send_line(&line).await.unwrap();
+        session.expect("length=10000").await.unwrap();
+    });
+}
+
+#[cfg(unix)]
+#[test]
+fn send_line_after_long_write_in_raw_mode() {
+    let mut session = expectrl::spawn(
+        r#"sh -c "head -c 10000 > /dev/null; echo done; read line; echo line=$line""#,
+    )
+    .unwrap();
+
+    let settings = session.tty_settings().unwrap();
+    session.set_raw_mode().unwrap();
+
+    #[cfg(not(feature = "async"))]
+    {
+        session.send("a".repeat(10000)).unwrap();
+        session.expect("done").unwrap();
+
+        session.set_tty_settings(&settings).unwrap();
+        session.send_line("b").unwrap();
+        session.expect("line=b").unwrap();
+    }
+
+    #[cfg(feature = "async")]
+    futures_lite::future::block_on(async {
+        session.send("a".repeat(10000)).await.unwrap();
+        session.expect("done").await.unwrap();
+
+        session.set_tty_settings(&settings).unwrap();
+        session.send_line("b").await.unwrap();
+        session.expect("line=b").await.unwrap();
+    });
+}
+
+#[cfg(unix)]
+#[test]
+fn send_longer_than_canonical_buffer_in_raw_mode() {
+    let data = "a".repeat(10000);
+
+    let mut session = expectrl::spawn(r#"sh -c "head -c 10000 | wc -c""#).unwrap();
+    session.set_raw_mode().unwrap();
+
+    #[cfg(not(feature = "async"))]
+    {
+        session.send(&data).unwrap();
+        session.expect("10000").unwrap();
+    }
+
+    #[cfg(feature = "async")]
+    futures_lite::future::block_on(async {
+        session.send(&data).await.unwrap();
+        session.expect("10000").await.unwrap();
+    });
+}
@@ -0,0 +1,41 @@
+#![cfg(unix)]
+
+use expectrl::matrix::Matrix;
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn matrix_run() {
+    let results = Matrix::new("sh")
+        .args(["-c", "echo $TERM $LANG"])
+        .terms(["xterm", "dumb"])
+        .locales(["C"])
+        .run(|env, session| {
+            let term = env.term.as_deref().unwrap();
+            session.expect(format!("{} C", term))?;
+            Ok(term.to_owned())
+        });
+
+    assert_eq!(results.len(), 2);
+    for (env, result) in results {
+        assert_eq!(env.term, Some(result.unwrap()));
+    }
+}
+
+#[cfg(feature = "async")]
+#[test]
+fn matrix_run() {
+    let results = Matrix::new("sh")
+        .args(["-c", "echo $TERM $LANG"])
+        .terms(["xterm", "dumb"])
+        .locales(["C"])
+        .run(|env, session| {
+            let term = env.term.as_deref().unwrap();
+            futures_lite::future::block_on(session.expect(format!("{} C", term)))?;
+            Ok(term.to_owned())
+        });
+
+    assert_eq!(results.len(), 2);
+    for (env, result) in results {
+        assert_eq!(env.term, Some(result.unwrap()));
+    }
+}
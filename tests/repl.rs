@@ -212,3 +212,188 @@ fn bash_control_chars() {
     p.send(ControlCode::Substitute).unwrap(); // suspend:SIGTSTPcon
     p.expect_prompt().unwrap();
 }
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn bash_continuation_prompt() {
+    let mut p = spawn_bash().unwrap();
+    let _ = p.execute("stty -echo").unwrap();
+
+    let output = p.execute("echo 'Hello\nWorld'").unwrap();
+    let output = String::from_utf8_lossy(&output);
+    assert!(output.contains("Hello\r\nWorld"), "{output:?}");
+
+    let err = p.execute("echo 'Hello").unwrap_err();
+    assert!(
+        err.to_string().contains("continuation prompt detected"),
+        "{err}"
+    );
+
+    let output = p.execute("echo World").unwrap();
+    assert_eq!(String::from_utf8_lossy(&output).trim(), "World");
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn bash_transcript() {
+    let mut p = spawn_bash().unwrap();
+    p.set_transcript_limit(10);
+    let _ = p.execute("stty -echo").unwrap();
+    let _ = p.execute("echo Hello World").unwrap();
+
+    let transcript = p.transcript();
+    assert_eq!(transcript.len(), 2);
+    assert_eq!(transcript.entries()[1].command(), "echo Hello World");
+    assert_eq!(
+        String::from_utf8_lossy(transcript.entries()[1].output()).trim(),
+        "Hello World"
+    );
+
+    let path = std::env::temp_dir().join("expectrl_bash_transcript.json");
+    p.save_transcript(&path, expectrl::TranscriptFormat::Json)
+        .unwrap();
+    let json = std::fs::read_to_string(&path).unwrap();
+    assert!(json.contains(r#""command":"echo Hello World""#), "{json}");
+    let _ = std::fs::remove_file(path);
+}
+
+#[cfg(not(feature = "async"))]
+#[ignore = "It requires zsh to be installed"]
+#[test]
+fn zsh() {
+    let mut p = expectrl::repl::spawn_zsh().unwrap();
+    let _ = p.execute("stty -echo").unwrap();
+    let output = p.execute("echo Hello World").unwrap();
+    assert_eq!(String::from_utf8_lossy(&output).trim(), "Hello World");
+}
+
+#[cfg(not(feature = "async"))]
+#[ignore = "It requires fish to be installed"]
+#[test]
+fn fish() {
+    let mut p = expectrl::repl::spawn_fish().unwrap();
+    let _ = p.execute("stty -echo").unwrap();
+    let output = p.execute("echo Hello World").unwrap();
+    assert_eq!(String::from_utf8_lossy(&output).trim(), "Hello World");
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn bash_execute_table() {
+    let mut p = spawn_bash().unwrap();
+    let _ = p.execute("stty -echo").unwrap();
+    let table = p
+        .execute_table("printf 'NAME SIZE\\nfoo 10\\nbar 20\\n'")
+        .unwrap();
+    assert_eq!(table.len(), 2);
+    assert_eq!(table[0]["NAME"], "foo");
+    assert_eq!(table[1]["SIZE"], "20");
+}
+
+#[cfg(feature = "async")]
+#[test]
+fn bash_execute_table() {
+    futures_lite::future::block_on(async {
+        let mut p = spawn_bash().await.unwrap();
+        let _ = p.execute("stty -echo").await.unwrap();
+        let table = p
+            .execute_table("printf 'NAME SIZE\\nfoo 10\\nbar 20\\n'")
+            .await
+            .unwrap();
+        assert_eq!(table.len(), 2);
+        assert_eq!(table[0]["NAME"], "foo");
+        assert_eq!(table[1]["SIZE"], "20");
+    });
+}
+
+/// Creates a directory with a fake `sudo` which accepts "secret" as a password
+/// and caches credentials after the first successful call.
+#[cfg(target_os = "linux")]
+fn fake_sudo() -> std::path::PathBuf {
+    use std::os::unix::fs::PermissionsExt;
+
+    const SCRIPT: &str = r#"#!/bin/sh
+[ "$1" = "-p" ] && { prompt=$2; shift 2; }
+cache="$(dirname "$0")/cached"
+if [ ! -f "$cache" ]; then
+    trap 'stty echo; exit 1' INT
+    stty -echo
+    printf '%s' "$prompt"
+    read -r password
+    if [ "$password" != "secret" ]; then
+        printf '\nSorry, try again.\n%s' "$prompt"
+        read -r password
+        exit 1
+    fi
+    stty echo
+    echo
+    touch "$cache"
+fi
+exec "$@"
+"#;
+
+    let dir = std::env::temp_dir().join(format!("expectrl-sudo-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let _ = std::fs::remove_file(dir.join("cached"));
+
+    let path = dir.join("sudo");
+    std::fs::write(&path, SCRIPT).unwrap();
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+    dir
+}
+
+#[cfg(not(feature = "async"))]
+#[cfg(target_os = "linux")]
+#[test]
+fn bash_execute_sudo() {
+    let dir = fake_sudo();
+
+    let mut p = spawn_bash().unwrap();
+    p.execute(format!("export PATH={}:$PATH", dir.display()))
+        .unwrap();
+
+    // a wrong password is rejected and the command is interrupted
+    assert!(p.execute_sudo("echo hello", "wrong").is_err());
+    assert!(!dir.join("cached").exists());
+
+    let output = p.execute_sudo("echo hello", "secret").unwrap();
+    let output = String::from_utf8_lossy(&output);
+    assert!(output.contains("hello"), "{output:?}");
+
+    // credentials are cached so the password isn't asked
+    let output = p.execute_sudo("echo world", "wrong").unwrap();
+    let output = String::from_utf8_lossy(&output);
+    assert!(output.contains("world"), "{output:?}");
+
+    let _ = std::fs::remove_dir_all(dir);
+}
+
+#[cfg(feature = "async")]
+#[cfg(target_os = "linux")]
+#[test]
+fn bash_execute_sudo() {
+    let dir = fake_sudo();
+
+    futures_lite::future::block_on(async {
+        let mut p = spawn_bash().await.unwrap();
+        p.execute(format!("export PATH={}:$PATH", dir.display()))
+            .await
+            .unwrap();
+
+        // a wrong password is rejected and the command is interrupted
+        assert!(p.execute_sudo("echo hello", "wrong").await.is_err());
+        assert!(!dir.join("cached").exists());
+
+        let output = p.execute_sudo("echo hello", "secret").await.unwrap();
+        let output = String::from_utf8_lossy(&output);
+        assert!(output.contains("hello"), "{output:?}");
+
+        // credentials are cached so the password isn't asked
+        let output = p.execute_sudo("echo world", "wrong").await.unwrap();
+        let output = String::from_utf8_lossy(&output);
+        assert!(output.contains("world"), "{output:?}");
+    });
+
+    let _ = std::fs::remove_dir_all(dir);
+}
@@ -0,0 +1,79 @@
+#![cfg(all(unix, feature = "scripts"))]
+
+use expectrl::{script::Script, spawn, Eof};
+
+#[test]
+fn script_run() {
+    let script: Script = r#"
+        spawn sh -c "echo 'login#'; read name; echo hello $name"
+        timeout 5
+        expect "login#" # a comment
+        send_line "world"
+        expect "hello world"
+        expect eof
+    "#
+    .parse()
+    .unwrap();
+
+    #[cfg(not(feature = "async"))]
+    script.run().unwrap();
+
+    #[cfg(feature = "async")]
+    futures_lite::future::block_on(script.run()).unwrap();
+}
+
+#[test]
+fn script_run_on() {
+    let script: Script = r#"
+        timeout 5
+        label ask
+        expect "(yes/no)" -> confirm, "done" -> end
+
+        label confirm
+        send_line "yes"
+        goto ask
+
+        label end
+        exit
+    "#
+    .parse()
+    .unwrap();
+
+    let mut session =
+        spawn(r#"sh -c "echo '(yes/no)'; read answer; echo got $answer; echo done""#).unwrap();
+
+    #[cfg(not(feature = "async"))]
+    {
+        script.run_on(&mut session).unwrap();
+        session.expect(Eof).unwrap();
+    }
+
+    #[cfg(feature = "async")]
+    futures_lite::future::block_on(async {
+        script.run_on(&mut session).await.unwrap();
+        session.expect(Eof).await.unwrap();
+    });
+}
+
+#[test]
+fn script_run_error() {
+    let script: Script = r#"
+        spawn echo hello
+        timeout 5
+        expect "bye"
+    "#
+    .parse()
+    .unwrap();
+
+    #[cfg(not(feature = "async"))]
+    let err = script.run().unwrap_err();
+
+    #[cfg(feature = "async")]
+    let err = futures_lite::future::block_on(script.run()).unwrap_err();
+
+    assert!(
+        err.to_string().contains("Script failed at line 4"),
+        "{}",
+        err
+    );
+}
@@ -1,4 +1,4 @@
-use expectrl::{spawn, Session};
+use expectrl::{spawn, spawn_args, Session};
 
 #[cfg(feature = "async")]
 use futures_lite::io::{AsyncReadExt, AsyncWriteExt};
@@ -215,3 +215,634 @@ fn test_session_as_writer() {
         }
     }
 }
+
+#[cfg(unix)]
+#[test]
+fn shutdown() {
+    use expectrl::Signal;
+    use std::time::Duration;
+
+    let mut session = spawn("sleep 10").unwrap();
+    let mut stubborn = spawn(r#"sh -c "trap '' TERM; echo ready; sleep 10""#).unwrap();
+
+    #[cfg(not(feature = "async"))]
+    {
+        let status = session.shutdown(Duration::from_secs(3)).unwrap();
+        assert_eq!(status.signal(), Some(Signal::SIGTERM));
+
+        stubborn.expect("ready").unwrap();
+        let status = stubborn.shutdown(Duration::from_millis(300)).unwrap();
+        assert_eq!(status.signal(), Some(Signal::SIGKILL));
+    }
+
+    #[cfg(feature = "async")]
+    futures_lite::future::block_on(async {
+        let status = session.shutdown(Duration::from_secs(3)).await.unwrap();
+        assert_eq!(status.signal(), Some(Signal::SIGTERM));
+
+        stubborn.expect("ready").await.unwrap();
+        let status = stubborn.shutdown(Duration::from_millis(300)).await.unwrap();
+        assert_eq!(status.signal(), Some(Signal::SIGKILL));
+    });
+}
+
+#[cfg(unix)]
+#[test]
+fn shutdown_exited() {
+    use std::time::Duration;
+
+    let mut session = spawn("sh -c 'exit 3'").unwrap();
+
+    #[cfg(not(feature = "async"))]
+    {
+        let status = session.wait_timeout(Duration::from_secs(3)).unwrap();
+        assert_eq!(status.and_then(|s| s.code()), Some(3));
+
+        let status = session.shutdown(Duration::from_secs(3)).unwrap();
+        assert_eq!(status.code(), Some(3));
+    }
+
+    #[cfg(feature = "async")]
+    futures_lite::future::block_on(async {
+        let status = session.wait_timeout(Duration::from_secs(3)).await.unwrap();
+        assert_eq!(status.and_then(|s| s.code()), Some(3));
+
+        let status = session.shutdown(Duration::from_secs(3)).await.unwrap();
+        assert_eq!(status.code(), Some(3));
+    });
+}
+
+#[cfg(unix)]
+#[test]
+fn wait_timeout() {
+    use std::time::Duration;
+
+    let mut session = spawn("sh -c 'sleep 0.5; exit 3'").unwrap();
+
+    #[cfg(not(feature = "async"))]
+    {
+        let status = session.wait_timeout(Duration::from_millis(100)).unwrap();
+        assert_eq!(status, None);
+
+        let status = session
+            .wait_timeout(Duration::from_secs(3))
+            .unwrap()
+            .unwrap();
+        assert_eq!(status.code(), Some(3));
+        assert_eq!(status.signal(), None);
+        assert!(!status.success());
+    }
+
+    #[cfg(feature = "async")]
+    futures_lite::future::block_on(async {
+        let status = session
+            .wait_timeout(Duration::from_millis(100))
+            .await
+            .unwrap();
+        assert_eq!(status, None);
+
+        let status = session
+            .wait_timeout(Duration::from_secs(3))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(status.code(), Some(3));
+        assert_eq!(status.signal(), None);
+        assert!(!status.success());
+    });
+}
+
+#[cfg(unix)]
+#[test]
+fn signal() {
+    use expectrl::Signal;
+    use std::time::Duration;
+
+    let mut session = spawn("sleep 10").unwrap();
+    session.signal(Signal::SIGUSR1).unwrap();
+
+    #[cfg(not(feature = "async"))]
+    let status = session.wait_timeout(Duration::from_secs(3)).unwrap();
+    #[cfg(feature = "async")]
+    let status =
+        futures_lite::future::block_on(session.wait_timeout(Duration::from_secs(3))).unwrap();
+
+    assert_eq!(status.unwrap().signal(), Some(Signal::SIGUSR1));
+}
+
+#[cfg(unix)]
+#[test]
+fn send_intr_and_eof() {
+    use expectrl::{Eof, Signal};
+    use std::time::Duration;
+
+    let mut session = spawn("cat").unwrap();
+    let mut interrupted = spawn("cat").unwrap();
+
+    #[cfg(not(feature = "async"))]
+    {
+        session.send_eof().unwrap();
+        session.expect(Eof).unwrap();
+
+        interrupted.send_intr().unwrap();
+        let status = interrupted.wait_timeout(Duration::from_secs(3)).unwrap();
+        assert_eq!(status.unwrap().signal(), Some(Signal::SIGINT));
+    }
+
+    #[cfg(feature = "async")]
+    futures_lite::future::block_on(async {
+        session.send_eof().await.unwrap();
+        session.expect(Eof).await.unwrap();
+
+        interrupted.send_intr().await.unwrap();
+        let status = interrupted
+            .wait_timeout(Duration::from_secs(3))
+            .await
+            .unwrap();
+        assert_eq!(status.unwrap().signal(), Some(Signal::SIGINT));
+    });
+}
+
+#[cfg(unix)]
+#[test]
+fn is_alive_keeps_exit_status() {
+    use expectrl::WaitStatus;
+    use std::time::Duration;
+
+    let mut session = spawn("sh -c 'exit 7'").unwrap();
+    while session.is_alive().unwrap() {
+        std::thread::sleep(Duration::from_millis(10));
+    }
+
+    assert!(!session.is_alive().unwrap());
+    assert_eq!(
+        session.get_process().wait().unwrap(),
+        WaitStatus::Exited(session.get_process().pid(), 7)
+    );
+
+    #[cfg(not(feature = "async"))]
+    let status = session.wait_timeout(Duration::from_secs(1)).unwrap();
+    #[cfg(feature = "async")]
+    let status =
+        futures_lite::future::block_on(session.wait_timeout(Duration::from_secs(1))).unwrap();
+
+    assert_eq!(status.unwrap().code(), Some(7));
+}
+
+#[cfg(unix)]
+#[test]
+fn spawn_args_doesnt_split_words() {
+    let mut session = spawn_args("printf", ["%s|%s\\n", "a  b", "'c\" d"]).unwrap();
+
+    #[cfg(not(feature = "async"))]
+    session.expect("a  b|'c\" d").unwrap();
+    #[cfg(feature = "async")]
+    futures_lite::future::block_on(session.expect("a  b|'c\" d")).unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+fn builder_separate_stderr() {
+    use std::process::Command;
+
+    let mut command = Command::new("sh");
+    let _ = command.args(["-c", "echo out; echo err >&2"]);
+
+    let mut session = Session::builder(command)
+        .separate_stderr(true)
+        .spawn()
+        .unwrap();
+
+    #[cfg(not(feature = "async"))]
+    {
+        let stderr = session.stderr().unwrap();
+        let m = stderr.expect("err").unwrap();
+        assert_eq!(m.before(), b"");
+        stderr.expect(expectrl::Eof).unwrap();
+
+        let m = session.expect(expectrl::Eof).unwrap();
+        assert_eq!(m.as_bytes(), b"out\r\n");
+    }
+    #[cfg(feature = "async")]
+    futures_lite::future::block_on(async {
+        let stderr = session.stderr().unwrap();
+        let m = stderr.expect("err").await.unwrap();
+        assert_eq!(m.before(), b"");
+        stderr.expect(expectrl::Eof).await.unwrap();
+
+        let m = session.expect(expectrl::Eof).await.unwrap();
+        assert_eq!(m.as_bytes(), b"out\r\n");
+    });
+}
+
+#[cfg(unix)]
+#[test]
+fn spawn_without_separate_stderr() {
+    let mut session = spawn("cat").unwrap();
+    assert!(session.stderr().is_none());
+}
+
+#[cfg(unix)]
+#[test]
+fn spawn_piped() {
+    use std::{process::Command, time::Duration};
+
+    let mut command = Command::new("sh");
+    let _ = command.args([
+        "-c",
+        "test -t 0 || echo no tty; read line; echo \"got $line\"",
+    ]);
+
+    let mut session = Session::spawn_piped(command).unwrap();
+    session.set_expect_timeout(Some(Duration::from_secs(5)));
+
+    #[cfg(not(feature = "async"))]
+    {
+        session.expect("no tty\n").unwrap();
+        session.send_line("Hello").unwrap();
+        session.expect("got Hello\n").unwrap();
+        session.expect(expectrl::Eof).unwrap();
+
+        let status = session.wait_timeout(Duration::from_secs(5)).unwrap();
+        assert!(status.unwrap().success());
+    }
+    #[cfg(feature = "async")]
+    futures_lite::future::block_on(async {
+        session.expect("no tty\n").await.unwrap();
+        session.send_line("Hello").await.unwrap();
+        session.expect("got Hello\n").await.unwrap();
+        session.expect(expectrl::Eof).await.unwrap();
+
+        let status = session.wait_timeout(Duration::from_secs(5)).await.unwrap();
+        assert!(status.unwrap().success());
+    });
+}
+
+#[cfg(unix)]
+#[test]
+fn builder_pre_exec() {
+    use nix::sys::resource::{setrlimit, Resource};
+    use std::process::Command;
+
+    let mut command = Command::new("sh");
+    let _ = command.args(["-c", "ulimit -n"]);
+
+    let builder = unsafe {
+        Session::builder(command).pre_exec(|| {
+            setrlimit(Resource::RLIMIT_NOFILE, 64, 64)?;
+            Ok(())
+        })
+    };
+
+    let mut session = builder.spawn().unwrap();
+
+    #[cfg(not(feature = "async"))]
+    session.expect("64").unwrap();
+    #[cfg(feature = "async")]
+    futures_lite::future::block_on(session.expect("64")).unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+fn builder_expect_timeout() {
+    use std::{process::Command, time::Duration};
+
+    let session = Session::builder(Command::new("cat"))
+        .expect_timeout(Some(Duration::from_millis(300)))
+        .spawn()
+        .unwrap();
+    assert_eq!(
+        session.get_expect_timeout(),
+        Some(Duration::from_millis(300))
+    );
+
+    let session = Session::builder(Command::new("cat"))
+        .expect_timeout(None)
+        .spawn()
+        .unwrap();
+    assert_eq!(session.get_expect_timeout(), None);
+}
+
+#[cfg(unix)]
+#[test]
+fn builder_pre_exec_error() {
+    use std::{io, process::Command};
+
+    let builder = unsafe {
+        Session::builder(Command::new("cat"))
+            .pre_exec(|| Err(io::Error::from_raw_os_error(nix::libc::EPERM)))
+    };
+
+    assert!(builder.spawn().is_err());
+}
+
+#[cfg(windows)]
+#[test]
+fn builder_console_size() {
+    use std::process::Command;
+
+    let mut session = Session::builder(Command::new("powershell"))
+        .console_size(30, 100)
+        .spawn()
+        .unwrap();
+    session.get_process_mut().resize(40, 120).unwrap();
+
+    #[cfg(not(feature = "async"))]
+    {
+        session
+            .send_line("$Host.UI.RawUI.WindowSize.Width")
+            .unwrap();
+        session.expect("120").unwrap();
+    }
+    #[cfg(feature = "async")]
+    futures_lite::future::block_on(async {
+        session
+            .send_line("$Host.UI.RawUI.WindowSize.Width")
+            .await
+            .unwrap();
+        session.expect("120").await.unwrap();
+    });
+}
+
+#[cfg(windows)]
+#[test]
+fn spawn_command_args_and_envs() {
+    use std::process::Command;
+
+    let mut command = Command::new("python");
+    let _ = command
+        .args([
+            "-c",
+            "import os, sys; print(sys.argv[1:], os.environ['EXPECTRL_VAR'], 'PATH' in os.environ)",
+        ])
+        .args(["a b", r#"c"d"#, r"e\"])
+        .env("EXPECTRL_VAR", "value")
+        .current_dir("./tests");
+
+    let mut session = Session::spawn(command).unwrap();
+
+    let expected = r#"['a b', 'c"d', 'e\\'] value True"#;
+    #[cfg(not(feature = "async"))]
+    session.expect(expected).unwrap();
+    #[cfg(feature = "async")]
+    futures_lite::future::block_on(session.expect(expected)).unwrap();
+}
+
+#[cfg(windows)]
+#[test]
+fn signal_ctrl_c() {
+    use expectrl::Signal;
+
+    let mut session = spawn("ping -t localhost").unwrap();
+
+    #[cfg(not(feature = "async"))]
+    session.expect("Reply").unwrap();
+    #[cfg(feature = "async")]
+    futures_lite::future::block_on(session.expect("Reply")).unwrap();
+
+    session.signal(Signal::SIGINT).unwrap();
+
+    let code = session.get_process().wait(Some(5000)).unwrap();
+    assert!(code == 0 || code == 3221225786);
+}
+
+#[cfg(unix)]
+#[test]
+fn kill_group() {
+    use expectrl::{Regex, Signal};
+    use nix::{sys::signal::kill, unistd::Pid};
+    use std::{thread, time::Duration};
+
+    let mut session = spawn(r#"sh -c "sleep 30 & echo pid=$!; wait""#).unwrap();
+
+    #[cfg(not(feature = "async"))]
+    let captures = session.expect(Regex(r"pid=(\d+)")).unwrap();
+    #[cfg(feature = "async")]
+    let captures = futures_lite::future::block_on(session.expect(Regex(r"pid=(\d+)"))).unwrap();
+
+    let pid = Pid::from_raw(captures.parse(1).unwrap());
+
+    session.kill_group(Signal::SIGKILL).unwrap();
+
+    for _ in 0..300 {
+        if kill(pid, None).is_err() {
+            return;
+        }
+
+        thread::sleep(Duration::from_millis(10));
+    }
+
+    panic!("a background process is still alive");
+}
+
+#[cfg(unix)]
+#[ignore = "It requires to be run as root"]
+#[test]
+fn builder_uid_gid() {
+    use std::process::Command;
+
+    let mut command = Command::new("sh");
+    let _ = command.args(["-c", "echo $(id -u):$(id -g)"]);
+
+    let mut session = Session::builder(command)
+        .uid(65534)
+        .gid(65534)
+        .spawn()
+        .unwrap();
+
+    #[cfg(not(feature = "async"))]
+    session.expect("65534:65534").unwrap();
+    #[cfg(feature = "async")]
+    futures_lite::future::block_on(session.expect("65534:65534")).unwrap();
+}
+
+#[cfg(unix)]
+#[ignore = "It requires to be run as root"]
+#[test]
+fn builder_uid_gid_separate_stderr() {
+    use std::process::Command;
+
+    let mut command = Command::new("sh");
+    let _ = command.args(["-c", "echo $(id -u):$(id -g) >&2"]);
+
+    let mut session = Session::builder(command)
+        .uid(65534)
+        .gid(65534)
+        .separate_stderr(true)
+        .spawn()
+        .unwrap();
+
+    let stderr = session.stderr().unwrap();
+    #[cfg(not(feature = "async"))]
+    stderr.expect("65534:65534").unwrap();
+    #[cfg(feature = "async")]
+    futures_lite::future::block_on(stderr.expect("65534:65534")).unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+fn skip_echo() {
+    let mut session = spawn(r#"sh -c "read line; echo got:$line""#).unwrap();
+    session.set_terminal_echo(true).unwrap();
+    assert!(session.is_terminal_echo().unwrap());
+    session.set_skip_echo(true);
+
+    #[cfg(not(feature = "async"))]
+    {
+        session.send_line("abc").unwrap();
+        let captures = session.expect("\r\n").unwrap();
+        assert_eq!(captures.before(), b"got:abc");
+    }
+
+    #[cfg(feature = "async")]
+    futures_lite::future::block_on(async {
+        session.send_line("abc").await.unwrap();
+        let captures = session.expect("\r\n").await.unwrap();
+        assert_eq!(captures.before(), b"got:abc");
+    });
+}
+
+#[cfg(unix)]
+#[test]
+fn tty_settings() {
+    use expectrl::termios::LocalFlags;
+
+    let mut session = spawn("cat").unwrap();
+    session.set_terminal_echo(true).unwrap();
+
+    let mut settings = session.tty_settings().unwrap();
+    assert!(settings.local_flags.contains(LocalFlags::ECHO));
+
+    settings
+        .local_flags
+        .remove(LocalFlags::ECHO | LocalFlags::ICANON);
+    session.set_tty_settings(&settings).unwrap();
+
+    let settings = session.tty_settings().unwrap();
+    assert!(!settings.local_flags.contains(LocalFlags::ICANON));
+    assert!(!session.is_terminal_echo().unwrap());
+
+    session.set_raw_mode().unwrap();
+
+    let settings = session.tty_settings().unwrap();
+    assert!(!settings.local_flags.contains(LocalFlags::ISIG));
+}
+
+#[cfg(unix)]
+#[test]
+fn pty_name() {
+    use std::os::unix::io::{AsFd, AsRawFd};
+
+    let mut session = spawn("tty").unwrap();
+
+    let name = session.pty_name().unwrap();
+    assert!(name.starts_with("/dev/"));
+    assert_eq!(session.as_fd().as_raw_fd(), session.as_raw_fd());
+
+    let name = name.to_str().unwrap().to_owned();
+
+    #[cfg(not(feature = "async"))]
+    session.expect(name).unwrap();
+    #[cfg(feature = "async")]
+    futures_lite::future::block_on(session.expect(name)).unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+fn send_slow() {
+    use std::{
+        process::Command,
+        time::{Duration, Instant},
+    };
+
+    let mut session = spawn("cat").unwrap();
+    let mut paced = Session::builder(Command::new("cat"))
+        .send_delay(Duration::from_millis(50))
+        .spawn()
+        .unwrap();
+
+    #[cfg(not(feature = "async"))]
+    {
+        let now = Instant::now();
+        session
+            .send_slow("Hello\n", Duration::from_millis(50))
+            .unwrap();
+        assert!(now.elapsed() >= Duration::from_millis(300));
+        session.expect("Hello").unwrap();
+
+        let now = Instant::now();
+        paced.send_line("World").unwrap();
+        assert!(now.elapsed() >= Duration::from_millis(300));
+        paced.expect("World").unwrap();
+    }
+
+    #[cfg(feature = "async")]
+    futures_lite::future::block_on(async {
+        let now = Instant::now();
+        session
+            .send_slow("Hello\n", Duration::from_millis(50))
+            .await
+            .unwrap();
+        assert!(now.elapsed() >= Duration::from_millis(300));
+        session.expect("Hello").await.unwrap();
+
+        let now = Instant::now();
+        paced.send_line("World").await.unwrap();
+        assert!(now.elapsed() >= Duration::from_millis(300));
+        paced.expect("World").await.unwrap();
+    });
+}
+
+#[cfg(unix)]
+#[test]
+fn record_asciicast() {
+    let path = std::env::temp_dir().join("expectrl_record_asciicast.cast");
+
+    let session = spawn("echo Hello World").unwrap();
+    let mut session = session.record_asciicast(&path).unwrap();
+
+    #[cfg(not(feature = "async"))]
+    session.expect("World").unwrap();
+    #[cfg(feature = "async")]
+    futures_lite::future::block_on(session.expect("World")).unwrap();
+
+    drop(session);
+
+    let content = std::fs::read_to_string(&path).unwrap();
+    let _ = std::fs::remove_file(&path);
+
+    let mut lines = content.lines();
+    assert!(lines
+        .next()
+        .unwrap()
+        .starts_with(r#"{"version": 2, "width": 80, "height": 24"#));
+    assert!(lines.any(|line| line.contains(r#""o", "Hello World"#)));
+}
+
+#[cfg(unix)]
+#[cfg(not(feature = "async"))]
+#[test]
+fn handoff_session() {
+    use expectrl::handoff::{recv_session, send_session};
+    use std::{os::unix::net::UnixStream, time::Duration};
+
+    let (sender, receiver) = UnixStream::pair().unwrap();
+
+    let mut session = spawn("cat").unwrap();
+    session.set_expect_timeout(Some(Duration::from_secs(5)));
+    session.send_line("Hello").unwrap();
+    session.expect("Hel").unwrap();
+    send_session(&sender, session).unwrap();
+
+    let mut session = recv_session(&receiver).unwrap();
+    assert_eq!(session.get_expect_timeout(), Some(Duration::from_secs(5)));
+
+    session.send_line("World").unwrap();
+    let found = session.expect("World").unwrap();
+    assert!(found.before().starts_with(b"lo"));
+
+    session
+        .get_process()
+        .kill(expectrl::Signal::SIGKILL)
+        .unwrap();
+}